@@ -266,7 +266,7 @@ impl Extension for UserInfo {
         "BlueFire:UserInfo"
     }
 
-    fn check(&self) -> Result<(), ()> {
+    fn check(&self) -> Result<(), BlueFireError> {
         Ok(())
     }
 
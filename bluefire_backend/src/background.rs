@@ -10,6 +10,7 @@ use std::sync::{
     {Arc, Mutex},
 };
 
+use crate::common::BlueFireError;
 use crate::context::{BlueFire, Extension};
 
 // -------------------------------------------------------------------------------------------------
@@ -80,7 +81,7 @@ impl Extension for Background {
         "BlueFire:Background"
     }
 
-    fn check(&self) -> Result<(), ()> {
+    fn check(&self) -> Result<(), BlueFireError> {
         Ok(())
     }
 
@@ -3,6 +3,7 @@
 
 //! Clock functionality.
 
+use crate::common::BlueFireError;
 use crate::context::Extension;
 
 // -------------------------------------------------------------------------------------------------
@@ -94,7 +95,7 @@ impl Extension for ClockExtension {
         "BlueFire:Clock"
     }
 
-    fn check(&self) -> Result<(), ()> {
+    fn check(&self) -> Result<(), BlueFireError> {
         Ok(())
     }
 
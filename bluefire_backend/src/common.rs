@@ -85,9 +85,61 @@ macro_rules! log_error {
 
 // -------------------------------------------------------------------------------------------------
 
-/// Errors returned from `bluefire` functions.
+/// Header carrying the identifier of the request.
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Generates an identifier unique within the process for a request which did not carry one.
+pub fn generate_request_id() -> String {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+    format!("{:x}-{:04x}", chrono::Utc::now().timestamp_millis(), count)
+}
+
+/// Formats the error followed by all its sources, e.g. `"Database query error: timeout: caused by:
+/// connection reset"`.
+pub fn error_chain(error: &dyn std::error::Error) -> String {
+    let mut result = error.to_string();
+    let mut source = error.source();
+    while let Some(cause) = source {
+        result += ": caused by: ";
+        result += &cause.to_string();
+        source = cause.source();
+    }
+    result
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// A trait mapping errors to HTTP responses.
+pub trait HttpError: std::error::Error {
+    /// Returns the status code of the response. The default is "internal server error".
+    fn status(&self) -> http::StatusCode {
+        http::StatusCode::INTERNAL_SERVER_ERROR
+    }
+
+    /// Returns the reason sent to the client, e.g. `"not_found"`. The default is `"internal"`.
+    fn reason(&self) -> &str {
+        "internal"
+    }
+
+    /// Returns the message sent to the client. By default server errors are not described to avoid
+    /// leaking internal details and the canonical reason of the status is returned instead.
+    fn message(&self) -> String {
+        let status = self.status();
+        if status.is_server_error() {
+            status.canonical_reason().unwrap_or_default().to_string()
+        } else {
+            self.to_string()
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Kinds of errors returned from `bluefire` functions.
 #[derive(Debug)]
-pub enum BlueFireError {
+pub enum ErrorKind {
     /// Database contained a password encoded using an unknown authentication algorithm.
     #[cfg(feature = "authentication")]
     UnknownAuthenticationAlgorithm {
@@ -123,6 +175,32 @@ pub enum BlueFireError {
         description: String,
     },
 
+    /// Failed to build or send an e-mail.
+    #[cfg(feature = "email")]
+    EmailSend,
+
+    /// The request is malformed. Rendered as `400 Bad Request`.
+    BadRequest {
+        /// Description of the problem.
+        message: String,
+    },
+
+    /// The request requires authentication. Rendered as `401 Unauthorized`.
+    Unauthorized,
+
+    /// The client is not allowed to access the resource. Rendered as `403 Forbidden`.
+    Forbidden,
+
+    /// The requested resource does not exist. Rendered as `404 Not Found`.
+    NotFound,
+
+    /// A service needed to handle the request is not functional. Rendered as
+    /// `503 Service Unavailable`.
+    Unavailable {
+        /// Name of the service.
+        service: String,
+    },
+
     /// Other error.
     Other {
         /// Description of the error.
@@ -130,77 +208,187 @@ pub enum BlueFireError {
     },
 }
 
+impl std::fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            #[cfg(feature = "authentication")]
+            ErrorKind::UnknownAuthenticationAlgorithm { algorithm_name } => {
+                write!(f, "Unknown authentication algorith '{}'", algorithm_name)
+            }
+            #[cfg(feature = "authentication")]
+            ErrorKind::InvalidPasswordHash => write!(f, "Invalid password hash"),
+            #[cfg(feature = "database")]
+            ErrorKind::UnexpectedResponseSize { size } => {
+                write!(f, "Unexpected response size '{}'", size)
+            }
+            #[cfg(feature = "database")]
+            ErrorKind::DatabaseRequestEncode { description } => {
+                write!(f, "Failed to encode database request: {}", description)
+            }
+            #[cfg(feature = "database")]
+            ErrorKind::DatabaseResponseDecode { description } => {
+                write!(f, "Failed to decode database response: {}", description)
+            }
+            #[cfg(feature = "database")]
+            ErrorKind::DatabaseQuery { description } => {
+                write!(f, "Database query error: {}", description)
+            }
+            #[cfg(feature = "email")]
+            ErrorKind::EmailSend => write!(f, "Failed to send an e-mail"),
+            ErrorKind::BadRequest { message } => write!(f, "Bad request: {}", message),
+            ErrorKind::Unauthorized => write!(f, "Authentication required"),
+            ErrorKind::Forbidden => write!(f, "Access forbidden"),
+            ErrorKind::NotFound => write!(f, "Not found"),
+            ErrorKind::Unavailable { service } => write!(f, "Service '{}' unavailable", service),
+            ErrorKind::Other { description } => write!(f, "{}", description),
+        }
+    }
+}
+
+/// Errors returned from `bluefire` functions. Consists of the kind of the error and optionally
+/// the underlying error which caused it.
+#[derive(Debug)]
+pub struct BlueFireError {
+    kind: ErrorKind,
+    source: Option<Box<dyn std::error::Error + Send + Sync>>,
+}
+
+impl BlueFireError {
+    /// Constructs a new `BlueFireError` of the given kind.
+    pub fn new(kind: ErrorKind) -> Self {
+        Self { kind, source: None }
+    }
+
+    /// Sets the underlying error.
+    pub fn with_source<E>(mut self, source: E) -> Self
+    where
+        E: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        self.source = Some(source.into());
+        self
+    }
+
+    /// Returns the kind of the error.
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+}
+
 impl BlueFireError {
     /// Constructs a new `BlueFireError`.
     #[cfg(feature = "authentication")]
     pub fn unknown_authentication_algorithm(algorithm_name: String) -> Self {
-        BlueFireError::UnknownAuthenticationAlgorithm { algorithm_name }
+        Self::new(ErrorKind::UnknownAuthenticationAlgorithm { algorithm_name })
     }
 
     /// Constructs a new `BlueFireError`.
     #[cfg(feature = "authentication")]
     pub fn invalid_password_hash() -> Self {
-        BlueFireError::InvalidPasswordHash
+        Self::new(ErrorKind::InvalidPasswordHash)
     }
 
     /// Constructs a new `BlueFireError`.
     #[cfg(feature = "database")]
     pub fn unexpected_response_size(size: usize) -> Self {
-        BlueFireError::UnexpectedResponseSize { size }
+        Self::new(ErrorKind::UnexpectedResponseSize { size })
     }
 
     /// Constructs a new `BlueFireError`.
     #[cfg(feature = "database")]
     pub fn database_request_encode(description: String) -> Self {
-        BlueFireError::DatabaseRequestEncode { description }
+        Self::new(ErrorKind::DatabaseRequestEncode { description })
     }
 
     /// Constructs a new `BlueFireError`.
     #[cfg(feature = "database")]
     pub fn database_response_decode(description: String) -> Self {
-        BlueFireError::DatabaseResponseDecode { description }
+        Self::new(ErrorKind::DatabaseResponseDecode { description })
     }
 
     /// Constructs a new `BlueFireError`.
     #[cfg(feature = "database")]
     pub fn database_query(description: String) -> Self {
-        BlueFireError::DatabaseQuery { description }
+        Self::new(ErrorKind::DatabaseQuery { description })
+    }
+
+    /// Constructs a new `BlueFireError`.
+    #[cfg(feature = "email")]
+    pub fn email_send() -> Self {
+        Self::new(ErrorKind::EmailSend)
+    }
+
+    /// Constructs a new `BlueFireError`.
+    pub fn bad_request(message: String) -> Self {
+        Self::new(ErrorKind::BadRequest { message })
+    }
+
+    /// Constructs a new `BlueFireError`.
+    pub fn unauthorized() -> Self {
+        Self::new(ErrorKind::Unauthorized)
+    }
+
+    /// Constructs a new `BlueFireError`.
+    pub fn forbidden() -> Self {
+        Self::new(ErrorKind::Forbidden)
+    }
+
+    /// Constructs a new `BlueFireError`.
+    pub fn not_found() -> Self {
+        Self::new(ErrorKind::NotFound)
+    }
+
+    /// Constructs a new `BlueFireError`.
+    pub fn unavailable(service: String) -> Self {
+        Self::new(ErrorKind::Unavailable { service })
     }
 
     /// Constructs a new `BlueFireError`.
     pub fn other(description: String) -> Self {
-        BlueFireError::Other { description }
+        Self::new(ErrorKind::Other { description })
     }
 }
 
-impl std::error::Error for BlueFireError {}
+impl std::error::Error for BlueFireError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.source {
+            Some(source) => Some(source.as_ref()),
+            None => None,
+        }
+    }
+}
 
 impl std::fmt::Display for BlueFireError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        match self {
-            #[cfg(feature = "authentication")]
-            BlueFireError::UnknownAuthenticationAlgorithm { algorithm_name } => {
-                write!(f, "Unknown authentication algorith '{}'", algorithm_name)
-            }
-            #[cfg(feature = "authentication")]
-            BlueFireError::InvalidPasswordHash {} => write!(f, "Invalid password hash"),
-            #[cfg(feature = "database")]
-            BlueFireError::UnexpectedResponseSize { size } => {
-                write!(f, "Unexpected response size '{}'", size)
-            }
-            #[cfg(feature = "database")]
-            BlueFireError::DatabaseRequestEncode { description } => {
-                write!(f, "Failed to encode database request: {}", description)
-            }
-            #[cfg(feature = "database")]
-            BlueFireError::DatabaseResponseDecode { description } => {
-                write!(f, "Failed to decode database response: {}", description)
-            }
-            #[cfg(feature = "database")]
-            BlueFireError::DatabaseQuery { description } => {
-                write!(f, "Database query error: {}", description)
-            }
-            BlueFireError::Other { description } => write!(f, "{}", description),
+        write!(f, "{}", self.kind)
+    }
+}
+
+impl HttpError for BlueFireError {
+    fn status(&self) -> http::StatusCode {
+        match self.kind {
+            ErrorKind::BadRequest { .. } => http::StatusCode::BAD_REQUEST,
+            ErrorKind::Unauthorized => http::StatusCode::UNAUTHORIZED,
+            ErrorKind::Forbidden => http::StatusCode::FORBIDDEN,
+            ErrorKind::NotFound => http::StatusCode::NOT_FOUND,
+            ErrorKind::Unavailable { .. } => http::StatusCode::SERVICE_UNAVAILABLE,
+            _ => http::StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
+
+    fn reason(&self) -> &str {
+        match self.kind {
+            ErrorKind::BadRequest { .. } => "bad_request",
+            ErrorKind::Unauthorized => "unauthorized",
+            ErrorKind::Forbidden => "forbidden",
+            ErrorKind::NotFound => "not_found",
+            ErrorKind::Unavailable { .. } => "unavailable",
+            _ => "internal",
+        }
+    }
+}
+
+impl From<ErrorKind> for BlueFireError {
+    fn from(kind: ErrorKind) -> Self {
+        Self::new(kind)
+    }
 }
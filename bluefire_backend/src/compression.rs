@@ -0,0 +1,298 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy of
+// the MPL was not distributed with this file, You can obtain one at http://mozilla.org/MPL/2.0/
+
+//! Response compression with `Accept-Encoding` content negotiation.
+
+use crate::common::Response;
+
+// -------------------------------------------------------------------------------------------------
+
+/// Default minimal size of a response body worth compressing.
+const DEFAULT_MIN_SIZE: usize = 1024;
+
+/// Prefixes of content types which are already compressed and should not be compressed again.
+const DEFAULT_EXCLUDED_CONTENT_TYPES: [&str; 8] = [
+    "image/png",
+    "image/jpeg",
+    "image/gif",
+    "image/webp",
+    "audio/",
+    "video/",
+    "font/woff",
+    "application/zip",
+];
+
+// -------------------------------------------------------------------------------------------------
+
+/// Content encoding of a response body.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Encoding {
+    /// No encoding.
+    Identity,
+
+    /// `gzip` encoding.
+    Gzip,
+
+    /// `br` (Brotli) encoding.
+    Brotli,
+}
+
+impl Encoding {
+    /// Returns the name of the encoding as used in `Accept-Encoding` and `Content-Encoding`
+    /// headers.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Encoding::Identity => "identity",
+            Encoding::Gzip => "gzip",
+            Encoding::Brotli => "br",
+        }
+    }
+
+    /// Returns the extension of files precompressed with this encoding.
+    pub fn extension(&self) -> Option<&'static str> {
+        match self {
+            Encoding::Identity => None,
+            Encoding::Gzip => Some("gz"),
+            Encoding::Brotli => Some("br"),
+        }
+    }
+
+    /// Compresses the given data.
+    pub fn encode(&self, data: &[u8]) -> Vec<u8> {
+        use std::io::Write;
+
+        match self {
+            Encoding::Identity => data.to_vec(),
+            Encoding::Gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(data).expect("Gzip: write data");
+                encoder.finish().expect("Gzip: finish encoding")
+            }
+            Encoding::Brotli => {
+                let mut result = Vec::new();
+                {
+                    let mut encoder = brotli::CompressorWriter::new(&mut result, 4096, 5, 22);
+                    encoder.write_all(data).expect("Brotli: write data");
+                }
+                result
+            }
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Chooses the best encoding accepted by the client according to the value of `Accept-Encoding`
+/// header. `available` lists the encodings the server can provide in order of preference. When
+/// quality values are equal the encoding appearing earlier in `available` wins.
+///
+/// Returns `None` if none of the available encodings is acceptable (including `identity`). Callers
+/// in this crate then send the identity encoding anyway instead of responding with `406 Not
+/// Acceptable`, as allowed by RFC 7231.
+pub fn negotiate(accept_encoding: Option<&str>, available: &[Encoding]) -> Option<Encoding> {
+    let accept_encoding = match accept_encoding {
+        Some(accept_encoding) => accept_encoding,
+        None => {
+            return if available.contains(&Encoding::Identity) {
+                Some(Encoding::Identity)
+            } else {
+                available.first().cloned()
+            };
+        }
+    };
+
+    let mut accepted = Vec::new();
+    for item in accept_encoding.split(',') {
+        let mut parts = item.split(';');
+        let name = parts.next().unwrap_or("").trim().to_lowercase();
+        if name.is_empty() {
+            continue;
+        }
+        let mut quality = 1.0;
+        for param in parts {
+            let param = param.trim();
+            if param.starts_with("q=") {
+                quality = param[2..].trim().parse::<f32>().unwrap_or(0.0);
+            }
+        }
+        accepted.push((name, quality));
+    }
+
+    let quality_of = |encoding: &Encoding| -> f32 {
+        let explicit = accepted.iter().find(|(name, _)| name == encoding.as_str());
+        let wildcard = accepted.iter().find(|(name, _)| name == "*");
+        match (explicit, wildcard, encoding) {
+            (Some((_, quality)), _, _) => *quality,
+            (None, Some((_, quality)), _) => *quality,
+            // `identity` is always acceptable unless explicitly excluded.
+            (None, None, Encoding::Identity) => 0.001,
+            (None, None, _) => 0.0,
+        }
+    };
+
+    let mut best: Option<(Encoding, f32)> = None;
+    for encoding in available.iter() {
+        let quality = quality_of(encoding);
+        if quality <= 0.0 {
+            continue;
+        }
+        match best {
+            Some((_, best_quality)) if best_quality >= quality => {}
+            _ => best = Some((*encoding, quality)),
+        }
+    }
+    best.map(|(encoding, _)| encoding)
+}
+
+/// Adds `Accept-Encoding` to the `Vary` header of the response unless already present.
+pub fn add_vary_accept_encoding(response: &mut Response) {
+    let headers = response.headers_mut();
+    let already_present = headers.get_all(http::header::VARY).iter().any(|value| {
+        value
+            .to_str()
+            .map(|value| value.to_lowercase().contains("accept-encoding"))
+            .unwrap_or(false)
+    });
+    if !already_present {
+        headers
+            .append(http::header::VARY, http::header::HeaderValue::from_static("Accept-Encoding"));
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Configuration of the response compression layer.
+///
+/// Compresses bodies of responses if the client accepts one of the configured encodings, the body
+/// is big enough and its content type is not known to be already compressed.
+#[derive(Clone, Debug)]
+pub struct Compression {
+    encodings: Vec<Encoding>,
+    min_size: usize,
+    excluded_content_types: Vec<String>,
+}
+
+impl Compression {
+    /// Constructs a new `Compression` preferring Brotli over gzip.
+    pub fn new() -> Self {
+        Self {
+            encodings: vec![Encoding::Brotli, Encoding::Gzip, Encoding::Identity],
+            min_size: DEFAULT_MIN_SIZE,
+            excluded_content_types: DEFAULT_EXCLUDED_CONTENT_TYPES
+                .iter()
+                .map(|content_type| content_type.to_string())
+                .collect(),
+        }
+    }
+
+    /// Sets the encodings the server is allowed to use in order of preference.
+    pub fn with_encodings(mut self, encodings: Vec<Encoding>) -> Self {
+        self.encodings = encodings;
+        if !self.encodings.contains(&Encoding::Identity) {
+            self.encodings.push(Encoding::Identity);
+        }
+        self
+    }
+
+    /// Sets the minimal size of a body to be compressed.
+    pub fn with_min_size(mut self, min_size: usize) -> Self {
+        self.min_size = min_size;
+        self
+    }
+
+    /// Excludes responses with content types starting with the given prefix from compression.
+    pub fn exclude_content_type(mut self, content_type_prefix: &str) -> Self {
+        self.excluded_content_types.push(content_type_prefix.to_lowercase());
+        self
+    }
+
+    /// Compresses the response if it is allowed by the request headers and the response itself.
+    /// If no available encoding is acceptable the response is sent uncompressed.
+    pub fn apply(&self, accept_encoding: Option<&str>, mut response: Response) -> Response {
+        if !self.is_compressible(&response) {
+            return response;
+        }
+
+        add_vary_accept_encoding(&mut response);
+        if response.body().len() < self.min_size {
+            return response;
+        }
+
+        match negotiate(accept_encoding, &self.encodings) {
+            Some(Encoding::Identity) | None => response,
+            Some(encoding) => {
+                let (mut parts, body) = response.into_parts();
+                let body = encoding.encode(&body);
+                parts.headers.insert(
+                    http::header::CONTENT_ENCODING,
+                    http::header::HeaderValue::from_static(encoding.as_str()),
+                );
+                parts.headers.remove(http::header::CONTENT_LENGTH);
+                Response::from_parts(parts, body)
+            }
+        }
+    }
+}
+
+impl Compression {
+    fn is_compressible(&self, response: &Response) -> bool {
+        if response.headers().contains_key(http::header::CONTENT_ENCODING) {
+            return false;
+        }
+
+        if response.status() == http::StatusCode::NO_CONTENT
+            || response.status() == http::StatusCode::NOT_MODIFIED
+            || response.status() == http::StatusCode::PARTIAL_CONTENT
+        {
+            return false;
+        }
+
+        if let Some(content_type) = response.headers().get(http::header::CONTENT_TYPE) {
+            if let Ok(content_type) = content_type.to_str() {
+                let content_type = content_type.to_lowercase();
+                for excluded in self.excluded_content_types.iter() {
+                    if content_type.starts_with(excluded.as_str()) {
+                        return false;
+                    }
+                }
+            }
+        }
+
+        true
+    }
+}
+
+impl Default for Compression {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::{negotiate, Encoding};
+
+    const ALL: [Encoding; 3] = [Encoding::Brotli, Encoding::Gzip, Encoding::Identity];
+
+    #[test]
+    fn test_negotiate_without_header() {
+        assert_eq!(negotiate(None, &ALL), Some(Encoding::Identity));
+    }
+
+    #[test]
+    fn test_negotiate_preference() {
+        assert_eq!(negotiate(Some("gzip, deflate, br"), &ALL), Some(Encoding::Brotli));
+        assert_eq!(negotiate(Some("gzip, deflate"), &ALL), Some(Encoding::Gzip));
+        assert_eq!(negotiate(Some("deflate"), &ALL), Some(Encoding::Identity));
+    }
+
+    #[test]
+    fn test_negotiate_quality() {
+        assert_eq!(negotiate(Some("br;q=0.5, gzip;q=0.8"), &ALL), Some(Encoding::Gzip));
+        assert_eq!(negotiate(Some("br;q=0, *"), &ALL), Some(Encoding::Gzip));
+        assert_eq!(negotiate(Some("identity;q=0"), &[Encoding::Identity]), None);
+    }
+}
@@ -16,6 +16,10 @@ use std::sync::Arc;
 use futures::Future;
 use traitobject;
 
+#[cfg(feature = "compression")]
+use crate::compression;
+#[cfg(feature = "live_reload")]
+use crate::live_reload;
 #[cfg(feature = "server")]
 use crate::server;
 
@@ -40,7 +44,7 @@ pub trait Extension: Any + Debug + Send {
     fn get_name(&self) -> &str;
 
     /// Checks if the extension is functional.
-    fn check(&self) -> Result<(), ()>;
+    fn check(&self) -> Result<(), common::BlueFireError>;
 
     /// Makes a copy of the extension.
     fn duplicate(&self) -> Box<dyn Extension>;
@@ -104,6 +108,12 @@ pub struct BlueFireKindler {
     middlewares: Vec<Box<dyn Middleware>>,
     router: Arc<router::Router>,
     reverse_router: Arc<router::ReverseRouter>,
+    #[cfg(feature = "compression")]
+    compression: Option<compression::Compression>,
+    #[cfg(feature = "live_reload")]
+    live_reload: Option<live_reload::LiveReload>,
+    #[cfg(feature = "server")]
+    body_limit: usize,
 }
 
 impl BlueFireKindler {
@@ -118,6 +128,12 @@ impl BlueFireKindler {
             middlewares: Vec::new(),
             router: Arc::new(router),
             reverse_router: Arc::new(reverse_router),
+            #[cfg(feature = "compression")]
+            compression: None,
+            #[cfg(feature = "live_reload")]
+            live_reload: None,
+            #[cfg(feature = "server")]
+            body_limit: server::DEFAULT_BODY_LIMIT,
         }
     }
 
@@ -133,14 +149,31 @@ impl BlueFireKindler {
         self
     }
 
+    /// Enables compression of responses.
+    #[cfg(feature = "compression")]
+    pub fn compress(mut self, compression: compression::Compression) -> Self {
+        self.compression = Some(compression);
+        self
+    }
+
+    /// Enables live reload of pages.
+    #[cfg(feature = "live_reload")]
+    pub fn live_reload(mut self, live_reload: live_reload::LiveReload) -> Self {
+        self.live_reload = Some(live_reload);
+        self
+    }
+
     /// Checks if all extensions are functional.
     pub fn perform_checks(&self) {
         log::info!(" => Checking the extensions:");
         for (_id, extension) in self.extensions.data.iter() {
-            if extension.check().is_ok() {
-                log::info!("  -> {}: ok", extension.get_name());
-            } else {
-                log::info!("  -> {}: NOT OK", extension.get_name());
+            match extension.check() {
+                Ok(()) => log::info!("  -> {}: ok", extension.get_name()),
+                Err(err) => log::info!(
+                    "  -> {}: NOT OK ({})",
+                    extension.get_name(),
+                    common::error_chain(&err)
+                ),
             }
         }
     }
@@ -151,9 +184,15 @@ impl BlueFireKindler {
         BlueFireWielder {
             middlewares: self.duplicate_middlewares(),
             router: self.router.clone(),
+            #[cfg(feature = "compression")]
+            compression: self.compression.clone(),
+            #[cfg(feature = "live_reload")]
+            live_reload: self.live_reload.clone(),
             context: BlueFire {
                 extensions: self.duplicate_extensions(),
                 params: common::ParamsMap::default(),
+                request_id: String::new(),
+                accept: None,
                 reverse_router: self.reverse_router.clone(),
             },
         }
@@ -167,12 +206,30 @@ impl Clone for BlueFireKindler {
             middlewares: self.duplicate_middlewares(),
             router: self.router.clone(),
             reverse_router: self.reverse_router.clone(),
+            #[cfg(feature = "compression")]
+            compression: self.compression.clone(),
+            #[cfg(feature = "live_reload")]
+            live_reload: self.live_reload.clone(),
+            #[cfg(feature = "server")]
+            body_limit: self.body_limit,
         }
     }
 }
 
 #[cfg(feature = "server")]
 impl BlueFireKindler {
+    /// Sets the maximal size of request bodies in bytes. Larger requests are rejected with
+    /// `413 Payload Too Large`.
+    pub fn limit_body(mut self, limit: usize) -> Self {
+        self.body_limit = limit;
+        self
+    }
+
+    /// Returns the maximal size of request bodies in bytes.
+    pub(crate) fn get_body_limit(&self) -> usize {
+        self.body_limit
+    }
+
     /// Starts an HTTP server on the given address.
     pub fn ignite_server(self, addr: &std::net::SocketAddr) {
         self.perform_checks();
@@ -207,6 +264,10 @@ impl BlueFireKindler {
 pub struct BlueFireWielder {
     middlewares: Vec<Box<dyn Middleware>>,
     router: Arc<router::Router>,
+    #[cfg(feature = "compression")]
+    compression: Option<compression::Compression>,
+    #[cfg(feature = "live_reload")]
+    live_reload: Option<live_reload::LiveReload>,
     context: BlueFire,
 }
 
@@ -218,17 +279,63 @@ impl BlueFireWielder {
         }
     }
 
-    /// Finds a handler for the request basing on the request path and executes it.
+    /// Finds a handler for the request basing on the request path and executes it. The request ID
+    /// is taken from the `X-Request-Id` header or generated if the header is not present. The
+    /// `Accept` header is kept in the context for rendering errors.
     pub fn route(&mut self, request: common::Request) -> common::Response {
         let (handler, params) = self.router.route(&request);
         self.context.params = params;
+        self.context.request_id = request
+            .headers()
+            .get(common::REQUEST_ID_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string())
+            .unwrap_or_else(common::generate_request_id);
+        self.context.accept = request
+            .headers()
+            .get(http::header::ACCEPT)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string());
         handler.handle(&mut self.context, request)
     }
 
-    /// Handles the request - notifies the middlewares and executes the handler.
+    /// Handles the request - notifies the middlewares, executes the handler and compresses the
+    /// response if compression was enabled. If live reload was enabled the reload script is
+    /// injected into HTML responses.
     pub fn serve(&mut self, request: common::Request) -> common::Response {
+        #[cfg(feature = "live_reload")]
+        {
+            if let Some(live_reload) = self.live_reload.as_ref() {
+                if live_reload.is_reload_request(&request) {
+                    return live_reload.make_reload_response();
+                }
+            }
+        }
+
         self.apply_middlewares(&request);
-        self.route(request)
+
+        #[cfg(feature = "compression")]
+        let accept_encoding = request
+            .headers()
+            .get(http::header::ACCEPT_ENCODING)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string());
+
+        let response = self.route(request);
+
+        #[cfg(feature = "live_reload")]
+        let response = match self.live_reload.as_ref() {
+            Some(live_reload) => live_reload.inject(response),
+            None => response,
+        };
+
+        #[cfg(feature = "compression")]
+        let response = match self.compression.as_ref() {
+            Some(compression) => compression.apply(accept_encoding.as_deref(), response),
+            None => response,
+        };
+
+        response
     }
 
     /// Returns immutable handler context.
@@ -248,6 +355,8 @@ impl BlueFireWielder {
 pub struct BlueFire {
     extensions: Extensions,
     params: common::ParamsMap,
+    request_id: String,
+    accept: Option<String>,
     reverse_router: Arc<router::ReverseRouter>,
 }
 
@@ -272,6 +381,16 @@ impl BlueFire {
         &self.params
     }
 
+    /// The identifier of the currently handled request used to correlate logs with responses.
+    pub fn request_id(&self) -> &str {
+        &self.request_id
+    }
+
+    /// The value of the `Accept` header of the currently handled request.
+    pub fn accept(&self) -> Option<&str> {
+        self.accept.as_deref()
+    }
+
     /// Returns a path for given label. `Path` allows to build a path to an HTTP resource.
     pub fn reverse(&self, label: &str) -> Option<&router::Path> {
         self.reverse_router.reverse(label)
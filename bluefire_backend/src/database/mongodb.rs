@@ -3,8 +3,6 @@
 
 //! Access to mongodb.
 
-// TODO: Provide better enum variants for casting into `BlueFireError`.
-
 use std::sync::Arc;
 
 use bson::{bson, doc};
@@ -34,14 +32,11 @@ impl MongoDatabase {
     }
 
     /// Checks connection to the server.
-    pub fn check_server_status(&self) -> Result<(), ()> {
+    pub fn check_server_status(&self) -> Result<(), BlueFireError> {
         let client = self.client_pool.pop();
         match client.get_server_status(None) {
             Ok(..) => Ok(()),
-            Err(err) => {
-                log::error!("Check for MongoDB connection failed: {}", err);
-                Err(())
-            }
+            Err(err) => Err(BlueFireError::unavailable("MongoDB".to_string()).with_source(err)),
         }
     }
 
@@ -107,7 +102,7 @@ impl Extension for MongoDatabase {
         "BlueFire:MongoDatabase"
     }
 
-    fn check(&self) -> Result<(), ()> {
+    fn check(&self) -> Result<(), BlueFireError> {
         self.check_server_status()
     }
 
@@ -130,24 +125,24 @@ impl std::fmt::Debug for MongoDatabase {
 
 impl From<bson::EncoderError> for BlueFireError {
     fn from(error: bson::EncoderError) -> Self {
-        BlueFireError::database_request_encode(format!("{:?}", error))
+        BlueFireError::database_request_encode("BSON encoder".to_string()).with_source(error)
     }
 }
 
 impl From<bson::DecoderError> for BlueFireError {
     fn from(error: bson::DecoderError) -> Self {
-        BlueFireError::database_response_decode(format!("{:?}", error))
+        BlueFireError::database_response_decode("BSON decoder".to_string()).with_source(error)
     }
 }
 
 impl From<bson::oid::Error> for BlueFireError {
     fn from(error: bson::oid::Error) -> Self {
-        BlueFireError::database_response_decode(format!("{:?}", error))
+        BlueFireError::database_response_decode("object ID".to_string()).with_source(error)
     }
 }
 
 impl From<mongo_driver::MongoError> for BlueFireError {
     fn from(error: mongo_driver::MongoError) -> Self {
-        BlueFireError::database_query(format!("{:?}", error))
+        BlueFireError::database_query("MongoDB".to_string()).with_source(error)
     }
 }
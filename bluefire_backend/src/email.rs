@@ -7,6 +7,7 @@ use std::cell::RefCell;
 use std::fmt::Debug;
 use std::sync::{Arc, Mutex, MutexGuard};
 
+use crate::common::BlueFireError;
 use crate::context::Extension;
 
 use lettre::{ClientSecurity, SmtpClient, SmtpTransport, Transport};
@@ -67,7 +68,7 @@ impl EMail {
 /// A trait for mailing managers.
 pub trait Mailer: Debug + Send {
     /// Sends an e-mail.
-    fn send(&mut self, email: EMail) -> Result<(), ()>;
+    fn send(&mut self, email: EMail) -> Result<(), BlueFireError>;
 }
 
 // -------------------------------------------------------------------------------------------------
@@ -84,7 +85,7 @@ impl FakeMailer {
 }
 
 impl Mailer for FakeMailer {
-    fn send(&mut self, email: EMail) -> Result<(), ()> {
+    fn send(&mut self, email: EMail) -> Result<(), BlueFireError> {
         log::info!(
             "Faking sending an email '{}' to {:?}",
             email.get_subject(),
@@ -123,7 +124,7 @@ impl SmtpMailer {
 }
 
 impl Mailer for SmtpMailer {
-    fn send(&mut self, email: EMail) -> Result<(), ()> {
+    fn send(&mut self, email: EMail) -> Result<(), BlueFireError> {
         log::info!(
             "Sending an email '{}' from '{}' to {:?}",
             email.get_subject(),
@@ -138,12 +139,12 @@ impl Mailer for SmtpMailer {
             builder = builder.to(recipient);
         }
 
-        match self.transport.send(builder.build().expect("Build an email").into()) {
+        let email = builder
+            .build()
+            .map_err(|err| BlueFireError::email_send().with_source(err.to_string()))?;
+        match self.transport.send(email.into()) {
             Ok(..) => Ok(()),
-            Err(err) => {
-                log::error!("Failed to send an email: {}", err);
-                Err(())
-            }
+            Err(err) => Err(BlueFireError::email_send().with_source(err)),
         }
     }
 }
@@ -190,7 +191,7 @@ impl MailingManager {
     }
 
     /// Sends the given e-mail using the mailer.
-    pub fn send(&mut self, email: EMail) -> Result<(), ()> {
+    pub fn send(&mut self, email: EMail) -> Result<(), BlueFireError> {
         let result = self.mailer.send(email.clone());
         if result.is_ok() {
             self.state.borrow_mut().sent(email);
@@ -235,7 +236,7 @@ impl Extension for MailingExtention {
     }
 
     // TODO: Check connection with SMTP server.
-    fn check(&self) -> Result<(), ()> {
+    fn check(&self) -> Result<(), BlueFireError> {
         Ok(())
     }
 
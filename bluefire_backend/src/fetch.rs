@@ -3,11 +3,14 @@
 
 //! Communication with other servers.
 
-/// Fetches a remote resource.
-pub fn fetch(
-    host: &str,
-    message: &bluefire_twine::message::Message,
-) -> reqwest::Result<reqwest::Response> {
+use bluefire_twine::{
+    message::Message,
+    transport::{RawResponse, Transport, TransportError},
+};
+
+/// Fetches a remote resource. Fails if the request could not be sent or the parts of the message
+/// could not be encoded.
+pub fn fetch(host: &str, message: &Message) -> Result<reqwest::Response, TransportError> {
     let client = reqwest::Client::new();
 
     let path = if message.query().is_empty() {
@@ -15,14 +18,61 @@ pub fn fetch(
     } else {
         String::from(host) + message.path() + "?" + message.query()
     };
-    let body = if (message.method() != "GET") && (message.method() != "HEAD") {
-        message.body().to_string()
+    let mut headers = message.headers().to_vec();
+    let body = if (message.method() == "GET") || (message.method() == "HEAD") {
+        Vec::new()
+    } else if !message.parts().is_empty() {
+        let boundary = crate::multipart::make_boundary(message.parts());
+        headers.push(("content-type".to_string(), crate::multipart::content_type(&boundary)));
+        crate::multipart::encode(message.parts(), &boundary)
+            .map_err(|err| TransportError::new(err.to_string()))?
     } else {
-        "".to_string()
+        message.body().as_bytes().to_vec()
     };
     let method =
         reqwest::Method::from_bytes(message.method().as_bytes()).expect("Fetch: Create method");
     let url = reqwest::Url::parse(&path).expect("Fetch: Create URL");
 
-    client.request(method, url).body(body).send()
+    let mut request = client.request(method, url).body(body);
+    for (name, value) in headers.iter() {
+        request = request.header(name.as_str(), value.as_str());
+    }
+    request.send().map_err(|err| TransportError::new(err.to_string()))
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// A blocking transport for generated API clients sending messages with `fetch`.
+#[derive(Clone, Debug)]
+pub struct BlockingTransport {
+    host: String,
+}
+
+impl BlockingTransport {
+    /// Constructs a new `BlockingTransport` sending messages to the given host.
+    pub fn new(host: &str) -> Self {
+        Self { host: host.to_string() }
+    }
+}
+
+impl<T> Transport<T> for BlockingTransport {
+    type Output = T;
+
+    fn send<F>(&self, message: Message, parse: F) -> T
+    where
+        F: FnOnce(Result<RawResponse, TransportError>) -> T + 'static,
+    {
+        let response = fetch(&self.host, &message).and_then(|mut response| {
+            let status = response.status().as_u16();
+            let content_type = response
+                .headers()
+                .get(reqwest::header::CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.to_string());
+            let mut body = Vec::new();
+            response.copy_to(&mut body).map_err(|err| TransportError::new(err.to_string()))?;
+            Ok(RawResponse { status, content_type, body })
+        });
+        parse(response)
+    }
 }
@@ -9,7 +9,8 @@
 
 #[macro_use]
 mod common;
-pub use self::common::{BlueFireError, Body, GlobalState, Handler, ParamsMap, Request, Response};
+pub use self::common::{error_chain, BlueFireError, ErrorKind, HttpError};
+pub use self::common::{Body, GlobalState, Handler, ParamsMap, Request, Response};
 
 pub mod clock;
 pub mod router;
@@ -28,6 +29,9 @@ pub use self::context::{Extension, Extensions, Middleware};
 #[macro_use]
 pub mod rest;
 
+#[cfg(feature = "rest")]
+pub mod response;
+
 #[cfg(feature = "database")]
 pub mod database;
 
@@ -40,14 +44,23 @@ pub mod email;
 #[cfg(feature = "scheduler")]
 pub mod scheduler;
 
+#[cfg(feature = "compression")]
+pub mod compression;
+
 #[cfg(feature = "static_files")]
 pub mod static_files;
 
+#[cfg(feature = "live_reload")]
+pub mod live_reload;
+
 #[cfg(feature = "background")]
 pub mod background;
 
 #[cfg(feature = "fetch")]
 pub mod fetch;
 
+#[cfg(feature = "multipart")]
+pub mod multipart;
+
 #[cfg(feature = "widgets")]
 pub mod widgets;
@@ -0,0 +1,232 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy of
+// the MPL was not distributed with this file, You can obtain one at http://mozilla.org/MPL/2.0/
+
+//! Live reload of pages for development.
+//!
+//! `LiveReload` watches a set of files and injects a small script into every HTML response. The
+//! script polls the server and reloads the page when any of the watched files changes or the server
+//! is restarted.
+
+use std::{
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    thread::JoinHandle,
+    time::{Duration, SystemTime},
+};
+
+use crate::{
+    common::{Request, Response},
+    static_files::read_modification_times,
+};
+
+/// Default path polled by the injected script.
+pub const DEFAULT_RELOAD_PATH: &str = "/_bluefire/reload";
+
+/// Interval between checks of modification times of watched files.
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Interval (in milliseconds) between polls made by the injected script.
+const POLL_INTERVAL_MS: u32 = 1000;
+
+// -------------------------------------------------------------------------------------------------
+
+/// Background thread checking modification times of watched files. The thread is stopped and
+/// joined when the `Watcher` is dropped.
+#[derive(Debug)]
+struct Watcher {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Watcher {
+    /// Starts the thread bumping `version` whenever any of the files changes.
+    fn start(paths: Vec<PathBuf>, version: Arc<AtomicUsize>) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+        let thread = std::thread::spawn(move || {
+            let mut times = read_modification_times(&paths);
+            while !thread_stop.load(Ordering::SeqCst) {
+                std::thread::park_timeout(WATCH_INTERVAL);
+                let new_times = read_modification_times(&paths);
+                if new_times != times {
+                    log_info!("Static files changed, reloading pages");
+                    version.fetch_add(1, Ordering::SeqCst);
+                    times = new_times;
+                }
+            }
+        });
+        Self { stop, thread: Some(thread) }
+    }
+}
+
+impl Drop for Watcher {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            thread.thread().unpark();
+            if thread.join().is_err() {
+                log_error!("Live reload watcher thread panicked");
+            }
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Tracks changes of watched files and notifies connected browsers about them.
+#[derive(Clone, Debug)]
+pub struct LiveReload {
+    reload_path: String,
+    started: u64,
+    version: Arc<AtomicUsize>,
+    watcher: Option<Arc<Watcher>>,
+}
+
+impl LiveReload {
+    /// Constructs a new `LiveReload` not watching any files.
+    pub fn new() -> Self {
+        let started = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+        Self {
+            reload_path: DEFAULT_RELOAD_PATH.to_string(),
+            started,
+            version: Arc::new(AtomicUsize::new(0)),
+            watcher: None,
+        }
+    }
+
+    /// Sets the path polled by the injected script.
+    pub fn with_reload_path(mut self, reload_path: &str) -> Self {
+        self.reload_path = reload_path.to_string();
+        self
+    }
+
+    /// Starts a background thread watching modification times of the given files. Directories are
+    /// watched recursively, so adding, removing or modifying any file inside them is noticed. Every
+    /// change triggers a reload of the connected pages. The thread is stopped when the last clone
+    /// of this `LiveReload` is dropped.
+    pub fn watch(mut self, paths: Vec<PathBuf>) -> Self {
+        self.watcher = Some(Arc::new(Watcher::start(paths, self.version.clone())));
+        self
+    }
+
+    /// Makes the connected pages reload.
+    pub fn notify(&self) {
+        self.version.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// Returns the current version of the watched files. The version changes after every change of
+    /// the files and every restart of the server.
+    pub fn version(&self) -> String {
+        format!("{}-{}", self.started, self.version.load(Ordering::SeqCst))
+    }
+
+    /// Returns the script to be injected into the pages.
+    pub fn script(&self) -> String {
+        format!(
+            concat!(
+                "<script>(function() {{",
+                "var version = null;",
+                "setInterval(function() {{",
+                "fetch(\"{path}\", {{ cache: \"no-store\" }})",
+                ".then(function(response) {{ return response.text(); }})",
+                ".then(function(current) {{",
+                "if (version === null) {{ version = current; }}",
+                "else if (version !== current) {{ window.location.reload(); }}",
+                "}})",
+                ".catch(function() {{}});",
+                "}}, {interval});",
+                "}})();</script>"
+            ),
+            path = self.reload_path,
+            interval = POLL_INTERVAL_MS,
+        )
+    }
+
+    /// Checks if the request is a poll made by the injected script.
+    pub fn is_reload_request(&self, request: &Request) -> bool {
+        request.uri().path() == self.reload_path
+    }
+
+    /// Prepares a response to the poll made by the injected script.
+    pub fn make_reload_response(&self) -> Response {
+        let mut builder = http::response::Builder::new();
+        builder
+            .status(http::StatusCode::OK)
+            .header(http::header::CONTENT_TYPE, "text/plain; charset=utf-8")
+            .header(http::header::CACHE_CONTROL, "no-store");
+        builder.body(self.version().into_bytes()).expect("Build reload response")
+    }
+
+    /// Injects the reload script into the response if it is an HTML page.
+    pub fn inject(&self, response: Response) -> Response {
+        let is_html = response
+            .headers()
+            .get(http::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_lowercase().starts_with("text/html"))
+            .unwrap_or(false);
+        if !is_html || response.headers().contains_key(http::header::CONTENT_ENCODING) {
+            return response;
+        }
+
+        let (mut parts, mut body) = response.into_parts();
+        let script = self.script().into_bytes();
+        let position = find_last(&body, b"</body>").unwrap_or(body.len());
+        body.splice(position..position, script);
+        parts.headers.remove(http::header::CONTENT_LENGTH);
+        Response::from_parts(parts, body)
+    }
+}
+
+impl Default for LiveReload {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns the position of the last occurrence of `needle` in `haystack`.
+fn find_last(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    if needle.len() > haystack.len() {
+        return None;
+    }
+    (0..=(haystack.len() - needle.len())).rev().find(|&i| &haystack[i..i + needle.len()] == needle)
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{atomic::Ordering, Arc};
+
+    use super::{find_last, LiveReload};
+
+    #[test]
+    fn test_find_last() {
+        assert_eq!(find_last(b"<body></body></body>", b"</body>"), Some(13));
+        assert_eq!(find_last(b"<p>text</p>", b"</body>"), None);
+        assert_eq!(find_last(b"", b"</body>"), None);
+    }
+
+    #[test]
+    fn test_watcher_stops_on_drop() {
+        let dir = std::env::temp_dir().join("bluefire_backend_live_reload_drop");
+        std::fs::create_dir_all(&dir).unwrap();
+        let live_reload = LiveReload::new().watch(vec![dir]);
+        let clone = live_reload.clone();
+        let stop = live_reload.watcher.as_ref().unwrap().stop.clone();
+        let version = live_reload.version.clone();
+
+        drop(live_reload);
+        assert!(!stop.load(Ordering::SeqCst));
+
+        drop(clone);
+        assert!(stop.load(Ordering::SeqCst));
+        assert_eq!(Arc::strong_count(&version), 1);
+    }
+}
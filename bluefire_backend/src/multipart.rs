@@ -0,0 +1,290 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy of
+// the MPL was not distributed with this file, You can obtain one at http://mozilla.org/MPL/2.0/
+
+//! Parsing and encoding of `multipart/form-data` bodies.
+
+use bluefire_twine::multipart::Part;
+
+use crate::common::Request;
+
+/// Default limit of the size of a multipart body (10 MiB).
+pub const DEFAULT_LIMIT: usize = 10 * 1024 * 1024;
+
+/// Error returned when parsing a multipart body fails.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MultipartError {
+    /// The body is larger than allowed.
+    TooLarge {
+        /// Size of the body.
+        size: usize,
+
+        /// The limit.
+        limit: usize,
+    },
+
+    /// The content type is not `multipart/form-data` or does not specify the boundary.
+    InvalidContentType,
+
+    /// The body does not have the expected structure.
+    Malformed(&'static str),
+
+    /// A name, file name or content type of a part to encode contains a line break.
+    InvalidHeader,
+}
+
+impl std::fmt::Display for MultipartError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            MultipartError::TooLarge { size, limit } => {
+                write!(f, "Multipart body of size {} exceeds the limit of {} bytes", size, limit)
+            }
+            MultipartError::InvalidContentType => write!(f, "Invalid multipart content type"),
+            MultipartError::Malformed(message) => {
+                write!(f, "Malformed multipart body: {}", message)
+            }
+            MultipartError::InvalidHeader => write!(f, "Multipart header contains a line break"),
+        }
+    }
+}
+
+impl std::error::Error for MultipartError {}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Finds the first occurrence of `needle` in `haystack` starting from `start`.
+fn find(haystack: &[u8], needle: &[u8], start: usize) -> Option<usize> {
+    if start > haystack.len() {
+        return None;
+    }
+    haystack[start..].windows(needle.len()).position(|window| window == needle).map(|i| i + start)
+}
+
+/// Splits the header value on semicolons which are not inside a quoted string.
+fn split_params(value: &str) -> Vec<&str> {
+    let mut items = Vec::new();
+    let mut quoted = false;
+    let mut escaped = false;
+    let mut start = 0;
+    for (i, c) in value.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match c {
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            ';' if !quoted => {
+                items.push(&value[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    items.push(&value[start..]);
+    items
+}
+
+/// Removes quotes and backslash escapes from a quoted string. Other values are returned as they are.
+fn unquote(value: &str) -> String {
+    if value.len() < 2 || !value.starts_with('"') || !value.ends_with('"') {
+        return value.to_string();
+    }
+    let mut result = String::new();
+    let mut chars = value[1..value.len() - 1].chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => result.extend(chars.next()),
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+/// Checks if the value would break the header it is written to.
+fn has_line_break(value: &str) -> bool {
+    value.contains(|c| c == '\r' || c == '\n')
+}
+
+/// Quotes the header parameter escaping quotes and backslashes. Fails if the value contains a line
+/// break.
+fn quote(value: &str) -> Result<String, MultipartError> {
+    if has_line_break(value) {
+        return Err(MultipartError::InvalidHeader);
+    }
+    Ok(format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\"")))
+}
+
+/// Splits a header value like `form-data; name="file"` into the value and its parameters.
+fn parse_params(value: &str) -> (String, Vec<(String, String)>) {
+    let mut items = split_params(value).into_iter();
+    let main = items.next().unwrap_or_default().trim().to_lowercase();
+    let params = items
+        .filter_map(|item| {
+            let mut parts = item.trim().splitn(2, '=');
+            let key = parts.next()?.trim().to_lowercase();
+            let value = unquote(parts.next()?.trim());
+            Some((key, value))
+        })
+        .collect();
+    (main, params)
+}
+
+/// Returns the boundary specified in the `multipart/form-data` content type.
+fn get_boundary(content_type: &str) -> Option<String> {
+    let (main, params) = parse_params(content_type);
+    if main != "multipart/form-data" {
+        return None;
+    }
+    params.into_iter().find(|(key, _)| key == "boundary").map(|(_, value)| value)
+}
+
+/// Parses the headers of a part.
+fn parse_part(headers: &[u8], data: &[u8]) -> Result<Part, MultipartError> {
+    let headers = std::str::from_utf8(headers)
+        .map_err(|_| MultipartError::Malformed("headers are not valid UTF-8"))?;
+    let mut name = None;
+    let mut file_name = None;
+    let mut content_type = None;
+    for line in headers.split("\r\n").filter(|line| !line.is_empty()) {
+        let mut parts = line.splitn(2, ':');
+        let key = parts.next().unwrap_or_default().trim().to_lowercase();
+        let value = parts.next().ok_or(MultipartError::Malformed("invalid header"))?.trim();
+        match key.as_str() {
+            "content-disposition" => {
+                for (key, value) in parse_params(value).1 {
+                    match key.as_str() {
+                        "name" => name = Some(value),
+                        "filename" => file_name = Some(value),
+                        _ => {}
+                    }
+                }
+            }
+            "content-type" => content_type = Some(value.to_string()),
+            _ => {}
+        }
+    }
+
+    let name = name.ok_or(MultipartError::Malformed("part without a name"))?;
+    Ok(Part { name, file_name, content_type, data: data.to_vec() })
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Parser of `multipart/form-data` bodies.
+#[derive(Clone, Debug)]
+pub struct MultipartParser {
+    limit: usize,
+}
+
+impl MultipartParser {
+    /// Constructs a new `MultipartParser` with the default size limit.
+    pub fn new() -> Self {
+        Self { limit: DEFAULT_LIMIT }
+    }
+
+    /// Sets the maximal size of the body in bytes.
+    ///
+    /// The limit caps only parsing of a body which was already read. Reading of request bodies is
+    /// limited by the server, see `BlueFireKindler::limit_body`.
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Parses the body of the request according to its `content-type` header.
+    pub fn parse_request(&self, request: &Request) -> Result<Vec<Part>, MultipartError> {
+        let content_type = request
+            .headers()
+            .get(http::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .ok_or(MultipartError::InvalidContentType)?;
+        self.parse(content_type, request.body())
+    }
+
+    /// Parses the body with the given value of the `content-type` header.
+    pub fn parse(&self, content_type: &str, body: &[u8]) -> Result<Vec<Part>, MultipartError> {
+        if body.len() > self.limit {
+            return Err(MultipartError::TooLarge { size: body.len(), limit: self.limit });
+        }
+
+        let boundary = get_boundary(content_type).ok_or(MultipartError::InvalidContentType)?;
+        let delimiter = format!("--{}", boundary).into_bytes();
+        let separator = format!("\r\n--{}", boundary).into_bytes();
+
+        let mut position = find(body, &delimiter, 0)
+            .ok_or(MultipartError::Malformed("missing boundary"))?
+            + delimiter.len();
+        let mut parts = Vec::new();
+        loop {
+            if body[position..].starts_with(b"--") {
+                return Ok(parts);
+            }
+            if !body[position..].starts_with(b"\r\n") {
+                return Err(MultipartError::Malformed("missing line break after boundary"));
+            }
+            position += 2;
+
+            let headers_end = find(body, b"\r\n\r\n", position)
+                .ok_or(MultipartError::Malformed("missing end of headers"))?;
+            let data_start = headers_end + 4;
+            let data_end = find(body, &separator, data_start)
+                .ok_or(MultipartError::Malformed("missing closing boundary"))?;
+
+            parts.push(parse_part(&body[position..headers_end], &body[data_start..data_end])?);
+            position = data_end + separator.len();
+        }
+    }
+}
+
+impl Default for MultipartParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Generates a boundary not contained in any of the parts.
+pub fn make_boundary(parts: &[Part]) -> String {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.subsec_nanos())
+        .unwrap_or_default();
+    let mut boundary = format!("bluefire-boundary-{:08x}", nanos);
+    while parts.iter().any(|part| find(&part.data, boundary.as_bytes(), 0).is_some()) {
+        boundary.push('x');
+    }
+    boundary
+}
+
+/// Returns the value of the `content-type` header for a multipart body with the given boundary.
+pub fn content_type(boundary: &str) -> String {
+    format!("multipart/form-data; boundary={}", boundary)
+}
+
+/// Encodes the parts as a `multipart/form-data` body using the given boundary. Fails if a name,
+/// file name or content type of a part contains a line break.
+pub fn encode(parts: &[Part], boundary: &str) -> Result<Vec<u8>, MultipartError> {
+    let mut body = Vec::new();
+    for part in parts {
+        body.extend(format!("--{}\r\n", boundary).into_bytes());
+        let disposition = match &part.file_name {
+            Some(file_name) => {
+                format!("name={}; filename={}", quote(&part.name)?, quote(file_name)?)
+            }
+            None => format!("name={}", quote(&part.name)?),
+        };
+        body.extend(format!("Content-Disposition: form-data; {}\r\n", disposition).into_bytes());
+        if let Some(content_type) = &part.content_type {
+            if has_line_break(content_type) {
+                return Err(MultipartError::InvalidHeader);
+            }
+            body.extend(format!("Content-Type: {}\r\n", content_type).into_bytes());
+        }
+        body.extend(b"\r\n");
+        body.extend(&part.data);
+        body.extend(b"\r\n");
+    }
+    body.extend(format!("--{}--\r\n", boundary).into_bytes());
+    Ok(body)
+}
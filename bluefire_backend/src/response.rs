@@ -0,0 +1,366 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy of
+// the MPL was not distributed with this file, You can obtain one at http://mozilla.org/MPL/2.0/
+
+//! Typed response builders.
+//!
+//! Instead of building responses by hand handlers may return any type implementing
+//! `IntoResponse`, e.g. `Json`, `Html`, `Redirect`, `NoContent`, `Created`, `Attachment` or
+//! `Negotiated` choosing between JSON and HTML renderings of the same data basing on the `Accept`
+//! header. Builders which do not need the context can be also converted with `Into<Response>`.
+
+use serde::Serialize;
+
+use crate::common::{HttpError, ParamsMap, Request, Response};
+use crate::context::BlueFire;
+use crate::rest;
+
+// -------------------------------------------------------------------------------------------------
+
+/// A trait for types which can be converted into a response.
+pub trait IntoResponse {
+    /// Converts `self` into a response. The context allows e.g. to resolve labels of routes.
+    fn into_response(self, context: &BlueFire) -> Response;
+}
+
+impl IntoResponse for Response {
+    fn into_response(self, _context: &BlueFire) -> Response {
+        self
+    }
+}
+
+impl IntoResponse for String {
+    fn into_response(self, _context: &BlueFire) -> Response {
+        make_response(http::StatusCode::OK, "text/plain; charset=utf-8", self.into_bytes())
+    }
+}
+
+impl IntoResponse for &'static str {
+    fn into_response(self, context: &BlueFire) -> Response {
+        self.to_string().into_response(context)
+    }
+}
+
+impl<T, E> IntoResponse for Result<T, E>
+where
+    T: IntoResponse,
+    E: HttpError,
+{
+    /// Converts the successful response or renders the error as a JSON or HTML error response
+    /// depending on the `Accept` header of the request.
+    fn into_response(self, context: &BlueFire) -> Response {
+        match self {
+            Ok(response) => response.into_response(context),
+            Err(error) => rest::handle_error(context, &error),
+        }
+    }
+}
+
+/// Builds a response with the given status, content type and body.
+fn make_response(status: http::StatusCode, content_type: &str, body: Vec<u8>) -> Response {
+    http::response::Builder::new()
+        .status(status)
+        .header(http::header::CONTENT_TYPE, content_type)
+        .body(body)
+        .expect("Build response")
+}
+
+/// Builds a response with empty body and the given status pointing to the given location. If the
+/// location is not a valid header value an "internal server error" response is returned.
+fn make_location_response(status: http::StatusCode, location: &str) -> Response {
+    match http::HeaderValue::from_str(location) {
+        Ok(value) => http::response::Builder::new()
+            .status(status)
+            .header(http::header::LOCATION, value)
+            .body(Vec::new())
+            .expect("Build response"),
+        Err(..) => {
+            log_error!("Invalid location '{}'", location);
+            make_empty_response(http::StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Builds the value of `Content-Disposition` header for an attachment. Besides the ASCII fallback
+/// the header contains the name encoded as described in RFC 6266 so that non-ASCII names are
+/// preserved.
+fn make_content_disposition(filename: &str) -> String {
+    let fallback: String = filename
+        .chars()
+        .filter(|c| *c != '"' && *c != '\\' && (c.is_ascii_graphic() || *c == ' '))
+        .collect();
+    let mut encoded = String::new();
+    for byte in filename.bytes() {
+        match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' => encoded.push(byte as char),
+            b'!' | b'#' | b'$' | b'&' | b'+' | b'-' | b'.' | b'^' | b'_' | b'`' | b'|' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded += &format!("%{:02X}", byte),
+        }
+    }
+    format!("attachment; filename=\"{}\"; filename*=UTF-8''{}", fallback, encoded)
+}
+
+/// Builds a response with empty body and the given status.
+fn make_empty_response(status: http::StatusCode) -> Response {
+    http::response::Builder::new().status(status).body(Vec::new()).expect("Build response")
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// A response with the value serialized to JSON. The default status is "OK".
+#[derive(Clone, Debug)]
+pub struct Json<T> {
+    value: T,
+    status: http::StatusCode,
+}
+
+impl<T> Json<T> {
+    /// Constructs a new `Json`.
+    pub fn new(value: T) -> Self {
+        Self { value, status: http::StatusCode::OK }
+    }
+
+    /// Sets the status of the response.
+    pub fn with_status(mut self, status: http::StatusCode) -> Self {
+        self.status = status;
+        self
+    }
+}
+
+impl<T: Serialize> From<Json<T>> for Response {
+    /// Serializes the value. If it fails an "internal server error" response is returned.
+    fn from(json: Json<T>) -> Response {
+        match serde_json::to_vec(&json.value) {
+            Ok(body) => make_response(json.status, "application/json", body),
+            Err(err) => {
+                log_error!("Failed to serialize response to JSON: {}", err);
+                make_empty_response(http::StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        }
+    }
+}
+
+impl<T: Serialize> IntoResponse for Json<T> {
+    fn into_response(self, _context: &BlueFire) -> Response {
+        self.into()
+    }
+}
+
+/// A response with an HTML page. The default status is "OK".
+#[derive(Clone, Debug)]
+pub struct Html {
+    body: String,
+    status: http::StatusCode,
+}
+
+impl Html {
+    /// Constructs a new `Html`.
+    pub fn new(body: String) -> Self {
+        Self { body, status: http::StatusCode::OK }
+    }
+
+    /// Sets the status of the response.
+    pub fn with_status(mut self, status: http::StatusCode) -> Self {
+        self.status = status;
+        self
+    }
+}
+
+impl From<Html> for Response {
+    fn from(html: Html) -> Response {
+        make_response(html.status, "text/html; charset=utf-8", html.body.into_bytes())
+    }
+}
+
+impl IntoResponse for Html {
+    fn into_response(self, _context: &BlueFire) -> Response {
+        self.into()
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// A response with the data rendered either as JSON or as HTML depending on which of them the
+/// client prefers according to the `Accept` header of the request. JSON is used by default.
+pub struct Negotiated<T, F> {
+    data: T,
+    render_html: F,
+    prefers_html: bool,
+}
+
+impl<T, F> Negotiated<T, F>
+where
+    T: Serialize,
+    F: FnOnce(&T) -> String,
+{
+    /// Constructs a new `Negotiated` rendering HTML with `render_html`.
+    pub fn new(request: &Request, data: T, render_html: F) -> Self {
+        let accept =
+            request.headers().get(http::header::ACCEPT).and_then(|value| value.to_str().ok());
+        Self { data, render_html, prefers_html: rest::prefers_html(accept) }
+    }
+}
+
+impl<T, F> From<Negotiated<T, F>> for Response
+where
+    T: Serialize,
+    F: FnOnce(&T) -> String,
+{
+    fn from(negotiated: Negotiated<T, F>) -> Response {
+        let mut response = if negotiated.prefers_html {
+            Html::new((negotiated.render_html)(&negotiated.data)).into()
+        } else {
+            Json::new(negotiated.data).into()
+        };
+        let vary = http::HeaderValue::from_static("accept");
+        response.headers_mut().append(http::header::VARY, vary);
+        response
+    }
+}
+
+impl<T, F> IntoResponse for Negotiated<T, F>
+where
+    T: Serialize,
+    F: FnOnce(&T) -> String,
+{
+    fn into_response(self, _context: &BlueFire) -> Response {
+        self.into()
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Target of a redirection.
+#[derive(Clone, Debug)]
+enum RedirectTarget {
+    Label { label: String, params: ParamsMap },
+    Uri { uri: String },
+}
+
+/// A redirection. The default status is "see other".
+#[derive(Clone, Debug)]
+pub struct Redirect {
+    target: RedirectTarget,
+    status: http::StatusCode,
+}
+
+impl Redirect {
+    /// Constructs a new `Redirect` to the route with the given label. The path is built by the
+    /// reverse router from the given parameters.
+    pub fn to(label: &str, params: ParamsMap) -> Self {
+        let target = RedirectTarget::Label { label: label.to_string(), params };
+        Self { target, status: http::StatusCode::SEE_OTHER }
+    }
+
+    /// Constructs a new `Redirect` to the given URI.
+    pub fn to_uri(uri: &str) -> Self {
+        let target = RedirectTarget::Uri { uri: uri.to_string() };
+        Self { target, status: http::StatusCode::SEE_OTHER }
+    }
+
+    /// Sets the status of the response, e.g. "moved permanently".
+    pub fn with_status(mut self, status: http::StatusCode) -> Self {
+        self.status = status;
+        self
+    }
+}
+
+impl IntoResponse for Redirect {
+    /// Builds the redirection. If no route has the label an "internal server error" response is
+    /// returned.
+    fn into_response(self, context: &BlueFire) -> Response {
+        let location = match self.target {
+            RedirectTarget::Label { label, params } => match context.reverse(&label) {
+                Some(path) => path.as_path(&params),
+                None => {
+                    log_error!("No route labeled '{}' to redirect to", label);
+                    return make_empty_response(http::StatusCode::INTERNAL_SERVER_ERROR);
+                }
+            },
+            RedirectTarget::Uri { uri } => uri,
+        };
+        make_location_response(self.status, &location)
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// A "no content" response.
+#[derive(Clone, Debug)]
+pub struct NoContent;
+
+impl From<NoContent> for Response {
+    fn from(_: NoContent) -> Response {
+        make_empty_response(http::StatusCode::NO_CONTENT)
+    }
+}
+
+impl IntoResponse for NoContent {
+    fn into_response(self, _context: &BlueFire) -> Response {
+        self.into()
+    }
+}
+
+/// A "created" response pointing to the created resource.
+#[derive(Clone, Debug)]
+pub struct Created {
+    /// Location of the created resource.
+    pub location: String,
+}
+
+impl From<Created> for Response {
+    fn from(created: Created) -> Response {
+        make_location_response(http::StatusCode::CREATED, &created.location)
+    }
+}
+
+impl IntoResponse for Created {
+    fn into_response(self, _context: &BlueFire) -> Response {
+        self.into()
+    }
+}
+
+/// A file sent as an attachment the browser should save instead of displaying.
+#[derive(Clone, Debug)]
+pub struct Attachment {
+    filename: String,
+    content_type: String,
+    data: Vec<u8>,
+}
+
+impl Attachment {
+    /// Constructs a new `Attachment`.
+    pub fn new(filename: &str, content_type: &str, data: Vec<u8>) -> Self {
+        Self { filename: filename.to_string(), content_type: content_type.to_string(), data }
+    }
+}
+
+impl From<Attachment> for Response {
+    /// Builds the response. If the content type is not a valid header value an "internal server
+    /// error" response is returned.
+    fn from(attachment: Attachment) -> Response {
+        let content_type = match http::HeaderValue::from_str(&attachment.content_type) {
+            Ok(content_type) => content_type,
+            Err(..) => {
+                log_error!("Invalid content type '{}'", attachment.content_type);
+                return make_empty_response(http::StatusCode::INTERNAL_SERVER_ERROR);
+            }
+        };
+        http::response::Builder::new()
+            .status(http::StatusCode::OK)
+            .header(http::header::CONTENT_TYPE, content_type)
+            .header(
+                http::header::CONTENT_DISPOSITION,
+                make_content_disposition(&attachment.filename).as_str(),
+            )
+            .body(attachment.data)
+            .expect("Build attachment response")
+    }
+}
+
+impl IntoResponse for Attachment {
+    fn into_response(self, _context: &BlueFire) -> Response {
+        self.into()
+    }
+}
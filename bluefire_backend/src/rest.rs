@@ -6,10 +6,11 @@
 use std::collections::HashMap;
 use std::convert::TryFrom;
 
-use bluefire_twine::constants;
+use bluefire_twine::{constants, validation::ValidationError, ValidationResult};
 
-use crate::common::{Handler, Request, Response};
+use crate::common::{self, Handler, HttpError, ParamsMap, Request, Response};
 use crate::context::BlueFire;
+use crate::response::{IntoResponse, Json};
 
 // -------------------------------------------------------------------------------------------------
 
@@ -30,6 +31,13 @@ pub trait Method {
 
     /// Response type.
     type Response: Into<Response>;
+
+    /// Validates the parsed request. Requests failing validation are rejected before reaching the
+    /// handler. The default implementation accepts all the requests; implementations generated by
+    /// `bluefire_protogen` use the `Validate` implementation of the request.
+    fn validate(_request: &Self::Request) -> ValidationResult<ValidationError> {
+        ValidationResult::new()
+    }
 }
 
 /// Currently in Rust using `?` operator for early exits is ergonomic only in functions returning
@@ -37,6 +45,284 @@ pub trait Method {
 /// the code. Therefore we return a `Result` with the same type in `Ok` and `Err` variants.
 pub type Reply<T> = Result<T, T>;
 
+/// Builds a "bad request" response listing all the errors if the request was parsed but failed
+/// validation (as done by `Method::validate` of the method `M`). Returns `None` if the request is
+/// valid or could not be parsed.
+///
+/// The body of the response has the following form:
+///
+/// ```json
+/// {
+///   "result": "error",
+///   "content": {
+///     "reason": "validation_failed",
+///     "errors": [{ "path": "address.zip", "error": "too_short" }]
+///   }
+/// }
+/// ```
+pub fn validate_request<M, E>(request: &Result<M::Request, E>) -> Option<Response>
+where
+    M: Method,
+{
+    let result = match request {
+        Ok(request) => M::validate(request),
+        Err(..) => return None,
+    };
+
+    if result.has_errors() {
+        Some(make_validation_error_response(&result))
+    } else {
+        None
+    }
+}
+
+/// Builds a "bad request" response listing the given validation errors.
+pub fn make_validation_error_response(result: &ValidationResult<ValidationError>) -> Response {
+    let body = serde_json::json!({
+        "result": "error",
+        "content": {
+            "reason": "validation_failed",
+            "errors": result.get_errors(),
+        },
+    });
+    Json::new(body).with_status(http::StatusCode::BAD_REQUEST).into()
+}
+
+/// Builds a "bad request" response for a request which could not be parsed.
+///
+/// The body of the response has the following form:
+///
+/// ```json
+/// { "result": "error", "content": { "reason": "bad_request", "message": "missing field `id`" } }
+/// ```
+pub fn make_bad_request_response(message: &str) -> Response {
+    let body = serde_json::json!({
+        "result": "error",
+        "content": { "reason": "bad_request", "message": message },
+    });
+    Json::new(body).with_status(http::StatusCode::BAD_REQUEST).into()
+}
+
+/// Builds a "method not allowed" response with empty body listing the allowed methods (e.g.
+/// `"GET, POST"`) in the `Allow` header.
+pub fn make_method_not_allowed_response(allowed: &str) -> Response {
+    http::response::Builder::new()
+        .status(http::StatusCode::METHOD_NOT_ALLOWED)
+        .header(http::header::ALLOW, allowed)
+        .body(Vec::new())
+        .expect("Build method not allowed response")
+}
+
+/// Builds a response to "OPTIONS" request allowing the given methods (e.g. `"GET, POST"`).
+pub fn make_options_response(allowed: &str) -> Response {
+    http::response::Builder::new()
+        .status(http::StatusCode::OK)
+        .header(http::header::ALLOW, allowed)
+        .header(http::header::ACCESS_CONTROL_ALLOW_METHODS, allowed)
+        .header(http::header::ACCESS_CONTROL_ALLOW_HEADERS, constants::BLUEFIRE_TOKEN_HEADER)
+        .body(Vec::new())
+        .expect("Build OPTIONS response")
+}
+
+/// Parses the request and the path parameters of the method `M` and passes them to `handle` if the
+/// request is valid.
+///
+/// Requests which could not be parsed are answered with `make_bad_request_response` and requests
+/// failing validation with `make_validation_error_response`. Used by handlers generated by
+/// `bluefire_protogen`.
+pub fn handle_method<M, F>(context: &BlueFire, request: Request, handle: F) -> Response
+where
+    M: Method,
+    M::PathParams: for<'a> TryFrom<&'a ParamsMap, Error = &'static str>,
+    <M::Request as TryFrom<Request>>::Error: std::fmt::Display,
+    F: FnOnce(&BlueFire, M::Request, M::PathParams) -> Reply<M::Response>,
+{
+    let path = match <M::PathParams as TryFrom<&ParamsMap>>::try_from(context.params()) {
+        Ok(path) => path,
+        Err(param) => return make_bad_request_response(&format!("Missing parameter '{}'", param)),
+    };
+    let request = match <M::Request as TryFrom<Request>>::try_from(request) {
+        Ok(request) => request,
+        Err(err) => return make_bad_request_response(&err.to_string()),
+    };
+    let result = M::validate(&request);
+    if result.has_errors() {
+        return make_validation_error_response(&result);
+    }
+    match handle(context, request, path) {
+        Ok(response) => response.into(),
+        Err(response) => response.into(),
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Tells if the client prefers HTML to JSON according to the value of the `Accept` header. JSON is
+/// preferred if the header is missing or lists neither of the types explicitly.
+pub fn prefers_html(accept: Option<&str>) -> bool {
+    let accept = match accept {
+        Some(accept) => accept,
+        None => return false,
+    };
+    let (mut html, mut json) = (0.0f32, 0.0f32);
+    for range in accept.split(',') {
+        let mut parts = range.split(';');
+        let media_type = parts.next().unwrap_or_default().trim().to_ascii_lowercase();
+        let quality = parts
+            .map(|param| param.trim())
+            .find(|param| param.starts_with("q="))
+            .and_then(|param| param[2..].parse::<f32>().ok())
+            .unwrap_or(1.0);
+        match media_type.as_str() {
+            "text/html" | "application/xhtml+xml" => html = html.max(quality),
+            "application/json" => json = json.max(quality),
+            _ => {}
+        }
+    }
+    html > json
+}
+
+/// Escapes characters with special meaning in HTML.
+fn escape_html(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            '\'' => result.push_str("&#39;"),
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+/// Builds a response describing the error as JSON or as an HTML page depending on the `Accept`
+/// header of the request. The request ID is sent in the `X-Request-Id` header and in the body.
+///
+/// The JSON body has the following form:
+///
+/// ```json
+/// {
+///   "result": "error",
+///   "content": { "reason": "not_found", "message": "Not found", "request_id": "16e0b4e8a1f-0001" }
+/// }
+/// ```
+pub fn make_error_response<E>(error: &E, request_id: &str, accept: Option<&str>) -> Response
+where
+    E: HttpError + ?Sized,
+{
+    let status = error.status();
+    let message = error.message();
+    let (content_type, body) = if prefers_html(accept) {
+        let title =
+            format!("{} {}", status.as_u16(), status.canonical_reason().unwrap_or_default());
+        let body = format!(
+            "<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"><title>{title}</title></head>\n\
+             <body>\n<h1>{title}</h1>\n<p>{message}</p>\n<p><small>Request ID: {id}</small></p>\n\
+             </body>\n</html>\n",
+            title = escape_html(&title),
+            message = escape_html(&message),
+            id = escape_html(request_id),
+        );
+        ("text/html; charset=utf-8", body)
+    } else {
+        let body = serde_json::json!({
+            "result": "error",
+            "content": { "reason": error.reason(), "message": message, "request_id": request_id },
+        });
+        ("application/json", body.to_string())
+    };
+    http::response::Builder::new()
+        .status(status)
+        .header(http::header::CONTENT_TYPE, content_type)
+        .header(common::REQUEST_ID_HEADER, request_id)
+        .body(body.into_bytes())
+        .expect("Build error response")
+}
+
+/// Logs the error with all its sources and the ID of the request and builds a response with
+/// `make_error_response` according to the `Accept` header of the request. Server errors are logged
+/// with the error level, client errors with the info level.
+pub fn handle_error<E>(context: &BlueFire, error: &E) -> Response
+where
+    E: HttpError,
+{
+    let status = error.status();
+    let chain = common::error_chain(error);
+    if status.is_server_error() {
+        log_error!("Request {} failed with {}: {}", context.request_id(), status, chain);
+    } else {
+        log_info!("Request {} failed with {}: {}", context.request_id(), status, chain);
+    }
+    make_error_response(error, context.request_id(), context.accept())
+}
+
+/// Trait for handlers which may fail with an error mapped to an HTTP response.
+pub trait FallibleHandler: std::fmt::Debug + Send + Sync {
+    /// Type of the successful responses, e.g. `Json` or `Redirect`.
+    type Output: IntoResponse = Response;
+
+    /// Type of the returned errors.
+    type Error: HttpError;
+
+    /// Handles the request.
+    fn handle(&self, context: &BlueFire, request: Request) -> Result<Self::Output, Self::Error>;
+}
+
+/// Adapts a `FallibleHandler` to the `Handler` trait. Returned errors are logged and rendered as
+/// JSON or HTML by `handle_error`.
+#[derive(Clone, Debug)]
+pub struct Fallible<H> {
+    handler: H,
+}
+
+impl<H> Fallible<H> {
+    /// Constructs a new `Fallible`.
+    pub fn new(handler: H) -> Self {
+        Self { handler }
+    }
+}
+
+impl<H> Handler for Fallible<H>
+where
+    H: FallibleHandler + Clone + 'static,
+{
+    fn handle(&self, context: &BlueFire, request: Request) -> Response {
+        self.handler.handle(context, request).into_response(context)
+    }
+
+    fn duplicate(&self) -> Box<dyn Handler> {
+        Box::new(self.clone())
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Returns the headers of the request as name-value pairs. Headers with values which are not valid
+/// strings are skipped.
+pub fn header_pairs(request: &Request) -> Vec<(String, String)> {
+    request
+        .headers()
+        .iter()
+        .filter_map(|(name, value)| {
+            value.to_str().ok().map(|value| (name.as_str().to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+/// Returns the cookies sent with the request as name-value pairs.
+pub fn cookie_pairs(request: &Request) -> Vec<(String, String)> {
+    request
+        .headers()
+        .get_all(http::header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(bluefire_twine::message::parse_cookies)
+        .collect()
+}
+
 // -------------------------------------------------------------------------------------------------
 
 /// Trait for simple REST handlers.
@@ -281,6 +567,9 @@ pub trait TypedRestHandler: Handler {
 }
 
 /// Implements `Handler` trait for use with `TypedRestHandler` trait.
+///
+/// Requests which were parsed successfully but failed validation are answered with a "bad request"
+/// response built by `validate_request` without calling the handler.
 #[macro_export]
 macro_rules! impl_handler_via_typed_handler {
     ($type:ty) => {
@@ -294,33 +583,73 @@ macro_rules! impl_handler_via_typed_handler {
                 let mut response = match request.method() {
                     &http::method::Method::OPTIONS => self.options(context, request),
                     &http::method::Method::GET => {
-                        match self.get(context, request.try_into(), params.try_into()) {
-                            Ok(response) => response.into(),
-                            Err(response) => response.into(),
+                        let request = request.try_into();
+                        let validation = bluefire_backend::rest::validate_request::<
+                            <$type as bluefire_backend::rest::TypedRestHandler>::GetMethod,
+                            _,
+                        >(&request);
+                        match validation {
+                            Some(response) => response,
+                            None => match self.get(context, request, params.try_into()) {
+                                Ok(response) => response.into(),
+                                Err(response) => response.into(),
+                            },
                         }
                     }
                     &http::method::Method::POST => {
-                        match self.post(context, request.try_into(), params.try_into()) {
-                            Ok(response) => response.into(),
-                            Err(response) => response.into(),
+                        let request = request.try_into();
+                        let validation = bluefire_backend::rest::validate_request::<
+                            <$type as bluefire_backend::rest::TypedRestHandler>::PostMethod,
+                            _,
+                        >(&request);
+                        match validation {
+                            Some(response) => response,
+                            None => match self.post(context, request, params.try_into()) {
+                                Ok(response) => response.into(),
+                                Err(response) => response.into(),
+                            },
                         }
                     }
                     &http::method::Method::PUT => {
-                        match self.put(context, request.try_into(), params.try_into()) {
-                            Ok(response) => response.into(),
-                            Err(response) => response.into(),
+                        let request = request.try_into();
+                        let validation = bluefire_backend::rest::validate_request::<
+                            <$type as bluefire_backend::rest::TypedRestHandler>::PutMethod,
+                            _,
+                        >(&request);
+                        match validation {
+                            Some(response) => response,
+                            None => match self.put(context, request, params.try_into()) {
+                                Ok(response) => response.into(),
+                                Err(response) => response.into(),
+                            },
                         }
                     }
                     &http::method::Method::PATCH => {
-                        match self.patch(context, request.try_into(), params.try_into()) {
-                            Ok(response) => response.into(),
-                            Err(response) => response.into(),
+                        let request = request.try_into();
+                        let validation = bluefire_backend::rest::validate_request::<
+                            <$type as bluefire_backend::rest::TypedRestHandler>::PatchMethod,
+                            _,
+                        >(&request);
+                        match validation {
+                            Some(response) => response,
+                            None => match self.patch(context, request, params.try_into()) {
+                                Ok(response) => response.into(),
+                                Err(response) => response.into(),
+                            },
                         }
                     }
                     &http::method::Method::DELETE => {
-                        match self.delete(context, request.try_into(), params.try_into()) {
-                            Ok(response) => response.into(),
-                            Err(response) => response.into(),
+                        let request = request.try_into();
+                        let validation = bluefire_backend::rest::validate_request::<
+                            <$type as bluefire_backend::rest::TypedRestHandler>::DeleteMethod,
+                            _,
+                        >(&request);
+                        match validation {
+                            Some(response) => response,
+                            None => match self.delete(context, request, params.try_into()) {
+                                Ok(response) => response.into(),
+                                Err(response) => response.into(),
+                            },
                         }
                     }
                     _ => self.make_default_response(request),
@@ -353,7 +682,7 @@ macro_rules! default_get_method {
         ) -> bluefire_backend::rest::Reply<bluefire_backend::rest::DefaultResponse> {
             Ok(bluefire_backend::rest::DefaultResponse)
         }
-    }
+    };
 }
 
 /// Default implementation for "POST" method. Returns an empty message with "method not allowed"
@@ -370,7 +699,7 @@ macro_rules! default_post_method {
         ) -> bluefire_backend::rest::Reply<bluefire_backend::rest::DefaultResponse> {
             Ok(bluefire_backend::rest::DefaultResponse)
         }
-    }
+    };
 }
 
 /// Default implementation for "PUT" method. Returns an empty message with "method not allowed"
@@ -387,7 +716,7 @@ macro_rules! default_put_method {
         ) -> bluefire_backend::rest::Reply<bluefire_backend::rest::DefaultResponse> {
             Ok(bluefire_backend::rest::DefaultResponse)
         }
-    }
+    };
 }
 
 /// Default implementation for "PATCH" method. Returns an empty message with "method not allowed"
@@ -404,7 +733,7 @@ macro_rules! default_patch_method {
         ) -> bluefire_backend::rest::Reply<bluefire_backend::rest::DefaultResponse> {
             Ok(bluefire_backend::rest::DefaultResponse)
         }
-    }
+    };
 }
 
 /// Default implementation for "DELETE" method. Returns an empty message with "method not allowed"
@@ -421,5 +750,5 @@ macro_rules! default_delete_method {
         ) -> bluefire_backend::rest::Reply<bluefire_backend::rest::DefaultResponse> {
             Ok(bluefire_backend::rest::DefaultResponse)
         }
-    }
+    };
 }
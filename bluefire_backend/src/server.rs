@@ -9,6 +9,18 @@ use std::sync::{Arc, Mutex};
 use crate::common;
 use crate::context::{BlueFireKindler, BlueFireWielder};
 
+/// Default limit of the size of request bodies (16 MiB).
+pub const DEFAULT_BODY_LIMIT: usize = 16 * 1024 * 1024;
+
+/// Error returned when reading the request body fails.
+enum BodyError {
+    /// The body could not be read.
+    Read(hyper::Error),
+
+    /// The body is larger than allowed.
+    TooLarge,
+}
+
 pub struct BlueFireNewService {
     bluefire_kindler: BlueFireKindler,
 }
@@ -28,18 +40,20 @@ impl hyper::service::NewService for BlueFireNewService {
     type Future = Box<dyn future::Future<Item = Self::Service, Error = Self::InitError> + Send>;
     fn new_service(&self) -> Self::Future {
         log_debug!("BlueFire: staring a new service");
-        let service = BlueFireService::new(Arc::new(Mutex::new(self.bluefire_kindler.kindle())));
+        let wielder = Arc::new(Mutex::new(self.bluefire_kindler.kindle()));
+        let service = BlueFireService::new(wielder, self.bluefire_kindler.get_body_limit());
         Box::new(future::ok(service))
     }
 }
 
 pub struct BlueFireService {
     bluefire_wielder: Arc<Mutex<BlueFireWielder>>,
+    body_limit: usize,
 }
 
 impl BlueFireService {
-    pub fn new(bluefire_wielder: Arc<Mutex<BlueFireWielder>>) -> Self {
-        BlueFireService { bluefire_wielder }
+    pub fn new(bluefire_wielder: Arc<Mutex<BlueFireWielder>>, body_limit: usize) -> Self {
+        BlueFireService { bluefire_wielder, body_limit }
     }
 }
 
@@ -53,6 +67,20 @@ impl BlueFireService {
         let new_body = hyper::Body::from(original_body);
         http::Response::from_parts(parts, new_body)
     }
+
+    fn too_large_response() -> http::Response<hyper::Body> {
+        let mut response = http::Response::new(hyper::Body::empty());
+        *response.status_mut() = http::StatusCode::PAYLOAD_TOO_LARGE;
+        response
+    }
+
+    fn content_length(parts: &http::request::Parts) -> Option<usize> {
+        parts
+            .headers
+            .get(http::header::CONTENT_LENGTH)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse().ok())
+    }
 }
 
 impl hyper::service::Service for BlueFireService {
@@ -64,15 +92,66 @@ impl hyper::service::Service for BlueFireService {
 
     fn call(&mut self, req: http::Request<Self::ReqBody>) -> Self::Future {
         let bluefire_wielder = self.bluefire_wielder.clone();
+        let limit = self.body_limit;
         let (parts, original_body) = req.into_parts();
-        Box::new(original_body.concat2().and_then(move |data| {
-            let req = Self::repack_request(parts, data.to_vec());
-            let resp = {
-                let mut bluefire = bluefire_wielder.lock().expect("Mutex lock");
-                bluefire.serve(req)
-            };
-            let resp = Self::repack_response(resp);
-            future::ok(resp)
+        if let Some(length) = Self::content_length(&parts) {
+            if length > limit {
+                log_info!("Rejecting request with body of {} bytes (limit {})", length, limit);
+                return Box::new(future::ok(Self::too_large_response()));
+            }
+        }
+
+        let body =
+            original_body.map_err(BodyError::Read).fold(Vec::new(), move |mut data, chunk| {
+                if data.len() + chunk.len() > limit {
+                    Err(BodyError::TooLarge)
+                } else {
+                    data.extend_from_slice(&chunk);
+                    Ok(data)
+                }
+            });
+        Box::new(body.then(move |result| match result {
+            Ok(data) => {
+                let req = Self::repack_request(parts, data);
+                let resp = {
+                    let mut bluefire = bluefire_wielder.lock().expect("Mutex lock");
+                    bluefire.serve(req)
+                };
+                Ok(Self::repack_response(resp))
+            }
+            Err(BodyError::TooLarge) => {
+                log_info!("Rejecting request with body exceeding the limit of {} bytes", limit);
+                Ok(Self::too_large_response())
+            }
+            Err(BodyError::Read(err)) => Err(err),
         }))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::router::RoutingBuilder;
+
+    fn call(body: &'static str, content_length: Option<&str>) -> http::StatusCode {
+        let wielder = BlueFireKindler::start(Box::new(RoutingBuilder::new())).kindle();
+        let mut service = BlueFireService::new(Arc::new(Mutex::new(wielder)), 8);
+        let mut request = http::Request::new(hyper::Body::from(body));
+        if let Some(length) = content_length {
+            let value = http::HeaderValue::from_str(length).expect("Header value");
+            request.headers_mut().insert(http::header::CONTENT_LENGTH, value);
+        }
+        let response =
+            hyper::service::Service::call(&mut service, request).wait().expect("Response");
+        response.status()
+    }
+
+    #[test]
+    fn test_body_limit() {
+        assert_eq!(call("12345678", None), http::StatusCode::NOT_FOUND);
+        assert_eq!(call("123456789", None), http::StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(call("1234", Some("4")), http::StatusCode::NOT_FOUND);
+        assert_eq!(call("1234", Some("1000")), http::StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
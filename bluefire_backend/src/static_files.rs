@@ -5,44 +5,577 @@
 //!
 //! Mainly for use with `bluefire_static_files_macros`.
 
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+
 use crate::{
     common::{Handler, Request, Response},
+    compression::{self, Encoding},
     context::BlueFire,
 };
 
+/// Format of dates in HTTP headers (IMF-fixdate).
+const HTTP_DATE_FORMAT: &str = "%a, %d %b %Y %H:%M:%S GMT";
+
+/// Max age used for immutable resources (one year).
+const IMMUTABLE_MAX_AGE: u32 = 31_536_000;
+
+// -------------------------------------------------------------------------------------------------
+
+mod utils {
+    /// Formats the given time as an HTTP date.
+    pub fn format_http_date(time: &chrono::DateTime<chrono::Utc>) -> String {
+        time.format(super::HTTP_DATE_FORMAT).to_string()
+    }
+
+    /// Parses an HTTP date.
+    pub fn parse_http_date(date: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+        chrono::DateTime::parse_from_rfc2822(date.trim())
+            .ok()
+            .map(|time| time.with_timezone(&chrono::Utc))
+    }
+
+    /// Checks if any of the entity tags listed in `If-None-Match` header matches one of the given
+    /// tags. Uses the weak comparison.
+    pub fn etag_matches(header: &str, etags: &[String]) -> bool {
+        fn strip_weak(etag: &str) -> &str {
+            let etag = etag.trim();
+            if etag.starts_with("W/") {
+                &etag[2..]
+            } else {
+                etag
+            }
+        }
+
+        header.split(',').map(strip_weak).any(|requested| {
+            requested == "*" || etags.iter().any(|etag| strip_weak(etag) == requested)
+        })
+    }
+
+    /// Represents a single byte range requested by a client.
+    #[derive(Debug, PartialEq, Eq)]
+    pub enum ByteRange {
+        /// The range can be satisfied. Both bounds are inclusive.
+        Satisfiable { first: usize, last: usize },
+
+        /// The range cannot be satisfied.
+        Unsatisfiable,
+    }
+
+    /// Parses `Range` header with a single byte range. Returns `None` if the header is malformed,
+    /// uses other units or contains many ranges - in such case the `Range` header should be
+    /// ignored.
+    pub fn parse_range(header: &str, length: usize) -> Option<ByteRange> {
+        let header = header.trim();
+        if !header.starts_with("bytes=") {
+            return None;
+        }
+
+        let spec = header["bytes=".len()..].trim();
+        if spec.contains(',') {
+            return None;
+        }
+
+        let dash = spec.find('-')?;
+        let (start, end) = (spec[..dash].trim(), spec[dash + 1..].trim());
+        if start.is_empty() {
+            let suffix = end.parse::<usize>().ok()?;
+            if suffix == 0 || length == 0 {
+                Some(ByteRange::Unsatisfiable)
+            } else {
+                let first = length.saturating_sub(suffix);
+                Some(ByteRange::Satisfiable { first, last: length - 1 })
+            }
+        } else {
+            let first = start.parse::<usize>().ok()?;
+            let last = if end.is_empty() { None } else { Some(end.parse::<usize>().ok()?) };
+            if let Some(last) = last {
+                if last < first {
+                    return None;
+                }
+            }
+            if first >= length {
+                Some(ByteRange::Unsatisfiable)
+            } else {
+                let last = last.map(|last| std::cmp::min(last, length - 1)).unwrap_or(length - 1);
+                Some(ByteRange::Satisfiable { first, last })
+            }
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Describes how clients and proxies are allowed to cache a static resource.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CachePolicy {
+    /// The resource may be cached but must be revalidated with the server before each use.
+    /// Revalidation is cheap thanks to `ETag` and `Last-Modified` headers.
+    Revalidate,
+
+    /// The resource may be cached for the given number of seconds.
+    MaxAge(u32),
+
+    /// The resource never changes under its URL (e.g. its name contains a hash of the content) and
+    /// may be cached for a long time without revalidation.
+    Immutable,
+
+    /// The resource must not be cached.
+    NoStore,
+}
+
+impl CachePolicy {
+    /// Returns the value of the `Cache-Control` header for this policy.
+    pub fn to_header_value(&self) -> String {
+        match self {
+            CachePolicy::Revalidate => "public, no-cache".to_string(),
+            CachePolicy::MaxAge(max_age) => format!("public, max-age={}", max_age),
+            CachePolicy::Immutable => format!("public, max-age={}, immutable", IMMUTABLE_MAX_AGE),
+            CachePolicy::NoStore => "no-store".to_string(),
+        }
+    }
+}
+
+impl Default for CachePolicy {
+    fn default() -> Self {
+        CachePolicy::Revalidate
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
 /// Handler for static files. Takes care for adding content type and cache related headers.
+///
+/// Besides the original content the handler may hold variants of the content precompressed at
+/// build time. The variant to be sent is chosen basing on the `Accept-Encoding` request header.
+///
+/// Conditional requests (`If-None-Match`, `If-Modified-Since`) are answered with
+/// `304 Not Modified` and single byte ranges (`Range`, `If-Range`) with `206 Partial Content`.
 #[derive(Clone, Debug)]
 pub struct StaticHandler {
     /// The content to be returned.
-    content: Vec<u8>,
+    content: Arc<Vec<u8>>,
+
+    /// Precompressed variants of the content in order of preference.
+    encoded: Vec<(Encoding, Arc<Vec<u8>>)>,
 
     /// Content type.
     content_type: String,
 
-    /// Time of creation of this handler.
-    last_modified: String,
+    /// Entity tag of the content (without quotes). Usually a hash of the content.
+    etag: Option<String>,
+
+    /// Time of the last modification of the content.
+    last_modified: chrono::DateTime<chrono::Utc>,
+
+    /// Caching policy.
+    cache_policy: CachePolicy,
 }
 
 impl StaticHandler {
-    /// Constructs a new `StaticHandler`.
+    /// Constructs a new `StaticHandler`. The last modification time is set to the current time.
     pub fn new(content: Vec<u8>, content_type: String) -> Self {
-        let last_modified = chrono::Utc::now().format("%a, %d %m %Y %H:%M:%S GMT").to_string();
-        Self { content, content_type, last_modified }
+        use chrono::TimeZone;
+        let last_modified = chrono::Utc.timestamp(chrono::Utc::now().timestamp(), 0);
+        Self {
+            content: Arc::new(content),
+            encoded: Vec::new(),
+            content_type,
+            etag: None,
+            last_modified,
+            cache_policy: CachePolicy::default(),
+        }
+    }
+
+    /// Adds a variant of the content precompressed with the given encoding.
+    pub fn with_encoded(mut self, encoding: Encoding, content: Vec<u8>) -> Self {
+        if encoding != Encoding::Identity {
+            self.encoded.push((encoding, Arc::new(content)));
+        }
+        self
+    }
+
+    /// Sets the entity tag of the content. The tag should be unique for the content, e.g. its
+    /// hash computed at build time.
+    pub fn with_etag(mut self, etag: &str) -> Self {
+        self.etag = Some(etag.trim_matches('"').to_string());
+        self
+    }
+
+    /// Sets the time of the last modification of the content.
+    pub fn with_last_modified(mut self, last_modified: chrono::DateTime<chrono::Utc>) -> Self {
+        self.last_modified = last_modified;
+        self
+    }
+
+    /// Sets the time of the last modification of the content as a number of seconds since Unix
+    /// epoch.
+    pub fn with_last_modified_timestamp(self, timestamp: i64) -> Self {
+        use chrono::TimeZone;
+        self.with_last_modified(chrono::Utc.timestamp(timestamp, 0))
+    }
+
+    /// Sets the caching policy.
+    pub fn with_cache_policy(mut self, cache_policy: CachePolicy) -> Self {
+        self.cache_policy = cache_policy;
+        self
+    }
+}
+
+impl StaticHandler {
+    /// Chooses the content variant matching the `Accept-Encoding` header of the request.
+    fn select_content(&self, request: &Request) -> (Encoding, Arc<Vec<u8>>) {
+        let accept_encoding = request
+            .headers()
+            .get(http::header::ACCEPT_ENCODING)
+            .and_then(|value| value.to_str().ok());
+
+        let mut available: Vec<Encoding> =
+            self.encoded.iter().map(|(encoding, _)| *encoding).collect();
+        available.push(Encoding::Identity);
+
+        match compression::negotiate(accept_encoding, &available) {
+            Some(Encoding::Identity) | None => (Encoding::Identity, self.content.clone()),
+            Some(encoding) => {
+                let content = self
+                    .encoded
+                    .iter()
+                    .find(|(e, _)| *e == encoding)
+                    .map(|(_, content)| content.clone())
+                    .expect("Find encoded content");
+                (encoding, content)
+            }
+        }
+    }
+
+    /// Returns the entity tag of the given representation of the content. Different encodings
+    /// are different representations so they get different tags.
+    fn make_etag(&self, encoding: Encoding) -> Option<String> {
+        self.etag.as_ref().map(|etag| match encoding.extension() {
+            Some(extension) => format!("\"{}-{}\"", etag, extension),
+            None => format!("\"{}\"", etag),
+        })
+    }
+
+    /// Returns entity tags of all representations of the content.
+    fn make_all_etags(&self) -> Vec<String> {
+        let mut etags: Vec<String> = self.make_etag(Encoding::Identity).into_iter().collect();
+        for (encoding, _) in self.encoded.iter() {
+            etags.extend(self.make_etag(*encoding));
+        }
+        etags
+    }
+
+    /// Checks if the client already has the current version of the content.
+    fn is_not_modified(&self, request: &Request) -> bool {
+        let headers = request.headers();
+        if let Some(if_none_match) = headers.get(http::header::IF_NONE_MATCH) {
+            // `If-Modified-Since` must be ignored when `If-None-Match` is present.
+            return match if_none_match.to_str() {
+                Ok(if_none_match) => utils::etag_matches(if_none_match, &self.make_all_etags()),
+                Err(..) => false,
+            };
+        }
+
+        if let Some(if_modified_since) = headers.get(http::header::IF_MODIFIED_SINCE) {
+            if let Some(since) = if_modified_since.to_str().ok().and_then(utils::parse_http_date) {
+                return self.last_modified <= since;
+            }
+        }
+
+        false
+    }
+
+    /// Checks if the `Range` header should be taken into account according to `If-Range` header.
+    fn is_range_applicable(&self, request: &Request) -> bool {
+        match request.headers().get(http::header::IF_RANGE) {
+            Some(if_range) => match if_range.to_str() {
+                Ok(if_range) => {
+                    let if_range = if_range.trim();
+                    if if_range.starts_with('"') {
+                        // Strong comparison is required for `If-Range`.
+                        self.make_etag(Encoding::Identity).map_or(false, |etag| etag == if_range)
+                    } else {
+                        // Dates in headers have one second precision.
+                        utils::parse_http_date(if_range)
+                            .map(|date| self.last_modified.timestamp() == date.timestamp())
+                            .unwrap_or(false)
+                    }
+                }
+                Err(..) => false,
+            },
+            None => true,
+        }
+    }
+
+    /// Prepares a response builder with headers common for all responses.
+    fn make_builder(
+        &self,
+        status: http::StatusCode,
+        encoding: Encoding,
+    ) -> http::response::Builder {
+        let mut builder = http::response::Builder::new();
+        builder
+            .status(status)
+            .header(http::header::CONTENT_TYPE, self.content_type.as_str())
+            .header(
+                http::header::LAST_MODIFIED,
+                utils::format_http_date(&self.last_modified).as_str(),
+            )
+            .header(http::header::CACHE_CONTROL, self.cache_policy.to_header_value().as_str())
+            .header(http::header::ACCEPT_RANGES, "bytes");
+        if let Some(etag) = self.make_etag(encoding) {
+            builder.header(http::header::ETAG, etag.as_str());
+        }
+        if !self.encoded.is_empty() {
+            builder.header(http::header::VARY, "Accept-Encoding");
+        }
+        builder
     }
 }
 
 impl Handler for StaticHandler {
+    fn handle(&self, _context: &BlueFire, request: Request) -> Response {
+        let (encoding, content) = self.select_content(&request);
+
+        if self.is_not_modified(&request) {
+            return self
+                .make_builder(http::StatusCode::NOT_MODIFIED, encoding)
+                .body(Vec::new())
+                .expect("Build not modified response");
+        }
+
+        // Ranges are served only from the not encoded content.
+        let range = request
+            .headers()
+            .get(http::header::RANGE)
+            .and_then(|range| range.to_str().ok())
+            .filter(|_| {
+                *request.method() == http::Method::GET && self.is_range_applicable(&request)
+            })
+            .and_then(|range| utils::parse_range(range, self.content.len()));
+
+        match range {
+            Some(utils::ByteRange::Satisfiable { first, last }) => {
+                let content_range = format!("bytes {}-{}/{}", first, last, self.content.len());
+                self.make_builder(http::StatusCode::PARTIAL_CONTENT, Encoding::Identity)
+                    .header(http::header::CONTENT_RANGE, content_range.as_str())
+                    .body(self.content[first..=last].to_vec())
+                    .expect("Build partial content response")
+            }
+            Some(utils::ByteRange::Unsatisfiable) => {
+                let content_range = format!("bytes */{}", self.content.len());
+                self.make_builder(http::StatusCode::RANGE_NOT_SATISFIABLE, Encoding::Identity)
+                    .header(http::header::CONTENT_RANGE, content_range.as_str())
+                    .body(Vec::new())
+                    .expect("Build range not satisfiable response")
+            }
+            None => {
+                let mut builder = self.make_builder(http::StatusCode::OK, encoding);
+                if encoding != Encoding::Identity {
+                    builder.header(http::header::CONTENT_ENCODING, encoding.as_str());
+                }
+                builder.body(content.as_ref().clone()).expect("Build response")
+            }
+        }
+    }
+
+    fn duplicate(&self) -> Box<dyn Handler> {
+        Box::new(self.clone())
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// A function transforming a source file into the content to be served (e.g. compiling SCSS into
+/// CSS). Returns a description of the problem on failure.
+pub type Processor = fn(&Path) -> Result<Vec<u8>, String>;
+
+/// Modification times of a set of files. Two snapshots differ if any of the files was added,
+/// removed or modified.
+pub(crate) type Snapshot = Vec<(PathBuf, Option<SystemTime>)>;
+
+/// Returns the modification times of the given files. Directories are listed recursively.
+pub(crate) fn read_modification_times(paths: &[PathBuf]) -> Snapshot {
+    fn visit(path: &Path, snapshot: &mut Snapshot) {
+        if path.is_dir() {
+            let mut entries: Vec<PathBuf> = match std::fs::read_dir(path) {
+                Ok(entries) => entries.filter_map(|entry| entry.ok()).map(|e| e.path()).collect(),
+                Err(..) => Vec::new(),
+            };
+            entries.sort();
+            for entry in entries {
+                visit(&entry, snapshot);
+            }
+        } else {
+            let modified = std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok();
+            snapshot.push((path.to_path_buf(), modified));
+        }
+    }
+
+    let mut snapshot = Vec::new();
+    for path in paths {
+        visit(path, &mut snapshot);
+    }
+    snapshot
+}
+
+/// Processed content of a file together with the modification times of the files it was made from.
+type Cached = Option<(Snapshot, Arc<Vec<u8>>)>;
+
+/// Handler serving a file read from the disk on every request. Intended for development where the
+/// files change while the server is running.
+///
+/// The content is reloaded (and processed with the optional `Processor`) only if the file or any of
+/// its dependencies (e.g. SCSS partials imported by the file) changed. Responses are never cached
+/// by clients.
+#[derive(Clone, Debug)]
+pub struct DiskHandler {
+    path: PathBuf,
+    content_type: String,
+    processor: Option<Processor>,
+    dependencies: Vec<PathBuf>,
+    cached: Arc<Mutex<Cached>>,
+}
+
+impl DiskHandler {
+    /// Constructs a new `DiskHandler` serving the file under the given path.
+    pub fn new(path: PathBuf, content_type: String) -> Self {
+        Self {
+            path,
+            content_type,
+            processor: None,
+            dependencies: Vec::new(),
+            cached: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Sets the processor transforming the file before serving it.
+    pub fn with_processor(mut self, processor: Processor) -> Self {
+        self.processor = Some(processor);
+        self
+    }
+
+    /// Adds a file or a directory (watched recursively) the content depends on. The content is
+    /// processed again whenever any of the dependencies changes.
+    pub fn with_dependency(mut self, path: PathBuf) -> Self {
+        self.dependencies.push(path);
+        self
+    }
+}
+
+impl DiskHandler {
+    /// Returns the content of the file, reading and processing it again if it or any of its
+    /// dependencies was modified.
+    fn load(&self) -> Result<Arc<Vec<u8>>, String> {
+        std::fs::metadata(&self.path)
+            .and_then(|metadata| metadata.modified())
+            .map_err(|err| format!("Read metadata of {:?}: {}", self.path, err))?;
+
+        let mut paths = vec![self.path.clone()];
+        paths.extend(self.dependencies.iter().cloned());
+        let snapshot = read_modification_times(&paths);
+
+        let mut cached = self.cached.lock().expect("Lock cached content");
+        if let Some((cached_snapshot, content)) = cached.as_ref() {
+            if *cached_snapshot == snapshot {
+                return Ok(content.clone());
+            }
+        }
+
+        let content = match self.processor {
+            Some(processor) => processor(&self.path)?,
+            None => std::fs::read(&self.path)
+                .map_err(|err| format!("Read file {:?}: {}", self.path, err))?,
+        };
+        let content = Arc::new(content);
+        *cached = Some((snapshot, content.clone()));
+        Ok(content)
+    }
+}
+
+impl Handler for DiskHandler {
     fn handle(&self, _context: &BlueFire, _request: Request) -> Response {
-        http::response::Builder::new()
-            .status(http::StatusCode::OK)
-            .header(http::header::CONTENT_TYPE, &self.content_type)
-            .header(http::header::LAST_MODIFIED, &self.last_modified)
-            .header(http::header::CACHE_CONTROL, "public")
-            .body(self.content.clone())
-            .expect("Build response")
+        let mut builder = http::response::Builder::new();
+        builder
+            .header(http::header::CACHE_CONTROL, CachePolicy::NoStore.to_header_value().as_str());
+        match self.load() {
+            Ok(content) => builder
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, self.content_type.as_str())
+                .body(content.as_ref().clone())
+                .expect("Build response"),
+            Err(err) => {
+                log_error!("Failed to serve {:?}: {}", self.path, err);
+                builder
+                    .status(http::StatusCode::INTERNAL_SERVER_ERROR)
+                    .header(http::header::CONTENT_TYPE, "text/plain; charset=utf-8")
+                    .body(err.into_bytes())
+                    .expect("Build error response")
+            }
+        }
     }
 
     fn duplicate(&self) -> Box<dyn Handler> {
         Box::new(self.clone())
     }
 }
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::read_modification_times;
+    use super::utils::{etag_matches, format_http_date, parse_http_date, parse_range, ByteRange};
+
+    #[test]
+    fn test_http_date() {
+        use chrono::TimeZone;
+        let time = chrono::Utc.ymd(1994, 11, 6).and_hms(8, 49, 37);
+        assert_eq!(format_http_date(&time), "Sun, 06 Nov 1994 08:49:37 GMT");
+        assert_eq!(parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT"), Some(time));
+        assert_eq!(parse_http_date("06/11/1994"), None);
+    }
+
+    #[test]
+    fn test_etag_matches() {
+        let etags = vec!["\"abc\"".to_string(), "\"abc-br\"".to_string()];
+        assert!(etag_matches("\"abc\"", &etags));
+        assert!(etag_matches("\"xyz\", W/\"abc-br\"", &etags));
+        assert!(etag_matches("*", &etags));
+        assert!(!etag_matches("\"xyz\"", &etags));
+    }
+
+    #[test]
+    fn test_parse_range() {
+        let s = |first, last| Some(ByteRange::Satisfiable { first, last });
+        assert_eq!(parse_range("bytes=0-99", 1000), s(0, 99));
+        assert_eq!(parse_range("bytes=900-", 1000), s(900, 999));
+        assert_eq!(parse_range("bytes=-100", 1000), s(900, 999));
+        assert_eq!(parse_range("bytes=900-2000", 1000), s(900, 999));
+        assert_eq!(parse_range("bytes=1000-", 1000), Some(ByteRange::Unsatisfiable));
+        assert_eq!(parse_range("bytes=0-1,5-6", 1000), None);
+        assert_eq!(parse_range("items=0-1", 1000), None);
+        assert_eq!(parse_range("bytes=5-1", 1000), None);
+    }
+
+    #[test]
+    fn test_read_modification_times() {
+        let dir = std::env::temp_dir().join("bluefire_backend_modification_times");
+        std::fs::create_dir_all(dir.join("partials")).unwrap();
+        std::fs::write(dir.join("theme.scss"), "@import 'partials/base';").unwrap();
+        std::fs::write(dir.join("partials").join("_base.scss"), "body {}").unwrap();
+
+        let snapshot = read_modification_times(&[dir.clone()]);
+        let paths: Vec<_> = snapshot.iter().map(|(path, _)| path.clone()).collect();
+        assert!(paths.contains(&dir.join("theme.scss")));
+        assert!(paths.contains(&dir.join("partials").join("_base.scss")));
+
+        std::fs::write(dir.join("partials").join("_colors.scss"), "$color: red;").unwrap();
+        assert_ne!(read_modification_times(&[dir.clone()]), snapshot);
+        std::fs::remove_file(dir.join("partials").join("_colors.scss")).unwrap();
+    }
+}
@@ -3,7 +3,7 @@
 
 //! Functionality related to translations.
 
-use crate::{BlueFire, Extension, Request};
+use crate::{BlueFire, BlueFireError, Extension, Request};
 pub use bluefire_translations::TranslationProvider;
 
 // -------------------------------------------------------------------------------------------------
@@ -63,7 +63,7 @@ impl Extension for TranslationExtension {
         "BlueFire:Translations"
     }
 
-    fn check(&self) -> Result<(), ()> {
+    fn check(&self) -> Result<(), BlueFireError> {
         Ok(())
     }
 
@@ -9,7 +9,7 @@ use bluefire_twine::id::Id;
 
 use bluefire_backend::authentication::prelude::*;
 use bluefire_backend::database::{DataProvider, Database};
-use bluefire_backend::Extension;
+use bluefire_backend::{BlueFireError, Extension};
 
 pub const INVALID_SESSION_ID: &str = "FFFFFFFFFFFFFFFFFFFFFFFF";
 pub const VALID_SESSION_ID: &str = "0102030405060708090A0B0C";
@@ -32,7 +32,7 @@ impl Extension for FakeDatabase {
         "BlueFire:FakeDatabase"
     }
 
-    fn check(&self) -> Result<(), ()> {
+    fn check(&self) -> Result<(), BlueFireError> {
         Ok(())
     }
 
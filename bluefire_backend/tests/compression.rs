@@ -0,0 +1,127 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy of
+// the MPL was not distributed with this file, You can obtain one at http://mozilla.org/MPL/2.0/
+
+//! Tests for `bluefire_backend::compression` module.
+
+pub mod common;
+
+use bluefire_backend::{compression::*, router::*, static_files::StaticHandler, *};
+
+mod env {
+    use super::*;
+    use crate::common::handlers::TestHandler;
+
+    pub const BIG_ID: &str = "big";
+
+    pub struct Env {
+        pub wielder: BlueFireWielder,
+    }
+
+    impl Env {
+        pub fn new(compression: Compression) -> Env {
+            let big_id: String = std::iter::repeat(BIG_ID).take(1000).collect();
+            let static_handler = StaticHandler::new(b"identity".to_vec(), "text/css".to_string())
+                .with_encoded(Encoding::Brotli, b"brotli".to_vec())
+                .with_encoded(Encoding::Gzip, b"gzip".to_vec());
+            let plain_handler =
+                StaticHandler::new(b"plain text".to_vec(), "text/plain".to_string());
+
+            let mut builder = RoutingBuilder::new();
+            builder.insert(
+                Host::new_nameless(),
+                Route::index().with_routes(vec![
+                    Route::exact("small").with_view(TestHandler::new("small")),
+                    Route::exact("big").with_view(TestHandler::new(&big_id)),
+                    Route::exact("static").with_view(Box::new(static_handler)),
+                    Route::exact("plain").with_view(Box::new(plain_handler)),
+                ]),
+            );
+
+            let wielder = BlueFireKindler::start(Box::new(builder)).compress(compression).kindle();
+            Env { wielder }
+        }
+
+        pub fn exec(&mut self, uri: &str, accept_encoding: Option<&str>) -> Response {
+            let mut builder = http::request::Builder::new();
+            builder.method(http::method::Method::GET).uri(uri);
+            if let Some(accept_encoding) = accept_encoding {
+                builder.header(http::header::ACCEPT_ENCODING, accept_encoding);
+            }
+            let request = builder.body("".into()).expect("Failed to build empty GET body");
+            self.wielder.serve(request)
+        }
+    }
+}
+
+fn content_encoding(response: &Response) -> Option<&str> {
+    response.headers().get(http::header::CONTENT_ENCODING).map(|value| value.to_str().unwrap())
+}
+
+#[test]
+fn test_small_response_is_not_compressed() {
+    let mut env = env::Env::new(Compression::new());
+    let response = env.exec("/small", Some("gzip, br"));
+    assert_eq!(content_encoding(&response), None);
+    assert_eq!(*response.body(), b"small".to_vec());
+    assert_eq!(response.headers().get(http::header::VARY).unwrap(), "Accept-Encoding");
+}
+
+#[test]
+fn test_big_response_is_compressed() {
+    let mut env = env::Env::new(Compression::new());
+
+    let response = env.exec("/big", Some("gzip, br"));
+    assert_eq!(content_encoding(&response), Some("br"));
+    assert!(response.body().len() < 3000);
+
+    let response = env.exec("/big", Some("gzip"));
+    assert_eq!(content_encoding(&response), Some("gzip"));
+    assert!(response.body().len() < 3000);
+
+    let response = env.exec("/big", None);
+    assert_eq!(content_encoding(&response), None);
+    assert_eq!(response.body().len(), 3000);
+}
+
+#[test]
+fn test_unacceptable_encoding_falls_back_to_identity() {
+    let mut env = env::Env::new(Compression::new());
+    let response = env.exec("/big", Some("identity;q=0, deflate"));
+    assert_eq!(response.status(), http::StatusCode::OK);
+    assert_eq!(content_encoding(&response), None);
+    assert_eq!(response.body().len(), 3000);
+
+    let response = env.exec("/static", Some("identity;q=0"));
+    assert_eq!(response.status(), http::StatusCode::OK);
+    assert_eq!(content_encoding(&response), None);
+    assert_eq!(*response.body(), b"identity".to_vec());
+}
+
+#[test]
+fn test_excluded_content_type_is_not_compressed() {
+    let mut env = env::Env::new(Compression::new().with_min_size(0));
+    let response = env.exec("/plain", Some("gzip, br"));
+    assert_eq!(content_encoding(&response), Some("br"));
+
+    let mut env = env::Env::new(Compression::new().with_min_size(0).exclude_content_type("text/"));
+    let response = env.exec("/plain", Some("gzip, br"));
+    assert_eq!(content_encoding(&response), None);
+    assert_eq!(*response.body(), b"plain text".to_vec());
+}
+
+#[test]
+fn test_precompressed_static_content_is_selected() {
+    let mut env = env::Env::new(Compression::new().with_min_size(0));
+
+    let response = env.exec("/static", Some("gzip, br"));
+    assert_eq!(content_encoding(&response), Some("br"));
+    assert_eq!(*response.body(), b"brotli".to_vec());
+
+    let response = env.exec("/static", Some("gzip"));
+    assert_eq!(content_encoding(&response), Some("gzip"));
+    assert_eq!(*response.body(), b"gzip".to_vec());
+
+    let response = env.exec("/static", Some("deflate"));
+    assert_eq!(content_encoding(&response), None);
+    assert_eq!(*response.body(), b"identity".to_vec());
+}
@@ -0,0 +1,100 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy of
+// the MPL was not distributed with this file, You can obtain one at http://mozilla.org/MPL/2.0/
+
+//! Tests for `bluefire_backend::live_reload` module and serving static files from disk.
+
+use std::path::PathBuf;
+
+use bluefire_backend::{live_reload::*, router::*, static_files::*, *};
+
+mod env {
+    use super::*;
+
+    pub const PAGE: &[u8] = b"<html><body>page</body></html>";
+
+    pub struct Env {
+        pub wielder: BlueFireWielder,
+        pub live_reload: LiveReload,
+    }
+
+    impl Env {
+        pub fn new(file_path: PathBuf) -> Env {
+            let page = StaticHandler::new(PAGE.to_vec(), "text/html".to_string());
+            let style = StaticHandler::new(b"body {}".to_vec(), "text/css".to_string());
+            let file = DiskHandler::new(file_path, "text/plain".to_string())
+                .with_processor(|path| Ok(std::fs::read(path).unwrap().to_ascii_uppercase()));
+
+            let mut builder = RoutingBuilder::new();
+            builder.insert(
+                Host::new_nameless(),
+                Route::index().with_routes(vec![
+                    Route::exact("page").with_view(Box::new(page)),
+                    Route::exact("style").with_view(Box::new(style)),
+                    Route::exact("file").with_view(Box::new(file)),
+                ]),
+            );
+
+            let live_reload = LiveReload::new();
+            let wielder =
+                BlueFireKindler::start(Box::new(builder)).live_reload(live_reload.clone()).kindle();
+            Env { wielder, live_reload }
+        }
+
+        pub fn exec(&mut self, uri: &str) -> Response {
+            let request = http::request::Builder::new()
+                .method(http::method::Method::GET)
+                .uri(uri)
+                .body("".into())
+                .expect("Failed to build empty GET body");
+            self.wielder.serve(request)
+        }
+    }
+
+    pub fn make_file(name: &str, content: &str) -> PathBuf {
+        let mut path = std::env::temp_dir();
+        path.push(format!("bluefire-live-reload-{}-{}", std::process::id(), name));
+        std::fs::write(&path, content).expect("Write test file");
+        path
+    }
+}
+
+#[test]
+fn test_script_is_injected_into_html() {
+    let mut env = env::Env::new(env::make_file("html", ""));
+
+    let response = env.exec("/page");
+    let body = String::from_utf8(response.body().clone()).unwrap();
+    assert!(body.contains(DEFAULT_RELOAD_PATH));
+    assert!(body.ends_with("</script></body></html>"));
+
+    let response = env.exec("/style");
+    assert_eq!(*response.body(), b"body {}".to_vec());
+}
+
+#[test]
+fn test_reload_version_changes_after_notification() {
+    let mut env = env::Env::new(env::make_file("version", ""));
+
+    let first = env.exec(DEFAULT_RELOAD_PATH);
+    assert_eq!(first.status(), http::StatusCode::OK);
+    assert_eq!(*first.body(), env.live_reload.version().into_bytes());
+
+    env.live_reload.notify();
+    let second = env.exec(DEFAULT_RELOAD_PATH);
+    assert_ne!(first.body(), second.body());
+}
+
+#[test]
+fn test_disk_handler_serves_processed_file() {
+    let path = env::make_file("disk", "content");
+    let mut env = env::Env::new(path.clone());
+
+    let response = env.exec("/file");
+    assert_eq!(response.status(), http::StatusCode::OK);
+    assert_eq!(*response.body(), b"CONTENT".to_vec());
+    assert_eq!(response.headers().get(http::header::CACHE_CONTROL).unwrap(), "no-store");
+
+    std::fs::remove_file(&path).unwrap();
+    let response = env.exec("/file");
+    assert_eq!(response.status(), http::StatusCode::INTERNAL_SERVER_ERROR);
+}
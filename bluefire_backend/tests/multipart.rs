@@ -0,0 +1,110 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy of
+// the MPL was not distributed with this file, You can obtain one at http://mozilla.org/MPL/2.0/
+
+//! Tests for `bluefire_backend::multipart` module.
+
+use bluefire_backend::multipart::*;
+use bluefire_twine::multipart::{File, Part};
+
+fn make_parts() -> Vec<Part> {
+    let file = File::new(b"\x00\x01\r\n--binary".to_vec())
+        .with_name("data.bin")
+        .with_content_type("application/octet-stream");
+    vec![Part::text("title", "Report"), Part::text("empty", ""), Part::file("data", &file)]
+}
+
+/// Checks if encoded parts are parsed back correctly.
+#[test]
+fn test_encode_and_parse() {
+    let parts = make_parts();
+    let boundary = make_boundary(&parts);
+    let body = encode(&parts, &boundary).unwrap();
+
+    let parsed = MultipartParser::new().parse(&content_type(&boundary), &body).unwrap();
+    assert_eq!(parsed, parts);
+}
+
+/// Checks if a body prepared by a browser is parsed correctly.
+#[test]
+fn test_parse_browser_body() {
+    let content_type = "multipart/form-data; boundary=\"----WebKitFormBoundary\"";
+    let body = "------WebKitFormBoundary\r\n\
+                Content-Disposition: form-data; name=\"file\"; filename=\"a.txt\"\r\n\
+                Content-Type: text/plain\r\n\
+                \r\n\
+                hello\r\n\
+                ------WebKitFormBoundary\r\n\
+                content-disposition: form-data; name=\"count\"\r\n\
+                \r\n\
+                3\r\n\
+                ------WebKitFormBoundary--\r\n";
+
+    let parts = MultipartParser::new().parse(content_type, body.as_bytes()).unwrap();
+    assert_eq!(parts.len(), 2);
+    assert_eq!(parts[0].name, "file");
+    assert_eq!(parts[0].file_name, Some("a.txt".to_string()));
+    assert_eq!(parts[0].content_type, Some("text/plain".to_string()));
+    assert_eq!(parts[0].data, b"hello".to_vec());
+    assert_eq!(parts[1], Part::text("count", "3"));
+}
+
+/// Checks if semicolons inside quoted parameters do not split them.
+#[test]
+fn test_parse_quoted_params() {
+    let content_type = "multipart/form-data; boundary=\"a;b\"";
+    let body = "--a;b\r\n\
+                Content-Disposition: form-data; name=\"file\"; filename=\"a;b.txt\"\r\n\
+                \r\n\
+                hello\r\n\
+                --a;b--\r\n";
+
+    let parts = MultipartParser::new().parse(content_type, body.as_bytes()).unwrap();
+    assert_eq!(parts.len(), 1);
+    assert_eq!(parts[0].name, "file");
+    assert_eq!(parts[0].file_name, Some("a;b.txt".to_string()));
+    assert_eq!(parts[0].data, b"hello".to_vec());
+}
+
+/// Checks if quotes and backslashes in names are escaped and parsed back.
+#[test]
+fn test_encode_and_parse_escaped_names() {
+    let file = File::new(b"hello".to_vec()).with_name("say \"hi\"; \\o/.txt");
+    let parts = vec![Part::text("a\"b", "1"), Part::file("data", &file)];
+    let body = encode(&parts, "boundary").unwrap();
+    let encoded = String::from_utf8_lossy(&body);
+    assert!(encoded.contains("name=\"a\\\"b\""));
+    assert!(encoded.contains("filename=\"say \\\"hi\\\"; \\\\o/.txt\""));
+
+    let parsed = MultipartParser::new().parse(&content_type("boundary"), &body).unwrap();
+    assert_eq!(parsed, parts);
+}
+
+/// Checks if line breaks in headers of parts are rejected.
+#[test]
+fn test_encode_line_breaks() {
+    let file = File::new(b"hello".to_vec()).with_name("a.txt\r\nContent-Type: text/html");
+    let parts = vec![Part::file("data", &file)];
+    assert_eq!(encode(&parts, "boundary"), Err(MultipartError::InvalidHeader));
+    let parts = vec![Part::text("name\n", "1")];
+    assert_eq!(encode(&parts, "boundary"), Err(MultipartError::InvalidHeader));
+    let file = File::new(b"hello".to_vec()).with_content_type("text/plain\r\nX-Injected: 1");
+    let parts = vec![Part::file("data", &file)];
+    assert_eq!(encode(&parts, "boundary"), Err(MultipartError::InvalidHeader));
+}
+
+/// Checks if bodies exceeding the limit and malformed bodies are rejected.
+#[test]
+fn test_parse_errors() {
+    let parts = make_parts();
+    let body = encode(&parts, "boundary").unwrap();
+    let content_type = content_type("boundary");
+
+    let parser = MultipartParser::new().with_limit(16);
+    let expected = MultipartError::TooLarge { size: body.len(), limit: 16 };
+    assert_eq!(parser.parse(&content_type, &body), Err(expected));
+
+    let parser = MultipartParser::new();
+    assert_eq!(parser.parse("application/json", &body), Err(MultipartError::InvalidContentType));
+    let truncated = &body[..body.len() - 20];
+    assert!(parser.parse(&content_type, truncated).is_err());
+}
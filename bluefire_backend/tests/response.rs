@@ -0,0 +1,167 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy of
+// the MPL was not distributed with this file, You can obtain one at http://mozilla.org/MPL/2.0/
+
+//! Tests for `bluefire_backend::response` module.
+
+use bluefire_backend::response::*;
+use bluefire_backend::router::*;
+use bluefire_backend::{BlueFire, BlueFireError, BlueFireKindler, BlueFireWielder};
+use bluefire_backend::{Handler, ParamsMap, Request, Response};
+
+#[derive(serde_derive::Serialize)]
+struct Item {
+    name: String,
+}
+
+/// A value which always fails to serialize.
+struct Unserializable;
+
+impl serde::Serialize for Unserializable {
+    fn serialize<S: serde::Serializer>(&self, _serializer: S) -> Result<S::Ok, S::Error> {
+        Err(serde::ser::Error::custom("unserializable"))
+    }
+}
+
+/// A view always failing with "not found".
+#[derive(Clone, Debug)]
+struct MissingView;
+
+impl Handler for MissingView {
+    fn handle(&self, context: &BlueFire, _request: Request) -> Response {
+        let result: Result<NoContent, BlueFireError> = Err(BlueFireError::not_found());
+        result.into_response(context)
+    }
+
+    fn duplicate(&self) -> Box<dyn Handler> {
+        Box::new(self.clone())
+    }
+}
+
+fn kindle() -> BlueFireWielder {
+    let mut builder = RoutingBuilder::new();
+    builder.insert(
+        Host::new_nameless(),
+        Route::index().with_routes(vec![
+            Route::exact("items").with_routes(vec![Route::param("item_id").with_label("item")]),
+            Route::exact("missing").with_view(Box::new(MissingView)),
+        ]),
+    );
+    BlueFireKindler::start(Box::new(builder)).kindle()
+}
+
+fn make_request(uri: &str, accept: &str) -> Request {
+    http::request::Builder::new()
+        .uri(uri)
+        .header(http::header::ACCEPT, accept)
+        .body(Vec::new())
+        .expect("Build request")
+}
+
+/// Checks if the builders set status, headers and body.
+#[test]
+fn test_builders() {
+    let wielder = kindle();
+    let context = wielder.get_context();
+
+    let item = Item { name: "Lamp".to_string() };
+    let response = Json::new(item).with_status(http::StatusCode::ACCEPTED).into_response(context);
+    assert_eq!(response.status(), http::StatusCode::ACCEPTED);
+    assert_eq!(response.headers()[http::header::CONTENT_TYPE], "application/json");
+    assert_eq!(*response.body(), br#"{"name":"Lamp"}"#.to_vec());
+
+    let response = Html::new("<p>Lamp</p>".to_string()).into_response(context);
+    assert_eq!(response.headers()[http::header::CONTENT_TYPE], "text/html; charset=utf-8");
+
+    let response = NoContent.into_response(context);
+    assert_eq!(response.status(), http::StatusCode::NO_CONTENT);
+
+    let response = Created { location: "/items/1".to_string() }.into_response(context);
+    assert_eq!(response.status(), http::StatusCode::CREATED);
+    assert_eq!(response.headers()[http::header::LOCATION], "/items/1");
+
+    let response =
+        Attachment::new("report \"1\".csv", "text/csv", b"a,b".to_vec()).into_response(context);
+    assert_eq!(
+        response.headers()[http::header::CONTENT_DISPOSITION],
+        "attachment; filename=\"report 1.csv\"; filename*=UTF-8''report%20%221%22.csv"
+    );
+
+    let response = Attachment::new("zażółć.txt", "text/plain", Vec::new()).into_response(context);
+    assert_eq!(
+        response.headers()[http::header::CONTENT_DISPOSITION],
+        "attachment; filename=\"za.txt\"; filename*=UTF-8''za%C5%BC%C3%B3%C5%82%C4%87.txt"
+    );
+
+    let response = Attachment::new("a.txt", "text/\nplain", Vec::new()).into_response(context);
+    assert_eq!(response.status(), http::StatusCode::INTERNAL_SERVER_ERROR);
+
+    let response = Json::new(Unserializable).into_response(context);
+    assert_eq!(response.status(), http::StatusCode::INTERNAL_SERVER_ERROR);
+}
+
+/// Checks if results are rendered either as the successful response or as the error.
+#[test]
+fn test_result() {
+    let wielder = kindle();
+    let context = wielder.get_context();
+
+    let result: Result<NoContent, BlueFireError> = Ok(NoContent);
+    let response = result.into_response(context);
+    assert_eq!(response.status(), http::StatusCode::NO_CONTENT);
+
+    let result: Result<NoContent, BlueFireError> = Err(BlueFireError::not_found());
+    let response = result.into_response(context);
+    assert_eq!(response.status(), http::StatusCode::NOT_FOUND);
+    assert_eq!(response.headers()[http::header::CONTENT_TYPE], "application/json");
+    let body: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+    assert_eq!(body["result"], "error");
+    assert_eq!(body["content"]["reason"], "not_found");
+
+    let mut wielder = kindle();
+    let response = wielder.serve(make_request("/missing", "text/html"));
+    assert_eq!(response.status(), http::StatusCode::NOT_FOUND);
+    assert_eq!(response.headers()[http::header::CONTENT_TYPE], "text/html; charset=utf-8");
+}
+
+/// Checks if redirections are resolved with the reverse router.
+#[test]
+fn test_redirect() {
+    let wielder = kindle();
+    let context = wielder.get_context();
+
+    let mut params = ParamsMap::new();
+    params.insert("item_id", "7".to_string());
+    let response = Redirect::to("item", params).into_response(context);
+    assert_eq!(response.status(), http::StatusCode::SEE_OTHER);
+    assert_eq!(response.headers()[http::header::LOCATION], "/items/7");
+
+    let response = Redirect::to_uri("https://example.com")
+        .with_status(http::StatusCode::MOVED_PERMANENTLY)
+        .into_response(context);
+    assert_eq!(response.status(), http::StatusCode::MOVED_PERMANENTLY);
+
+    let response = Redirect::to("unknown", ParamsMap::new()).into_response(context);
+    assert_eq!(response.status(), http::StatusCode::INTERNAL_SERVER_ERROR);
+}
+
+/// Checks if JSON or HTML is chosen according to the `Accept` header.
+#[test]
+fn test_negotiated() {
+    let wielder = kindle();
+    let context = wielder.get_context();
+    let render = |item: &Item| format!("<p>{}</p>", item.name);
+
+    let cases = vec![
+        ("application/json", "application/json"),
+        ("*/*", "application/json"),
+        ("text/html,application/xhtml+xml,*/*;q=0.8", "text/html; charset=utf-8"),
+        ("application/json, text/html;q=0.5", "application/json"),
+    ];
+    for (accept, content_type) in cases {
+        let item = Item { name: "Lamp".to_string() };
+        let response =
+            Negotiated::new(&make_request("/items", accept), item, render).into_response(context);
+        assert_eq!(response.headers()[http::header::CONTENT_TYPE], content_type, "{}", accept);
+        assert_eq!(response.headers()[http::header::VARY], "accept");
+    }
+}
@@ -19,3 +19,353 @@ impl TypedRestHandler for View {
 }
 
 bluefire_backend::impl_handler_via_typed_handler!(View);
+
+// -------------------------------------------------------------------------------------------------
+
+mod item {
+    use std::collections::HashMap;
+    use std::convert::TryFrom;
+
+    use bluefire_backend::{rest, BlueFire, Handler, Request, Response};
+    use bluefire_twine::validation::{Validate, ValidationError};
+    use bluefire_twine::ValidationResult;
+
+    pub struct ItemPath {
+        pub item_id: String,
+    }
+
+    impl TryFrom<&HashMap<&'static str, String>> for ItemPath {
+        type Error = &'static str;
+
+        fn try_from(params: &HashMap<&'static str, String>) -> Result<Self, Self::Error> {
+            let item_id = params.get("item_id").ok_or("item_id")?.clone();
+            Ok(Self { item_id })
+        }
+    }
+
+    pub struct ItemRequest {
+        pub count: u8,
+    }
+
+    impl TryFrom<Request> for ItemRequest {
+        type Error = String;
+
+        fn try_from(request: Request) -> Result<Self, Self::Error> {
+            let query = request.uri().query().unwrap_or_default();
+            let count = query.trim_start_matches("count=").parse().map_err(|_| "Invalid count")?;
+            Ok(Self { count })
+        }
+    }
+
+    impl Validate for ItemRequest {
+        fn validate(&self) -> ValidationResult<ValidationError> {
+            let mut result = ValidationResult::new();
+            if self.count > 10 {
+                result.add(ValidationError::new("count", "too_big"));
+            }
+            result
+        }
+    }
+
+    pub struct ItemResponse(String);
+
+    impl From<ItemResponse> for Response {
+        fn from(response: ItemResponse) -> Response {
+            http::Response::new(response.0.into_bytes())
+        }
+    }
+
+    pub struct ItemMethod;
+
+    impl rest::Method for ItemMethod {
+        type PathParams = ItemPath;
+        type Request = ItemRequest;
+        type Response = ItemResponse;
+
+        fn validate(request: &ItemRequest) -> ValidationResult<ValidationError> {
+            request.validate()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct ItemView;
+
+    impl Handler for ItemView {
+        fn handle(&self, context: &BlueFire, request: Request) -> Response {
+            match *request.method() {
+                http::Method::GET => {
+                    rest::handle_method::<ItemMethod, _>(context, request, |_, request, path| {
+                        Ok(ItemResponse(format!("{}:{}", path.item_id, request.count)))
+                    })
+                }
+                _ => rest::make_method_not_allowed_response("GET, OPTIONS"),
+            }
+        }
+
+        fn duplicate(&self) -> Box<dyn Handler> {
+            Box::new(self.clone())
+        }
+    }
+}
+
+/// Checks if `handle_method` parses and validates requests before passing them to the handler.
+#[test]
+fn test_handle_method() {
+    use bluefire_backend::{router::*, BlueFireKindler};
+
+    let mut builder = RoutingBuilder::new();
+    builder.insert(
+        Host::new_nameless(),
+        Route::index().with_routes(vec![Route::exact("items")
+            .with_routes(vec![Route::param("item_id").with_view(Box::new(item::ItemView))])]),
+    );
+    let mut wielder = BlueFireKindler::start(Box::new(builder)).kindle();
+    let mut exec = |method: http::Method, uri: &str| {
+        let request = http::request::Builder::new()
+            .method(method)
+            .uri(uri.parse::<http::uri::Uri>().expect("Parse URI"))
+            .body(Vec::new())
+            .expect("Build request");
+        wielder.route(request)
+    };
+
+    let response = exec(http::Method::GET, "/items/abc?count=3");
+    assert_eq!(response.status(), http::StatusCode::OK);
+    assert_eq!(*response.body(), b"abc:3".to_vec());
+
+    let response = exec(http::Method::GET, "/items/abc?count=three");
+    assert_eq!(response.status(), http::StatusCode::BAD_REQUEST);
+    let body: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+    assert_eq!(body["content"]["reason"], "bad_request");
+
+    let response = exec(http::Method::GET, "/items/abc?count=20");
+    assert_eq!(response.status(), http::StatusCode::BAD_REQUEST);
+    let body: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+    assert_eq!(body["content"]["reason"], "validation_failed");
+    assert_eq!(body["content"]["errors"][0]["path"], "count");
+
+    let response = exec(http::Method::DELETE, "/items/abc");
+    assert_eq!(response.status(), http::StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(response.headers()[http::header::ALLOW], "GET, OPTIONS");
+}
+
+// -------------------------------------------------------------------------------------------------
+
+mod note {
+    use std::convert::TryFrom;
+
+    use bluefire_backend::{rest, BlueFire, Request, Response};
+    use bluefire_twine::validation::{Validate, ValidationError};
+    use bluefire_twine::ValidationResult;
+
+    pub struct NoteRequest {
+        pub text: String,
+    }
+
+    impl TryFrom<Request> for NoteRequest {
+        type Error = serde_json::Error;
+
+        fn try_from(request: Request) -> Result<Self, Self::Error> {
+            let body: serde_json::Value = serde_json::from_slice(request.body())?;
+            Ok(Self { text: body["text"].as_str().unwrap_or_default().to_string() })
+        }
+    }
+
+    impl Validate for NoteRequest {
+        fn validate(&self) -> ValidationResult<ValidationError> {
+            let mut result = ValidationResult::new();
+            if self.text.is_empty() {
+                result.add(ValidationError::new("text", "empty"));
+            }
+            result
+        }
+    }
+
+    pub struct NoteResponse(String);
+
+    impl From<NoteResponse> for Response {
+        fn from(response: NoteResponse) -> Response {
+            http::Response::new(response.0.into_bytes())
+        }
+    }
+
+    /// Method validating the requests.
+    pub struct CheckedNoteMethod;
+
+    impl rest::Method for CheckedNoteMethod {
+        type PathParams = rest::DefaultPath;
+        type Request = NoteRequest;
+        type Response = NoteResponse;
+
+        fn validate(request: &NoteRequest) -> ValidationResult<ValidationError> {
+            request.validate()
+        }
+    }
+
+    /// Method relying on the default (accepting) validation.
+    pub struct UncheckedNoteMethod;
+
+    impl rest::Method for UncheckedNoteMethod {
+        type PathParams = rest::DefaultPath;
+        type Request = NoteRequest;
+        type Response = NoteResponse;
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct NoteView;
+
+    impl rest::TypedRestHandler for NoteView {
+        bluefire_backend::default_get_method!();
+        bluefire_backend::default_patch_method!();
+        bluefire_backend::default_delete_method!();
+
+        type PostMethod = CheckedNoteMethod;
+        type PutMethod = UncheckedNoteMethod;
+
+        fn post(
+            &self,
+            _context: &BlueFire,
+            request: Result<NoteRequest, serde_json::Error>,
+            _path: Result<rest::DefaultPath, &'static str>,
+        ) -> rest::Reply<NoteResponse> {
+            match request {
+                Ok(request) => Ok(NoteResponse(format!("post:{}", request.text))),
+                Err(err) => Err(NoteResponse(err.to_string())),
+            }
+        }
+
+        fn put(
+            &self,
+            _context: &BlueFire,
+            request: Result<NoteRequest, serde_json::Error>,
+            _path: Result<rest::DefaultPath, &'static str>,
+        ) -> rest::Reply<NoteResponse> {
+            match request {
+                Ok(request) => Ok(NoteResponse(format!("put:{}", request.text))),
+                Err(err) => Err(NoteResponse(err.to_string())),
+            }
+        }
+    }
+}
+
+bluefire_backend::impl_handler_via_typed_handler!(note::NoteView);
+
+/// Checks if typed handlers reject requests failing validation of the method before calling the
+/// handler and if methods without validation accept all the requests.
+#[test]
+fn test_typed_handler_validation() {
+    use bluefire_backend::{router::*, BlueFireKindler};
+
+    let mut builder = RoutingBuilder::new();
+    builder.insert(
+        Host::new_nameless(),
+        Route::index().with_routes(vec![Route::exact("notes").with_view(Box::new(note::NoteView))]),
+    );
+    let mut wielder = BlueFireKindler::start(Box::new(builder)).kindle();
+    let mut exec = |method: http::Method, body: &str| {
+        let request = http::request::Builder::new()
+            .method(method)
+            .uri("/notes")
+            .body(body.as_bytes().to_vec())
+            .expect("Build request");
+        wielder.route(request)
+    };
+
+    let response = exec(http::Method::POST, r#"{"text":"hello"}"#);
+    assert_eq!(response.status(), http::StatusCode::OK);
+    assert_eq!(*response.body(), b"post:hello".to_vec());
+
+    let response = exec(http::Method::POST, r#"{"text":""}"#);
+    assert_eq!(response.status(), http::StatusCode::BAD_REQUEST);
+    let body: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+    assert_eq!(body["content"]["reason"], "validation_failed");
+    assert_eq!(body["content"]["errors"][0]["path"], "text");
+    assert_eq!(body["content"]["errors"][0]["error"], "empty");
+
+    let response = exec(http::Method::PUT, r#"{"text":""}"#);
+    assert_eq!(response.status(), http::StatusCode::OK);
+    assert_eq!(*response.body(), b"put:".to_vec());
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Debug)]
+struct FailingView;
+
+impl bluefire_backend::rest::FallibleHandler for FailingView {
+    type Error = bluefire_backend::BlueFireError;
+
+    fn handle(
+        &self,
+        _context: &BlueFire,
+        request: bluefire_backend::Request,
+    ) -> Result<bluefire_backend::Response, Self::Error> {
+        use bluefire_backend::BlueFireError;
+        match request.uri().path() {
+            "/missing" => Err(BlueFireError::not_found()),
+            "/broken" => Err(BlueFireError::other("Broken <view>".to_string())
+                .with_source(std::io::Error::new(std::io::ErrorKind::Other, "disk full"))),
+            _ => Ok(http::Response::new(b"ok".to_vec())),
+        }
+    }
+}
+
+/// Checks if errors returned by fallible handlers are rendered according to the `Accept` header
+/// and carry the request ID.
+#[test]
+fn test_fallible_handler() {
+    use bluefire_backend::{rest::Fallible, router::*, BlueFireKindler};
+
+    let view = || Box::new(Fallible::new(FailingView));
+    let mut builder = RoutingBuilder::new();
+    builder.insert(
+        Host::new_nameless(),
+        Route::index().with_routes(vec![
+            Route::exact("missing").with_view(view()),
+            Route::exact("broken").with_view(view()),
+            Route::exact("fine").with_view(view()),
+        ]),
+    );
+    let mut wielder = BlueFireKindler::start(Box::new(builder)).kindle();
+    let mut exec = |uri: &str, accept: &str| {
+        let request = http::request::Builder::new()
+            .uri(uri.parse::<http::uri::Uri>().expect("Parse URI"))
+            .header(http::header::ACCEPT, accept)
+            .header("x-request-id", "req-1")
+            .body(Vec::new())
+            .expect("Build request");
+        wielder.route(request)
+    };
+
+    let response = exec("/fine", "*/*");
+    assert_eq!(response.status(), http::StatusCode::OK);
+
+    let response = exec("/missing", "application/json");
+    assert_eq!(response.status(), http::StatusCode::NOT_FOUND);
+    assert_eq!(response.headers()["x-request-id"], "req-1");
+    let body: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+    assert_eq!(body["content"]["reason"], "not_found");
+    assert_eq!(body["content"]["request_id"], "req-1");
+
+    let response = exec("/broken", "text/html,application/json;q=0.9");
+    assert_eq!(response.status(), http::StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(response.headers()[http::header::CONTENT_TYPE], "text/html; charset=utf-8");
+    let body = String::from_utf8(response.body().clone()).unwrap();
+    assert!(body.contains("500 Internal Server Error"));
+    assert!(!body.contains("Broken"));
+}
+
+/// Checks if the error chain contains all the sources.
+#[test]
+fn test_error_chain() {
+    use bluefire_backend::{error_chain, BlueFireError, ErrorKind, HttpError};
+
+    let error = BlueFireError::unavailable("Mailer".to_string())
+        .with_source(std::io::Error::new(std::io::ErrorKind::Other, "connection refused"));
+    match error.kind() {
+        ErrorKind::Unavailable { service } => assert_eq!(service, "Mailer"),
+        _ => panic!("Unexpected error kind"),
+    }
+    assert_eq!(error.status(), http::StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(error_chain(&error), "Service 'Mailer' unavailable: caused by: connection refused");
+}
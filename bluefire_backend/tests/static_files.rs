@@ -0,0 +1,140 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy of
+// the MPL was not distributed with this file, You can obtain one at http://mozilla.org/MPL/2.0/
+
+//! Tests for `bluefire_backend::static_files` module.
+
+use bluefire_backend::{router::*, static_files::*, *};
+
+mod env {
+    use super::*;
+
+    pub const CONTENT: &[u8] = b"0123456789";
+    pub const ETAG: &str = "abcdef";
+    pub const LAST_MODIFIED: &str = "Sun, 06 Nov 1994 08:49:37 GMT";
+    pub const EARLIER: &str = "Sat, 05 Nov 1994 08:49:37 GMT";
+    pub const LATER: &str = "Mon, 07 Nov 1994 08:49:37 GMT";
+
+    pub struct Env {
+        pub wielder: BlueFireWielder,
+    }
+
+    impl Env {
+        pub fn new(cache_policy: CachePolicy) -> Env {
+            let handler = StaticHandler::new(CONTENT.to_vec(), "text/plain".to_string())
+                .with_etag(ETAG)
+                .with_last_modified_timestamp(784111777)
+                .with_cache_policy(cache_policy);
+
+            let mut builder = RoutingBuilder::new();
+            builder.insert(
+                Host::new_nameless(),
+                Route::index().with_routes(vec![Route::exact("file").with_view(Box::new(handler))]),
+            );
+
+            let wielder = BlueFireKindler::start(Box::new(builder)).kindle();
+            Env { wielder }
+        }
+
+        pub fn exec(&mut self, headers: Vec<(http::header::HeaderName, &str)>) -> Response {
+            let mut builder = http::request::Builder::new();
+            builder.method(http::method::Method::GET).uri("/file");
+            for (name, value) in headers {
+                builder.header(name, value);
+            }
+            let request = builder.body("".into()).expect("Failed to build empty GET body");
+            self.wielder.serve(request)
+        }
+    }
+}
+
+fn header<'a>(response: &'a Response, name: http::header::HeaderName) -> &'a str {
+    response.headers().get(name).expect("Header present").to_str().expect("Header to string")
+}
+
+#[test]
+fn test_headers() {
+    let mut env = env::Env::new(CachePolicy::Revalidate);
+    let response = env.exec(vec![]);
+    assert_eq!(response.status(), http::StatusCode::OK);
+    assert_eq!(*response.body(), env::CONTENT.to_vec());
+    assert_eq!(header(&response, http::header::ETAG), "\"abcdef\"");
+    assert_eq!(header(&response, http::header::LAST_MODIFIED), env::LAST_MODIFIED);
+    assert_eq!(header(&response, http::header::CACHE_CONTROL), "public, no-cache");
+    assert_eq!(header(&response, http::header::ACCEPT_RANGES), "bytes");
+}
+
+#[test]
+fn test_immutable_cache_policy() {
+    let mut env = env::Env::new(CachePolicy::Immutable);
+    let response = env.exec(vec![]);
+    assert_eq!(
+        header(&response, http::header::CACHE_CONTROL),
+        "public, max-age=31536000, immutable"
+    );
+}
+
+#[test]
+fn test_if_none_match() {
+    let mut env = env::Env::new(CachePolicy::Revalidate);
+
+    let response = env.exec(vec![(http::header::IF_NONE_MATCH, "\"abcdef\"")]);
+    assert_eq!(response.status(), http::StatusCode::NOT_MODIFIED);
+    assert!(response.body().is_empty());
+
+    let response = env.exec(vec![(http::header::IF_NONE_MATCH, "\"other\"")]);
+    assert_eq!(response.status(), http::StatusCode::OK);
+}
+
+#[test]
+fn test_if_modified_since() {
+    let mut env = env::Env::new(CachePolicy::Revalidate);
+
+    let response = env.exec(vec![(http::header::IF_MODIFIED_SINCE, env::LAST_MODIFIED)]);
+    assert_eq!(response.status(), http::StatusCode::NOT_MODIFIED);
+
+    let response = env.exec(vec![(http::header::IF_MODIFIED_SINCE, env::EARLIER)]);
+    assert_eq!(response.status(), http::StatusCode::OK);
+}
+
+#[test]
+fn test_range() {
+    let mut env = env::Env::new(CachePolicy::Revalidate);
+
+    let response = env.exec(vec![(http::header::RANGE, "bytes=2-4")]);
+    assert_eq!(response.status(), http::StatusCode::PARTIAL_CONTENT);
+    assert_eq!(*response.body(), b"234".to_vec());
+    assert_eq!(header(&response, http::header::CONTENT_RANGE), "bytes 2-4/10");
+
+    let response = env.exec(vec![(http::header::RANGE, "bytes=20-")]);
+    assert_eq!(response.status(), http::StatusCode::RANGE_NOT_SATISFIABLE);
+    assert_eq!(header(&response, http::header::CONTENT_RANGE), "bytes */10");
+}
+
+#[test]
+fn test_if_range() {
+    let mut env = env::Env::new(CachePolicy::Revalidate);
+
+    let response =
+        env.exec(vec![(http::header::RANGE, "bytes=-3"), (http::header::IF_RANGE, "\"abcdef\"")]);
+    assert_eq!(response.status(), http::StatusCode::PARTIAL_CONTENT);
+    assert_eq!(*response.body(), b"789".to_vec());
+
+    let response =
+        env.exec(vec![(http::header::RANGE, "bytes=-3"), (http::header::IF_RANGE, "\"other\"")]);
+    assert_eq!(response.status(), http::StatusCode::OK);
+    assert_eq!(*response.body(), env::CONTENT.to_vec());
+
+    let response = env.exec(vec![
+        (http::header::RANGE, "bytes=-3"),
+        (http::header::IF_RANGE, env::LAST_MODIFIED),
+    ]);
+    assert_eq!(response.status(), http::StatusCode::PARTIAL_CONTENT);
+    assert_eq!(*response.body(), b"789".to_vec());
+
+    for date in [env::EARLIER, env::LATER].iter() {
+        let response =
+            env.exec(vec![(http::header::RANGE, "bytes=-3"), (http::header::IF_RANGE, date)]);
+        assert_eq!(response.status(), http::StatusCode::OK);
+        assert_eq!(*response.body(), env::CONTENT.to_vec());
+    }
+}
@@ -5,6 +5,8 @@
 
 use std::path::PathBuf;
 
+use crate::error::BuildError;
+
 /// Returns the `cargo` manifest path.
 pub fn get_manifest_path() -> PathBuf {
     let dir = std::env::var("CARGO_MANIFEST_DIR").expect("Read CARGO_MANIFEST_DIR variable");
@@ -29,25 +31,26 @@ pub fn as_absolute_path(relative_path: &str) -> PathBuf {
 }
 
 /// Reads a file from the cargo manifest path.
-pub fn read_manifest_path(input: &str) -> String {
+pub fn read_manifest_path(input: &str) -> Result<String, BuildError> {
     let mut input_path = get_manifest_path();
     input_path.push(input);
 
-    std::fs::read_to_string(input_path.clone()).expect(&format!("Read file: {:?}", input_path))
+    std::fs::read_to_string(&input_path).map_err(|err| BuildError::io(&input_path, err))
 }
 
 /// Writes to a file in the output directory.
-pub fn write_out_file(output: &str, content: &str) -> PathBuf {
-    use std::io::Write;
-
+pub fn write_out_file(output: &str, content: &str) -> Result<PathBuf, BuildError> {
     let mut output_path = get_out_dir();
     output_path.push(output);
 
-    let mut file =
-        std::fs::File::create(&output_path).expect(&format!("Create file: {:?}", &output_path));
-    file.write_all(content.as_bytes()).expect(&format!("Write to file: {:?}", &output_path));
+    std::fs::write(&output_path, content).map_err(|err| BuildError::io(&output_path, err))?;
+    Ok(output_path)
+}
 
-    output_path
+/// Returns the name of the profile the crate is built with as given by cargo to build scripts
+/// ("debug" or "release"). Falls back to `compilation_mode` outside of build scripts.
+pub fn get_profile() -> String {
+    std::env::var("PROFILE").unwrap_or_else(|_| compilation_mode().to_string())
 }
 
 /// Returns a string representing a compilition mode: "release" in release mode and "debug"
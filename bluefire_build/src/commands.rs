@@ -3,11 +3,29 @@
 
 //! Helpers for executing other programs needed for building `bluefire` appications.
 
-fn handle_output(output: std::process::Output, args: &Vec<&str>) {
-    use std::io::{self, Write};
-    if !output.status.success() {
-        io::stderr().write_all(&output.stderr).unwrap();
-        panic!("Failed to run a command with args: {:?}", args);
+use crate::error::BuildError;
+
+/// Runs the program with the given arguments and returns an error if it could not be started or
+/// did not succeed.
+fn execute(program: &str, args: &Vec<&str>) -> Result<(), BuildError> {
+    let to_error = |stderr: String| BuildError::Command {
+        program: program.to_string(),
+        args: args.iter().map(|arg| arg.to_string()).collect(),
+        stderr,
+    };
+
+    let output = std::process::Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|err| to_error(format!("Failed to execute: {}", err)))?;
+    handle_output(output).map_err(to_error)
+}
+
+fn handle_output(output: std::process::Output) -> Result<(), String> {
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).to_string())
     }
 }
 
@@ -39,11 +57,21 @@ pub mod wasm_bindgen {
         input: String,
         outdir: Option<String>,
         target: Option<Target>,
+        keep_debug: bool,
+        remove_name_section: bool,
+        remove_producers_section: bool,
     }
 
     impl WasmBindgen {
         pub fn new(input: String) -> Self {
-            Self { input, outdir: None, target: None }
+            Self {
+                input,
+                outdir: None,
+                target: None,
+                keep_debug: false,
+                remove_name_section: false,
+                remove_producers_section: false,
+            }
         }
 
         pub fn outdir(mut self, outdir: String) -> Self {
@@ -56,7 +84,22 @@ pub mod wasm_bindgen {
             self
         }
 
-        pub fn run(self) {
+        pub fn keep_debug(mut self) -> Self {
+            self.keep_debug = true;
+            self
+        }
+
+        pub fn remove_name_section(mut self) -> Self {
+            self.remove_name_section = true;
+            self
+        }
+
+        pub fn remove_producers_section(mut self) -> Self {
+            self.remove_producers_section = true;
+            self
+        }
+
+        pub fn run(self) -> Result<(), super::BuildError> {
             let mut args: Vec<&str> = vec![&self.input];
             if let Some(outdir) = self.outdir.as_ref() {
                 args.push("--out-dir");
@@ -66,13 +109,60 @@ pub mod wasm_bindgen {
                 args.push("--target");
                 args.push(target.to_str());
             }
+            if self.keep_debug {
+                args.push("--keep-debug");
+            }
+            if self.remove_name_section {
+                args.push("--remove-name-section");
+            }
+            if self.remove_producers_section {
+                args.push("--remove-producers-section");
+            }
+
+            super::execute("wasm-bindgen", &args)
+        }
+    }
+}
+
+/// `wasm-opt` command wrapper.
+///
+/// See the documentation of `wasm-opt` (from `binaryen`) for information about the arguments.
+pub mod wasm_opt {
+    #![allow(missing_docs)]
+
+    pub struct WasmOpt {
+        input: String,
+        output: String,
+        level: Option<&'static str>,
+        strip_debug: bool,
+    }
+
+    impl WasmOpt {
+        pub fn new(input: String, output: String) -> Self {
+            Self { input, output, level: None, strip_debug: false }
+        }
+
+        /// Sets the optimization level flag, e.g. `-O3` or `-Oz`.
+        pub fn level(mut self, level: &'static str) -> Self {
+            self.level = Some(level);
+            self
+        }
+
+        pub fn strip_debug(mut self) -> Self {
+            self.strip_debug = true;
+            self
+        }
 
-            let output = std::process::Command::new("wasm-bindgen")
-                .args(&args)
-                .output()
-                .expect("failed to execute `wasm-bindgen`");
+        pub fn run(self) -> Result<(), super::BuildError> {
+            let mut args: Vec<&str> = vec![&self.input, "-o", &self.output];
+            if let Some(level) = self.level {
+                args.push(level);
+            }
+            if self.strip_debug {
+                args.push("--strip-debug");
+            }
 
-            super::handle_output(output, &args);
+            super::execute("wasm-opt", &args)
         }
     }
 }
@@ -83,6 +173,7 @@ pub mod wasm_bindgen {
 pub mod sass {
     #![allow(missing_docs)]
 
+    #[derive(Clone, Copy, Debug)]
     pub enum Style {
         Nested,
         Compact,
@@ -101,6 +192,7 @@ pub mod sass {
         }
     }
 
+    #[derive(Clone, Copy, Debug)]
     pub enum Sourcemap {
         Auto,
         File,
@@ -147,7 +239,7 @@ pub mod sass {
             self
         }
 
-        pub fn run(self) {
+        pub fn run(self) -> Result<(), super::BuildError> {
             let mut args: Vec<&str> = vec![&self.input, &self.output];
 
             if let Some(style) = self.style {
@@ -168,12 +260,7 @@ pub mod sass {
                 args.push(&cache_location);
             }
 
-            let output = std::process::Command::new("sass")
-                .args(&args)
-                .output()
-                .expect("failed to execute `sass`");
-
-            super::handle_output(output, &args);
+            super::execute("sass", &args)
         }
     }
 }
@@ -0,0 +1,65 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy of
+// the MPL was not distributed with this file, You can obtain one at http://mozilla.org/MPL/2.0/
+
+//! Precompression of static files.
+//!
+//! Files compressed at build time can be served without any runtime cost to clients accepting the
+//! given encoding.
+
+use std::{io::Write, path::PathBuf};
+
+use crate::error::BuildError;
+
+/// Extension appended to names of gzip-compressed files.
+pub const GZIP_EXTENSION: &str = "gz";
+
+/// Extension appended to names of Brotli-compressed files.
+pub const BROTLI_EXTENSION: &str = "br";
+
+/// Extensions of files which are already compressed and are not worth compressing again.
+const INCOMPRESSIBLE_EXTENSIONS: [&str; 7] = ["png", "jpg", "jpeg", "gif", "webp", "woff", "woff2"];
+
+/// Checks if the file is worth compressing judging by its extension.
+pub fn is_compressible(path: &PathBuf) -> bool {
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some(extension) => !INCOMPRESSIBLE_EXTENSIONS.contains(&extension.to_lowercase().as_str()),
+        None => true,
+    }
+}
+
+/// Returns the path of the compressed variant of the file with the given extension appended.
+pub fn compressed_path(path: &PathBuf, extension: &str) -> PathBuf {
+    let mut file_name = path.file_name().expect("Get file name").to_os_string();
+    file_name.push(".");
+    file_name.push(extension);
+    path.with_file_name(file_name)
+}
+
+/// Compresses the given data using gzip with the best compression level.
+pub fn gzip(data: &[u8]) -> Vec<u8> {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::best());
+    encoder.write_all(data).expect("Gzip: write data");
+    encoder.finish().expect("Gzip: finish encoding")
+}
+
+/// Compresses the given data using Brotli with the best compression level.
+pub fn brotli(data: &[u8]) -> Vec<u8> {
+    let mut result = Vec::new();
+    {
+        let mut encoder = brotli::CompressorWriter::new(&mut result, 4096, 11, 22);
+        encoder.write_all(data).expect("Brotli: write data");
+    }
+    result
+}
+
+/// Writes gzip- and Brotli-compressed variants of the given file next to it. The variants have
+/// `.gz` and `.br` extensions appended to the original file name.
+pub fn precompress(path: &PathBuf) -> Result<(), BuildError> {
+    let data = std::fs::read(path).map_err(|err| BuildError::io(path, err))?;
+
+    let gzip_path = compressed_path(path, GZIP_EXTENSION);
+    std::fs::write(&gzip_path, gzip(&data)).map_err(|err| BuildError::io(&gzip_path, err))?;
+
+    let brotli_path = compressed_path(path, BROTLI_EXTENSION);
+    std::fs::write(&brotli_path, brotli(&data)).map_err(|err| BuildError::io(&brotli_path, err))
+}
@@ -0,0 +1,93 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy of
+// the MPL was not distributed with this file, You can obtain one at http://mozilla.org/MPL/2.0/
+
+//! Errors reported while processing files at build time.
+
+use std::path::{Path, PathBuf};
+
+/// Error returned by the build helpers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BuildError {
+    /// The static files spec could not be read.
+    Spec(bluefire_static_files_core::Error),
+
+    /// A file could not be read or written.
+    Io {
+        /// Path to the file.
+        path: PathBuf,
+
+        /// Description of the problem.
+        message: String,
+    },
+
+    /// An external command failed.
+    Command {
+        /// Name of the program.
+        program: String,
+
+        /// Arguments passed to the program.
+        args: Vec<String>,
+
+        /// Error output of the program.
+        stderr: String,
+    },
+
+    /// An SCSS file failed to compile.
+    Scss(crate::scss::ScssError),
+
+    /// A file could not be minified.
+    Minify {
+        /// Path to the file.
+        path: PathBuf,
+
+        /// Description of the problem.
+        message: String,
+    },
+
+    /// A glob pattern is invalid or matched files could not be read.
+    Glob {
+        /// The pattern.
+        pattern: String,
+
+        /// Description of the problem.
+        message: String,
+    },
+}
+
+impl BuildError {
+    /// Constructs a new `BuildError::Io`.
+    pub fn io(path: &Path, err: std::io::Error) -> Self {
+        BuildError::Io { path: path.to_path_buf(), message: err.to_string() }
+    }
+}
+
+impl std::fmt::Display for BuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BuildError::Spec(err) => write!(f, "{}", err),
+            BuildError::Io { path, message } => write!(f, "{}: {}", path.display(), message),
+            BuildError::Command { program, args, stderr } => {
+                write!(f, "`{}` failed (args: {:?}):\n{}", program, args, stderr.trim_end())
+            }
+            BuildError::Scss(err) => write!(f, "{}", err),
+            BuildError::Minify { path, message } => {
+                write!(f, "{}: failed to minify: {}", path.display(), message)
+            }
+            BuildError::Glob { pattern, message } => write!(f, "glob '{}': {}", pattern, message),
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+impl From<bluefire_static_files_core::Error> for BuildError {
+    fn from(err: bluefire_static_files_core::Error) -> Self {
+        BuildError::Spec(err)
+    }
+}
+
+impl From<crate::scss::ScssError> for BuildError {
+    fn from(err: crate::scss::ScssError) -> Self {
+        BuildError::Scss(err)
+    }
+}
@@ -0,0 +1,41 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy of
+// the MPL was not distributed with this file, You can obtain one at http://mozilla.org/MPL/2.0/
+
+//! Hashing of static file contents.
+
+use std::path::PathBuf;
+
+use sha2::Digest;
+
+use crate::error::BuildError;
+
+/// Returns a hexadecimal representation of SHA-256 hash of the given data.
+pub fn content_hash(data: &[u8]) -> String {
+    let digest = sha2::Sha256::digest(data);
+    digest.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Returns a hexadecimal representation of SHA-256 hash of the content of the given file.
+pub fn file_hash(path: &PathBuf) -> Result<String, BuildError> {
+    let data = std::fs::read(path).map_err(|err| BuildError::io(path, err))?;
+    Ok(content_hash(&data))
+}
+
+/// Returns a Subresource Integrity hash (SHA-384) of the content of the given file in the format
+/// expected by the `integrity` attribute (`sha384-<base64 encoded digest>`).
+pub fn file_integrity(path: &PathBuf) -> Result<String, BuildError> {
+    let data = std::fs::read(path).map_err(|err| BuildError::io(path, err))?;
+    Ok(format!("sha384-{}", base64::encode(&sha2::Sha384::digest(&data))))
+}
+
+/// Returns the time of the last modification of the given file as a number of seconds since Unix
+/// epoch.
+pub fn file_modification_timestamp(path: &PathBuf) -> Result<i64, BuildError> {
+    let modified = std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .map_err(|err| BuildError::io(path, err))?;
+    match modified.duration_since(std::time::UNIX_EPOCH) {
+        Ok(duration) => Ok(duration.as_secs() as i64),
+        Err(..) => Ok(0),
+    }
+}
@@ -7,4 +7,8 @@
 
 pub mod cargo;
 pub mod commands;
+pub mod compression;
+pub mod error;
+pub mod hashing;
+pub mod scss;
 pub mod static_files;
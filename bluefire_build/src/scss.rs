@@ -0,0 +1,227 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy of
+// the MPL was not distributed with this file, You can obtain one at http://mozilla.org/MPL/2.0/
+
+//! SCSS compilation.
+//!
+//! By default SCSS files are compiled in-process by `rsass`. The external `sass` command can be
+//! used instead by choosing `Backend::External`.
+
+use std::path::{Path, PathBuf};
+
+pub use crate::commands::sass::{Sourcemap, Style};
+pub use bluefire_static_files_core::ScssBackend as Backend;
+
+// -------------------------------------------------------------------------------------------------
+
+/// Error returned when compilation of an SCSS file fails.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScssError {
+    /// Path to the file which failed to compile.
+    pub file: PathBuf,
+
+    /// Line (starting from 1) where the error was found, if known.
+    pub line: Option<usize>,
+
+    /// Column (starting from 1) where the error was found, if known.
+    pub column: Option<usize>,
+
+    /// Description of the error.
+    pub message: String,
+}
+
+impl ScssError {
+    /// Constructs a new `ScssError` trying to extract the location of the error from the message.
+    pub fn new(file: &Path, message: String) -> Self {
+        let (line, column) = match parse_location(&message) {
+            Some((line, column)) => (Some(line), column),
+            None => (None, None),
+        };
+        Self { file: file.to_path_buf(), line, column, message: message.trim().to_string() }
+    }
+}
+
+impl std::fmt::Display for ScssError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.file.display())?;
+        if let Some(line) = self.line {
+            write!(f, ":{}", line)?;
+            if let Some(column) = self.column {
+                write!(f, ":{}", column)?;
+            }
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+impl std::error::Error for ScssError {}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Compiles SCSS files into CSS.
+pub struct Compiler {
+    backend: Backend,
+    style: Style,
+    sourcemap: Sourcemap,
+    cache_location: Option<String>,
+}
+
+impl Compiler {
+    /// Constructs a new `Compiler` using the given backend, producing expanded output without
+    /// source maps.
+    pub fn new(backend: Backend) -> Self {
+        Self { backend, style: Style::Expanded, sourcemap: Sourcemap::None, cache_location: None }
+    }
+
+    /// Sets the output style. The native backend supports only compressed and expanded styles and
+    /// fails to compile with the other styles.
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Sets the source map generation mode. Source maps are generated only by the external backend;
+    /// the native backend fails to compile if they are requested.
+    pub fn sourcemap(mut self, sourcemap: Sourcemap) -> Self {
+        self.sourcemap = sourcemap;
+        self
+    }
+
+    /// Sets the cache location used by the external backend.
+    pub fn cache_location(mut self, cache_location: String) -> Self {
+        self.cache_location = Some(cache_location);
+        self
+    }
+
+    /// Compiles the given file and returns the resulting CSS.
+    pub fn compile(self, input_path: &Path) -> Result<Vec<u8>, ScssError> {
+        match self.backend {
+            Backend::Native => self.compile_native(input_path),
+            Backend::External => self.compile_external(input_path),
+        }
+    }
+}
+
+impl Compiler {
+    fn compile_native(self, input_path: &Path) -> Result<Vec<u8>, ScssError> {
+        let unsupported = |option: &str| {
+            let message = format!("{} is not supported by the native backend", option);
+            ScssError::new(input_path, message)
+        };
+        let style = match self.style {
+            Style::Compressed => rsass::OutputStyle::Compressed,
+            Style::Expanded => rsass::OutputStyle::Expanded,
+            style @ Style::Nested | style @ Style::Compact => {
+                return Err(unsupported(&format!("{:?} style", style)));
+            }
+        };
+        if let Sourcemap::Auto | Sourcemap::File | Sourcemap::Inline = self.sourcemap {
+            return Err(unsupported("Source map generation"));
+        }
+
+        rsass::compile_scss_file(input_path, style)
+            .map_err(|err| ScssError::new(input_path, err.to_string()))
+    }
+
+    fn compile_external(self, input_path: &Path) -> Result<Vec<u8>, ScssError> {
+        let to_error = |message: String| ScssError::new(input_path, message);
+        let input_str = input_path.to_str().ok_or_else(|| to_error("Invalid path".to_string()))?;
+
+        let mut output_path = std::env::temp_dir();
+        output_path.push(format!("bluefire-sass-{}", std::process::id()));
+        std::fs::create_dir_all(&output_path).map_err(|err| to_error(err.to_string()))?;
+        output_path.push(input_path.file_name().unwrap_or_default());
+        output_path.set_extension("css");
+        let output_str = output_path.to_str().expect("Cast path to a string").to_string();
+
+        let mut sass = crate::commands::sass::Sass::new(input_str.to_string(), output_str)
+            .style(self.style)
+            .sourcemap(self.sourcemap);
+        if let Some(cache_location) = self.cache_location {
+            sass = sass.cache_location(cache_location);
+        }
+        sass.run().map_err(|err| match err {
+            crate::error::BuildError::Command { stderr, .. } => to_error(stderr),
+            err => to_error(err.to_string()),
+        })?;
+
+        std::fs::read(&output_path).map_err(|err| to_error(err.to_string()))
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Extracts the line and (optionally) column from an error message. Recognizes the formats used by
+/// `rsass` and `sass` (`file.scss:3:5`, `file.scss 3:5` and `on line 3`).
+fn parse_location(message: &str) -> Option<(usize, Option<usize>)> {
+    fn parse_line_column(text: &str) -> Option<(usize, Option<usize>)> {
+        let mut parts = text.trim_end_matches(|c: char| !c.is_ascii_digit()).split(':');
+        let line = parts.next()?.parse::<usize>().ok()?;
+        let column = match parts.next() {
+            Some(column) => Some(column.parse::<usize>().ok()?),
+            None => None,
+        };
+        Some((line, column))
+    }
+
+    for word in message.split_whitespace() {
+        // `file.scss:3:5`
+        if let Some(position) = word.find(".scss:") {
+            if let Some(location) = parse_line_column(&word[position + 6..]) {
+                return Some(location);
+            }
+        }
+    }
+
+    let words: Vec<&str> = message.split_whitespace().collect();
+    for pair in words.windows(2) {
+        // `file.scss 3:5`
+        if pair[0].ends_with(".scss") && pair[1].contains(':') {
+            if let Some(location) = parse_line_column(pair[1]) {
+                return Some(location);
+            }
+        }
+
+        // `on line 3`
+        if pair[0] == "line" {
+            if let Some((line, _)) = parse_line_column(pair[1]) {
+                return Some((line, None));
+            }
+        }
+    }
+
+    None
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{parse_location, Backend, Compiler, Sourcemap, Style};
+
+    #[test]
+    fn test_parse_location() {
+        assert_eq!(parse_location("Parse error: theme.scss:3:5: expected ;"), Some((3, Some(5))));
+        assert_eq!(
+            parse_location("Error: expected \";\".\n  theme.scss 12:8  root stylesheet"),
+            Some((12, Some(8)))
+        );
+        assert_eq!(parse_location("Error: Invalid CSS on line 7 of theme.scss"), Some((7, None)));
+        assert_eq!(parse_location("Undefined variable: $color"), None);
+    }
+
+    #[test]
+    fn test_native_unsupported_options() {
+        let path = Path::new("theme.scss");
+
+        let result = Compiler::new(Backend::Native).style(Style::Nested).compile(path);
+        let error = result.expect_err("Nested style should not be supported");
+        assert_eq!(error.message, "Nested style is not supported by the native backend");
+        assert_eq!(error.line, None);
+
+        let result = Compiler::new(Backend::Native).sourcemap(Sourcemap::Inline).compile(path);
+        let error = result.expect_err("Source maps should not be supported");
+        assert_eq!(error.message, "Source map generation is not supported by the native backend");
+    }
+}
@@ -5,16 +5,39 @@
 
 #![warn(missing_docs)]
 
-use std::{fs::File, path::PathBuf};
+use std::{collections::BTreeMap, path::PathBuf};
 
 use bluefire_static_files_core::prelude::*;
 
+use crate::error::BuildError;
+
+/// Name of the manifest file written to the output directory.
+pub const MANIFEST_FILE_NAME: &str = "static-manifest.json";
+
+/// Number of hexadecimal digits of the content hash embedded in the names of served files.
+pub const NAME_HASH_LENGTH: usize = 6;
+
 // -------------------------------------------------------------------------------------------------
 
 mod utils {
     pub fn path_to_str(path: &std::path::PathBuf) -> String {
         path.to_str().expect("Expected to cast path to an UTF8 string").to_string()
     }
+
+    pub fn read_to_string(path: &std::path::PathBuf) -> Result<String, super::BuildError> {
+        std::fs::read_to_string(path).map_err(|err| super::BuildError::io(path, err))
+    }
+
+    pub fn write(path: &std::path::PathBuf, content: &[u8]) -> Result<(), super::BuildError> {
+        std::fs::write(path, content).map_err(|err| super::BuildError::io(path, err))
+    }
+
+    /// Makes a valid Rust identifier from the given path.
+    pub fn make_field_name(path: &str) -> String {
+        path.chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+            .collect()
+    }
 }
 
 // -------------------------------------------------------------------------------------------------
@@ -22,109 +45,514 @@ mod utils {
 /// Processes static files defined in `static.yaml`. This function should be called from your
 /// `build.rs` script to provide files required by `bluefire_static_files_macros`.
 ///
-/// - uses `wasm-bindgen` to process `wasm` files
-/// - uses `sass` to convert `scss` to `css`
-/// - minimizes `js` files
+/// - uses `wasm-bindgen` to process `wasm` files, optionally optimizes them with `wasm-opt` and
+///   strips debug sections in release builds
+/// - converts `scss` to `css` in-process (or with the external `sass` command if
+///   `scss_backend: external` is specified)
+/// - minimizes `js` and `css` files
+/// - copies other files (images, fonts, HTML, JSON, SVG and files matched by glob patterns)
+/// - creates gzip and Brotli compressed variants of all the compressible output files (unless
+///   disabled with `precompress: false`)
+/// - writes a JSON manifest mapping names of the files to names with embedded content hash to the
+///   output directory and, if `manifest` is specified, to the given path
+///
+/// Errors are printed and make the build script exit with a failure. Use `try_build` to handle
+/// them differently.
 pub fn build() {
+    if let Err(err) = try_build() {
+        eprintln!("error: {}", err);
+        std::process::exit(1);
+    }
+}
+
+/// Processes static files defined in `static.yaml` like `build`, returning the first error
+/// encountered.
+pub fn try_build() -> Result<(), BuildError> {
     let path = crate::cargo::get_manifest_path();
-    FileProcessor::new(path).process();
+    FileProcessor::new(path)?.process()
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Describes a file produced from a source defined in `static.yaml`.
+#[derive(Clone, Debug)]
+pub struct OutputFile {
+    /// Path to the source file or `None` if the file is not read from the source directory (like
+    /// WASM files which are produced by the compiler).
+    pub source_path: Option<PathBuf>,
+
+    /// Path to the file in the output directory.
+    pub path: PathBuf,
+
+    /// Name of the generated field representing the file.
+    pub field_name: String,
+
+    /// Name of the file as it will be accessible from the server (without the content hash).
+    pub name: String,
+}
+
+/// Returns the list of files produced from the given source. `root` is the directory containing
+/// `static.yaml`.
+pub fn output_files(
+    root: &PathBuf,
+    spec: &Spec,
+    source: &Source,
+) -> Result<Vec<OutputFile>, BuildError> {
+    fn make_path(base_name: &str, suffix: &str, extension: &str) -> PathBuf {
+        let mut path = crate::cargo::get_out_dir();
+        path.push(base_name.to_string() + suffix);
+        path.set_extension(extension);
+        path
+    }
+
+    let mut source_dir = root.clone();
+    source_dir.push(&spec.source_dir);
+
+    match &source.variant {
+        Type::Wasm { field_name_wasm, field_name_js, .. } => Ok(vec![
+            OutputFile {
+                source_path: None,
+                path: make_path(&source.input_base_name, "_bg", "wasm"),
+                field_name: field_name_wasm.clone(),
+                name: source.output_base_name.clone() + ".wasm",
+            },
+            OutputFile {
+                source_path: None,
+                path: make_path(&source.input_base_name, "", "js"),
+                field_name: field_name_js.clone(),
+                name: source.output_base_name.clone() + ".js",
+            },
+        ]),
+        Type::Glob { pattern, field_prefix } => {
+            let mut full_pattern = source_dir.clone();
+            full_pattern.push(pattern);
+            let to_error = |message: String| BuildError::Glob { pattern: pattern.clone(), message };
+            let paths = glob::glob(&utils::path_to_str(&full_pattern))
+                .map_err(|err| to_error(err.to_string()))?;
+
+            let mut result = Vec::new();
+            let mut relative_paths = Vec::new();
+            for path in paths {
+                let path = path.map_err(|err| to_error(err.to_string()))?;
+                if !path.is_file() {
+                    continue;
+                }
+                let relative_path = path.strip_prefix(&source_dir).expect("Strip source dir");
+                let relative_str = utils::path_to_str(&relative_path.to_path_buf());
+                let (name, field_name) =
+                    glob_names(&relative_str, std::path::MAIN_SEPARATOR, field_prefix);
+                let mut output_path = crate::cargo::get_out_dir();
+                output_path.push(&name);
+                result.push(OutputFile {
+                    source_path: Some(path.clone()),
+                    path: output_path,
+                    field_name,
+                    name,
+                });
+                relative_paths.push(relative_str);
+            }
+            check_glob_names(pattern, &relative_paths, &result)?;
+            Ok(result)
+        }
+        Type::Js { field_name }
+        | Type::Scss { field_name }
+        | Type::Css { field_name }
+        | Type::Html { field_name }
+        | Type::Json { field_name }
+        | Type::Svg { field_name }
+        | Type::Image { field_name, .. }
+        | Type::Font { field_name, .. } => {
+            let input_extension = source.variant.input_extension().expect("Input extension");
+            let output_extension = source.variant.output_extension().expect("Output extension");
+
+            let mut source_path = source_dir.clone();
+            source_path.push(&source.input_base_name);
+            source_path.set_extension(input_extension);
+
+            Ok(vec![OutputFile {
+                source_path: Some(source_path),
+                path: make_path(&source.output_base_name, "", output_extension),
+                field_name: field_name.clone(),
+                name: source.output_base_name.clone() + "." + output_extension,
+            }])
+        }
+    }
+}
+
+/// Returns the served name and the field name of a file matched by a glob pattern given its path
+/// relative to the source directory. Separators are replaced by dashes in the served name, SCSS
+/// files are served as CSS and field names starting with a digit are prefixed with an underscore.
+fn glob_names(relative_path: &str, separator: char, field_prefix: &str) -> (String, String) {
+    let mut name = relative_path.replace(separator, "-");
+    if name.ends_with(".scss") {
+        name = name[..name.len() - ".scss".len()].to_string() + ".css";
+    }
+    let mut field_name = field_prefix.to_string() + &utils::make_field_name(&name);
+    if field_name.starts_with(|c: char| c.is_ascii_digit()) {
+        field_name.insert(0, '_');
+    }
+    (name, field_name)
+}
+
+/// Checks that no two files matched by a glob pattern are served under the same name or
+/// represented by the same field, e.g. `a/b-c.png` and `a-b/c.png`.
+fn check_glob_names(
+    pattern: &str,
+    relative_paths: &[String],
+    files: &[OutputFile],
+) -> Result<(), BuildError> {
+    let mut names = BTreeMap::new();
+    let mut field_names = BTreeMap::new();
+    for (relative_path, file) in relative_paths.iter().zip(files.iter()) {
+        let duplicate = match names.insert(&file.name, relative_path) {
+            Some(other) => Some((other, "name", &file.name)),
+            None => field_names
+                .insert(&file.field_name, relative_path)
+                .map(|other| (other, "field name", &file.field_name)),
+        };
+        if let Some((other, what, value)) = duplicate {
+            return Err(BuildError::Glob {
+                pattern: pattern.to_string(),
+                message: format!(
+                    "files '{}' and '{}' have the same {} '{}'",
+                    other, relative_path, what, value
+                ),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Compiles the given SCSS file into CSS with the in-process compiler. Used for serving files from
+/// disk in development mode.
+pub fn compile_scss(input_path: &std::path::Path) -> Result<Vec<u8>, String> {
+    crate::scss::Compiler::new(crate::scss::Backend::Native)
+        .compile(input_path)
+        .map_err(|err| err.to_string())
+}
+
+/// Compiles the given SCSS file into CSS with the external `sass` command. Used for serving files
+/// from disk in development mode.
+pub fn compile_scss_external(input_path: &std::path::Path) -> Result<Vec<u8>, String> {
+    crate::scss::Compiler::new(crate::scss::Backend::External)
+        .compile(input_path)
+        .map_err(|err| err.to_string())
+}
+
+/// Embeds the hash in the name of a file before its extension, e.g. `theme-gray.css` becomes
+/// `theme-gray.3fa9c1.css`.
+pub fn hashed_name(name: &str, hash: &str) -> String {
+    let hash = &hash[..std::cmp::min(hash.len(), NAME_HASH_LENGTH)];
+    match name.rfind('.') {
+        Some(pos) => format!("{}.{}{}", &name[..pos], hash, &name[pos..]),
+        None => format!("{}.{}", name, hash),
+    }
+}
+
+/// Returns the name under which the given file will be served. If `hash_names` option is enabled
+/// the name contains the hash of the content of the file.
+pub fn served_name(spec: &Spec, file: &OutputFile) -> Result<String, BuildError> {
+    if spec.hash_names {
+        Ok(hashed_name(&file.name, &crate::hashing::file_hash(&file.path)?))
+    } else {
+        Ok(file.name.clone())
+    }
 }
 
 // -------------------------------------------------------------------------------------------------
 
+/// Options of a WASM source.
+struct WasmParams<'a> {
+    target_path: &'a str,
+    triple: &'a str,
+    target: &'a WasmTarget,
+    wasm_opt: &'a Option<WasmOptLevel>,
+    strip_debug: Option<bool>,
+    keep_debug: bool,
+}
+
 struct FileProcessor {
     spec: Spec,
     path: PathBuf,
 }
 
 impl FileProcessor {
-    pub fn new(path: PathBuf) -> Self {
+    pub fn new(path: PathBuf) -> Result<Self, BuildError> {
         let mut template_path = path.clone();
         template_path.push("static.yaml");
         println!("cargo:rerun-if-changed={}", utils::path_to_str(&template_path));
 
-        let spec = Spec::read(template_path);
+        let spec = Spec::read(template_path)?;
 
-        Self { spec, path }
+        Ok(Self { spec, path })
     }
 
-    pub fn process(self) {
+    pub fn process(self) -> Result<(), BuildError> {
+        let mut manifest = BTreeMap::new();
         for source in self.spec.sources.iter() {
             match &source.variant {
-                Type::Js { .. } => self.process_js(source),
-                Type::Scss { .. } => self.process_scss(source),
-                Type::Wasm { target_path, .. } => {
-                    self.process_wasm(target_path, &source.input_base_name)
+                Type::Wasm {
+                    target_path,
+                    triple,
+                    target,
+                    wasm_opt,
+                    strip_debug,
+                    keep_debug,
+                    ..
+                } => {
+                    let wasm = WasmParams {
+                        target_path,
+                        triple,
+                        target,
+                        wasm_opt,
+                        strip_debug: *strip_debug,
+                        keep_debug: *keep_debug,
+                    };
+                    self.process_wasm(source, wasm)?
+                }
+                Type::Glob { .. } => {
+                    // Makes cargo notice files added to the source directory.
+                    let mut source_dir = self.path.clone();
+                    source_dir.push(&self.spec.source_dir);
+                    println!("cargo:rerun-if-changed={}", utils::path_to_str(&source_dir));
                 }
+                _ => {}
+            }
+
+            for output_file in output_files(&self.path, &self.spec, source)? {
+                if let Some(source_path) = output_file.source_path.as_ref() {
+                    println!("cargo:rerun-if-changed={}", utils::path_to_str(source_path));
+                    self.process_file(source_path, &output_file.path)?;
+                }
+                if self.spec.precompress && crate::compression::is_compressible(&output_file.path) {
+                    crate::compression::precompress(&output_file.path)?;
+                }
+                manifest.insert(output_file.name.clone(), served_name(&self.spec, &output_file)?);
             }
         }
-    }
 
-    pub fn process_js(&self, source: &Source) {
-        use std::io::{Read, Write};
+        self.write_manifest(&manifest)
+    }
 
-        let mut input_path = self.path.clone();
-        input_path.push(&self.spec.source_dir);
-        input_path.push(&source.input_base_name);
-        input_path.set_extension("js");
-        println!("cargo:rerun-if-changed={}", utils::path_to_str(&input_path));
+    pub fn write_manifest(&self, manifest: &BTreeMap<String, String>) -> Result<(), BuildError> {
+        let content = serde_json::to_string_pretty(manifest).expect("Serialize manifest");
+        crate::cargo::write_out_file(MANIFEST_FILE_NAME, &content)?;
 
-        let mut output_path = crate::cargo::get_out_dir();
-        output_path.push(&source.output_base_name);
-        output_path.set_extension("js");
+        if let Some(manifest_path) = self.spec.manifest.as_ref() {
+            let mut path = self.path.clone();
+            path.push(manifest_path);
+            utils::write(&path, content.as_bytes())?;
+        }
+        Ok(())
+    }
 
-        let mut input_content = String::new();
-        let mut input_file =
-            File::open(&input_path).expect(&format!("Open file: {:?}", &input_path));
-        input_file
-            .read_to_string(&mut input_content)
-            .expect(&format!("Read file: {:?}", &input_path));
+    pub fn process_file(
+        &self,
+        input_path: &PathBuf,
+        output_path: &PathBuf,
+    ) -> Result<(), BuildError> {
+        match input_path.extension().and_then(|extension| extension.to_str()) {
+            Some("js") => self.process_js(input_path, output_path),
+            Some("scss") => self.process_scss(input_path, output_path),
+            Some("css") => self.process_css(input_path, output_path),
+            _ => std::fs::copy(input_path, output_path)
+                .map(|_| ())
+                .map_err(|err| BuildError::io(input_path, err)),
+        }
+    }
 
+    pub fn process_js(
+        &self,
+        input_path: &PathBuf,
+        output_path: &PathBuf,
+    ) -> Result<(), BuildError> {
+        let input_content = utils::read_to_string(input_path)?;
         let content = minifier::js::minify(&input_content);
-        let mut output_file =
-            File::create(&output_path).expect(&format!("Create file: {:?}", &output_path));
-        output_file
-            .write_all(content.as_bytes())
-            .expect(&format!("Write to file: {:?}", &output_path));
-    }
-
-    pub fn process_scss(&self, source: &Source) {
-        let mut input_path = self.path.clone();
-        input_path.push(&self.spec.source_dir);
-        input_path.push(&source.input_base_name);
-        input_path.set_extension("scss");
-        let input_str = utils::path_to_str(&input_path);
-        println!("cargo:rerun-if-changed={}", input_str);
-
-        let mut output_path = crate::cargo::get_out_dir();
-        output_path.push(&source.output_base_name);
-        output_path.set_extension("css");
-        let output_str = utils::path_to_str(&output_path);
+        utils::write(output_path, content.as_bytes())
+    }
 
+    pub fn process_css(
+        &self,
+        input_path: &PathBuf,
+        output_path: &PathBuf,
+    ) -> Result<(), BuildError> {
+        let input_content = utils::read_to_string(input_path)?;
+        let content = minifier::css::minify(&input_content).map_err(|err| BuildError::Minify {
+            path: input_path.clone(),
+            message: err.to_string(),
+        })?;
+        utils::write(output_path, content.as_bytes())
+    }
+
+    pub fn process_scss(
+        &self,
+        input_path: &PathBuf,
+        output_path: &PathBuf,
+    ) -> Result<(), BuildError> {
         let mut cache_location = crate::cargo::get_out_dir();
         cache_location.push("sass-cache");
         let cache_str = utils::path_to_str(&cache_location);
 
-        crate::commands::sass::Sass::new(input_str, output_str)
-            .style(crate::commands::sass::Style::Expanded)
-            .sourcemap(crate::commands::sass::Sourcemap::None)
+        let content = crate::scss::Compiler::new(self.spec.scss_backend)
+            .style(crate::scss::Style::Expanded)
+            .sourcemap(crate::scss::Sourcemap::None)
             .cache_location(cache_str)
-            .run();
+            .compile(input_path)?;
+
+        utils::write(output_path, &content)
     }
 
-    pub fn process_wasm(&self, target_path: &str, input_base_name: &str) {
+    fn process_wasm(&self, source: &Source, wasm: WasmParams) -> Result<(), BuildError> {
+        let WasmParams { target_path, triple, target, wasm_opt, strip_debug, keep_debug } = wasm;
+
+        let profile = crate::cargo::get_profile();
+        let strip_debug = strip_debug.unwrap_or(profile == "release");
+
         let mut path = crate::cargo::get_manifest_path();
         path.push(target_path);
-        path.push("wasm32-unknown-unknown");
-        path.push(crate::cargo::compilation_mode());
-        path.push(&input_base_name);
+        path.push(triple);
+        path.push(&profile);
+        path.push(&source.input_base_name);
         path.set_extension("wasm");
         let path_str = utils::path_to_str(&path);
         println!("cargo:rerun-if-changed={}", path_str);
 
-        crate::commands::wasm_bindgen::WasmBindgen::new(path_str)
-            .target(crate::commands::wasm_bindgen::Target::NoModules)
-            .outdir(utils::path_to_str(&crate::cargo::get_out_dir()))
-            .run();
+        let target = match target {
+            WasmTarget::Web => crate::commands::wasm_bindgen::Target::Web,
+            WasmTarget::NoModules => crate::commands::wasm_bindgen::Target::NoModules,
+        };
+        let mut wasm_bindgen = crate::commands::wasm_bindgen::WasmBindgen::new(path_str)
+            .target(target)
+            .outdir(utils::path_to_str(&crate::cargo::get_out_dir()));
+        if strip_debug {
+            wasm_bindgen = wasm_bindgen.remove_name_section().remove_producers_section();
+        } else if keep_debug {
+            wasm_bindgen = wasm_bindgen.keep_debug();
+        }
+        wasm_bindgen.run()?;
+
+        if let Some(level) = wasm_opt {
+            let wasm_path =
+                utils::path_to_str(&output_files(&self.path, &self.spec, source)?[0].path);
+            let mut wasm_opt =
+                crate::commands::wasm_opt::WasmOpt::new(wasm_path.clone(), wasm_path)
+                    .level(level.to_flag());
+            if strip_debug {
+                wasm_opt = wasm_opt.strip_debug();
+            }
+            wasm_opt.run()?;
+        }
+        Ok(())
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::{
+        check_glob_names, glob_names, hashed_name, try_build, utils::make_field_name,
+        FileProcessor, OutputFile,
+    };
+    use bluefire_static_files_core::Error;
+
+    use crate::error::BuildError;
+
+    fn write_spec(name: &str, content: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("bluefire_build_{}", name));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("static.yaml"), content).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_hashed_name() {
+        assert_eq!(hashed_name("theme-gray.css", "3fa9c1d2e3"), "theme-gray.3fa9c1.css");
+        assert_eq!(hashed_name("app.min.js", "abcdef"), "app.min.abcdef.js");
+        assert_eq!(hashed_name("LICENSE", "abcdef"), "LICENSE.abcdef");
+    }
+
+    #[test]
+    fn test_make_field_name() {
+        assert_eq!(make_field_name("img/Logo-small.png"), "img_logo_small_png");
+    }
+
+    #[test]
+    fn test_glob_names() {
+        let names = |path: &str, prefix: &str| glob_names(path, '/', prefix);
+        assert_eq!(
+            names("img/logo.png", "img_"),
+            ("img-logo.png".into(), "img_img_logo_png".into())
+        );
+        assert_eq!(names("404.html", ""), ("404.html".into(), "_404_html".into()));
+        assert_eq!(names("css/app.scss", ""), ("css-app.css".into(), "css_app_css".into()));
+    }
+
+    #[test]
+    fn test_check_glob_names() {
+        let make_files = |paths: &[&str]| -> (Vec<String>, Vec<OutputFile>) {
+            let files = paths
+                .iter()
+                .map(|path| {
+                    let (name, field_name) = glob_names(path, '/', "");
+                    OutputFile { source_path: None, path: PathBuf::new(), field_name, name }
+                })
+                .collect();
+            (paths.iter().map(|path| path.to_string()).collect(), files)
+        };
+
+        let (paths, files) = make_files(&["a/b.png", "a/c.png"]);
+        assert_eq!(check_glob_names("**/*.png", &paths, &files), Ok(()));
+
+        let (paths, files) = make_files(&["a/b-c.png", "a-b/c.png"]);
+        match check_glob_names("**/*.png", &paths, &files) {
+            Err(BuildError::Glob { pattern, message }) => {
+                assert_eq!(pattern, "**/*.png");
+                assert!(message.contains("'a/b-c.png' and 'a-b/c.png'"));
+            }
+            result => panic!("Unexpected result: {:?}", result),
+        }
+
+        let (paths, files) = make_files(&["a_b.png", "a-b.png"]);
+        assert!(check_glob_names("*.png", &paths, &files).is_err());
+    }
+
+    #[test]
+    fn test_spec_errors() {
+        // The crate itself does not define any static files.
+        match try_build() {
+            Err(BuildError::Spec(Error::Io { path, .. })) => assert!(path.ends_with("static.yaml")),
+            result => panic!("Expected IO error, got {:?}", result),
+        }
+
+        let dir = write_spec("malformed_spec", "---\nsource_dir: [static\n");
+        match FileProcessor::new(dir.clone()).map(|_| ()) {
+            Err(BuildError::Spec(Error::Yaml { path, line, .. })) => {
+                assert_eq!(path, dir.join("static.yaml"));
+                assert!(line.is_some());
+            }
+            result => panic!("Expected YAML error, got {:?}", result),
+        }
+
+        let spec = "---\nsource_dir: static\nsources:\n  - type: pdf\n    field_name: doc\n";
+        let dir = write_spec("unknown_type", spec);
+        match FileProcessor::new(dir).map(|_| ()) {
+            Err(error @ BuildError::Spec(Error::Yaml { .. })) => {
+                assert!(error.to_string().contains("unknown variant `pdf`"))
+            }
+            result => panic!("Expected YAML error, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_invalid_glob() {
+        let spec = "---\nsource_dir: static\nsources:\n  - type: glob\n    pattern: \"img/[\"\n";
+        let dir = write_spec("invalid_glob", spec);
+        match FileProcessor::new(dir).unwrap().process() {
+            Err(BuildError::Glob { pattern, .. }) => assert_eq!(pattern, "img/["),
+            result => panic!("Expected glob error, got {:?}", result),
+        }
     }
 }
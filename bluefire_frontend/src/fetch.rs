@@ -3,21 +3,62 @@
 
 //! Communication with the server.
 
-use bluefire_twine::constants::*;
+use futures::Future;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+
+use bluefire_twine::{
+    constants::*,
+    message::Message,
+    multipart::Part,
+    transport::{RawResponse, Transport, TransportError},
+};
+
+/// Converts parts of a multipart message into `FormData`. The browser chooses the boundary and
+/// sets the content type.
+fn make_form_data(parts: &[Part]) -> wasm_bindgen::JsValue {
+    let form = web_sys::FormData::new().expect("Initialize form data");
+    for part in parts {
+        match &part.file_name {
+            Some(file_name) => {
+                let data = js_sys::Array::of1(&js_sys::Uint8Array::from(part.data.as_slice()));
+                let mut options = web_sys::BlobPropertyBag::new();
+                if let Some(content_type) = &part.content_type {
+                    options.type_(content_type);
+                }
+                let blob = web_sys::Blob::new_with_u8_array_sequence_and_options(&data, &options)
+                    .expect("Create blob");
+                form.append_with_blob_and_filename(&part.name, &blob, file_name)
+                    .expect("Append file");
+            }
+            None => form
+                .append_with_str(&part.name, &String::from_utf8_lossy(&part.data))
+                .expect("Append field"),
+        }
+    }
+    form.into()
+}
 
 /// Fetches a remote resource.
-pub fn fetch(host: &str, message: &bluefire_twine::message::Message) -> js_sys::Promise {
+pub fn fetch(host: &str, message: &Message) -> js_sys::Promise {
     let mut request_init = web_sys::RequestInit::new();
     request_init.method(message.method());
     if (message.method() != "GET") && (message.method() != "HEAD") {
-        request_init.body(Some(&wasm_bindgen::JsValue::from_str(message.body())));
+        if message.parts().is_empty() {
+            request_init.body(Some(&wasm_bindgen::JsValue::from_str(message.body())));
+        } else {
+            request_init.body(Some(&make_form_data(message.parts())));
+        }
     }
 
+    let headers = web_sys::Headers::new().expect("Initialize headers");
     if let Some(session_id) = crate::authentication::get_session_cookie() {
-        let headers = web_sys::Headers::new().expect("Initialize headers");
         headers.append(BLUEFIRE_TOKEN_HEADER, &session_id).expect("Append header");
-        request_init.headers(&headers);
     }
+    for (name, value) in message.headers() {
+        headers.append(name, value).expect("Append header");
+    }
+    request_init.headers(&headers);
 
     let path = if message.query().is_empty() {
         String::from(host) + message.path()
@@ -31,7 +72,52 @@ pub fn fetch(host: &str, message: &bluefire_twine::message::Message) -> js_sys::
     web_sys::window().unwrap().fetch_with_request(&request)
 }
 
+// -------------------------------------------------------------------------------------------------
+
+/// An asynchronous transport for generated API clients sending messages with `fetch`. Results are
+/// returned as futures.
+#[derive(Clone, Debug)]
+pub struct FetchTransport {
+    host: String,
+}
+
+impl FetchTransport {
+    /// Constructs a new `FetchTransport` sending messages to the given host.
+    pub fn new(host: &str) -> Self {
+        Self { host: host.to_string() }
+    }
+}
+
+impl<Y, E> Transport<Result<Y, E>> for FetchTransport
+where
+    Y: 'static,
+    E: 'static,
+{
+    type Output = Box<dyn Future<Item = Y, Error = E>>;
+
+    fn send<F>(&self, message: Message, parse: F) -> Self::Output
+    where
+        F: FnOnce(Result<RawResponse, TransportError>) -> Result<Y, E> + 'static,
+    {
+        let future = JsFuture::from(fetch(&self.host, &message))
+            .and_then(|value| {
+                let response: web_sys::Response = value.dyn_into()?;
+                let content_type = response.headers().get("content-type")?;
+                Ok((response.status(), content_type, response.array_buffer()?))
+            })
+            .and_then(|(status, content_type, buffer)| {
+                JsFuture::from(buffer).map(move |buffer| {
+                    let body = js_sys::Uint8Array::new(&buffer).to_vec();
+                    RawResponse { status, content_type, body }
+                })
+            })
+            .map_err(|err| TransportError::new(format!("{:?}", err)))
+            .then(parse);
+        Box::new(future)
+    }
+}
+
 /// Prelude for `fetch` module.
 pub mod prelude {
-    pub use super::fetch;
+    pub use super::{fetch, FetchTransport};
 }
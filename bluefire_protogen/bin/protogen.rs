@@ -8,10 +8,18 @@
 use std::io::Write;
 use std::str::FromStr;
 
+use bluefire_protogen::error::Error;
+
 enum Mode {
     Proto,
     Routes,
     Paths,
+    OpenApi,
+    TypeScript,
+    Lint,
+    Diff,
+    Mock,
+    Docs,
 }
 
 impl FromStr for Mode {
@@ -24,31 +32,141 @@ impl FromStr for Mode {
             Ok(Mode::Routes)
         } else if string == "paths" {
             Ok(Mode::Paths)
+        } else if string == "openapi" {
+            Ok(Mode::OpenApi)
+        } else if string == "typescript" {
+            Ok(Mode::TypeScript)
+        } else if string == "lint" {
+            Ok(Mode::Lint)
+        } else if string == "diff" {
+            Ok(Mode::Diff)
+        } else if string == "mock" {
+            Ok(Mode::Mock)
+        } else if string == "docs" {
+            Ok(Mode::Docs)
         } else {
             Err(())
         }
     }
 }
 
-fn gen_proto(input: String) {
-    let api = bluefire_protogen::spec::Api::from_str(&input).expect("Parse the spec file");
+fn gen_proto(input: &str) -> Result<String, Error> {
+    let api = bluefire_protogen::spec::Api::read_checked(input)?;
     let generator = bluefire_protogen::rust_generator::RustGenerator::new();
-    let result = generator.generate_api(&api);
-    std::io::stdout().write(result.as_ref()).unwrap();
+    generator.generate_api(&api)
 }
 
-fn gen_routes(input: String) {
-    let routes = bluefire_protogen::spec::Routes::from_str(&input).expect("Parse the spec file");
+fn gen_routes(input: &str) -> Result<String, Error> {
+    let routes = bluefire_protogen::spec::Routes::read(input)?;
     let generator = bluefire_protogen::rust_generator::RustGenerator::new();
-    let result = generator.generate_routes(&routes);
-    std::io::stdout().write(result.as_ref()).unwrap();
+    Ok(generator.generate_routes(&routes))
 }
 
-fn gen_paths(input: String) {
-    let routes = bluefire_protogen::spec::Routes::from_str(&input).expect("Parse the spec file");
+fn gen_paths(input: &str) -> Result<String, Error> {
+    let routes = bluefire_protogen::spec::Routes::read(input)?;
     let generator = bluefire_protogen::rust_generator::RustGenerator::new();
-    let result = generator.generate_paths(&routes);
-    std::io::stdout().write(result.as_ref()).unwrap();
+    Ok(generator.generate_paths(&routes))
+}
+
+fn gen_openapi(input: &str, title: Option<&str>, version: Option<&str>) -> Result<String, Error> {
+    let api = bluefire_protogen::spec::Api::read_checked(input)?;
+    let mut generator = bluefire_protogen::openapi_generator::OpenApiGenerator::new();
+    if let Some(title) = title {
+        generator = generator.with_title(title);
+    }
+    if let Some(version) = version {
+        generator = generator.with_version(version);
+    }
+    generator.generate_api(&api)
+}
+
+fn gen_typescript(input: &str) -> Result<String, Error> {
+    let api = bluefire_protogen::spec::Api::read_checked(input)?;
+    let generator = bluefire_protogen::typescript_generator::TypeScriptGenerator::new();
+    generator.generate_api(&api)
+}
+
+fn gen_docs(
+    input: &str,
+    title: Option<&str>,
+    theme: &str,
+    static_root: &str,
+    static_manifest: Option<&str>,
+) -> Result<String, Error> {
+    let api = bluefire_protogen::spec::Api::read_checked(input)?;
+    let manifest = match static_manifest {
+        Some(path) => Some(bluefire_protogen::docs_generator::read_static_manifest(path)?),
+        None => None,
+    };
+    let stylesheet =
+        bluefire_protogen::docs_generator::theme_stylesheet(static_root, theme, manifest.as_ref());
+    let mut generator =
+        bluefire_protogen::docs_generator::DocsGenerator::new().with_stylesheet(&stylesheet);
+    if let Some(title) = title {
+        generator = generator.with_title(title);
+    }
+    generator.generate_api(&api)
+}
+
+/// Prints all the problems found in the specification. Returns `true` if the check passed.
+fn lint(input: &str, deny_warnings: bool) -> Result<bool, Error> {
+    let diagnostics = bluefire_protogen::spec::Api::lint(input)?;
+    for diagnostic in diagnostics.iter() {
+        println!("{}:{}", diagnostic.file_path(std::path::Path::new(input)).display(), diagnostic);
+    }
+
+    let errors = diagnostics.iter().filter(|diagnostic| diagnostic.is_error()).count();
+    let warnings = diagnostics.len() - errors;
+    println!("{}: {} error(s), {} warning(s)", input, errors, warnings);
+    Ok(errors == 0 && (warnings == 0 || !deny_warnings))
+}
+
+/// Exit code used in 'diff' mode when breaking changes are found.
+const BREAKING_CHANGES_EXIT_CODE: i32 = 2;
+
+/// Prints changes between the two specifications. Returns `true` if there are no breaking changes.
+fn diff(old: &str, new: &str, json: bool) -> Result<bool, Error> {
+    let old = bluefire_protogen::spec::Api::read_checked(old)?;
+    let new = bluefire_protogen::spec::Api::read_checked(new)?;
+    let report = old.diff(&new);
+    if json {
+        println!("{}", report.to_json());
+    } else {
+        print!("{}", report);
+    }
+    Ok(!report.is_breaking())
+}
+
+/// Serves example responses for the specification until the process is killed.
+#[cfg(feature = "mock")]
+fn mock(input: &str, address: std::net::SocketAddr, seed: Option<u64>) -> Result<String, Error> {
+    let api = bluefire_protogen::spec::Api::read_checked(input)?;
+    let mut server = bluefire_protogen::mock::MockServer::new(api);
+    if let Some(seed) = seed {
+        server = server.with_seed(seed);
+    }
+
+    eprintln!("Serving mock API from '{}' on http://{}", input, address);
+    let builder = Box::new(server.routing_builder());
+    bluefire_backend::BlueFireKindler::start(builder).ignite_server(&address);
+    Ok(String::new())
+}
+
+#[cfg(not(feature = "mock"))]
+fn mock(_input: &str, _address: std::net::SocketAddr, _seed: Option<u64>) -> Result<String, Error> {
+    eprintln!("error: protogen was built without the 'mock' feature");
+    std::process::exit(1);
+}
+
+/// Checks if the argument can be parsed to the given type.
+fn validate<T: FromStr>(value: String) -> Result<(), String> {
+    value.parse::<T>().map(|_| ()).map_err(|_| format!("Invalid value '{}'", value))
+}
+
+/// Exits reporting a missing argument the way clap reports missing required arguments.
+fn missing_argument(usage: &str, mode: &str) -> ! {
+    let message = format!("The argument '{}' is required in '{}' mode", usage, mode);
+    clap::Error::with_description(&message, clap::ErrorKind::MissingRequiredArgument).exit()
 }
 
 fn main() {
@@ -60,24 +178,168 @@ fn main() {
                 .help("Sets the mode")
                 .takes_value(true)
                 .required(true)
-                .possible_values(&["protocol", "routes", "paths"]),
+                .possible_values(&[
+                    "protocol",
+                    "routes",
+                    "paths",
+                    "openapi",
+                    "typescript",
+                    "lint",
+                    "diff",
+                    "mock",
+                    "docs",
+                ]),
         )
         .arg(
             clap::Arg::with_name("input")
                 .long("input")
                 .value_name("INPUT")
-                .help("File to read the specifications from")
-                .required(true)
+                .help("File to read the specifications from (required in all modes but 'diff')")
+                .conflicts_with("old")
                 .takes_value(true),
         )
+        .arg(
+            clap::Arg::with_name("old")
+                .long("old")
+                .value_name("OLD")
+                .help(
+                    "File to read the old version of the specifications from (used in 'diff' mode)",
+                )
+                .requires("new")
+                .required_if("mode", "diff")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("new")
+                .long("new")
+                .value_name("NEW")
+                .help(
+                    "File to read the new version of the specifications from (used in 'diff' mode)",
+                )
+                .requires("old")
+                .required_if("mode", "diff")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("format")
+                .long("format")
+                .value_name("FORMAT")
+                .help("Format of the report (used in 'diff' mode)")
+                .takes_value(true)
+                .possible_values(&["text", "json"])
+                .default_value("text"),
+        )
+        .arg(
+            clap::Arg::with_name("title")
+                .long("title")
+                .value_name("TITLE")
+                .help("Title of the API (used in 'openapi' and 'docs' modes)")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("api-version")
+                .long("api-version")
+                .value_name("VERSION")
+                .help("Version of the API (used in 'openapi' mode)")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("address")
+                .long("address")
+                .value_name("ADDRESS")
+                .help("Address to listen on (used in 'mock' mode)")
+                .takes_value(true)
+                .validator(validate::<std::net::SocketAddr>)
+                .default_value("127.0.0.1:8000"),
+        )
+        .arg(
+            clap::Arg::with_name("seed")
+                .long("seed")
+                .value_name("SEED")
+                .help("Seed making the generated examples reproducible (used in 'mock' mode)")
+                .takes_value(true)
+                .validator(validate::<u64>),
+        )
+        .arg(
+            clap::Arg::with_name("theme")
+                .long("theme")
+                .value_name("THEME")
+                .help("Theme from 'bluefire_static_files' styling the page (used in 'docs' mode)")
+                .takes_value(true)
+                .possible_values(&["gray", "steelblue", "vermilion"])
+                .default_value("steelblue"),
+        )
+        .arg(
+            clap::Arg::with_name("static-root")
+                .long("static-root")
+                .value_name("ROOT")
+                .help("Root under which the static files are served (used in 'docs' mode)")
+                .takes_value(true)
+                .default_value("static"),
+        )
+        .arg(
+            clap::Arg::with_name("static-manifest")
+                .long("static-manifest")
+                .value_name("MANIFEST")
+                .help(
+                    "Static files manifest resolving the hashed name of the theme file, e.g. \
+                     'bluefire_static_files/static-manifest.json' (used in 'docs' mode)",
+                )
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("deny-warnings")
+                .long("deny-warnings")
+                .help("Fails if any warnings are found (used in 'lint' mode)"),
+        )
         .get_matches();
 
-    let mode = Mode::from_str(matches.value_of("mode").unwrap()).unwrap();
-    let input = matches.value_of("input").unwrap();
-    let spec = std::fs::read_to_string(input).expect("Read file");
-    match mode {
-        Mode::Proto => gen_proto(spec),
-        Mode::Routes => gen_routes(spec),
-        Mode::Paths => gen_paths(spec),
+    let mode_name = matches.value_of("mode").unwrap();
+    let mode = Mode::from_str(mode_name).unwrap();
+    // Clap can not require an argument depending on the value of another one.
+    let input = || match matches.value_of("input") {
+        Some(input) => input,
+        None => missing_argument("--input <INPUT>", mode_name),
+    };
+    let result = match mode {
+        Mode::Proto => gen_proto(input()),
+        Mode::Routes => gen_routes(input()),
+        Mode::Paths => gen_paths(input()),
+        Mode::OpenApi => {
+            gen_openapi(input(), matches.value_of("title"), matches.value_of("api-version"))
+        }
+        Mode::TypeScript => gen_typescript(input()),
+        Mode::Lint => match lint(input(), matches.is_present("deny-warnings")) {
+            Ok(passed) => std::process::exit(if passed { 0 } else { 1 }),
+            Err(err) => Err(err),
+        },
+        Mode::Diff => {
+            // Both are required in this mode.
+            let old = matches.value_of("old").unwrap();
+            let new = matches.value_of("new").unwrap();
+            match diff(old, new, matches.value_of("format") == Some("json")) {
+                Ok(true) => std::process::exit(0),
+                Ok(false) => std::process::exit(BREAKING_CHANGES_EXIT_CODE),
+                Err(err) => Err(err),
+            }
+        }
+        Mode::Mock => {
+            let address = matches.value_of("address").unwrap().parse().unwrap();
+            mock(input(), address, matches.value_of("seed").map(|seed| seed.parse().unwrap()))
+        }
+        Mode::Docs => gen_docs(
+            input(),
+            matches.value_of("title"),
+            matches.value_of("theme").unwrap(),
+            matches.value_of("static-root").unwrap(),
+            matches.value_of("static-manifest"),
+        ),
+    };
+    match result {
+        Ok(result) => std::io::stdout().write_all(result.as_ref()).expect("Write to stdout"),
+        Err(err) => {
+            eprintln!("error: {}", err);
+            std::process::exit(1);
+        }
     }
 }
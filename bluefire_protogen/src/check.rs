@@ -0,0 +1,1365 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy of
+// the MPL was not distributed with this file, You can obtain one at http://mozilla.org/MPL/2.0/
+
+//! Semantic checks of API specifications.
+//!
+//! Deserialization verifies only the format of the specification. `Api::check` verifies references
+//! between definitions, uniqueness of names, applicability of validation rules and other problems
+//! which would otherwise be detected only while generating or compiling the code.
+
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use yaml_rust::parser::{Event, MarkedEventReceiver, Parser};
+use yaml_rust::scanner::Marker;
+
+use crate::{error::Error, imports, pagination, spec, utils};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Severity of a diagnostic.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    /// The specification is valid but probably contains a mistake.
+    Warning,
+
+    /// Code cannot be generated from the specification.
+    Error,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A step on the path from the root of the specification to one of its nodes.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum NodeKey {
+    /// A key in a mapping.
+    Key(String),
+
+    /// An index in a sequence.
+    Index(usize),
+}
+
+/// A position in the specification file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Location {
+    /// Line (starting from 1).
+    pub line: usize,
+
+    /// Column (starting from 1).
+    pub column: usize,
+}
+
+/// A problem found in the specification.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// Severity of the problem.
+    pub severity: Severity,
+
+    /// Path to the node containing the problem.
+    pub node: Vec<NodeKey>,
+
+    /// Position of the node in the specification file, if known.
+    pub location: Option<Location>,
+
+    /// Path to the imported file containing the node or `None` if the node is in the checked file.
+    pub file: Option<PathBuf>,
+
+    /// Description of the problem.
+    pub message: String,
+}
+
+impl Diagnostic {
+    /// Constructs a new `Diagnostic` without location.
+    pub fn new(severity: Severity, node: Vec<NodeKey>, message: String) -> Self {
+        Self { severity, node, location: None, file: None, message }
+    }
+
+    /// Returns the path to the file containing the node given the path to the checked file.
+    pub fn file_path<'a>(&'a self, checked: &'a Path) -> &'a Path {
+        self.file.as_ref().map(|file| file.as_path()).unwrap_or(checked)
+    }
+
+    /// Checks if the diagnostic is an error.
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    /// Formats the path to the node, e.g. `types[2].container.members[0]`.
+    pub fn node_path(&self) -> String {
+        let mut result = String::new();
+        for key in self.node.iter() {
+            match key {
+                NodeKey::Key(key) if result.is_empty() => result += key,
+                NodeKey::Key(key) => result += &format!(".{}", key),
+                NodeKey::Index(index) => result += &format!("[{}]", index),
+            }
+        }
+        result
+    }
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.location {
+            Some(location) => write!(f, "{}:{}: ", location.line, location.column)?,
+            None => write!(f, "{}: ", self.node_path())?,
+        }
+        write!(f, "{}: {}", self.severity, self.message)
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Helper for building node paths.
+#[derive(Clone, Debug, Default)]
+struct Node(Vec<NodeKey>);
+
+impl Node {
+    fn key(&self, key: &str) -> Node {
+        let mut keys = self.0.clone();
+        keys.push(NodeKey::Key(key.to_string()));
+        Node(keys)
+    }
+
+    fn index(&self, index: usize) -> Node {
+        let mut keys = self.0.clone();
+        keys.push(NodeKey::Index(index));
+        Node(keys)
+    }
+}
+
+/// Returns the name of the check as used in the specification.
+fn check_name(check: &spec::Check) -> &'static str {
+    match check {
+        spec::Check::Email => "email",
+        spec::Check::Url => "url",
+        spec::Check::Uuid => "uuid",
+        spec::Check::NonEmpty => "non_empty",
+    }
+}
+
+/// Returns the name of the condition as used in the specification.
+fn condition_name(condition: &spec::Condition) -> &'static str {
+    match condition {
+        spec::Condition::Le(..) => "le",
+        spec::Condition::Ge(..) => "ge",
+        spec::Condition::LenEq(..) => "len_eq",
+        spec::Condition::LenLe(..) => "len_le",
+        spec::Condition::LenGe(..) => "len_ge",
+        spec::Condition::Regex(..) => "regex",
+        spec::Condition::OneOf(..) => "one_of",
+        spec::Condition::EqField(..) => "eq_field",
+        spec::Condition::LeField(..) => "le_field",
+        spec::Condition::GeField(..) => "ge_field",
+    }
+}
+
+/// Returns the name of the simple type as used in the specification.
+fn simple_type_name(simple_type: &spec::SimpleType) -> String {
+    serde_yaml::to_value(simple_type)
+        .ok()
+        .and_then(|value| value.as_str().map(|name| name.to_string()))
+        .unwrap_or_default()
+}
+
+/// Checks if the file member is a single file, an optional file or a vector of files.
+fn is_file_container(member: &spec::Member) -> bool {
+    let layers = member.container.as_ref().map(|container| container.layers()).unwrap_or_default();
+    match layers.as_slice() {
+        [] | [spec::Layer::Optional] | [spec::Layer::Vector] => true,
+        _ => false,
+    }
+}
+
+/// Describes the innermost type of a member as far as validation is concerned.
+enum BaseType<'a> {
+    /// A simple type or a type defined as a simple type.
+    Simple(spec::SimpleType),
+
+    /// Any other defined type.
+    Other(&'a utils::Name),
+
+    /// A type which is not defined.
+    Unknown,
+}
+
+/// A validated member together with the other members it may be compared with.
+#[derive(Clone, Copy)]
+struct Siblings<'a> {
+    /// Type of the validated member.
+    tipe: &'a spec::MemberType,
+
+    /// All the members of the structure or request.
+    members: &'a [spec::Member],
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Collects diagnostics while traversing the specification.
+struct Checker<'a> {
+    api: &'a spec::Api,
+    diagnostics: Vec<Diagnostic>,
+    used_types: HashSet<String>,
+    used_yields: HashSet<String>,
+    used_reasons: HashSet<String>,
+
+    /// If `false` definitions which are never used are not reported (e.g. in imported files).
+    report_unused: bool,
+}
+
+impl<'a> Checker<'a> {
+    fn new(api: &'a spec::Api) -> Self {
+        Self {
+            api,
+            diagnostics: Vec::new(),
+            used_types: HashSet::new(),
+            used_yields: HashSet::new(),
+            used_reasons: HashSet::new(),
+            report_unused: true,
+        }
+    }
+
+    fn error(&mut self, node: Node, message: String) {
+        self.diagnostics.push(Diagnostic::new(Severity::Error, node.0, message));
+    }
+
+    fn warning(&mut self, node: Node, message: String) {
+        self.diagnostics.push(Diagnostic::new(Severity::Warning, node.0, message));
+    }
+
+    fn find_type(&self, name: &utils::Name) -> Option<&'a spec::TypeDef> {
+        self.api.types.iter().find(|tipe| tipe.name == *name)
+    }
+
+    /// Reports all but the first occurrences of each name.
+    fn check_unique<'b, I>(&mut self, node: Node, names: I, what: &str)
+    where
+        I: Iterator<Item = &'b utils::Name>,
+    {
+        let mut seen = HashSet::new();
+        for (index, name) in names.enumerate() {
+            if !seen.insert(name.kebab_case()) {
+                let message = format!("{} '{}' is defined more than once", what, name.kebab_case());
+                self.error(node.index(index).key("name"), message);
+            }
+        }
+    }
+
+    fn run(mut self) -> Vec<Diagnostic> {
+        let api = self.api;
+        let root = Node::default();
+        self.check_unique(root.key("types"), api.types.iter().map(|t| &t.name), "Type");
+        self.check_unique(root.key("yields"), api.yields.iter().map(|y| &y.name), "Yield");
+        self.check_unique(root.key("reasons"), api.reasons.iter().map(|r| &r.name), "Reason");
+        self.check_unique(root.key("methods"), api.methods.iter().map(|m| &m.name), "Method");
+
+        for (index, tipe) in api.types.iter().enumerate() {
+            self.check_type(root.key("types").index(index), tipe);
+        }
+        for (index, yeeld) in api.yields.iter().enumerate() {
+            let node = root.key("yields").index(index).key("args");
+            self.check_members(node.clone(), &yeeld.args);
+            self.check_non_args(node.clone(), &yeeld.args);
+            if yeeld.is_binary() && !yeeld.args.is_empty() {
+                let message =
+                    format!("Binary yield '{}' cannot have arguments", yeeld.name.kebab_case());
+                self.error(node, message);
+            }
+        }
+        for (index, reason) in api.reasons.iter().enumerate() {
+            let node = root.key("reasons").index(index).key("cases");
+            self.check_unique(node.clone(), reason.cases.iter().map(|c| &c.name), "Case");
+            for (index, case) in reason.cases.iter().enumerate() {
+                self.check_members(node.index(index).key("args"), &case.args);
+                self.check_non_args(node.index(index).key("args"), &case.args);
+            }
+        }
+
+        let paths = self.check_routes(root.key("routes"), &api.routes);
+        for (index, method) in api.methods.iter().enumerate() {
+            self.check_method(root.key("methods").index(index), method, &paths);
+        }
+
+        if !self.report_unused {
+            return self.diagnostics;
+        }
+        for (index, tipe) in api.types.iter().enumerate() {
+            if !self.used_types.contains(&tipe.name.kebab_case()) {
+                let message = format!("Type '{}' is never used", tipe.name.kebab_case());
+                self.warning(root.key("types").index(index).key("name"), message);
+            }
+        }
+        for (index, yeeld) in api.yields.iter().enumerate() {
+            if !self.used_yields.contains(&yeeld.name.kebab_case()) {
+                let message = format!("Yield '{}' is never used", yeeld.name.kebab_case());
+                self.warning(root.key("yields").index(index).key("name"), message);
+            }
+        }
+        for (index, reason) in api.reasons.iter().enumerate() {
+            if !self.used_reasons.contains(&reason.name.kebab_case()) {
+                let message = format!("Reason '{}' is never used", reason.name.kebab_case());
+                self.warning(root.key("reasons").index(index).key("name"), message);
+            }
+        }
+
+        self.diagnostics
+    }
+
+    fn check_type(&mut self, node: Node, tipe: &spec::TypeDef) {
+        let node = node.key("container");
+        match &tipe.container {
+            spec::TypeRepr::Simple { simple_type: spec::SimpleType::File, .. } => {
+                let message = format!(
+                    "Type '{}' cannot be a file, use the 'file' type directly in request arguments",
+                    tipe.name.kebab_case()
+                );
+                self.error(node.key("type"), message);
+            }
+            spec::TypeRepr::Simple { simple_type, validation: Some(validation) } => {
+                let node = node.key("validation");
+                self.check_patterns(node.clone(), validation);
+                self.check_rules(node.clone(), validation, &BaseType::Simple(simple_type.clone()));
+                if validation.each.is_some() {
+                    let message = "'each' can be used only on vectors and maps".to_string();
+                    self.error(node.key("each"), message);
+                }
+            }
+            spec::TypeRepr::Simple { validation: None, .. } | spec::TypeRepr::External => {}
+            spec::TypeRepr::Struct { members } | spec::TypeRepr::Union { members } => {
+                self.check_members(node.key("members"), members);
+                self.check_non_args(node.key("members"), members);
+            }
+            spec::TypeRepr::Enum { values } => {
+                let mut seen = HashSet::new();
+                for (index, value) in values.iter().enumerate() {
+                    if !seen.insert(value.kebab_case()) {
+                        let message =
+                            format!("Value '{}' is defined more than once", value.kebab_case());
+                        self.error(node.key("values").index(index), message);
+                    }
+                }
+            }
+        }
+    }
+
+    fn check_members(&mut self, node: Node, members: &[spec::Member]) {
+        self.check_unique(node.clone(), members.iter().map(|m| &m.name), "Member");
+        for (index, member) in members.iter().enumerate() {
+            self.check_member(node.index(index), member, members);
+        }
+    }
+
+    /// Reports members with a location given or of file type, which are meaningful only for
+    /// request arguments.
+    fn check_non_args(&mut self, node: Node, members: &[spec::Member]) {
+        for (index, member) in members.iter().enumerate() {
+            if member.location.is_some() {
+                let message = format!(
+                    "Location of member '{}' is ignored outside of request arguments",
+                    member.name.kebab_case()
+                );
+                self.error(node.index(index).key("in"), message);
+            }
+            if member.is_file() {
+                let message = format!(
+                    "Member '{}' of file type can be used only as a request argument",
+                    member.name.kebab_case()
+                );
+                self.error(node.index(index).key("type"), message);
+            }
+        }
+    }
+
+    fn check_member(&mut self, node: Node, member: &spec::Member, siblings: &[spec::Member]) {
+        let base = match &member.tipe {
+            spec::MemberType::Simple(simple_type) => BaseType::Simple(simple_type.clone()),
+            spec::MemberType::Defined(name) => {
+                self.used_types.insert(name.kebab_case());
+                match self.find_type(name).map(|tipe| &tipe.container) {
+                    Some(spec::TypeRepr::Simple { simple_type, .. }) => {
+                        BaseType::Simple(simple_type.clone())
+                    }
+                    Some(..) => BaseType::Other(name),
+                    None => {
+                        let message = format!("Type '{}' is not defined", name.kebab_case());
+                        self.error(node.key("type"), message);
+                        BaseType::Unknown
+                    }
+                }
+            }
+        };
+
+        if let Some(validation) = &member.validation {
+            let layers =
+                member.container.as_ref().map(|container| container.layers()).unwrap_or_default();
+            let siblings = if layers.is_empty() {
+                Some(Siblings { tipe: &member.tipe, members: siblings })
+            } else {
+                None
+            };
+            self.check_validation(node.key("validation"), &layers, &base, validation, siblings);
+        }
+    }
+
+    /// Checks if the validation rules can be applied to a value wrapped in the given containers.
+    /// Conditions comparing members are allowed only if `siblings` are given.
+    fn check_validation(
+        &mut self,
+        node: Node,
+        layers: &[spec::Layer],
+        base: &BaseType,
+        validation: &spec::Validation,
+        siblings: Option<Siblings>,
+    ) {
+        match layers.split_first() {
+            Some((spec::Layer::Optional, rest)) => {
+                self.check_validation(node, rest, base, validation, siblings);
+            }
+            Some((_, rest)) => {
+                self.check_patterns(node.clone(), validation);
+                for (index, check) in validation.checks.iter().enumerate() {
+                    if !check.applies_to_collection() {
+                        let message = format!(
+                            "Check '{}' cannot be applied to a collection; use 'each' to validate \
+                             its elements",
+                            check_name(check)
+                        );
+                        self.error(node.key("checks").index(index), message);
+                    }
+                }
+                for (index, condition) in validation.conditions.iter().enumerate() {
+                    if !condition.applies_to_collection() {
+                        let message = format!(
+                            "Condition '{}' cannot be applied to a collection; use 'each' to \
+                             validate its elements",
+                            condition_name(condition)
+                        );
+                        self.error(node.key("conditions").index(index), message);
+                    }
+                }
+                if let Some(each) = &validation.each {
+                    self.check_validation(node.key("each"), rest, base, each, None);
+                }
+            }
+            None => {
+                self.check_patterns(node.clone(), validation);
+                self.check_rules(node.clone(), validation, base);
+                self.check_fields(node.clone(), validation, siblings);
+                if validation.each.is_some() {
+                    let message = "'each' can be used only on vectors and maps".to_string();
+                    self.error(node.key("each"), message);
+                }
+            }
+        }
+    }
+
+    /// Checks if the checks and conditions (other than the ones comparing members) can be applied
+    /// to values of the given type.
+    fn check_rules(&mut self, node: Node, validation: &spec::Validation, base: &BaseType) {
+        let type_name = match base {
+            BaseType::Simple(simple_type) => simple_type_name(simple_type),
+            BaseType::Other(name) => name.kebab_case(),
+            BaseType::Unknown => return,
+        };
+        let simple_type = match base {
+            BaseType::Simple(simple_type) => Some(simple_type),
+            _ => None,
+        };
+
+        for (index, check) in validation.checks.iter().enumerate() {
+            if !simple_type.map(|simple_type| check.applies_to(simple_type)).unwrap_or(false) {
+                let message = format!(
+                    "Check '{}' cannot be applied to type '{}'",
+                    check_name(check),
+                    type_name
+                );
+                self.error(node.key("checks").index(index), message);
+            }
+        }
+        for (index, condition) in validation.conditions.iter().enumerate() {
+            if condition.field().is_some() {
+                continue;
+            }
+            if !simple_type.map(|simple_type| condition.applies_to(simple_type)).unwrap_or(false) {
+                let message = format!(
+                    "Condition '{}' cannot be applied to type '{}'",
+                    condition_name(condition),
+                    type_name
+                );
+                self.error(node.key("conditions").index(index), message);
+            }
+        }
+    }
+
+    /// Checks if the regular expressions used in the conditions are valid.
+    fn check_patterns(&mut self, node: Node, validation: &spec::Validation) {
+        for (index, condition) in validation.conditions.iter().enumerate() {
+            if let spec::Condition::Regex(pattern) = condition {
+                if let Err(err) = bluefire_twine::validation::compile_regex(pattern) {
+                    // Syntax errors span several lines with the problem described in the last one.
+                    let err = err.to_string();
+                    let reason =
+                        err.lines().last().unwrap_or_default().trim_start_matches("error: ");
+                    let message = format!("Invalid regular expression '{}': {}", pattern, reason);
+                    self.error(node.key("conditions").index(index), message);
+                }
+            }
+        }
+    }
+
+    /// Checks if the conditions comparing members refer to existing members of the same type.
+    fn check_fields(
+        &mut self,
+        node: Node,
+        validation: &spec::Validation,
+        siblings: Option<Siblings>,
+    ) {
+        for (index, condition) in validation.conditions.iter().enumerate() {
+            let field = match condition.field() {
+                Some(field) => field,
+                None => continue,
+            };
+            let node = node.key("conditions").index(index);
+            let name = condition_name(condition);
+            match siblings {
+                None => {
+                    let message = format!(
+                        "Condition '{}' can be used only on members of structures and requests \
+                         without containers",
+                        name
+                    );
+                    self.error(node, message);
+                }
+                Some(siblings) => match siblings.members.iter().find(|m| m.name == *field) {
+                    Some(member) if member.container.is_some() => {
+                        let message = format!(
+                            "Condition '{}' cannot compare with member '{}' as it has a container",
+                            name,
+                            field.kebab_case()
+                        );
+                        self.error(node, message);
+                    }
+                    Some(member) if member.tipe != *siblings.tipe => {
+                        let message = format!(
+                            "Condition '{}' cannot compare with member '{}' as it has a different \
+                             type",
+                            name,
+                            field.kebab_case()
+                        );
+                        self.error(node, message);
+                    }
+                    Some(..) => {}
+                    None => {
+                        let message = format!(
+                            "Condition '{}' refers to unknown member '{}'",
+                            name,
+                            field.kebab_case()
+                        );
+                        self.error(node, message);
+                    }
+                },
+            }
+        }
+    }
+
+    /// Checks names of the routes and returns names of all the paths.
+    fn check_routes(&mut self, node: Node, routes: &[spec::Route]) -> HashSet<String> {
+        fn collect_paths(routes: &[spec::Route], node: Node, found: &mut Vec<(Node, String)>) {
+            for (index, route) in routes.iter().enumerate() {
+                let node = node.index(index);
+                if let Some(name) = &route.name {
+                    found.push((node.key("name"), name.kebab_case()));
+                }
+                collect_paths(&route.routes, node.key("routes"), found);
+            }
+        }
+
+        let mut found = Vec::new();
+        collect_paths(routes, node, &mut found);
+
+        let mut paths = HashSet::new();
+        for (node, name) in found {
+            if !paths.insert(name.clone()) {
+                self.error(node, format!("Path '{}' is defined more than once", name));
+            }
+        }
+        paths
+    }
+
+    fn check_method(&mut self, node: Node, method: &spec::Method, paths: &HashSet<String>) {
+        let request = &method.request;
+        let path = request.path.kebab_case();
+        if !paths.contains(&path) {
+            let message = format!("Path '{}' is not defined", path);
+            self.error(node.key("request").key("path"), message);
+        }
+
+        if !request.format.is_json() && !request.has_body() {
+            let message = format!(
+                "GET method '{}' cannot have a {} body",
+                method.name.kebab_case(),
+                request.format.as_str()
+            );
+            self.error(node.key("request").key("format"), message);
+        }
+        if request.limit.is_some() && request.format != spec::BodyFormat::Multipart {
+            let message = format!(
+                "Limit of method '{}' is ignored for a {} body",
+                method.name.kebab_case(),
+                request.format.as_str()
+            );
+            self.warning(node.key("request").key("limit"), message);
+        }
+
+        let args = node.key("request").key("args");
+        self.check_members(args.clone(), &request.args);
+        for (index, arg) in request.args.iter().enumerate() {
+            let location = request.location_of(arg);
+            if arg.is_file() {
+                let multipart = request.format == spec::BodyFormat::Multipart;
+                if !multipart || location != spec::ArgLocation::Body || !is_file_container(arg) {
+                    let message = format!(
+                        "File argument '{}' of method '{}' can be sent only in a multipart body \
+                         as a single, optional or vector of files",
+                        arg.name.kebab_case(),
+                        method.name.kebab_case()
+                    );
+                    self.error(args.index(index), message);
+                }
+            } else if location == spec::ArgLocation::Body
+                && !request.format.is_json()
+                && !self.is_query_encodable(arg)
+            {
+                let message = format!(
+                    "Argument '{}' of method '{}' cannot be encoded as a {} field",
+                    arg.name.kebab_case(),
+                    method.name.kebab_case(),
+                    request.format.as_str()
+                );
+                self.error(args.index(index), message);
+            } else if location == spec::ArgLocation::Body && !request.has_body() {
+                let message = format!(
+                    "Argument '{}' of GET method '{}' cannot be sent in the body",
+                    arg.name.kebab_case(),
+                    method.name.kebab_case()
+                );
+                self.error(args.index(index).key("in"), message);
+            } else if location != spec::ArgLocation::Body && !self.is_query_encodable(arg) {
+                let message = format!(
+                    "Argument '{}' of method '{}' cannot be encoded as a {} parameter",
+                    arg.name.kebab_case(),
+                    method.name.kebab_case(),
+                    location.as_str()
+                );
+                self.error(args.index(index), message);
+            }
+        }
+
+        if let Some(pagination) = &method.pagination {
+            self.check_pagination(node.clone(), method, pagination);
+        }
+
+        let response = &method.response;
+        let node = node.key("response");
+        let success = response.success.kebab_case();
+        self.used_yields.insert(success.clone());
+        if !self.api.yields.iter().any(|yeeld| yeeld.name == response.success) {
+            self.error(node.key("success"), format!("Yield '{}' is not defined", success));
+        }
+        if let Some(failure) = &response.failure {
+            self.check_reason(node.key("failure"), failure, spec::ReasonVariant::Failure);
+        }
+        self.check_reason(node.key("error"), &response.error, spec::ReasonVariant::Error);
+    }
+
+    /// Checks the pagination settings and that the arguments and the yield declared explicitly
+    /// match the ones which would be generated.
+    fn check_pagination(
+        &mut self,
+        node: Node,
+        method: &spec::Method,
+        pagination: &spec::Pagination,
+    ) {
+        let name = method.name.kebab_case();
+        if pagination.default_limit == 0 || pagination.default_limit > pagination.max_limit {
+            let message = format!(
+                "Default limit of method '{}' must be between 1 and the maximal limit {}",
+                name, pagination.max_limit
+            );
+            self.error(node.key("pagination").key("default_limit"), message);
+        }
+
+        let fields = match &pagination.item {
+            spec::MemberType::Simple(..) => Vec::new(),
+            spec::MemberType::Defined(item) => match self.find_type(item).map(|t| &t.container) {
+                Some(spec::TypeRepr::Struct { members }) => {
+                    members.iter().map(|member| member.name.clone()).collect()
+                }
+                Some(..) => Vec::new(),
+                None => {
+                    let message = format!("Type '{}' is not defined", item.kebab_case());
+                    self.error(node.key("pagination").key("item"), message);
+                    return;
+                }
+            },
+        };
+        for (key, names) in vec![("filter", &pagination.filter), ("sort", &pagination.sort)] {
+            for (index, field) in names.iter().enumerate() {
+                if !fields.contains(field) {
+                    let message = format!(
+                        "Items of method '{}' have no field '{}' to {} by",
+                        name,
+                        field.kebab_case(),
+                        key
+                    );
+                    self.error(node.key("pagination").key(key).index(index), message);
+                }
+            }
+        }
+
+        let args = &method.request.args;
+        for expected in pagination::args(method, pagination) {
+            let position = args.iter().position(|arg| arg.name == expected.name);
+            if let Some((index, arg)) = position.map(|index| (index, &args[index])) {
+                if arg.tipe != expected.tipe || arg.container != expected.container {
+                    let message = format!(
+                        "Argument '{}' of paginated method '{}' must be of type '{}'",
+                        arg.name.kebab_case(),
+                        name,
+                        expected.type_expression()
+                    );
+                    self.error(node.key("request").key("args").index(index).key("type"), message);
+                }
+            }
+        }
+
+        let yeeld = self.api.yields.iter().find(|yeeld| yeeld.name == method.response.success);
+        if let Some(yeeld) = yeeld {
+            for expected in pagination::page_yield(method, pagination).args {
+                let found = yeeld.args.iter().any(|arg| {
+                    arg.name == expected.name
+                        && arg.tipe == expected.tipe
+                        && arg.container == expected.container
+                });
+                if !found {
+                    let message = format!(
+                        "Yield '{}' of paginated method '{}' must have member '{}' of type '{}'",
+                        yeeld.name.kebab_case(),
+                        name,
+                        expected.name.kebab_case(),
+                        expected.type_expression()
+                    );
+                    self.error(node.key("response").key("success"), message);
+                }
+            }
+        }
+    }
+
+    fn check_reason(&mut self, node: Node, name: &utils::Name, variant: spec::ReasonVariant) {
+        self.used_reasons.insert(name.kebab_case());
+        match self.api.reasons.iter().find(|reason| reason.name == *name) {
+            Some(reason) if reason.variant.as_str() != variant.as_str() => {
+                let message = format!(
+                    "Reason '{}' is declared as '{}' but used as '{}'",
+                    name.kebab_case(),
+                    reason.variant.as_str(),
+                    variant.as_str()
+                );
+                self.warning(node, message);
+            }
+            Some(..) => {}
+            None => self.error(node, format!("Reason '{}' is not defined", name.kebab_case())),
+        }
+    }
+
+    /// Checks if the member can be serialized to a query string, header or cookie, i.e. it is an
+    /// optional or required scalar.
+    fn is_query_encodable(&self, member: &spec::Member) -> bool {
+        let layers =
+            member.container.as_ref().map(|container| container.layers()).unwrap_or_default();
+        if layers.iter().any(|layer| *layer != spec::Layer::Optional) {
+            return false;
+        }
+        if member.is_file() {
+            return false;
+        }
+        match &member.tipe {
+            spec::MemberType::Simple(..) => true,
+            spec::MemberType::Defined(name) => match self.find_type(name) {
+                Some(tipe) => match tipe.container {
+                    spec::TypeRepr::Simple { .. } | spec::TypeRepr::Enum { .. } => true,
+                    _ => false,
+                },
+                // Already reported as an unknown type.
+                None => true,
+            },
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Records positions of all the nodes of a YAML document.
+#[derive(Default)]
+struct Locator {
+    frames: Vec<Frame>,
+    node: Vec<NodeKey>,
+    locations: HashMap<Vec<NodeKey>, Location>,
+}
+
+/// State of a collection being parsed. The mark of a mapping is kept until its first key is
+/// found, because the mapping is located at that key.
+enum Frame {
+    Mapping { key: Option<String>, start: Option<Marker> },
+    Sequence { index: usize },
+}
+
+impl Locator {
+    fn enter_node(&mut self) {
+        match self.frames.last() {
+            Some(Frame::Mapping { key, .. }) => {
+                self.node.push(NodeKey::Key(key.clone().unwrap_or_default()))
+            }
+            Some(Frame::Sequence { index }) => self.node.push(NodeKey::Index(*index)),
+            None => {}
+        }
+    }
+
+    fn record(&mut self, mark: Marker) {
+        let location = Location { line: mark.line(), column: mark.col() + 1 };
+        self.locations.entry(self.node.clone()).or_insert(location);
+    }
+
+    fn begin_node(&mut self, mark: Marker) {
+        self.enter_node();
+        self.record(mark);
+    }
+
+    fn end_node(&mut self) {
+        match self.frames.last_mut() {
+            Some(Frame::Mapping { key, .. }) => *key = None,
+            Some(Frame::Sequence { index }) => *index += 1,
+            None => return,
+        }
+        self.node.pop();
+    }
+}
+
+impl MarkedEventReceiver for Locator {
+    fn on_event(&mut self, event: Event, mark: Marker) {
+        match event {
+            Event::Scalar(value, ..) => {
+                if let Some(Frame::Mapping { key, start }) = self.frames.last_mut() {
+                    if key.is_none() {
+                        *key = Some(value);
+                        if start.take().is_some() {
+                            self.record(mark);
+                        }
+                        return;
+                    }
+                }
+                self.begin_node(mark);
+                self.end_node();
+            }
+            Event::Alias(..) => {
+                self.begin_node(mark);
+                self.end_node();
+            }
+            Event::MappingStart(..) => {
+                self.enter_node();
+                self.frames.push(Frame::Mapping { key: None, start: Some(mark) });
+            }
+            Event::SequenceStart(..) => {
+                self.begin_node(mark);
+                self.frames.push(Frame::Sequence { index: 0 });
+            }
+            Event::MappingEnd | Event::SequenceEnd => {
+                if let Some(Frame::Mapping { start: Some(mark), .. }) = self.frames.pop() {
+                    // The mapping is empty.
+                    self.record(mark);
+                }
+                self.end_node();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Assigns locations in the given YAML source to the diagnostics. If a node cannot be found, the
+/// location of its closest ancestor is used.
+pub fn locate(diagnostics: &mut [Diagnostic], source: &str) {
+    let mut locator = Locator::default();
+    if Parser::new(source.chars()).load(&mut locator, false).is_err() {
+        return;
+    }
+
+    for diagnostic in diagnostics.iter_mut() {
+        diagnostic.location = (0..=diagnostic.node.len())
+            .rev()
+            .filter_map(|len| locator.locations.get(&diagnostic.node[..len]))
+            .next()
+            .cloned();
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+impl spec::Api {
+    /// Checks the specification and returns all the found problems. Code should not be generated
+    /// if any of them is an error.
+    pub fn check(&self) -> Vec<Diagnostic> {
+        Checker::new(self).run()
+    }
+
+    /// Fails with `Error::Check` if checking the specification finds any errors. The generators
+    /// call it before generating code, so problems in specifications which were not read with
+    /// `read_checked` (e.g. parsed with `from_str`) are reported instead of aborting generation.
+    pub fn ensure_checked(&self) -> Result<(), Error> {
+        let diagnostics = self.check();
+        if diagnostics.iter().any(|diagnostic| diagnostic.is_error()) {
+            Err(Error::Check { path: PathBuf::new(), diagnostics })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Reads the specification from the given file and checks it. The returned diagnostics contain
+    /// locations in the file.
+    pub fn lint<P: AsRef<Path>>(path: P) -> Result<Vec<Diagnostic>, Error> {
+        Self::read_and_check(path.as_ref()).map(|(_, diagnostics)| diagnostics)
+    }
+
+    /// Reads the specification from the given file and fails if checking it finds any errors.
+    pub fn read_checked<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let (api, diagnostics) = Self::read_and_check(path)?;
+        if diagnostics.iter().any(|diagnostic| diagnostic.is_error()) {
+            Err(Error::Check { path: path.to_path_buf(), diagnostics })
+        } else {
+            Ok(api)
+        }
+    }
+
+    /// Checks the file and all the files it imports. Each file is checked against its own source
+    /// so the locations are correct and definitions from imported files are reported only once.
+    fn read_and_check(path: &Path) -> Result<(Self, Vec<Diagnostic>), Error> {
+        let (api, mut diagnostics) = check_file(path, true)?;
+        for imported_path in imports::imported_files(path)? {
+            let (_, imported) = check_file(&imported_path, false)?;
+            diagnostics.extend(
+                imported.into_iter().map(|diagnostic| Diagnostic {
+                    file: Some(imported_path.clone()),
+                    ..diagnostic
+                }),
+            );
+        }
+        Ok((api, diagnostics))
+    }
+}
+
+/// Ranges of indices of the types, yields and reasons merged from imported files.
+struct ImportedRanges {
+    types: Range<usize>,
+    yields: Range<usize>,
+    reasons: Range<usize>,
+}
+
+impl ImportedRanges {
+    /// Checks if the node belongs to one of the imported definitions.
+    fn contains(&self, node: &[NodeKey]) -> bool {
+        match node {
+            [NodeKey::Key(key), NodeKey::Index(index), ..] => match key.as_str() {
+                "types" => self.types.contains(index),
+                "yields" => self.yields.contains(index),
+                "reasons" => self.reasons.contains(index),
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+}
+
+/// Reads and checks a single file. Problems in the definitions imported by the file are not
+/// reported since the imported files are checked separately.
+fn check_file(path: &Path, report_unused: bool) -> Result<(spec::Api, Vec<Diagnostic>), Error> {
+    let source = spec::read_file(path)?;
+    let local: spec::Api = spec::parse_yaml(path, &source)?;
+    let (types, yields, reasons) = (local.types.len(), local.yields.len(), local.reasons.len());
+    let mut api = imports::resolve(path, local)?;
+    // Imported definitions are appended to the local ones and followed by the ones generated for
+    // paginated methods.
+    let imported = ImportedRanges {
+        types: types..api.types.len(),
+        yields: yields..api.yields.len(),
+        reasons: reasons..api.reasons.len(),
+    };
+    pagination::expand(&mut api);
+
+    let mut checker = Checker::new(&api);
+    checker.report_unused = report_unused;
+    let mut diagnostics = checker.run();
+    diagnostics.retain(|diagnostic| !imported.contains(&diagnostic.node));
+    locate(&mut diagnostics, &source);
+    Ok((api, diagnostics))
+}
+
+// -------------------------------------------------------------------------------------------------
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::{locate, Location, Severity};
+    use crate::error::{Error, ReadError};
+    use crate::fixtures::Fixture;
+    use crate::{spec::Api, utils::write_test_files};
+
+    fn spec() -> String {
+        Fixture::new()
+            .with_members("- name: count\n  type: count\n")
+            .with_types(
+                "- name: count
+  container:
+    repr: simple
+    type: u32
+    validation:
+      conditions:
+        - len_le: 3
+- name: filter
+  container:
+    repr: struct
+    members:
+      - name: tags
+        type: vector<string>
+",
+            )
+            .with_args(
+                "- name: filter
+  type: filter
+- name: page
+  type: page
+",
+            )
+            .to_yaml()
+    }
+
+    #[test]
+    fn test_check() {
+        let spec = spec();
+        let api = Api::from_str(&spec).unwrap();
+        let mut diagnostics = api.check();
+        locate(&mut diagnostics, &spec);
+
+        let found: Vec<(Severity, String, Option<Location>)> =
+            diagnostics.iter().map(|d| (d.severity, d.node_path(), d.location)).collect();
+        let location = |line, column| Some(Location { line, column });
+        assert_eq!(
+            found,
+            vec![
+                (
+                    Severity::Error,
+                    "types[1].container.validation.conditions[0]".to_string(),
+                    location(15, 13)
+                ),
+                (Severity::Error, "methods[0].request.args[1].type".to_string(), location(46, 17)),
+                (Severity::Error, "methods[0].request.args[0]".to_string(), location(43, 11)),
+            ]
+        );
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "15:13: error: Condition 'len_le' cannot be applied to type 'u32'"
+        );
+    }
+
+    const BODIES_SPEC: &str = "---
+routes:
+  - name: avatar
+    exact: avatar
+yields:
+  - name: image
+    code: 200-ok
+    content_type: image/png
+    args:
+      - name: size
+        type: u32
+reasons:
+  - name: avatar-error
+    variant: error
+    cases:
+      - name: internal
+        code: 500-internal-server-error
+methods:
+  - name: get-avatar
+    request:
+      method: get
+      path: avatar
+      format: form
+    response:
+      success: image
+      error: avatar-error
+  - name: put-avatar
+    request:
+      method: put
+      path: avatar
+      format: form
+      args:
+        - name: image
+          type: file
+        - name: tags
+          type: vector<string>
+    response:
+      success: image
+      error: avatar-error
+";
+
+    fn pagination_spec() -> String {
+        Fixture::new()
+            .with_members("- name: name\n  type: string\n")
+            .with_methods(
+                "- name: list-items
+  request:
+    method: get
+    path: items
+    args:
+      - name: limit
+        type: optional<u8>
+  pagination:
+    style: offset
+    item: item
+    default_limit: 200
+    sort: [name, created]
+  response:
+    success: items
+    error: items-error
+",
+            )
+            .to_yaml()
+    }
+
+    #[test]
+    fn test_check_pagination() {
+        let spec = pagination_spec();
+        let api = Api::from_str(&spec).unwrap();
+        let mut diagnostics = api.check();
+        locate(&mut diagnostics, &spec);
+        let found: Vec<(String, Option<Location>)> =
+            diagnostics.iter().map(|d| (d.node_path(), d.location)).collect();
+        let location = |line, column| Some(Location { line, column });
+        assert_eq!(
+            found,
+            vec![
+                ("methods[1].pagination.default_limit".to_string(), location(42, 22)),
+                ("methods[1].pagination.sort[1]".to_string(), location(43, 20)),
+                ("methods[1].request.args[0].type".to_string(), location(38, 17)),
+                ("methods[1].response.success".to_string(), location(45, 16)),
+            ]
+        );
+        assert_eq!(
+            diagnostics[2].message,
+            "Argument 'limit' of paginated method 'list-items' must be of type 'optional<u32>'"
+        );
+    }
+
+    #[test]
+    fn test_check_bodies() {
+        let api = Api::from_str(BODIES_SPEC).unwrap();
+        let found: Vec<String> = api.check().iter().map(|d| d.node_path()).collect();
+        assert_eq!(
+            found,
+            vec![
+                "yields[0].args",
+                "methods[0].request.format",
+                "methods[1].request.args[0]",
+                "methods[1].request.args[1]",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_errors() {
+        let malformed = "---\ntypes:\n  - name: [item\n";
+        let spec = spec();
+        let dir =
+            write_test_files("read_errors", &[("malformed.yaml", malformed), ("api.yaml", &spec)]);
+
+        match Api::read_checked(dir.join("missing.yaml")) {
+            Err(Error::Read(ReadError::Io { path, .. })) => {
+                assert_eq!(path, dir.join("missing.yaml"))
+            }
+            result => panic!("Expected IO error, got {:?}", result),
+        }
+
+        match Api::read_checked(dir.join("malformed.yaml")) {
+            Err(Error::Read(ReadError::Yaml { path, line, .. })) => {
+                assert_eq!(path, dir.join("malformed.yaml"));
+                assert!(line.is_some());
+            }
+            result => panic!("Expected YAML error, got {:?}", result),
+        }
+
+        match Api::read_checked(dir.join("api.yaml")) {
+            Err(error @ Error::Check { .. }) => {
+                let path = dir.join("api.yaml").display().to_string();
+                let message = error.to_string();
+                assert!(message.starts_with(&format!("{}: the specification is not valid", path)));
+                assert!(message.contains(&format!("{}:15:13: error: Condition 'len_le'", path)));
+            }
+            result => panic!("Expected check error, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_check_fields() {
+        let spec = "---
+types:
+  - name: account
+    container:
+      repr: struct
+      members:
+        - name: password
+          type: string
+        - name: repeated
+          type: string
+          validation:
+            conditions:
+              - eq_field: password
+        - name: count
+          type: u32
+          validation:
+            conditions:
+              - le_field: password
+              - ge_field: limit
+        - name: tags
+          type: string
+          container: vector
+        - name: tag
+          type: string
+          validation:
+            conditions:
+              - eq_field: tags
+";
+        let api = Api::from_str(spec).unwrap();
+        let found: Vec<(String, String)> = api
+            .check()
+            .iter()
+            .filter(|d| d.is_error())
+            .map(|d| (d.node_path(), d.message.clone()))
+            .collect();
+        let prefix = "types[0].container.members";
+        assert_eq!(
+            found,
+            vec![
+                (
+                    format!("{}[2].validation.conditions[0]", prefix),
+                    "Condition 'le_field' cannot compare with member 'password' as it has a \
+                     different type"
+                        .to_string()
+                ),
+                (
+                    format!("{}[2].validation.conditions[1]", prefix),
+                    "Condition 'ge_field' refers to unknown member 'limit'".to_string()
+                ),
+                (
+                    format!("{}[4].validation.conditions[0]", prefix),
+                    "Condition 'eq_field' cannot compare with member 'tags' as it has a container"
+                        .to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_patterns() {
+        let spec = "---
+types:
+  - name: code
+    container:
+      repr: simple
+      type: string
+      validation:
+        conditions:
+          - regex: \"[A-Z]{3}\"
+          - regex: \"([A-Z]\"
+";
+        let api = Api::from_str(spec).unwrap();
+        let found: Vec<(String, String)> =
+            api.check().iter().map(|d| (d.node_path(), d.message.clone())).collect();
+        assert_eq!(
+            found,
+            vec![
+                (
+                    "types[0].container.validation.conditions[1]".to_string(),
+                    "Invalid regular expression '([A-Z]': unclosed group".to_string()
+                ),
+                ("types[0].name".to_string(), "Type 'code' is never used".to_string()),
+            ]
+        );
+    }
+
+    const IMPORTING_SPEC: &str = "---
+imports:
+  - path: common.yaml
+types:
+  - name: item
+    container:
+      repr: struct
+      members:
+        - name: page
+          type: page
+";
+
+    const IMPORTED_SPEC: &str = "---
+types:
+  - name: page
+    container:
+      repr: simple
+      type: u32
+      validation:
+        conditions:
+          - len_le: 3
+  - name: unused
+    container:
+      repr: simple
+      type: string
+";
+
+    #[test]
+    fn test_check_imports() {
+        let files = [("main.yaml", IMPORTING_SPEC), ("common.yaml", IMPORTED_SPEC)];
+        let dir = write_test_files("check_imports", &files);
+        let diagnostics = Api::lint(dir.join("main.yaml")).unwrap();
+
+        let found: Vec<_> = diagnostics
+            .iter()
+            .map(|d| (d.file.clone(), d.severity, d.node_path(), d.location))
+            .collect();
+        let location = |line, column| Some(Location { line, column });
+        assert_eq!(
+            found,
+            vec![
+                (None, Severity::Warning, "types[0].name".to_string(), location(5, 11)),
+                (
+                    Some(dir.join("common.yaml")),
+                    Severity::Error,
+                    "types[0].container.validation.conditions[0]".to_string(),
+                    location(9, 13)
+                ),
+            ]
+        );
+
+        match Api::read_checked(dir.join("main.yaml")) {
+            Err(error @ Error::Check { .. }) => {
+                let path = dir.join("common.yaml").display().to_string();
+                assert!(error.to_string().contains(&format!("{}:9:13: error:", path)));
+            }
+            result => panic!("Expected check error, got {:?}", result),
+        }
+    }
+}
@@ -0,0 +1,600 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy of
+// the MPL was not distributed with this file, You can obtain one at http://mozilla.org/MPL/2.0/
+
+//! Contract tests checking if the handlers of a router honour an API specification.
+//!
+//! `ContractTester` sends requests for every method of the specification through
+//! `BlueFireWielder::serve` without starting a server and checks the responses:
+//!
+//! - requests with arguments generated by `ExampleGenerator` must be answered with the success
+//!   yield or one of the declared failure or error cases, using the declared status code and
+//!   content matching the declared members,
+//! - requests with an argument violating its validation must be rejected with status 400.
+//!
+//! ```ignore
+//! let api = bluefire_protogen::spec::Api::read_checked("api.yaml")?;
+//! let kindler = bluefire_backend::BlueFireKindler::start(Box::new(routing_builder));
+//! bluefire_protogen::contract::ContractTester::new(&api, &kindler)
+//!     .with_header("x-bluefire-token", &token)
+//!     .run()
+//!     .assert_ok();
+//! ```
+
+use std::collections::HashMap;
+
+use serde_json::{Map, Value};
+
+use bluefire_backend::{multipart, BlueFireKindler, BlueFireWielder, Request, Response};
+use bluefire_twine::multipart::{File, Part};
+
+use crate::examples::ExampleGenerator;
+use crate::{spec, utils};
+
+/// Default number of requests with valid arguments sent for every method.
+const DEFAULT_SAMPLES: usize = 3;
+
+// -------------------------------------------------------------------------------------------------
+
+/// A response not matching the specification.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Violation {
+    /// Name of the method in kebab-case.
+    pub method: String,
+
+    /// HTTP method and URI of the request.
+    pub request: String,
+
+    /// Description of the mismatch.
+    pub message: String,
+}
+
+impl std::fmt::Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} ({}): {}", self.method, self.request, self.message)
+    }
+}
+
+/// Result of a contract test.
+#[derive(Clone, Debug, Default)]
+pub struct ContractReport {
+    /// Number of sent requests.
+    pub requests: usize,
+
+    /// Responses not matching the specification.
+    pub violations: Vec<Violation>,
+}
+
+impl ContractReport {
+    /// Checks if all the responses matched the specification.
+    pub fn is_ok(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    /// Panics listing all the violations if there are any.
+    pub fn assert_ok(&self) {
+        if !self.is_ok() {
+            panic!("Contract test failed: {}", self);
+        }
+    }
+}
+
+impl std::fmt::Display for ContractReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} requests, {} violations", self.requests, self.violations.len())?;
+        for violation in self.violations.iter() {
+            write!(f, "\n  {}", violation)?;
+        }
+        Ok(())
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Kind of a response matching the specification.
+#[derive(Clone, Debug, PartialEq)]
+enum Outcome {
+    /// The success yield.
+    Success,
+
+    /// A declared failure or error case with the given status code.
+    Case(u16),
+
+    /// The built-in validation error.
+    ValidationFailed,
+}
+
+/// Sends requests generated from a specification to a router and checks the responses.
+pub struct ContractTester<'a> {
+    api: &'a spec::Api,
+    wielder: BlueFireWielder,
+    seed: u64,
+    samples: usize,
+    host: Option<String>,
+    headers: Vec<(String, String)>,
+    path_params: HashMap<String, String>,
+}
+
+impl<'a> ContractTester<'a> {
+    /// Constructs a new `ContractTester` for the given specification and router.
+    pub fn new(api: &'a spec::Api, kindler: &BlueFireKindler) -> Self {
+        Self {
+            api,
+            wielder: kindler.kindle(),
+            seed: 0,
+            samples: DEFAULT_SAMPLES,
+            host: None,
+            headers: Vec::new(),
+            path_params: HashMap::new(),
+        }
+    }
+
+    /// Sets the seed of the generated arguments.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Sets the number of requests with valid arguments sent for every method.
+    pub fn with_samples(mut self, samples: usize) -> Self {
+        self.samples = samples;
+        self
+    }
+
+    /// Sets the `host` header of the requests.
+    pub fn with_host(mut self, host: &str) -> Self {
+        self.host = Some(host.to_string());
+        self
+    }
+
+    /// Adds a header sent with every request, e.g. an authentication token.
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Sets the value of the path parameter with the given name. Parameters without values are
+    /// filled with random strings.
+    pub fn with_path_param(mut self, name: &str, value: &str) -> Self {
+        self.path_params.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    /// Sends the requests for all the methods of the specification and returns the report.
+    pub fn run(&mut self) -> ContractReport {
+        let mut report = ContractReport::default();
+        let api = self.api;
+        let mut generator = ExampleGenerator::new(api, self.seed);
+        let paths = spec::routes_to_paths(None, &api.routes);
+        for method in api.methods.iter() {
+            match paths.iter().find(|path| path.name == method.request.path) {
+                Some(path) => self.check_method(method, path, &mut generator, &mut report),
+                None => report.violations.push(Violation {
+                    method: method.name.kebab_case(),
+                    request: String::new(),
+                    message: format!("Path '{}' not found", method.request.path.kebab_case()),
+                }),
+            }
+        }
+        report
+    }
+}
+
+impl<'a> ContractTester<'a> {
+    fn check_method(
+        &mut self,
+        method: &spec::Method,
+        path: &spec::Path,
+        generator: &mut ExampleGenerator,
+        report: &mut ContractReport,
+    ) {
+        for _ in 0..self.samples {
+            let args = generator.members(&method.request.args);
+            let request = self.make_request(method, path, &args, generator);
+            let description = format!("{} {}", request.method(), request.uri());
+            let response = self.wielder.serve(request);
+            report.requests += 1;
+            let message = match self.match_response(method, &response) {
+                Ok(Outcome::ValidationFailed) => Some(format!(
+                    "Valid request rejected: {}",
+                    String::from_utf8_lossy(response.body())
+                )),
+                Ok(..) => None,
+                Err(message) => Some(message),
+            };
+            if let Some(message) = message {
+                let method = method.name.kebab_case();
+                report.violations.push(Violation { method, request: description, message });
+            }
+        }
+
+        for arg in method.request.args.iter() {
+            let value = match generator.invalid(arg) {
+                Some(value) => value,
+                None => continue,
+            };
+            let mut args = generator.members(&method.request.args);
+            args.insert(arg.name.snake_case(), value.clone());
+            let request = self.make_request(method, path, &args, generator);
+            let description = format!("{} {}", request.method(), request.uri());
+            let response = self.wielder.serve(request);
+            report.requests += 1;
+            let message = match self.match_response(method, &response) {
+                Ok(Outcome::ValidationFailed) | Ok(Outcome::Case(400)) => None,
+                Ok(..) => {
+                    Some(format!("Invalid value {} of '{}' accepted", value, arg.name.snake_case()))
+                }
+                Err(message) => Some(message),
+            };
+            if let Some(message) = message {
+                let method = method.name.kebab_case();
+                report.violations.push(Violation { method, request: description, message });
+            }
+        }
+    }
+
+    /// Builds a request placing the arguments in their locations.
+    fn make_request(
+        &self,
+        method: &spec::Method,
+        path: &spec::Path,
+        args: &Map<String, Value>,
+        generator: &mut ExampleGenerator,
+    ) -> Request {
+        let request = &method.request;
+        let mut uri = String::new();
+        for segment in path.segments.iter() {
+            let segment = match segment {
+                spec::Segment::Exact(name) => name.snake_case(),
+                spec::Segment::Str(name) => match self.path_params.get(&name.kebab_case()) {
+                    Some(value) => value.clone(),
+                    None => generator.alphanumeric(8),
+                },
+            };
+            uri = uri + "/" + &segment;
+        }
+        if uri.is_empty() {
+            uri.push('/');
+        }
+        let query = encode(&text_pairs(&request.query_args(), args, utils::Name::snake_case));
+        if !query.is_empty() {
+            uri = uri + "?" + &query;
+        }
+
+        let mut builder = http::Request::builder();
+        builder.method(request.method.to_str()).uri(uri.as_str());
+        if let Some(host) = &self.host {
+            builder.header(http::header::HOST, host.as_str());
+        }
+        for (name, value) in self.headers.iter() {
+            builder.header(name.as_str(), value.as_str());
+        }
+        for (name, value) in text_pairs(&request.header_args(), args, utils::Name::kebab_case) {
+            builder.header(name.as_str(), value.as_str());
+        }
+        let cookies = text_pairs(&request.cookie_args(), args, utils::Name::snake_case);
+        if !cookies.is_empty() {
+            let cookies = bluefire_twine::message::format_cookies(&cookies);
+            builder.header(http::header::COOKIE, cookies.as_str());
+        }
+
+        let body = if request.is_json() {
+            let body: Map<String, Value> = request
+                .body_args()
+                .iter()
+                .filter_map(|arg| {
+                    let name = arg.name.snake_case();
+                    args.get(&name).map(|value| (name, value.clone()))
+                })
+                .collect();
+            builder.header(http::header::CONTENT_TYPE, "application/json");
+            Value::from(body).to_string().into_bytes()
+        } else if request.is_form() {
+            let pairs = text_pairs(&request.text_body_args(), args, utils::Name::snake_case);
+            builder.header(http::header::CONTENT_TYPE, "application/x-www-form-urlencoded");
+            encode(&pairs).into_bytes()
+        } else if request.is_multipart() {
+            let pairs = text_pairs(&request.text_body_args(), args, utils::Name::snake_case);
+            let mut parts: Vec<Part> =
+                pairs.iter().map(|(name, value)| Part::text(name, value)).collect();
+            for arg in request.file_args() {
+                let count = if arg.container == Some(spec::ContainerType::Vector) { 2 } else { 1 };
+                for index in 0..count {
+                    let file = File::new(generator.bytes(16))
+                        .with_name(&format!("file{}.bin", index + 1))
+                        .with_content_type("application/octet-stream");
+                    parts.push(Part::file(&arg.name.snake_case(), &file));
+                }
+            }
+            let boundary = multipart::make_boundary(&parts);
+            let content_type = multipart::content_type(&boundary);
+            builder.header(http::header::CONTENT_TYPE, content_type.as_str());
+            multipart::encode(&parts, &boundary).expect("Encode generated parts")
+        } else {
+            Vec::new()
+        };
+        builder.body(body).expect("Build contract test request")
+    }
+
+    /// Checks if the response matches the success yield or one of the cases of the method.
+    fn match_response(
+        &self,
+        method: &spec::Method,
+        response: &Response,
+    ) -> Result<Outcome, String> {
+        let status = response.status().as_u16();
+        let yeeld = spec::find_yield(&method.response.success, &self.api.yields)
+            .map_err(|err| err.to_string())?;
+        if yeeld.content_type.is_some() && status == yeeld.code.code() {
+            return match response.headers().get(http::header::CONTENT_TYPE) {
+                Some(..) => Ok(Outcome::Success),
+                None => Err("Binary response without content type".to_string()),
+            };
+        }
+
+        let body: Value = serde_json::from_slice(response.body())
+            .map_err(|_| format!("Response with status {} has no JSON body", status))?;
+        let content = &body["content"];
+        let result = body["result"].as_str().unwrap_or_default();
+        if result == "success" {
+            expect_status(status, &yeeld.code)?;
+            self.check_members(&yeeld.args, content, "content")?;
+            return Ok(Outcome::Success);
+        }
+
+        let find_reason = |name: &utils::Name| {
+            spec::find_reason(name, &self.api.reasons).map_err(|err| err.to_string())
+        };
+        let mut reasons = vec![find_reason(&method.response.error)?];
+        if let Some(failure) = &method.response.failure {
+            reasons.push(find_reason(failure)?);
+        }
+        if !reasons.iter().any(|reason| reason.variant.as_str() == result) {
+            return Err(format!("Response with status {} has invalid result", status));
+        }
+
+        let reason = content["reason"]
+            .as_str()
+            .ok_or_else(|| format!("Response with status {} has no reason", status))?;
+        if result == "error" && reason == "validation_failed" && status == 400 {
+            return Ok(Outcome::ValidationFailed);
+        }
+        let case = reasons
+            .iter()
+            .filter(|r| r.variant.as_str() == result)
+            .flat_map(|r| r.cases.iter())
+            .find(|case| case.name.snake_case() == reason)
+            .ok_or_else(|| format!("Undeclared {} reason '{}'", result, reason))?;
+        expect_status(status, &case.code)?;
+        self.check_members(&case.args, content, "content")?;
+        Ok(Outcome::Case(status))
+    }
+
+    fn check_members(
+        &self,
+        members: &[spec::Member],
+        value: &Value,
+        path: &str,
+    ) -> Result<(), String> {
+        let object = value.as_object().ok_or_else(|| format!("'{}' is not an object", path))?;
+        for member in members.iter() {
+            let name = member.name.snake_case();
+            let path = format!("{}.{}", path, name);
+            let layers =
+                member.container.as_ref().map(|container| container.layers()).unwrap_or_default();
+            match object.get(&name) {
+                Some(value) => self.check_layers(&layers, &member.tipe, value, &path)?,
+                None if member.is_optional() => {}
+                None => return Err(format!("'{}' is missing", path)),
+            }
+        }
+        Ok(())
+    }
+
+    fn check_layers(
+        &self,
+        layers: &[spec::Layer],
+        tipe: &spec::MemberType,
+        value: &Value,
+        path: &str,
+    ) -> Result<(), String> {
+        let (layer, inner) = match layers.split_first() {
+            Some(split) => split,
+            None => return self.check_type(tipe, value, path),
+        };
+        match (layer, value) {
+            (spec::Layer::Optional, Value::Null) => Ok(()),
+            (spec::Layer::Optional, value) => self.check_layers(inner, tipe, value, path),
+            (spec::Layer::Vector, Value::Array(items)) => {
+                items.iter().enumerate().try_for_each(|(index, item)| {
+                    self.check_layers(inner, tipe, item, &format!("{}[{}]", path, index))
+                })
+            }
+            (spec::Layer::Map, Value::Object(map)) => map.iter().try_for_each(|(key, item)| {
+                self.check_layers(inner, tipe, item, &format!("{}.{}", path, key))
+            }),
+            (spec::Layer::Vector, _) => Err(format!("'{}' is not an array", path)),
+            (_, _) => Err(format!("'{}' is not an object", path)),
+        }
+    }
+
+    fn check_type(&self, tipe: &spec::MemberType, value: &Value, path: &str) -> Result<(), String> {
+        let name = match tipe {
+            spec::MemberType::Simple(simple_type) => return check_simple(simple_type, value, path),
+            spec::MemberType::Defined(name) => name,
+        };
+        let tipe = match self.api.types.iter().find(|tipe| tipe.name == *name) {
+            Some(tipe) => tipe,
+            None => return Err(format!("Unknown type '{}'", name.kebab_case())),
+        };
+        match &tipe.container {
+            spec::TypeRepr::Simple { simple_type, .. } => check_simple(simple_type, value, path),
+            spec::TypeRepr::External => Ok(()),
+            spec::TypeRepr::Struct { members } => self.check_members(members, value, path),
+            spec::TypeRepr::Union { members } => {
+                let variant = value["variant"]
+                    .as_str()
+                    .ok_or_else(|| format!("'{}' is not a '{}'", path, name.kebab_case()))?;
+                let member = members
+                    .iter()
+                    .find(|member| member.name.snake_case() == variant)
+                    .ok_or_else(|| format!("'{}' has unknown variant '{}'", path, variant))?;
+                let layers = member.container.as_ref().map(|c| c.layers()).unwrap_or_default();
+                let path = format!("{}.content", path);
+                self.check_layers(&layers, &member.tipe, &value["content"], &path)
+            }
+            spec::TypeRepr::Enum { values } => {
+                if values.iter().any(|v| value.as_str() == Some(v.snake_case().as_str())) {
+                    Ok(())
+                } else {
+                    Err(format!("'{}' is not a '{}'", path, name.kebab_case()))
+                }
+            }
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Checks if the value is a valid JSON representation of the simple type.
+fn check_simple(simple_type: &spec::SimpleType, value: &Value, path: &str) -> Result<(), String> {
+    let valid = match simple_type {
+        spec::SimpleType::Bool => value.is_boolean(),
+        spec::SimpleType::U8 => value.as_u64().map_or(false, |v| v <= u64::from(std::u8::MAX)),
+        spec::SimpleType::U32 => value.as_u64().map_or(false, |v| v <= u64::from(std::u32::MAX)),
+        spec::SimpleType::U64 => value.is_u64(),
+        spec::SimpleType::I32 => value
+            .as_i64()
+            .map_or(false, |v| v >= i64::from(std::i32::MIN) && v <= i64::from(std::i32::MAX)),
+        spec::SimpleType::I64 => value.is_i64(),
+        spec::SimpleType::F32 | spec::SimpleType::F64 => value.is_number(),
+        spec::SimpleType::File => true,
+        _ => value.is_string(),
+    };
+    if valid {
+        Ok(())
+    } else {
+        let name = serde_json::to_value(simple_type).unwrap_or_default();
+        Err(format!("'{}' is not a valid {}", path, name.as_str().unwrap_or_default()))
+    }
+}
+
+/// Checks if the status code of the response is the declared one.
+fn expect_status(status: u16, code: &spec::HttpResponse) -> Result<(), String> {
+    if status == code.code() {
+        Ok(())
+    } else {
+        Err(format!("Expected status {}, got {}", code.code(), status))
+    }
+}
+
+/// Returns names and text values of the given arguments. Missing and null values are skipped.
+fn text_pairs(
+    members: &[spec::Member],
+    args: &Map<String, Value>,
+    name: fn(&utils::Name) -> String,
+) -> Vec<(String, String)> {
+    members
+        .iter()
+        .filter_map(|member| {
+            let value = match args.get(&member.name.snake_case())? {
+                Value::Null => return None,
+                Value::String(string) => string.clone(),
+                value => value.to_string(),
+            };
+            Some((name(&member.name), value))
+        })
+        .collect()
+}
+
+/// Encodes the pairs as `application/x-www-form-urlencoded`.
+fn encode(pairs: &[(String, String)]) -> String {
+    serde_urlencoded::to_string(pairs).expect("Encode pairs")
+}
+
+// -------------------------------------------------------------------------------------------------
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use bluefire_backend::router::{Host, Route, RoutingBuilder};
+    use bluefire_backend::{BlueFire, BlueFireKindler, Handler, Request, Response};
+
+    use super::ContractTester;
+    use crate::{fixtures::Fixture, spec::Api};
+
+    fn api() -> Api {
+        Fixture::new()
+            .with_members(
+                "- name: count
+  type: u32
+- name: label
+  type: string
+  container: optional
+",
+            )
+            .with_args(
+                "- name: limit
+  type: u8
+  validation:
+    conditions:
+      - ge: 1
+      - le: 10
+",
+            )
+            .to_api()
+    }
+
+    /// Handler implementing `get-items`. The broken one returns counts as strings and does not
+    /// validate the limit.
+    #[derive(Clone, Debug)]
+    struct ItemsHandler {
+        broken: bool,
+    }
+
+    impl Handler for ItemsHandler {
+        fn handle(&self, _context: &BlueFire, request: Request) -> Response {
+            let query = request.uri().query().unwrap_or_default();
+            let limit: u64 = query.trim_start_matches("limit=").parse().unwrap_or_default();
+            let (status, body) = if self.broken {
+                (200, json!({ "result": "success", "content": { "items": [{ "count": "1" }] } }))
+            } else if limit < 1 || limit > 10 {
+                let content = json!({ "reason": "validation_failed", "errors": [] });
+                (400, json!({ "result": "error", "content": content }))
+            } else {
+                let items = json!([{ "count": limit, "label": null }, { "count": 1 }]);
+                (200, json!({ "result": "success", "content": { "items": items } }))
+            };
+            http::Response::builder().status(status).body(body.to_string().into_bytes()).unwrap()
+        }
+
+        fn duplicate(&self) -> Box<dyn Handler> {
+            Box::new(self.clone())
+        }
+    }
+
+    fn make_kindler(broken: bool) -> BlueFireKindler {
+        let route = Route::exact("items").with_view(Box::new(ItemsHandler { broken }));
+        let mut builder = RoutingBuilder::new();
+        builder.insert(Host::new_nameless(), Route::index().with_routes(vec![route]));
+        BlueFireKindler::start(Box::new(builder))
+    }
+
+    #[test]
+    fn test_contract() {
+        let api = api();
+
+        let report = ContractTester::new(&api, &make_kindler(false)).run();
+        assert!(report.is_ok(), "{}", report);
+        assert_eq!(report.requests, 4);
+
+        let report = ContractTester::new(&api, &make_kindler(true)).with_samples(2).run();
+        assert_eq!(report.requests, 3);
+        assert_eq!(report.violations.len(), 3);
+        let violation = &report.violations[0];
+        assert_eq!(violation.method, "get-items");
+        assert!(violation.request.starts_with("GET /items?limit="));
+        assert_eq!(violation.message, "'content.items[0].count' is not a valid u32");
+    }
+}
@@ -0,0 +1,612 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy of
+// the MPL was not distributed with this file, You can obtain one at http://mozilla.org/MPL/2.0/
+
+//! Detection of changes between two versions of an API specification.
+//!
+//! Changes are classified as breaking if clients generated from the old specification may stop
+//! working with a server implementing the new one. Members of types are treated conservatively as
+//! types may be used both in requests and in responses.
+
+use serde_derive::Serialize;
+
+use crate::{openapi_generator::format_path, spec, utils};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Classification of a change.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum Compatibility {
+    /// Existing clients may stop working.
+    #[serde(rename = "breaking")]
+    Breaking,
+
+    /// Existing clients continue to work.
+    #[serde(rename = "non_breaking")]
+    NonBreaking,
+}
+
+/// A single change between two versions of a specification.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Change {
+    /// Classification of the change.
+    pub compatibility: Compatibility,
+
+    /// Dot-separated path to the changed definition, e.g. `methods.get-user.request.args.page`.
+    pub path: String,
+
+    /// Description of the change.
+    pub message: String,
+}
+
+/// All the changes between two versions of a specification.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct Report {
+    /// List of changes in order of appearance in the specification.
+    pub changes: Vec<Change>,
+}
+
+impl Report {
+    /// Checks if any of the changes is breaking.
+    pub fn is_breaking(&self) -> bool {
+        self.changes.iter().any(|change| change.compatibility == Compatibility::Breaking)
+    }
+
+    /// Returns changes with the given classification.
+    pub fn filter(&self, compatibility: Compatibility) -> Vec<&Change> {
+        self.changes.iter().filter(|change| change.compatibility == compatibility).collect()
+    }
+
+    /// Serializes the report to JSON.
+    pub fn to_json(&self) -> String {
+        let value = serde_json::json!({
+            "breaking": self.is_breaking(),
+            "changes": self.changes,
+        });
+        serde_json::to_string_pretty(&value).expect("Serialize diff report")
+    }
+}
+
+impl std::fmt::Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let breaking = self.filter(Compatibility::Breaking);
+        let non_breaking = self.filter(Compatibility::NonBreaking);
+        for (title, changes) in &[("Breaking changes", &breaking), ("Other changes", &non_breaking)]
+        {
+            if !changes.is_empty() {
+                writeln!(f, "{}:", title)?;
+                for change in changes.iter() {
+                    writeln!(f, "  - {}: {}", change.path, change.message)?;
+                }
+            }
+        }
+        writeln!(f, "{} breaking, {} other change(s)", breaking.len(), non_breaking.len())
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Direction in which values of compared members are sent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Direction {
+    /// From the client to the server.
+    Request,
+
+    /// From the server to the client.
+    Response,
+
+    /// Unknown direction; the most strict rules apply.
+    Both,
+}
+
+/// Formats the type of a member as written in the specification.
+fn member_type(member: &spec::Member) -> String {
+    let base = match &member.tipe {
+        spec::MemberType::Simple(simple_type) => serde_yaml::to_value(simple_type)
+            .ok()
+            .and_then(|value| value.as_str().map(|name| name.to_string()))
+            .unwrap_or_default(),
+        spec::MemberType::Defined(name) => name.kebab_case(),
+    };
+    match &member.container {
+        Some(container) => container.wrap(base, |layer, inner| match layer {
+            spec::Layer::Vector => format!("vector<{}>", inner),
+            spec::Layer::Optional => format!("optional<{}>", inner),
+            spec::Layer::Map => format!("map<{}>", inner),
+        }),
+        None => base,
+    }
+}
+
+/// Checks if the new validation accepts values rejected by the old one.
+fn is_tightened(old: Option<&spec::Validation>, new: Option<&spec::Validation>) -> bool {
+    match (old, new) {
+        (_, None) => false,
+        (None, Some(..)) => true,
+        (Some(old), Some(new)) => {
+            new.checks.iter().any(|check| !old.checks.contains(check))
+                || new.conditions.iter().any(|condition| !old.conditions.contains(condition))
+                || is_tightened(old.each.as_ref().map(|e| &**e), new.each.as_ref().map(|e| &**e))
+        }
+    }
+}
+
+/// Compares two specifications collecting the changes.
+struct Differ {
+    changes: Vec<Change>,
+}
+
+impl Differ {
+    fn new() -> Self {
+        Self { changes: Vec::new() }
+    }
+
+    fn add(&mut self, compatibility: Compatibility, path: String, message: String) {
+        self.changes.push(Change { compatibility, path, message });
+    }
+
+    fn breaking(&mut self, path: String, message: String) {
+        self.add(Compatibility::Breaking, path, message);
+    }
+
+    fn non_breaking(&mut self, path: String, message: String) {
+        self.add(Compatibility::NonBreaking, path, message);
+    }
+
+    /// Reports removed and added names. Returns pairs of definitions present in both lists.
+    fn match_names<'a, T, F>(
+        &mut self,
+        path: &str,
+        what: &str,
+        old: &'a [T],
+        new: &'a [T],
+        name: F,
+    ) -> Vec<(&'a T, &'a T)>
+    where
+        F: Fn(&T) -> &utils::Name,
+    {
+        self.match_names_with(path, what, old, new, name, Compatibility::NonBreaking)
+    }
+
+    /// Like `match_names` but reports added names with the given compatibility.
+    fn match_names_with<'a, T, F>(
+        &mut self,
+        path: &str,
+        what: &str,
+        old: &'a [T],
+        new: &'a [T],
+        name: F,
+        added: Compatibility,
+    ) -> Vec<(&'a T, &'a T)>
+    where
+        F: Fn(&T) -> &utils::Name,
+    {
+        let mut pairs = Vec::new();
+        for old_item in old.iter() {
+            let item_name = name(old_item);
+            let item_path = format!("{}.{}", path, item_name.kebab_case());
+            match new.iter().find(|new_item| name(new_item) == item_name) {
+                Some(new_item) => pairs.push((old_item, new_item)),
+                None => self.breaking(item_path, format!("{} removed", what)),
+            }
+        }
+        for new_item in new.iter() {
+            let item_name = name(new_item);
+            if !old.iter().any(|old_item| name(old_item) == item_name) {
+                let item_path = format!("{}.{}", path, item_name.kebab_case());
+                self.add(added, item_path, format!("{} added", what));
+            }
+        }
+        pairs
+    }
+
+    fn diff_api(&mut self, old: &spec::Api, new: &spec::Api) {
+        for (old, new) in self.match_names("types", "Type", &old.types, &new.types, |t| &t.name) {
+            self.diff_type(old, new);
+        }
+
+        let old_paths = spec::routes_to_paths(None, &old.routes);
+        let new_paths = spec::routes_to_paths(None, &new.routes);
+        for (old, new) in self.match_names("paths", "Path", &old_paths, &new_paths, |p| &p.name) {
+            let (old_format, new_format) = (format_path(old), format_path(new));
+            if old_format != new_format {
+                let message = format!("Path changed from '{}' to '{}'", old_format, new_format);
+                self.breaking(format!("paths.{}", old.name.kebab_case()), message);
+            }
+        }
+
+        for (old, new) in self.match_names("yields", "Yield", &old.yields, &new.yields, |y| &y.name)
+        {
+            let path = format!("yields.{}", old.name.kebab_case());
+            self.diff_code(&path, &old.code, &new.code);
+            if old.content_type != new.content_type {
+                let format = |content_type: &Option<String>| {
+                    content_type.clone().unwrap_or_else(|| "json".to_string())
+                };
+                let message = format!(
+                    "Content type changed from '{}' to '{}'",
+                    format(&old.content_type),
+                    format(&new.content_type)
+                );
+                self.breaking(format!("{}.content_type", path), message);
+            }
+            self.diff_members(&format!("{}.args", path), &old.args, &new.args, Direction::Response);
+        }
+
+        let pairs = self.match_names("reasons", "Reason", &old.reasons, &new.reasons, |r| &r.name);
+        for (old, new) in pairs {
+            self.diff_reason(old, new);
+        }
+
+        let pairs = self.match_names("methods", "Method", &old.methods, &new.methods, |m| &m.name);
+        for (old, new) in pairs {
+            self.diff_method(old, new);
+        }
+    }
+
+    fn diff_type(&mut self, old: &spec::TypeDef, new: &spec::TypeDef) {
+        let path = format!("types.{}", old.name.kebab_case());
+        match (&old.container, &new.container) {
+            (
+                spec::TypeRepr::Simple { simple_type: old_type, validation: old_validation },
+                spec::TypeRepr::Simple { simple_type: new_type, validation: new_validation },
+            ) => {
+                if old_type != new_type {
+                    self.breaking(path, "Underlying type changed".to_string());
+                } else if is_tightened(old_validation.as_ref(), new_validation.as_ref()) {
+                    self.breaking(path, "Validation rules tightened".to_string());
+                } else if old_validation != new_validation {
+                    self.non_breaking(path, "Validation rules relaxed".to_string());
+                }
+            }
+            (spec::TypeRepr::External, spec::TypeRepr::External) => {}
+            (spec::TypeRepr::Struct { members: old }, spec::TypeRepr::Struct { members: new }) => {
+                self.diff_members(&format!("{}.members", path), old, new, Direction::Both);
+            }
+            // Generated enums reject unknown variants so clients fail to deserialize added ones.
+            (spec::TypeRepr::Union { members: old }, spec::TypeRepr::Union { members: new }) => {
+                let path = format!("{}.members", path);
+                let breaking = Compatibility::Breaking;
+                for (old, new) in
+                    self.match_names_with(&path, "Variant", old, new, |m| &m.name, breaking)
+                {
+                    if member_type(old) != member_type(new) {
+                        let path = format!("{}.{}", path, old.name.kebab_case());
+                        let message = format!(
+                            "Type changed from '{}' to '{}'",
+                            member_type(old),
+                            member_type(new)
+                        );
+                        self.breaking(path, message);
+                    }
+                }
+            }
+            (spec::TypeRepr::Enum { values: old }, spec::TypeRepr::Enum { values: new }) => {
+                let path = format!("{}.values", path);
+                self.match_names_with(&path, "Value", old, new, |v| v, Compatibility::Breaking);
+            }
+            _ => self.breaking(path, "Representation changed".to_string()),
+        }
+    }
+
+    /// Compares members of a structure, yield, case or request. A removed member and an added
+    /// member of the same type are reported as a rename.
+    fn diff_members(
+        &mut self,
+        path: &str,
+        old: &[spec::Member],
+        new: &[spec::Member],
+        direction: Direction,
+    ) {
+        let removed: Vec<&spec::Member> =
+            old.iter().filter(|o| !new.iter().any(|n| n.name == o.name)).collect();
+        let mut added: Vec<&spec::Member> =
+            new.iter().filter(|n| !old.iter().any(|o| o.name == n.name)).collect();
+
+        for member in removed {
+            let member_path = format!("{}.{}", path, member.name.kebab_case());
+            let renamed = added.iter().position(|added| member_type(added) == member_type(member));
+            if let Some(position) = renamed {
+                let new_name = added.remove(position).name.kebab_case();
+                self.breaking(member_path, format!("Member renamed to '{}'", new_name));
+            } else if direction == Direction::Request {
+                self.non_breaking(member_path, "Member removed".to_string());
+            } else {
+                self.breaking(member_path, "Member removed".to_string());
+            }
+        }
+
+        for member in added {
+            let member_path = format!("{}.{}", path, member.name.kebab_case());
+            if member.is_optional() {
+                self.non_breaking(member_path, "Optional member added".to_string());
+            } else if direction == Direction::Response {
+                self.non_breaking(member_path, "Required member added".to_string());
+            } else {
+                self.breaking(member_path, "Required member added".to_string());
+            }
+        }
+
+        for old_member in old.iter() {
+            let new_member = match new.iter().find(|new| new.name == old_member.name) {
+                Some(new_member) => new_member,
+                None => continue,
+            };
+            let member_path = format!("{}.{}", path, old_member.name.kebab_case());
+            let (old_type, new_type) = (member_type(old_member), member_type(new_member));
+            if old_type != new_type {
+                let message = format!("Type changed from '{}' to '{}'", old_type, new_type);
+                self.breaking(member_path, message);
+            } else if is_tightened(old_member.validation.as_ref(), new_member.validation.as_ref()) {
+                let message = "Validation rules tightened".to_string();
+                if direction == Direction::Response {
+                    self.non_breaking(member_path, message);
+                } else {
+                    self.breaking(member_path, message);
+                }
+            } else if old_member.validation != new_member.validation {
+                self.non_breaking(member_path, "Validation rules relaxed".to_string());
+            }
+        }
+    }
+
+    fn diff_code(&mut self, path: &str, old: &spec::HttpResponse, new: &spec::HttpResponse) {
+        let (old, new) = (old.code(), new.code());
+        if old != new {
+            let message = format!("Status code changed from {} to {}", old, new);
+            self.breaking(path.to_string(), message);
+        }
+    }
+
+    fn diff_reason(&mut self, old: &spec::Reason, new: &spec::Reason) {
+        let path = format!("reasons.{}", old.name.kebab_case());
+        if old.variant.as_str() != new.variant.as_str() {
+            let message = format!(
+                "Variant changed from '{}' to '{}'",
+                old.variant.as_str(),
+                new.variant.as_str()
+            );
+            self.breaking(path.clone(), message);
+        }
+
+        let cases_path = format!("{}.cases", path);
+        for (old, new) in self.match_names(&cases_path, "Case", &old.cases, &new.cases, |c| &c.name)
+        {
+            let path = format!("{}.{}", cases_path, old.name.kebab_case());
+            self.diff_code(&path, &old.code, &new.code);
+            self.diff_members(&format!("{}.args", path), &old.args, &new.args, Direction::Response);
+        }
+    }
+
+    fn diff_method(&mut self, old: &spec::Method, new: &spec::Method) {
+        let path = format!("methods.{}", old.name.kebab_case());
+        let (old_request, new_request) = (&old.request, &new.request);
+        if old_request.method != new_request.method {
+            let message = format!(
+                "HTTP method changed from {} to {}",
+                old_request.method.to_str(),
+                new_request.method.to_str()
+            );
+            self.breaking(format!("{}.request.method", path), message);
+        }
+        if old_request.path != new_request.path {
+            let message = format!(
+                "Path changed from '{}' to '{}'",
+                old_request.path.kebab_case(),
+                new_request.path.kebab_case()
+            );
+            self.breaking(format!("{}.request.path", path), message);
+        }
+        if old_request.format != new_request.format {
+            let message = format!(
+                "Body format changed from '{}' to '{}'",
+                old_request.format.as_str(),
+                new_request.format.as_str()
+            );
+            self.breaking(format!("{}.request.format", path), message);
+        }
+        if old_request.limit != new_request.limit {
+            // A missing limit means the default one of the server which may be smaller.
+            let compatibility = match (old_request.limit, new_request.limit) {
+                (Some(old_limit), Some(new_limit)) if new_limit > old_limit => {
+                    Compatibility::NonBreaking
+                }
+                _ => Compatibility::Breaking,
+            };
+            let format = |limit: Option<usize>| {
+                limit.map(|limit| limit.to_string()).unwrap_or_else(|| "none".to_string())
+            };
+            let message = format!(
+                "Limit changed from {} to {}",
+                format(old_request.limit),
+                format(new_request.limit)
+            );
+            self.add(compatibility, format!("{}.request.limit", path), message);
+        }
+        self.diff_members(
+            &format!("{}.request.args", path),
+            &old_request.args,
+            &new_request.args,
+            Direction::Request,
+        );
+        for old_arg in old_request.args.iter() {
+            let new_arg = new_request.args.iter().find(|new_arg| new_arg.name == old_arg.name);
+            if let Some(new_arg) = new_arg {
+                let old_location = old_request.location_of(old_arg);
+                let new_location = new_request.location_of(new_arg);
+                if old_location != new_location {
+                    let message = format!(
+                        "Location changed from '{}' to '{}'",
+                        old_location.as_str(),
+                        new_location.as_str()
+                    );
+                    let arg_path = format!("{}.request.args.{}", path, old_arg.name.kebab_case());
+                    self.breaking(arg_path, message);
+                }
+            }
+        }
+
+        let (old_response, new_response) = (&old.response, &new.response);
+        let response_path = format!("{}.response", path);
+        let references = [
+            ("success", Some(&old_response.success), Some(&new_response.success)),
+            ("failure", old_response.failure.as_ref(), new_response.failure.as_ref()),
+            ("error", Some(&old_response.error), Some(&new_response.error)),
+        ];
+        for (field, old, new) in references.iter() {
+            if old != new {
+                let format = |name: &Option<&utils::Name>| {
+                    name.map(|name| name.kebab_case()).unwrap_or_else(|| "none".to_string())
+                };
+                let message = format!("Changed from '{}' to '{}'", format(old), format(new));
+                self.breaking(format!("{}.{}", response_path, field), message);
+            }
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+impl spec::Api {
+    /// Compares this (old) version of the specification with the new one.
+    pub fn diff(&self, new: &spec::Api) -> Report {
+        let mut differ = Differ::new();
+        differ.diff_api(self, new);
+        Report { changes: differ.changes }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::{Compatibility, Differ, Direction};
+    use crate::fixtures::Fixture;
+    use crate::spec::{Api, ContainerType, Member, MemberType, SimpleType};
+    use crate::utils::Name;
+
+    fn member(name: &str, simple_type: SimpleType, container: Option<ContainerType>) -> Member {
+        Member {
+            name: Name::new(name),
+            tipe: MemberType::Simple(simple_type),
+            container,
+            location: None,
+            description: None,
+            example: None,
+            validation: None,
+        }
+    }
+
+    fn diff(old: &[Member], new: &[Member], direction: Direction) -> Vec<(Compatibility, String)> {
+        let mut differ = Differ::new();
+        differ.diff_members("args", old, new, direction);
+        differ.changes.into_iter().map(|change| (change.compatibility, change.path)).collect()
+    }
+
+    fn changes(old: &Api, new: &Api) -> Vec<(Compatibility, String)> {
+        let report = old.diff(new);
+        report.changes.into_iter().map(|change| (change.compatibility, change.path)).collect()
+    }
+
+    /// Returns the shared specification with a `color` enum and a `shape` union.
+    fn variants_api(values: &str, members: &str) -> Api {
+        Fixture::new()
+            .with_types(&format!(
+                "- name: color
+  container:
+    repr: enum
+    values: [{}]
+- name: shape
+  container:
+    repr: union
+    members:
+{}",
+                values, members
+            ))
+            .to_api()
+    }
+
+    /// Returns the shared specification with an `upload` method with the given limit line.
+    fn upload_api(limit: &str) -> Api {
+        Fixture::new()
+            .with_methods(&format!(
+                "- name: upload
+  request:
+    method: post
+    path: items
+    format: multipart
+{}    args:
+      - name: image
+        type: file
+  response:
+    success: items
+    error: items-error
+",
+                limit
+            ))
+            .to_api()
+    }
+
+    #[test]
+    fn test_members_diff() {
+        let old = vec![
+            member("name", SimpleType::Str, None),
+            member("age", SimpleType::U8, None),
+            member("nick", SimpleType::Str, Some(ContainerType::Optional)),
+        ];
+        let new = vec![
+            member("name", SimpleType::Str, None),
+            member("age", SimpleType::U32, None),
+            member("nickname", SimpleType::Str, Some(ContainerType::Optional)),
+            member("page", SimpleType::U32, Some(ContainerType::Optional)),
+            member("token", SimpleType::Str, None),
+        ];
+
+        assert_eq!(
+            diff(&old, &new, Direction::Request),
+            vec![
+                (Compatibility::Breaking, "args.nick".to_string()),
+                (Compatibility::NonBreaking, "args.page".to_string()),
+                (Compatibility::Breaking, "args.token".to_string()),
+                (Compatibility::Breaking, "args.age".to_string()),
+            ]
+        );
+        assert_eq!(
+            diff(&old, &new, Direction::Response),
+            vec![
+                (Compatibility::Breaking, "args.nick".to_string()),
+                (Compatibility::NonBreaking, "args.page".to_string()),
+                (Compatibility::NonBreaking, "args.token".to_string()),
+                (Compatibility::Breaking, "args.age".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_added_variants_are_breaking() {
+        let circle = "      - name: circle\n        type: u32\n";
+        let square = "      - name: square\n        type: u32\n";
+        let old = variants_api("red, green", circle);
+        let new = variants_api("red, green, blue", &format!("{}{}", circle, square));
+        assert_eq!(
+            changes(&old, &new),
+            vec![
+                (Compatibility::Breaking, "types.color.values.blue".to_string()),
+                (Compatibility::Breaking, "types.shape.members.square".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_limit_diff() {
+        let none = upload_api("");
+        let small = upload_api("    limit: 1024\n");
+        let large = upload_api("    limit: 2048\n");
+        let path = "methods.upload.request.limit".to_string();
+        assert_eq!(changes(&small, &large), vec![(Compatibility::NonBreaking, path.clone())]);
+        assert_eq!(changes(&large, &small), vec![(Compatibility::Breaking, path.clone())]);
+        assert_eq!(changes(&none, &small), vec![(Compatibility::Breaking, path.clone())]);
+        assert_eq!(changes(&small, &none), vec![(Compatibility::Breaking, path)]);
+        assert!(changes(&small, &small).is_empty());
+    }
+}
@@ -0,0 +1,421 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy of
+// the MPL was not distributed with this file, You can obtain one at http://mozilla.org/MPL/2.0/
+
+//! Generation of HTML API reference.
+//!
+//! The generated page describes the routes, methods with their arguments, responses and error
+//! cases as well as all the types, yields and reasons, including the validation rules and the
+//! `description` and `example` fields of the specification. It uses the classes defined by the
+//! themes from `bluefire_static_files`, so it is styled by linking one of them.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use askama::Template;
+use serde_json::Value;
+
+use crate::error::{Error, ReadError};
+use crate::{openapi_generator, spec, utils};
+
+const DEFAULT_TITLE: &str = "API";
+const DEFAULT_THEME: &str = "steelblue";
+const DEFAULT_STATIC_ROOT: &str = "static";
+
+/// Reads the static files manifest (`static-manifest.json` written by `bluefire_build`) mapping
+/// names of the files to the names they are served under.
+pub fn read_static_manifest(path: &str) -> Result<BTreeMap<String, String>, Error> {
+    let path = PathBuf::from(path);
+    let content = std::fs::read_to_string(&path)
+        .map_err(|err| ReadError::Io { path: path.clone(), message: err.to_string() })?;
+    Ok(serde_json::from_str(&content).map_err(|err| ReadError::from_json(path, err))?)
+}
+
+/// Returns the path under which the theme with the given name is served by the route built by
+/// `bluefire_static_files`, e.g. `/static/bluefire/theme-steelblue.css`. If the manifest is given
+/// the name of the file is resolved through it, so the path contains the content hash when
+/// `hash_names` is enabled.
+pub fn theme_stylesheet(
+    static_root: &str,
+    theme: &str,
+    manifest: Option<&BTreeMap<String, String>>,
+) -> String {
+    let name = format!("theme-{}.css", theme);
+    let name = manifest.and_then(|manifest| manifest.get(&name)).unwrap_or(&name);
+    format!("/{}/bluefire/{}", static_root, name)
+}
+
+// -------------------------------------------------------------------------------------------------
+
+impl spec::Check {
+    /// Describes the check in human-readable form.
+    fn docs_rule(&self) -> String {
+        match self {
+            spec::Check::Email => "email address".to_string(),
+            spec::Check::Url => "URL".to_string(),
+            spec::Check::Uuid => "UUID".to_string(),
+            spec::Check::NonEmpty => "not empty".to_string(),
+        }
+    }
+}
+
+impl spec::Condition {
+    /// Describes the condition in human-readable form.
+    fn docs_rule(&self) -> String {
+        match self {
+            spec::Condition::Le(value) => format!("at most {}", value),
+            spec::Condition::Ge(value) => format!("at least {}", value),
+            spec::Condition::LenEq(len) => format!("length equal to {}", len),
+            spec::Condition::LenLe(len) => format!("length at most {}", len),
+            spec::Condition::LenGe(len) => format!("length at least {}", len),
+            spec::Condition::Regex(pattern) => format!("matches {}", pattern),
+            spec::Condition::OneOf(values) => format!("one of: {}", values.join(", ")),
+            spec::Condition::EqField(name) => format!("equal to {}", name.snake_case()),
+            spec::Condition::LeField(name) => format!("at most {}", name.snake_case()),
+            spec::Condition::GeField(name) => format!("at least {}", name.snake_case()),
+        }
+    }
+}
+
+/// Describes all the checks and conditions of the validation in human-readable form.
+fn rules(validation: Option<&spec::Validation>) -> Vec<String> {
+    let validation = match validation {
+        Some(validation) => validation,
+        None => return Vec::new(),
+    };
+    let mut result: Vec<String> = validation.checks.iter().map(|check| check.docs_rule()).collect();
+    result.extend(validation.conditions.iter().map(|condition| condition.docs_rule()));
+    if let Some(each) = &validation.each {
+        let each = rules(Some(each));
+        if !each.is_empty() {
+            result.push(format!("each element: {}", each.join(", ")));
+        }
+    }
+    result
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// A row of a table describing members.
+struct MemberRow {
+    /// Name of the member as sent in the request or response.
+    pub name: String,
+
+    /// Type expression of the member.
+    pub tipe: String,
+
+    /// Anchor of the defined type of the member.
+    pub anchor: Option<String>,
+
+    /// Location of the argument in the request.
+    pub location: Option<&'static str>,
+
+    /// Tells if the member may be omitted.
+    pub optional: bool,
+
+    /// Human-readable validation rules.
+    pub rules: Vec<String>,
+
+    /// Description of the member.
+    pub description: String,
+
+    /// Example value of the member.
+    pub example: Option<String>,
+}
+
+/// Template for generating the HTML reference.
+#[derive(Template)]
+#[template(path = "docs.html")]
+struct DocsTemplate<'a> {
+    pub api: &'a spec::Api,
+    pub title: &'a str,
+    pub stylesheet: &'a str,
+    pub generator: DocsCallback<'a>,
+}
+
+impl<'a> DocsTemplate<'a> {
+    pub fn new(
+        api: &'a spec::Api,
+        paths: &'a Vec<spec::Path>,
+        title: &'a str,
+        stylesheet: &'a str,
+    ) -> Self {
+        Self { api, title, stylesheet, generator: DocsCallback::new(api, paths) }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Helper structure for calling rust code from within a template.
+struct DocsCallback<'a> {
+    api: &'a spec::Api,
+    paths: &'a Vec<spec::Path>,
+}
+
+impl<'a> DocsCallback<'a> {
+    /// Constructs a new `DocsCallback`.
+    pub fn new(api: &'a spec::Api, paths: &'a Vec<spec::Path>) -> Self {
+        Self { api, paths }
+    }
+
+    /// Returns the paths used by at least one method.
+    pub fn paths(&self) -> Vec<&'a spec::Path> {
+        self.api.paths_in_use(self.paths)
+    }
+
+    /// Formats the path with parameters in braces.
+    pub fn format_path(&self, name: &utils::Name) -> String {
+        let path = self
+            .paths
+            .iter()
+            .find(|path| path.name == *name)
+            .expect(spec::CHECKED_REFERENCE);
+        openapi_generator::format_path(path)
+    }
+
+    /// Returns the methods using the given path.
+    pub fn methods_of(&self, path: &spec::Path) -> Vec<spec::Method> {
+        let methods = self.api.methods.iter();
+        methods.filter(|method| method.request.path == path.name).cloned().collect()
+    }
+
+    /// Returns the yield with the given name.
+    pub fn find_yield(&self, name: &utils::Name) -> spec::Yield {
+        spec::find_yield(name, &self.api.yields).expect(spec::CHECKED_REFERENCE)
+    }
+
+    /// Returns the failure (if present) and error reasons of the method.
+    pub fn reasons_of(&self, method: &spec::Method) -> Vec<spec::Reason> {
+        let find = |name: &utils::Name| {
+            spec::find_reason(name, &self.api.reasons).expect(spec::CHECKED_REFERENCE)
+        };
+        let mut reasons = Vec::new();
+        if let Some(failure) = &method.response.failure {
+            reasons.push(find(failure));
+        }
+        reasons.push(find(&method.response.error));
+        reasons
+    }
+
+    /// Describes the members of a type, yield or case.
+    pub fn rows(&self, members: &Vec<spec::Member>) -> Vec<MemberRow> {
+        members.iter().map(|member| self.row(member, None)).collect()
+    }
+
+    /// Describes the arguments of the request.
+    pub fn request_rows(&self, request: &spec::Request) -> Vec<MemberRow> {
+        request.args.iter().map(|arg| self.row(arg, Some(request.location_of(arg)))).collect()
+    }
+
+    /// Returns the name of the simple type as in the specification.
+    pub fn simple_type(&self, simple_type: &spec::SimpleType) -> String {
+        let value = serde_yaml::to_value(simple_type).expect("Serialize simple type");
+        value.as_str().unwrap_or_default().to_string()
+    }
+
+    /// Returns the text or an empty string.
+    pub fn text(&self, text: &Option<String>) -> String {
+        text.clone().unwrap_or_default()
+    }
+
+    /// Describes the validation of a simple type.
+    pub fn rules(&self, validation: &Option<spec::Validation>) -> Vec<String> {
+        rules(validation.as_ref())
+    }
+
+    /// Formats the value as pretty-printed JSON.
+    pub fn json(&self, value: &Value) -> String {
+        serde_json::to_string_pretty(value).expect("Serialize example to JSON")
+    }
+}
+
+impl<'a> DocsCallback<'a> {
+    fn row(&self, member: &spec::Member, location: Option<spec::ArgLocation>) -> MemberRow {
+        let name = match location {
+            Some(spec::ArgLocation::Header) => member.name.kebab_case(),
+            _ => member.name.snake_case(),
+        };
+        let anchor = match &member.tipe {
+            spec::MemberType::Defined(name) => Some(format!("type-{}", name.kebab_case())),
+            spec::MemberType::Simple(..) => None,
+        };
+        MemberRow {
+            name,
+            tipe: member.type_expression(),
+            anchor,
+            location: location.map(|location| location.as_str()),
+            optional: member.is_optional(),
+            rules: rules(member.validation.as_ref()),
+            description: member.description.clone().unwrap_or_default(),
+            example: member.example.as_ref().map(|example| example.to_string()),
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Generator of HTML API reference.
+pub struct DocsGenerator {
+    title: String,
+    stylesheet: String,
+}
+
+impl DocsGenerator {
+    /// Constructs a new `DocsGenerator`. By default the page links the `steelblue` theme served
+    /// under the `static` root by its plain name. Use `with_stylesheet` together with
+    /// `theme_stylesheet` to link the theme by its hashed name.
+    pub fn new() -> Self {
+        Self {
+            title: DEFAULT_TITLE.to_string(),
+            stylesheet: theme_stylesheet(DEFAULT_STATIC_ROOT, DEFAULT_THEME, None),
+        }
+    }
+
+    /// Sets the title of the API.
+    pub fn with_title(mut self, title: &str) -> Self {
+        self.title = title.to_string();
+        self
+    }
+
+    /// Sets the URL of the linked stylesheet.
+    pub fn with_stylesheet(mut self, stylesheet: &str) -> Self {
+        self.stylesheet = stylesheet.to_string();
+        self
+    }
+
+    /// Generates the HTML page with the API reference. Fails if checking the specification finds
+    /// any errors.
+    pub fn generate_api(&self, api: &spec::Api) -> Result<String, Error> {
+        api.ensure_checked()?;
+        let paths = spec::routes_to_paths(None, &api.routes);
+        Ok(DocsTemplate::new(api, &paths, &self.title, &self.stylesheet)
+            .render()
+            .expect("Render docs template"))
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::{read_static_manifest, rules, theme_stylesheet, DocsGenerator};
+    use crate::error::{Error, ReadError};
+    use crate::spec::{Api, Condition, Validation};
+    use crate::utils::write_test_files;
+
+    const SPEC: &str = "---
+types:
+  - name: item
+    description: An item <b>stored</b> in the inventory.
+    container:
+      repr: struct
+      members:
+        - name: name
+          type: string
+          description: Display name of the item.
+          example: Screwdriver
+          validation:
+            conditions:
+              - len_ge: 3
+routes:
+  - name: items
+    exact: items
+    routes:
+      - string: item-id
+        name: item
+reasons:
+  - name: item-error
+    variant: error
+    cases:
+      - name: not-found
+        code: 404-not-found
+        description: No item with the given ID exists.
+yields:
+  - name: item
+    code: 200-ok
+    args:
+      - name: item
+        type: item
+methods:
+  - name: get-item
+    description: Returns a single item.
+    request:
+      method: get
+      path: item
+      args:
+        - name: api-key
+          type: string
+          in: header
+    response:
+      success: item
+      error: item-error
+";
+
+    #[test]
+    fn test_generate_docs() {
+        let api = Api::from_str(SPEC).unwrap();
+        let html = DocsGenerator::new().with_title("Inventory").generate_api(&api).unwrap();
+        assert!(html.contains("<title>Inventory</title>"));
+        assert!(html.contains("theme-steelblue.css"));
+        assert!(html.contains("{item_id}"));
+        assert!(html.contains("id=\"method-get-item\""));
+        assert!(html.contains("Returns a single item."));
+        assert!(html.contains("api-key"));
+        assert!(html.contains("href=\"#type-item\""));
+        assert!(html.contains("length at least 3"));
+        assert!(html.contains("No item with the given ID exists."));
+        assert!(html.contains("An item &lt;b&gt;stored"));
+    }
+
+    #[test]
+    fn test_theme_stylesheet() {
+        let mut manifest = std::collections::BTreeMap::new();
+        manifest.insert("theme-gray.css".to_string(), "theme-gray.3fa9c1.css".to_string());
+        assert_eq!(theme_stylesheet("static", "gray", None), "/static/bluefire/theme-gray.css");
+        assert_eq!(
+            theme_stylesheet("static", "gray", Some(&manifest)),
+            "/static/bluefire/theme-gray.3fa9c1.css"
+        );
+        assert_eq!(
+            theme_stylesheet("files", "vermilion", Some(&manifest)),
+            "/files/bluefire/theme-vermilion.css"
+        );
+    }
+
+    #[test]
+    fn test_read_static_manifest() {
+        let manifest = "{\n  \"theme-gray.css\": \"theme-gray.3fa9c1.css\"\n}";
+        let malformed = "{\n  \"theme-gray.css\": 1\n}";
+        let dir = write_test_files(
+            "static_manifest",
+            &[("static-manifest.json", manifest), ("malformed.json", malformed)],
+        );
+
+        let path = dir.join("static-manifest.json");
+        let manifest = read_static_manifest(path.to_str().unwrap()).unwrap();
+        assert_eq!(manifest["theme-gray.css"], "theme-gray.3fa9c1.css");
+
+        let path = dir.join("malformed.json");
+        match read_static_manifest(path.to_str().unwrap()) {
+            Err(Error::Read(ReadError::Json { line, .. })) => assert_eq!(line, 2),
+            result => panic!("Expected JSON error, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_rules() {
+        let validation = Validation {
+            checks: Vec::new(),
+            conditions: vec![Condition::Ge(1.0), Condition::Le(2.5)],
+            each: Some(Box::new(Validation {
+                checks: Vec::new(),
+                conditions: vec![Condition::OneOf(vec!["a".to_string(), "b".to_string()])],
+                each: None,
+            })),
+        };
+        assert_eq!(
+            rules(Some(&validation)),
+            vec!["at least 1", "at most 2.5", "each element: one of: a, b"]
+        );
+    }
+}
@@ -0,0 +1,177 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy of
+// the MPL was not distributed with this file, You can obtain one at http://mozilla.org/MPL/2.0/
+
+//! Errors reported while reading API specifications and generating code.
+
+use std::path::PathBuf;
+
+use bluefire_build::error::BuildError;
+
+use crate::{check::Diagnostic, utils};
+
+/// Error returned when a specification file could not be read or parsed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReadError {
+    /// The file could not be read.
+    Io {
+        /// Path to the file.
+        path: PathBuf,
+
+        /// Description of the problem.
+        message: String,
+    },
+
+    /// The file is not valid YAML or does not match the expected structure.
+    Yaml {
+        /// Path to the file.
+        path: PathBuf,
+
+        /// Line (starting from 1) of the invalid node, if known.
+        line: Option<usize>,
+
+        /// Column (starting from 1) of the invalid node, if known.
+        column: Option<usize>,
+
+        /// Description of the problem.
+        message: String,
+    },
+
+    /// The file is not valid JSON or does not match the expected structure (e.g. the static files
+    /// manifest).
+    Json {
+        /// Path to the file.
+        path: PathBuf,
+
+        /// Line (starting from 1) of the invalid value.
+        line: usize,
+
+        /// Column (starting from 1) of the invalid value.
+        column: usize,
+
+        /// Description of the problem.
+        message: String,
+    },
+}
+
+impl ReadError {
+    /// Constructs a new `ReadError::Yaml` from a YAML parsing error.
+    pub fn from_yaml(path: PathBuf, err: serde_yaml::Error) -> Self {
+        let location = err.location();
+        ReadError::Yaml {
+            path,
+            line: location.as_ref().map(|location| location.line()),
+            column: location.as_ref().map(|location| location.column()),
+            message: err.to_string(),
+        }
+    }
+
+    /// Constructs a new `ReadError::Json` from a JSON parsing error.
+    pub fn from_json(path: PathBuf, err: serde_json::Error) -> Self {
+        ReadError::Json { path, line: err.line(), column: err.column(), message: err.to_string() }
+    }
+}
+
+impl std::fmt::Display for ReadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReadError::Io { path, message } => write!(f, "{}: {}", path.display(), message),
+            ReadError::Yaml { path, line: Some(line), column: Some(column), message } => {
+                write!(f, "{}:{}:{}: {}", path.display(), line, column, message)
+            }
+            ReadError::Yaml { path, message, .. } => write!(f, "{}: {}", path.display(), message),
+            ReadError::Json { path, line, column, message } => {
+                write!(f, "{}:{}:{}: {}", path.display(), line, column, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReadError {}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Error returned when reading a specification or generating code from it fails.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The specification file could not be read or is not valid YAML.
+    Read(ReadError),
+
+    /// The specification file is well-formed but checking it found errors.
+    Check {
+        /// Path to the file (empty if the specification was not read from a file).
+        path: PathBuf,
+
+        /// All the problems found in the file, including warnings.
+        diagnostics: Vec<Diagnostic>,
+    },
+
+    /// The specification refers to a definition which does not exist.
+    UnknownReference {
+        /// Kind of the definition, e.g. `yield`.
+        kind: &'static str,
+
+        /// Name of the definition.
+        name: String,
+    },
+
+    /// The specification files import each other in a cycle.
+    ImportCycle {
+        /// Paths to the files in the cycle, starting and ending with the same file.
+        cycle: Vec<PathBuf>,
+    },
+
+    /// Generated code could not be written.
+    Build(BuildError),
+}
+
+impl Error {
+    /// Constructs a new `Error::UnknownReference` to the definition of the given kind and name.
+    pub fn unknown_reference(kind: &'static str, name: &utils::Name) -> Self {
+        Error::UnknownReference { kind, name: name.kebab_case() }
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Read(err) => write!(f, "{}", err),
+            Error::Check { path, diagnostics } => {
+                // Specifications which were not read from a file have an empty path.
+                let has_path = |path: &std::path::Path| !path.as_os_str().is_empty();
+                if has_path(path) {
+                    write!(f, "{}: ", path.display())?;
+                }
+                write!(f, "the specification is not valid")?;
+                for diagnostic in diagnostics.iter() {
+                    writeln!(f)?;
+                    let file_path = diagnostic.file_path(path);
+                    if has_path(file_path) {
+                        write!(f, "{}:", file_path.display())?;
+                    }
+                    write!(f, "{}", diagnostic)?;
+                }
+                Ok(())
+            }
+            Error::ImportCycle { cycle } => {
+                let paths: Vec<_> = cycle.iter().map(|path| path.display().to_string()).collect();
+                write!(f, "{}: import cycle: {}", paths[0], paths.join(" -> "))
+            }
+            Error::UnknownReference { kind, name } => write!(f, "No {} '{}' found", kind, name),
+            Error::Build(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<ReadError> for Error {
+    fn from(err: ReadError) -> Self {
+        Error::Read(err)
+    }
+}
+
+impl From<BuildError> for Error {
+    fn from(err: BuildError) -> Self {
+        Error::Build(err)
+    }
+}
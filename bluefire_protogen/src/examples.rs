@@ -0,0 +1,617 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy of
+// the MPL was not distributed with this file, You can obtain one at http://mozilla.org/MPL/2.0/
+
+//! Generation of example values of members of an API specification.
+//!
+//! Values are taken from the `example` field of the members (or their types, yields and cases) if
+//! present or generated randomly so that they pass validation. Strings validated with regular
+//! expressions cannot be generated reliably so they should be given examples.
+
+use serde_json::{json, Map, Value};
+
+use crate::{spec, utils};
+
+/// Depth of nested types after which optional values and collections are left empty.
+const MAX_DEPTH: usize = 4;
+
+// -------------------------------------------------------------------------------------------------
+
+/// A simple pseudo-random number generator (xorshift64*). Good enough for examples.
+struct Random {
+    state: u64,
+}
+
+impl Random {
+    fn new(seed: u64) -> Self {
+        Self { state: (seed ^ 0x9e37_79b9_7f4a_7c15) | 1 }
+    }
+
+    fn next(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// Returns a number from the inclusive range.
+    fn range(&mut self, min: i128, max: i128) -> i128 {
+        if max <= min {
+            return min;
+        }
+        min + (self.next() as i128) % (max - min + 1)
+    }
+
+    fn float(&mut self, min: f64, max: f64) -> f64 {
+        let fraction = (self.next() >> 11) as f64 / (1u64 << 53) as f64;
+        let value = min + (max - min) * fraction;
+        ((value * 100.0).round() / 100.0).max(min).min(max)
+    }
+
+    fn chance(&mut self, denominator: u64) -> bool {
+        self.next() % denominator == 0
+    }
+
+    fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.range(0, items.len() as i128 - 1) as usize]
+    }
+
+    fn chars(&mut self, alphabet: &[u8], len: usize) -> String {
+        (0..len).map(|_| *self.pick(alphabet) as char).collect()
+    }
+
+    fn alphanumeric(&mut self, len: usize) -> String {
+        self.chars(b"abcdefghijklmnopqrstuvwxyz0123456789", len)
+    }
+
+    fn hex(&mut self, len: usize) -> String {
+        self.chars(b"0123456789abcdef", len)
+    }
+
+    fn bytes(&mut self, len: usize) -> Vec<u8> {
+        (0..len).map(|_| self.next() as u8).collect()
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Returns the inclusive range of lengths allowed by the validation, preferring lengths close to
+/// the default one.
+fn length_range(validation: Option<&spec::Validation>, default: usize) -> (usize, usize) {
+    let (mut min, mut max) = (0, usize::max_value());
+    if let Some(validation) = validation {
+        if validation.checks.contains(&spec::Check::NonEmpty) {
+            min = 1;
+        }
+        for condition in validation.conditions.iter() {
+            match condition {
+                spec::Condition::LenEq(len) => {
+                    min = *len as usize;
+                    max = *len as usize;
+                }
+                spec::Condition::LenLe(len) => max = max.min(*len as usize),
+                spec::Condition::LenGe(len) => min = min.max(*len as usize),
+                _ => {}
+            }
+        }
+    }
+    (min.max(default / 2).min(max), min.max(default).min(max))
+}
+
+/// Returns the inclusive range of values allowed by the validation.
+fn value_range(validation: Option<&spec::Validation>, min: f64, max: f64) -> (f64, f64) {
+    let (mut low, mut high) = (None, None);
+    for condition in validation.iter().flat_map(|validation| validation.conditions.iter()) {
+        match condition {
+            spec::Condition::Ge(value) => low = Some(f64::from(*value)),
+            spec::Condition::Le(value) => high = Some(f64::from(*value)),
+            _ => {}
+        }
+    }
+    let (low, high) = match (low, high) {
+        (Some(low), Some(high)) => (low, high),
+        (Some(low), None) => (low, low + 100.0),
+        (None, Some(high)) => (high - 100.0, high),
+        (None, None) => (0.0, 100.0),
+    };
+    (low.max(min), high.min(max))
+}
+
+/// Merges the validation of a simple type with the validation of a member of that type.
+fn merge_validations(
+    first: Option<&spec::Validation>,
+    second: Option<&spec::Validation>,
+) -> Option<spec::Validation> {
+    match (first, second) {
+        (Some(first), Some(second)) => {
+            let mut merged = first.clone();
+            merged.checks.extend(second.checks.iter().cloned());
+            merged.conditions.extend(second.conditions.iter().cloned());
+            Some(merged)
+        }
+        (first, second) => first.or(second).cloned(),
+    }
+}
+
+/// Returns a length of a string or collection violating the validation.
+fn invalid_length(validation: Option<&spec::Validation>) -> Option<usize> {
+    let validation = validation?;
+    let length = validation.conditions.iter().find_map(|condition| match condition {
+        spec::Condition::LenGe(len) if *len > 0 => Some(*len as usize - 1),
+        spec::Condition::LenLe(len) | spec::Condition::LenEq(len) => Some(*len as usize + 1),
+        _ => None,
+    });
+    if length.is_none() && validation.checks.contains(&spec::Check::NonEmpty) {
+        Some(0)
+    } else {
+        length
+    }
+}
+
+/// Converts an integer to a JSON number.
+fn integer_value(value: i128) -> Value {
+    if value < 0 {
+        (value as i64).into()
+    } else {
+        (value as u64).into()
+    }
+}
+
+/// Formats a date with the given offset in days from the beginning of 2019.
+fn format_date(days: i128) -> String {
+    let (year, day_of_year) = (2019 + days / 336, days % 336);
+    format!("{:04}-{:02}-{:02}", year, day_of_year / 28 + 1, day_of_year % 28 + 1)
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Generates JSON values of the members of the specification.
+pub struct ExampleGenerator<'a> {
+    api: &'a spec::Api,
+    random: Random,
+    depth: usize,
+}
+
+impl<'a> ExampleGenerator<'a> {
+    /// Constructs a new `ExampleGenerator`. The same seed always gives the same values.
+    pub fn new(api: &'a spec::Api, seed: u64) -> Self {
+        Self { api, random: Random::new(seed), depth: 0 }
+    }
+
+    /// Generates an object with values of the given members. Members compared to other members
+    /// with `eq_field`, `le_field` or `ge_field` conditions take the value of the other member.
+    pub fn members(&mut self, members: &[spec::Member]) -> Map<String, Value> {
+        let mut object = Map::new();
+        for member in members.iter() {
+            object.insert(member.name.snake_case(), self.member(member));
+        }
+        for member in members.iter().filter(|member| member.example.is_none()) {
+            let conditions = member.validation.iter().flat_map(|v| v.conditions.iter());
+            for field in conditions.filter_map(|condition| condition.field()) {
+                if let Some(value) = object.get(&field.snake_case()).cloned() {
+                    if !value.is_null() {
+                        object.insert(member.name.snake_case(), value);
+                    }
+                }
+            }
+        }
+        object
+    }
+
+    /// Returns the example of the member or generates a value for it.
+    pub fn member(&mut self, member: &spec::Member) -> Value {
+        if let Some(example) = &member.example {
+            return example.clone();
+        }
+        let layers =
+            member.container.as_ref().map(|container| container.layers()).unwrap_or_default();
+        self.layers(&layers, &member.tipe, member.validation.as_ref())
+    }
+
+    /// Returns the example or generated content of the success response with the given yield.
+    pub fn yield_content(&mut self, yeeld: &spec::Yield) -> Value {
+        match &yeeld.example {
+            Some(example) => example.clone(),
+            None => self.members(&yeeld.args).into(),
+        }
+    }
+
+    /// Returns the example or generated content of the failure or error response with the given
+    /// case.
+    pub fn case_content(&mut self, case: &spec::Case) -> Value {
+        let mut content = Map::new();
+        content.insert("reason".to_string(), case.name.snake_case().into());
+        match &case.example {
+            Some(Value::Object(example)) => content.extend(example.clone()),
+            _ => content.extend(self.members(&case.args)),
+        }
+        content.into()
+    }
+
+    /// Returns random bytes, e.g. for the content of binary yields or files.
+    pub fn bytes(&mut self, len: usize) -> Vec<u8> {
+        self.random.bytes(len)
+    }
+
+    /// Returns a random alphanumeric string.
+    pub fn alphanumeric(&mut self, len: usize) -> String {
+        self.random.alphanumeric(len)
+    }
+
+    /// Generates a value of the member violating its validation or returns `None` if the
+    /// validation does not have any rule which can be reliably violated. Cross-field conditions,
+    /// regular expressions and validation of elements of collections are not taken into account.
+    pub fn invalid(&mut self, member: &spec::Member) -> Option<Value> {
+        let mut layers =
+            member.container.as_ref().map(|container| container.layers()).unwrap_or_default();
+        while layers.first() == Some(&spec::Layer::Optional) {
+            layers.remove(0);
+        }
+        let validation = member.validation.as_ref();
+
+        if let Some((layer, inner)) = layers.split_first() {
+            let len = invalid_length(validation)?;
+            let each = validation.and_then(|validation| validation.each.as_ref()).map(|e| &**e);
+            return match layer {
+                spec::Layer::Vector => {
+                    let items: Vec<Value> =
+                        (0..len).map(|_| self.layers(inner, &member.tipe, each)).collect();
+                    Some(items.into())
+                }
+                spec::Layer::Map => {
+                    let mut map = Map::new();
+                    for index in 0..len {
+                        let value = self.layers(inner, &member.tipe, each);
+                        map.insert(format!("key{}", index + 1), value);
+                    }
+                    Some(map.into())
+                }
+                _ => None,
+            };
+        }
+
+        match &member.tipe {
+            spec::MemberType::Simple(simple_type) => self.invalid_simple(simple_type, validation),
+            spec::MemberType::Defined(name) => {
+                let api = self.api;
+                match api.types.iter().find(|tipe| tipe.name == *name).map(|t| &t.container) {
+                    Some(spec::TypeRepr::Simple { simple_type, validation: type_validation }) => {
+                        let merged = merge_validations(type_validation.as_ref(), validation);
+                        self.invalid_simple(simple_type, merged.as_ref())
+                    }
+                    _ => None,
+                }
+            }
+        }
+    }
+}
+
+impl<'a> ExampleGenerator<'a> {
+    fn layers(
+        &mut self,
+        layers: &[spec::Layer],
+        tipe: &spec::MemberType,
+        validation: Option<&spec::Validation>,
+    ) -> Value {
+        let (layer, inner) = match layers.split_first() {
+            Some(split) => split,
+            None => return self.value(tipe, validation),
+        };
+        let too_deep = self.depth >= MAX_DEPTH;
+        let each = validation.and_then(|validation| validation.each.as_ref()).map(|each| &**each);
+        match layer {
+            spec::Layer::Optional => {
+                if too_deep || self.random.chance(4) {
+                    Value::Null
+                } else {
+                    self.layers(inner, tipe, validation)
+                }
+            }
+            spec::Layer::Vector => {
+                let (min, max) = length_range(validation, if too_deep { 0 } else { 2 });
+                let len = self.random.range(min as i128, max as i128) as usize;
+                (0..len).map(|_| self.layers(inner, tipe, each)).collect::<Vec<_>>().into()
+            }
+            spec::Layer::Map => {
+                let (min, max) = length_range(validation, if too_deep { 0 } else { 2 });
+                let len = self.random.range(min as i128, max as i128) as usize;
+                let mut map = Map::new();
+                for index in 0..len {
+                    map.insert(format!("key{}", index + 1), self.layers(inner, tipe, each));
+                }
+                map.into()
+            }
+        }
+    }
+
+    fn value(&mut self, tipe: &spec::MemberType, validation: Option<&spec::Validation>) -> Value {
+        match tipe {
+            spec::MemberType::Simple(simple_type) => self.simple(simple_type, validation),
+            spec::MemberType::Defined(name) => self.defined(name, validation),
+        }
+    }
+
+    fn defined(&mut self, name: &utils::Name, validation: Option<&spec::Validation>) -> Value {
+        let api = self.api;
+        let tipe = match api.types.iter().find(|tipe| tipe.name == *name) {
+            Some(tipe) => tipe,
+            None => return Value::Null,
+        };
+        if let Some(example) = &tipe.example {
+            return example.clone();
+        }
+
+        self.depth += 1;
+        let value = match &tipe.container {
+            spec::TypeRepr::Simple { simple_type, validation: type_validation } => {
+                let merged = merge_validations(type_validation.as_ref(), validation);
+                self.simple(simple_type, merged.as_ref())
+            }
+            spec::TypeRepr::External => Value::Null,
+            spec::TypeRepr::Struct { members } => self.members(members).into(),
+            spec::TypeRepr::Union { members } if !members.is_empty() => {
+                let member = self.random.pick(members);
+                json!({ "variant": member.name.snake_case(), "content": self.member(member) })
+            }
+            spec::TypeRepr::Union { .. } => Value::Null,
+            spec::TypeRepr::Enum { values } if !values.is_empty() => {
+                self.random.pick(values).snake_case().into()
+            }
+            spec::TypeRepr::Enum { .. } => Value::Null,
+        };
+        self.depth -= 1;
+        value
+    }
+
+    fn simple(
+        &mut self,
+        simple_type: &spec::SimpleType,
+        validation: Option<&spec::Validation>,
+    ) -> Value {
+        match simple_type {
+            spec::SimpleType::Bool => (self.random.next() % 2 == 0).into(),
+            spec::SimpleType::U8 => self.integer(validation, 0, 255),
+            spec::SimpleType::U32 => self.integer(validation, 0, i128::from(std::u32::MAX)),
+            spec::SimpleType::U64 => self.integer(validation, 0, i128::from(std::u64::MAX)),
+            spec::SimpleType::I32 => {
+                self.integer(validation, i128::from(std::i32::MIN), i128::from(std::i32::MAX))
+            }
+            spec::SimpleType::I64 => {
+                self.integer(validation, i128::from(std::i64::MIN), i128::from(std::i64::MAX))
+            }
+            spec::SimpleType::F32 | spec::SimpleType::F64 => {
+                let (min, max) = value_range(validation, std::f64::MIN, std::f64::MAX);
+                json!(self.random.float(min, max))
+            }
+            spec::SimpleType::Str => self.string(validation).into(),
+            spec::SimpleType::Id => self.random.hex(24).into(),
+            spec::SimpleType::DateTime => {
+                let date = format_date(self.random.range(0, 671));
+                let seconds = self.random.range(0, 86399);
+                let (hours, minutes) = (seconds / 3600, seconds / 60 % 60);
+                format!("{}T{:02}:{:02}:{:02}Z", date, hours, minutes, seconds % 60).into()
+            }
+            spec::SimpleType::Date => format_date(self.random.range(0, 671)).into(),
+            spec::SimpleType::Uuid => self.uuid().into(),
+            spec::SimpleType::Bytes => {
+                let (min, max) = length_range(validation, 8);
+                let len = self.random.range(min as i128, max as i128) as usize;
+                serde_json::to_value(bluefire_twine::Bytes::new(self.random.bytes(len)))
+                    .expect("Serialize bytes")
+            }
+            spec::SimpleType::File => Value::Null,
+        }
+    }
+
+    fn invalid_simple(
+        &mut self,
+        simple_type: &spec::SimpleType,
+        validation: Option<&spec::Validation>,
+    ) -> Option<Value> {
+        let validation = validation?;
+        let (min, max) = match simple_type {
+            spec::SimpleType::U8 => (0, 255),
+            spec::SimpleType::U32 => (0, i128::from(std::u32::MAX)),
+            spec::SimpleType::U64 => (0, i128::from(std::u64::MAX)),
+            spec::SimpleType::I32 => (i128::from(std::i32::MIN), i128::from(std::i32::MAX)),
+            spec::SimpleType::I64 => (i128::from(std::i64::MIN), i128::from(std::i64::MAX)),
+            spec::SimpleType::F32 | spec::SimpleType::F64 => {
+                return validation.conditions.iter().find_map(|condition| match condition {
+                    spec::Condition::Ge(value) => Some(json!(f64::from(*value) - 1.0)),
+                    spec::Condition::Le(value) => Some(json!(f64::from(*value) + 1.0)),
+                    _ => None,
+                });
+            }
+            spec::SimpleType::Str => return self.invalid_string(validation).map(Value::from),
+            spec::SimpleType::Bytes => {
+                let bytes = bluefire_twine::Bytes::new(
+                    self.random.bytes(invalid_length(Some(validation))?),
+                );
+                return Some(serde_json::to_value(bytes).expect("Serialize bytes"));
+            }
+            _ => return None,
+        };
+        validation.conditions.iter().find_map(|condition| {
+            let value = match condition {
+                spec::Condition::Ge(value) => f64::from(*value).ceil() as i128 - 1,
+                spec::Condition::Le(value) => f64::from(*value).floor() as i128 + 1,
+                _ => return None,
+            };
+            if value >= min && value <= max {
+                Some(integer_value(value))
+            } else {
+                None
+            }
+        })
+    }
+
+    fn invalid_string(&mut self, validation: &spec::Validation) -> Option<String> {
+        for condition in validation.conditions.iter() {
+            if let spec::Condition::OneOf(values) = condition {
+                let mut value = format!("not-{}", self.random.alphanumeric(8));
+                while values.contains(&value) {
+                    value.push('x');
+                }
+                return Some(value);
+            }
+        }
+        if let Some(len) = invalid_length(Some(validation)) {
+            return Some(self.random.alphanumeric(len));
+        }
+        if validation.checks.contains(&spec::Check::Email) {
+            Some(format!("{}.example.com", self.random.alphanumeric(8)))
+        } else if validation.checks.contains(&spec::Check::Url) {
+            Some(format!("not a url {}", self.random.alphanumeric(8)))
+        } else if validation.checks.contains(&spec::Check::Uuid) {
+            Some(self.random.hex(32))
+        } else {
+            None
+        }
+    }
+
+    fn integer(&mut self, validation: Option<&spec::Validation>, min: i128, max: i128) -> Value {
+        let (low, high) = value_range(validation, min as f64, max as f64);
+        integer_value(self.random.range(low.ceil() as i128, high.floor() as i128))
+    }
+
+    fn string(&mut self, validation: Option<&spec::Validation>) -> String {
+        let default = spec::Validation { checks: Vec::new(), conditions: Vec::new(), each: None };
+        let validation = validation.unwrap_or(&default);
+        for condition in validation.conditions.iter() {
+            if let spec::Condition::OneOf(values) = condition {
+                if !values.is_empty() {
+                    return self.random.pick(values).clone();
+                }
+            }
+        }
+
+        let (min, max) = length_range(Some(validation), 8);
+        let len = self.random.range(min as i128, max as i128) as usize;
+        if validation.checks.contains(&spec::Check::Email) {
+            format!("{}@example.com", self.random.alphanumeric(len.max(1)))
+        } else if validation.checks.contains(&spec::Check::Url) {
+            format!("https://example.com/{}", self.random.alphanumeric(len))
+        } else if validation.checks.contains(&spec::Check::Uuid) {
+            self.uuid()
+        } else {
+            self.random.alphanumeric(len)
+        }
+    }
+
+    fn uuid(&mut self) -> String {
+        let variant = *self.random.pick(b"89ab") as char;
+        let hex = self.random.hex(30);
+        format!(
+            "{}-{}-4{}-{}{}-{}",
+            &hex[0..8],
+            &hex[8..12],
+            &hex[12..15],
+            variant,
+            &hex[15..18],
+            &hex[18..30]
+        )
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::ExampleGenerator;
+    use crate::{fixtures::Fixture, spec::Api};
+
+    fn api() -> Api {
+        Fixture::new()
+            .with_members(
+                "- name: count
+  type: u32
+  validation:
+    conditions:
+      - ge: 10
+      - le: 20
+- name: minimum
+  type: u32
+- name: email
+  type: string
+  validation:
+    checks: [email]
+- name: code
+  type: string
+  validation:
+    conditions:
+      - len_eq: 5
+- name: tags
+  type: vector<tag>
+  validation:
+    conditions:
+      - len_ge: 3
+- name: total
+  type: u32
+  validation:
+    conditions:
+      - ge_field: minimum
+- name: title
+  type: string
+  example: Example title
+",
+            )
+            .with_types(
+                "- name: tag
+  container:
+    repr: simple
+    type: string
+    validation:
+      conditions:
+        - one_of: [red, green]
+",
+            )
+            .to_api()
+    }
+
+    #[test]
+    fn test_examples_pass_validation() {
+        let api = api();
+        let mut generator = ExampleGenerator::new(&api, 7);
+        for _ in 0..20 {
+            let item = generator.member(&api.yields[0].args[0]);
+            for item in item.as_array().unwrap() {
+                let count = item["count"].as_u64().unwrap();
+                assert!(count >= 10 && count <= 20);
+                assert!(item["email"].as_str().unwrap().ends_with("@example.com"));
+                assert_eq!(item["code"].as_str().unwrap().len(), 5);
+                let tags = item["tags"].as_array().unwrap();
+                assert!(tags.len() >= 3);
+                assert!(tags.iter().all(|tag| *tag == json!("red") || *tag == json!("green")));
+                assert!(item["total"].as_u64() >= item["minimum"].as_u64());
+                assert_eq!(item["title"], json!("Example title"));
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_values() {
+        let api = api();
+        let mut generator = ExampleGenerator::new(&api, 7);
+        let members = match &api.types[0].container {
+            crate::spec::TypeRepr::Struct { members } => members.clone(),
+            _ => unreachable!(),
+        };
+
+        assert_eq!(generator.invalid(&members[0]), Some(json!(9)));
+        assert_eq!(generator.invalid(&members[1]), None);
+        assert!(!generator.invalid(&members[2]).unwrap().as_str().unwrap().contains('@'));
+        assert_eq!(generator.invalid(&members[3]).unwrap().as_str().unwrap().len(), 6);
+        assert_eq!(generator.invalid(&members[4]).unwrap().as_array().unwrap().len(), 2);
+        assert_eq!(generator.invalid(&members[5]), None);
+        let tag = crate::spec::Member {
+            tipe: crate::spec::MemberType::Defined(crate::utils::Name::new("tag")),
+            container: None,
+            validation: None,
+            ..members[6].clone()
+        };
+        let value = generator.invalid(&tag).unwrap();
+        assert!(value != json!("red") && value != json!("green"));
+    }
+}
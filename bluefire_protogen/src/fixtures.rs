@@ -0,0 +1,121 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy of
+// the MPL was not distributed with this file, You can obtain one at http://mozilla.org/MPL/2.0/
+
+//! Specification shared by tests of many modules.
+//!
+//! The base specification describes an `items` API: an `item` structure, an `items` route, an
+//! `items` yield returning a vector of items, an `items-error` reason with an `internal` case and a
+//! `get-items` method. Tests extend it with YAML fragments written without indentation.
+
+// Some of the builder methods are used only by tests of optional features.
+#![allow(dead_code)]
+
+use crate::spec::Api;
+
+/// Members of `item` used when a test does not provide its own.
+const DEFAULT_MEMBERS: &str = "- name: count\n  type: u32\n";
+
+/// Builder of the shared test specification.
+#[derive(Clone, Debug, Default)]
+pub struct Fixture {
+    members: Option<String>,
+    types: String,
+    cases: String,
+    args: String,
+    methods: String,
+}
+
+impl Fixture {
+    /// Constructs the base specification.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces the members of `item`.
+    pub fn with_members(mut self, members: &str) -> Self {
+        self.members = Some(members.to_string());
+        self
+    }
+
+    /// Adds types after `item`.
+    pub fn with_types(mut self, types: &str) -> Self {
+        self.types += types;
+        self
+    }
+
+    /// Adds cases to `items-error` after `internal`.
+    pub fn with_cases(mut self, cases: &str) -> Self {
+        self.cases += cases;
+        self
+    }
+
+    /// Adds arguments to the request of `get-items`.
+    pub fn with_args(mut self, args: &str) -> Self {
+        self.args += args;
+        self
+    }
+
+    /// Adds methods after `get-items`.
+    pub fn with_methods(mut self, methods: &str) -> Self {
+        self.methods += methods;
+        self
+    }
+
+    /// Returns the specification as YAML.
+    pub fn to_yaml(&self) -> String {
+        let members = self.members.as_ref().map(String::as_str).unwrap_or(DEFAULT_MEMBERS);
+        let args = if self.args.is_empty() {
+            String::new()
+        } else {
+            format!("      args:\n{}", indent(&self.args, 8))
+        };
+        format!(
+            "---
+types:
+  - name: item
+    container:
+      repr: struct
+      members:
+{members}{types}routes:
+  - name: items
+    exact: items
+yields:
+  - name: items
+    code: 200-ok
+    args:
+      - name: items
+        type: vector<item>
+reasons:
+  - name: items-error
+    variant: error
+    cases:
+      - name: internal
+        code: 500-internal-server-error
+{cases}methods:
+  - name: get-items
+    request:
+      method: get
+      path: items
+{args}    response:
+      success: items
+      error: items-error
+{methods}",
+            members = indent(members, 8),
+            types = indent(&self.types, 2),
+            cases = indent(&self.cases, 6),
+            args = args,
+            methods = indent(&self.methods, 2),
+        )
+    }
+
+    /// Parses the specification.
+    pub fn to_api(&self) -> Api {
+        Api::from_str(&self.to_yaml()).expect("Parse the test specification")
+    }
+}
+
+/// Indents every line of the YAML fragment by the given number of spaces.
+fn indent(yaml: &str, spaces: usize) -> String {
+    let prefix = " ".repeat(spaces);
+    yaml.lines().map(|line| format!("{}{}\n", prefix, line)).collect()
+}
@@ -0,0 +1,220 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy of
+// the MPL was not distributed with this file, You can obtain one at http://mozilla.org/MPL/2.0/
+
+//! Resolution of imports between specification files.
+//!
+//! A specification may import types, yields and reasons from other files:
+//!
+//! ```yaml
+//! imports:
+//!   - path: ../common/errors.yaml
+//!     namespace: common
+//! ```
+//!
+//! Paths are relative to the importing file. If a namespace is given, it is prepended to the names
+//! of all the imported definitions (e.g. `internal-error` becomes `common-internal-error`). Imports
+//! are resolved recursively and definitions imported more than once (e.g. when two imported files
+//! import the same one) are merged if they are identical. Routes and methods are not imported.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::{error::Error, spec, utils};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Resolves imports of specifications, keeping track of visited files.
+#[derive(Default)]
+struct Resolver {
+    /// Files being currently resolved, used to detect cycles.
+    stack: Vec<PathBuf>,
+
+    /// All the imported files in order of appearance.
+    files: Vec<PathBuf>,
+}
+
+impl Resolver {
+    fn resolve_file(&mut self, path: &Path) -> Result<spec::Api, Error> {
+        let api = spec::read_yaml(path)?;
+        self.resolve(path, api)
+    }
+
+    fn resolve(&mut self, path: &Path, mut api: spec::Api) -> Result<spec::Api, Error> {
+        let canonical = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        if let Some(position) = self.stack.iter().position(|visited| *visited == canonical) {
+            let mut cycle = self.stack[position..].to_vec();
+            cycle.push(canonical);
+            return Err(Error::ImportCycle { cycle });
+        }
+
+        self.stack.push(canonical);
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+        for import in api.imports.clone() {
+            let import_path = dir.join(&import.path);
+            if !self.files.contains(&import_path) {
+                self.files.push(import_path.clone());
+            }
+
+            let mut imported = self.resolve_file(&import_path)?;
+            if let Some(namespace) = &import.namespace {
+                apply_namespace(&mut imported, namespace);
+            }
+            merge(&mut api.types, imported.types, |tipe| &tipe.name);
+            merge(&mut api.yields, imported.yields, |yeeld| &yeeld.name);
+            merge(&mut api.reasons, imported.reasons, |reason| &reason.name);
+        }
+        self.stack.pop();
+        Ok(api)
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Prefixes the names of all the types, yields and reasons and the references to the types.
+fn apply_namespace(api: &mut spec::Api, namespace: &utils::Name) {
+    let types: HashSet<String> = api.types.iter().map(|tipe| tipe.name.kebab_case()).collect();
+    let rename_members = |members: &mut Vec<spec::Member>| {
+        for member in members.iter_mut() {
+            if let spec::MemberType::Defined(name) = &member.tipe {
+                if types.contains(&name.kebab_case()) {
+                    member.tipe = spec::MemberType::Defined(name.with_prefix(namespace));
+                }
+            }
+        }
+    };
+
+    for tipe in api.types.iter_mut() {
+        tipe.name = tipe.name.with_prefix(namespace);
+        match &mut tipe.container {
+            spec::TypeRepr::Struct { members } | spec::TypeRepr::Union { members } => {
+                rename_members(members)
+            }
+            spec::TypeRepr::Simple { .. }
+            | spec::TypeRepr::External
+            | spec::TypeRepr::Enum { .. } => {}
+        }
+    }
+    for yeeld in api.yields.iter_mut() {
+        yeeld.name = yeeld.name.with_prefix(namespace);
+        rename_members(&mut yeeld.args);
+    }
+    for reason in api.reasons.iter_mut() {
+        reason.name = reason.name.with_prefix(namespace);
+        for case in reason.cases.iter_mut() {
+            rename_members(&mut case.args);
+        }
+    }
+}
+
+/// Appends the imported definitions skipping the ones identical to already present definitions.
+/// Conflicting definitions are kept so that they are reported when checking the specification.
+fn merge<T, F>(definitions: &mut Vec<T>, imported: Vec<T>, name: F)
+where
+    T: Serialize,
+    F: Fn(&T) -> &utils::Name,
+{
+    for definition in imported {
+        let value = serde_yaml::to_value(&definition).ok();
+        let duplicate = definitions.iter().any(|present| {
+            name(present) == name(&definition) && serde_yaml::to_value(present).ok() == value
+        });
+        if !duplicate {
+            definitions.push(definition);
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Merges the definitions from files imported by `api` (read from `path`) into it.
+pub(crate) fn resolve(path: &Path, api: spec::Api) -> Result<spec::Api, Error> {
+    Resolver::default().resolve(path, api)
+}
+
+/// Returns paths to all the files imported (directly or indirectly) by the given specification
+/// file. Useful for tracking changes in build scripts and macros.
+pub fn imported_files<P: AsRef<Path>>(path: P) -> Result<Vec<PathBuf>, Error> {
+    let mut resolver = Resolver::default();
+    resolver.resolve_file(path.as_ref())?;
+    Ok(resolver.files)
+}
+
+// -------------------------------------------------------------------------------------------------
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::imported_files;
+    use crate::{error::Error, spec::Api, spec::MemberType, utils::write_test_files, utils::Name};
+
+    const MAIN: &str = "---
+imports:
+  - path: common/errors.yaml
+    namespace: common
+  - path: common/page.yaml
+types:
+  - name: item
+    container:
+      repr: struct
+      members:
+        - name: page
+          type: page
+";
+
+    const ERRORS: &str = "---
+imports:
+  - path: page.yaml
+reasons:
+  - name: internal-error
+    variant: error
+    cases:
+      - name: internal
+        code: 500-internal-server-error
+        args:
+          - name: page
+            type: page
+";
+
+    const PAGE: &str = "---
+types:
+  - name: page
+    container:
+      repr: struct
+      members:
+        - name: number
+          type: u32
+";
+
+    #[test]
+    fn test_imports() {
+        let files =
+            [("main.yaml", MAIN), ("common/errors.yaml", ERRORS), ("common/page.yaml", PAGE)];
+        let dir = write_test_files("imports", &files);
+        let api = Api::read(dir.join("main.yaml")).unwrap();
+
+        let types: Vec<_> = api.types.iter().map(|tipe| tipe.name.kebab_case()).collect();
+        assert_eq!(types, vec!["item", "common-page", "page"]);
+        let reasons: Vec<_> = api.reasons.iter().map(|reason| reason.name.kebab_case()).collect();
+        assert_eq!(reasons, vec!["common-internal-error"]);
+        let arg = &api.reasons[0].cases[0].args[0];
+        assert_eq!(arg.tipe, MemberType::Defined(Name::new("common-page")));
+
+        let imported = imported_files(dir.join("main.yaml")).unwrap();
+        let expected = vec![dir.join("common/errors.yaml"), dir.join("common/page.yaml")];
+        assert_eq!(imported, expected);
+    }
+
+    #[test]
+    fn test_import_cycle() {
+        let first = "---\nimports:\n  - path: second.yaml\n";
+        let second = "---\nimports:\n  - path: first.yaml\n";
+        let dir =
+            write_test_files("import_cycle", &[("first.yaml", first), ("second.yaml", second)]);
+        match Api::read(dir.join("first.yaml")) {
+            Err(Error::ImportCycle { cycle }) => assert_eq!(cycle.len(), 3),
+            result => panic!("Expected import cycle, got {:?}", result),
+        }
+    }
+}
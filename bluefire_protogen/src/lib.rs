@@ -7,13 +7,31 @@
 //! file) and generate `Rust` code from it to mitigate later need to modify the code in many places
 //! (potentially introducing bugs).
 //!
-//! This crate provides a generator that can be used in `build.rs` scripts. See also
+//! This crate provides a generator that can be used in `build.rs` scripts as well as generators of
+//! OpenAPI 3 documents, `TypeScript` clients and HTML API reference. With the `mock` feature
+//! enabled it can also serve example responses for an API which is not implemented yet and with the
+//! `contract` feature it can check if handlers of a router honour the specification. See also
 //! `bluefire_protogen_macros` for related macros.
 
 // TODO: Macros do not use binaries, so split the binaries from this crate.
 
 #![warn(missing_docs)]
 
+pub mod check;
+#[cfg(feature = "contract")]
+pub mod contract;
+pub mod diff;
+pub mod docs_generator;
+pub mod error;
+pub mod examples;
+#[cfg(test)]
+mod fixtures;
+pub mod imports;
+#[cfg(feature = "mock")]
+pub mod mock;
+pub mod openapi_generator;
+pub mod pagination;
 pub mod rust_generator;
 pub mod spec;
+pub mod typescript_generator;
 pub mod utils;
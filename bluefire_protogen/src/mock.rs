@@ -0,0 +1,288 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy of
+// the MPL was not distributed with this file, You can obtain one at http://mozilla.org/MPL/2.0/
+
+//! Mock server answering requests with example responses built from an API specification.
+//!
+//! Every method defined in the specification is answered with its success yield. Values of the
+//! arguments are generated with `ExampleGenerator` from the `examples` module.
+//!
+//! A failure or error case can be requested by sending its name in the `bluefire-mock-case`
+//! header, e.g. `bluefire-mock-case: not-found`.
+//!
+//! ```ignore
+//! let api = bluefire_protogen::spec::Api::read_checked("api.yaml")?;
+//! let builder = bluefire_protogen::mock::MockServer::new(api).routing_builder();
+//! bluefire_backend::BlueFireKindler::start(Box::new(builder)).ignite_server(&address);
+//! ```
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use serde_json::{json, Value};
+
+use bluefire_backend::router::{Host, Route, RoutingBuilder};
+use bluefire_backend::{BlueFire, Handler, Request, Response};
+
+use crate::error::Error;
+use crate::examples::ExampleGenerator;
+use crate::spec;
+
+/// Name of the header selecting the failure or error case to respond with.
+pub const MOCK_CASE_HEADER: &str = "bluefire-mock-case";
+
+// -------------------------------------------------------------------------------------------------
+
+/// Handler answering all the methods of one path with example responses.
+#[derive(Clone, Debug)]
+struct MockHandler {
+    api: Arc<spec::Api>,
+    methods: Vec<spec::Method>,
+    seed: Option<u64>,
+    counter: Arc<AtomicU64>,
+}
+
+impl MockHandler {
+    /// Returns the seed for the next request.
+    fn next_seed(&self) -> u64 {
+        let base = self.seed.unwrap_or_else(|| {
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|duration| duration.as_nanos() as u64)
+                .unwrap_or_default()
+        });
+        base.wrapping_add(self.counter.fetch_add(1, Ordering::SeqCst))
+    }
+
+    fn respond(&self, request: &Request) -> Response {
+        if *request.method() == http::Method::OPTIONS {
+            let methods: Vec<&str> =
+                self.methods.iter().map(|method| method.request.method.to_str()).collect();
+            let headers = format!(
+                "{}, {}",
+                bluefire_twine::constants::BLUEFIRE_TOKEN_HEADER,
+                MOCK_CASE_HEADER
+            );
+            return http::response::Builder::new()
+                .status(http::StatusCode::OK)
+                .header(http::header::ACCESS_CONTROL_ALLOW_METHODS, methods.join(", ").as_str())
+                .header(http::header::ACCESS_CONTROL_ALLOW_HEADERS, headers.as_str())
+                .body(Vec::new())
+                .expect("Build OPTIONS response");
+        }
+
+        let method = self
+            .methods
+            .iter()
+            .find(|method| method.request.method.to_str() == request.method().as_str());
+        let method = match method {
+            Some(method) => method,
+            None => return make_response(http::StatusCode::METHOD_NOT_ALLOWED, Value::Null),
+        };
+
+        let mut generator = ExampleGenerator::new(&self.api, self.next_seed());
+        let case_name = request
+            .headers()
+            .get(MOCK_CASE_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.trim().replace('_', "-"));
+        if let Some(case_name) = case_name {
+            let mut reasons = Vec::new();
+            if let Some(failure) = &method.response.failure {
+                reasons.push(spec::find_reason(failure, &self.api.reasons));
+            }
+            reasons.push(spec::find_reason(&method.response.error, &self.api.reasons));
+            let reasons = match reasons.into_iter().collect::<Result<Vec<_>, _>>() {
+                Ok(reasons) => reasons,
+                Err(err) => return make_spec_error_response(&err),
+            };
+            for reason in reasons.iter() {
+                if let Some(case) = reason.cases.iter().find(|c| c.name.kebab_case() == case_name) {
+                    let body = json!({
+                        "result": reason.variant.as_str(),
+                        "content": generator.case_content(case),
+                    });
+                    return make_response(status(&case.code), body);
+                }
+            }
+            let message =
+                format!("Method '{}' has no case '{}'", method.name.kebab_case(), case_name);
+            return make_response(http::StatusCode::BAD_REQUEST, message.into());
+        }
+
+        let yeeld = match spec::find_yield(&method.response.success, &self.api.yields) {
+            Ok(yeeld) => yeeld,
+            Err(err) => return make_spec_error_response(&err),
+        };
+        match &yeeld.content_type {
+            Some(content_type) => http::response::Builder::new()
+                .status(status(&yeeld.code))
+                .header(http::header::CONTENT_TYPE, content_type.as_str())
+                .header(http::header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+                .body(generator.bytes(16))
+                .expect("Build mock response"),
+            None => {
+                let body =
+                    json!({ "result": "success", "content": generator.yield_content(&yeeld) });
+                make_response(status(&yeeld.code), body)
+            }
+        }
+    }
+}
+
+impl Handler for MockHandler {
+    fn handle(&self, _context: &BlueFire, request: Request) -> Response {
+        self.respond(&request)
+    }
+
+    fn duplicate(&self) -> Box<dyn Handler> {
+        Box::new(self.clone())
+    }
+}
+
+/// Converts the response code from the specification to a status code.
+fn status(code: &spec::HttpResponse) -> http::StatusCode {
+    http::StatusCode::from_u16(code.code()).expect("Valid status code")
+}
+
+/// Builds a JSON response accessible from any origin.
+fn make_response(status: http::StatusCode, body: Value) -> Response {
+    http::response::Builder::new()
+        .status(status)
+        .header(http::header::CONTENT_TYPE, "application/json")
+        .header(http::header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+        .body(body.to_string().into_bytes())
+        .expect("Build mock response")
+}
+
+/// Builds a response reporting a problem with the specification the mock is generated from.
+fn make_spec_error_response(err: &Error) -> Response {
+    make_response(http::StatusCode::INTERNAL_SERVER_ERROR, err.to_string().into())
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Builds routes serving example responses for all the methods of the API.
+#[derive(Clone, Debug)]
+pub struct MockServer {
+    api: Arc<spec::Api>,
+    seed: Option<u64>,
+}
+
+impl MockServer {
+    /// Constructs a new `MockServer`. By default values are different in every response.
+    pub fn new(api: spec::Api) -> Self {
+        Self { api: Arc::new(api), seed: None }
+    }
+
+    /// Sets the seed making the generated values reproducible.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Returns the top-level route with a mock handler for every path used by some method.
+    pub fn route(&self) -> Route {
+        let counter = Arc::new(AtomicU64::new(0));
+        let routes = self.api.routes.iter().map(|route| self.make_route(route, &counter)).collect();
+        Route::index().with_routes(routes)
+    }
+
+    /// Returns a routing builder serving the mock routes on any host.
+    pub fn routing_builder(&self) -> RoutingBuilder {
+        let mut builder = RoutingBuilder::new();
+        builder.insert(Host::new_nameless(), self.route());
+        builder
+    }
+}
+
+impl MockServer {
+    fn make_route(&self, route: &spec::Route, counter: &Arc<AtomicU64>) -> Route {
+        // Routes are built once for the lifetime of the server so leaking the names is fine.
+        let mut result = match &route.segment {
+            spec::Segment::Exact(name) => Route::exact(leak(name.snake_case())),
+            spec::Segment::Str(name) => Route::param(leak(name.snake_case())),
+        };
+        if let Some(name) = &route.name {
+            let methods: Vec<spec::Method> = self
+                .api
+                .methods
+                .iter()
+                .filter(|method| method.request.path == *name)
+                .cloned()
+                .collect();
+            if !methods.is_empty() {
+                let api = self.api.clone();
+                let (seed, counter) = (self.seed, counter.clone());
+                result = result.with_view(Box::new(MockHandler { api, methods, seed, counter }));
+            }
+        }
+        let routes = route.routes.iter().map(|route| self.make_route(route, counter)).collect();
+        result.with_routes(routes)
+    }
+}
+
+fn leak(name: String) -> &'static str {
+    Box::leak(name.into_boxed_str())
+}
+
+// -------------------------------------------------------------------------------------------------
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{atomic::AtomicU64, Arc};
+
+    use serde_json::{json, Value};
+
+    use super::{MockHandler, MOCK_CASE_HEADER};
+    use crate::fixtures::Fixture;
+
+    #[test]
+    fn test_mock_responses() {
+        let api = Fixture::new()
+            .with_cases(
+                "- name: limit-exceeded
+  code: 409-conflict
+  args:
+    - name: limit
+      type: u8
+",
+            )
+            .to_api();
+        let handler = MockHandler {
+            methods: api.methods.clone(),
+            api: Arc::new(api),
+            seed: Some(1),
+            counter: Arc::new(AtomicU64::new(0)),
+        };
+        let body = |response: bluefire_backend::Response| -> Value {
+            serde_json::from_slice(response.body()).unwrap()
+        };
+
+        let request = http::Request::get("/items").body(Vec::new()).unwrap();
+        let response = handler.respond(&request);
+        assert_eq!(response.status(), http::StatusCode::OK);
+        let response = body(response);
+        assert_eq!(response["result"], json!("success"));
+        assert!(response["content"]["items"].is_array());
+
+        let request = http::Request::get("/items")
+            .header(MOCK_CASE_HEADER, "limit_exceeded")
+            .body(Vec::new())
+            .unwrap();
+        let response = handler.respond(&request);
+        assert_eq!(response.status(), http::StatusCode::CONFLICT);
+        let response = body(response);
+        assert_eq!(response["result"], json!("error"));
+        assert_eq!(response["content"]["reason"], json!("limit_exceeded"));
+        assert!(response["content"]["limit"].is_u64());
+
+        let request = http::Request::get("/items")
+            .header(MOCK_CASE_HEADER, "unknown")
+            .body(Vec::new())
+            .unwrap();
+        assert_eq!(handler.respond(&request).status(), http::StatusCode::BAD_REQUEST);
+        let request = http::Request::delete("/items").body(Vec::new()).unwrap();
+        assert_eq!(handler.respond(&request).status(), http::StatusCode::METHOD_NOT_ALLOWED);
+    }
+}
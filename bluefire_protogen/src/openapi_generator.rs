@@ -0,0 +1,675 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy of
+// the MPL was not distributed with this file, You can obtain one at http://mozilla.org/MPL/2.0/
+
+//! Generation of OpenAPI 3 documents.
+//!
+//! The generated document describes the JSON representation produced by the code generated by
+//! `RustGenerator`.
+
+use serde_json::{json, Map, Value};
+
+use crate::error::Error;
+use crate::{spec, utils};
+
+const OPENAPI_VERSION: &str = "3.0.2";
+const DEFAULT_TITLE: &str = "API";
+const DEFAULT_VERSION: &str = "0.1.0";
+const SCHEMAS_PREFIX: &str = "#/components/schemas/";
+
+// -------------------------------------------------------------------------------------------------
+
+impl spec::HttpResponse {
+    /// Returns the response code as a string used as a key in OpenAPI responses.
+    fn openapi_code(&self) -> &'static str {
+        match self {
+            spec::HttpResponse::Ok => "200",
+            spec::HttpResponse::Created => "201",
+            spec::HttpResponse::BadRequest => "400",
+            spec::HttpResponse::Unauthorized => "401",
+            spec::HttpResponse::Forbidden => "403",
+            spec::HttpResponse::NotFound => "404",
+            spec::HttpResponse::Conflict => "409",
+            spec::HttpResponse::InternalServerError => "500",
+        }
+    }
+}
+
+impl spec::SimpleType {
+    /// Returns the JSON Schema of the type.
+    fn openapi_schema(&self) -> Map<String, Value> {
+        let value = match self {
+            spec::SimpleType::Bool => json!({ "type": "boolean" }),
+            spec::SimpleType::U8 => json!({ "type": "integer", "minimum": 0, "maximum": 255 }),
+            // Values above `i32::MAX` do not fit in `int32`.
+            spec::SimpleType::U32 => json!({
+                "type": "integer",
+                "format": "int64",
+                "minimum": 0,
+                "maximum": std::u32::MAX,
+            }),
+            spec::SimpleType::U64 => json!({ "type": "integer", "format": "int64", "minimum": 0 }),
+            spec::SimpleType::I32 => json!({ "type": "integer", "format": "int32" }),
+            spec::SimpleType::I64 => json!({ "type": "integer", "format": "int64" }),
+            spec::SimpleType::F32 => json!({ "type": "number", "format": "float" }),
+            spec::SimpleType::F64 => json!({ "type": "number", "format": "double" }),
+            spec::SimpleType::Str => json!({ "type": "string" }),
+            spec::SimpleType::Id => json!({ "type": "string", "pattern": "^[0-9a-f]{24}$" }),
+            spec::SimpleType::DateTime => json!({ "type": "string", "format": "date-time" }),
+            spec::SimpleType::Date => json!({ "type": "string", "format": "date" }),
+            spec::SimpleType::Uuid => json!({ "type": "string", "format": "uuid" }),
+            spec::SimpleType::Bytes => json!({ "type": "string", "format": "byte" }),
+            spec::SimpleType::File => json!({ "type": "string", "format": "binary" }),
+        };
+        match value {
+            Value::Object(map) => map,
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl spec::Member {
+    /// Returns the JSON Schema of the member.
+    fn openapi_schema(&self) -> Value {
+        let layers =
+            self.container.as_ref().map(|container| container.layers()).unwrap_or_default();
+        member_schema(&layers, &self.tipe, self.validation.as_ref())
+    }
+
+    fn is_required(&self) -> bool {
+        !self.is_optional()
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Returns a reference to a schema defined in the components section.
+fn schema_ref(name: &str) -> Value {
+    json!({ "$ref": format!("{}{}", SCHEMAS_PREFIX, name) })
+}
+
+/// Adds the description and the example (if present) to the schema.
+fn add_annotations(schema: &mut Value, description: &Option<String>, example: &Option<Value>) {
+    if let Value::Object(map) = schema {
+        if let Some(description) = description {
+            map.insert("description".to_string(), description.clone().into());
+        }
+        if let Some(example) = example {
+            map.insert("example".to_string(), example.clone());
+        }
+    }
+}
+
+/// Converts the condition value to a JSON number without introducing rounding artifacts.
+fn number(value: f32) -> Value {
+    match value.to_string().parse::<f64>().ok().and_then(serde_json::Number::from_f64) {
+        Some(number) => Value::Number(number),
+        None => Value::Null,
+    }
+}
+
+/// Returns a JSON Schema of a value wrapped in the given containers (starting from the outermost
+/// one). Constraints of members of defined types are not included as they would require resolving
+/// the type.
+fn member_schema(
+    layers: &[spec::Layer],
+    tipe: &spec::MemberType,
+    validation: Option<&spec::Validation>,
+) -> Value {
+    let (layer, rest) = match layers.split_first() {
+        Some(split) => split,
+        None => {
+            return match tipe {
+                spec::MemberType::Simple(simple_type) => {
+                    let mut schema = simple_type.openapi_schema();
+                    if let Some(validation) = validation {
+                        add_simple_constraints(&mut schema, simple_type, validation);
+                    }
+                    schema.into()
+                }
+                spec::MemberType::Defined(name) => schema_ref(&name.camel_case()),
+            }
+        }
+    };
+
+    let each = validation.and_then(|validation| validation.each.as_ref()).map(|each| &**each);
+    let (mut schema, min, max) = match layer {
+        spec::Layer::Optional => {
+            let inner = member_schema(rest, tipe, validation);
+            return json!({ "allOf": [inner], "nullable": true });
+        }
+        spec::Layer::Vector => {
+            let schema = json!({ "type": "array", "items": member_schema(rest, tipe, each) });
+            (schema, "minItems", "maxItems")
+        }
+        spec::Layer::Map => {
+            let inner = member_schema(rest, tipe, each);
+            let schema = json!({ "type": "object", "additionalProperties": inner });
+            (schema, "minProperties", "maxProperties")
+        }
+    };
+
+    if let (Some(validation), Value::Object(map)) = (validation, &mut schema) {
+        add_length_constraints(map, validation, min, max);
+    }
+    schema
+}
+
+/// Adds constraints on the length of a string or collection to the schema.
+fn add_length_constraints(
+    schema: &mut Map<String, Value>,
+    validation: &spec::Validation,
+    min: &str,
+    max: &str,
+) {
+    if validation.checks.contains(&spec::Check::NonEmpty) {
+        schema.insert(min.to_string(), 1.into());
+    }
+    for condition in validation.conditions.iter() {
+        match condition {
+            spec::Condition::LenEq(len) => {
+                schema.insert(min.to_string(), (*len).into());
+                schema.insert(max.to_string(), (*len).into());
+            }
+            spec::Condition::LenLe(len) => {
+                schema.insert(max.to_string(), (*len).into());
+            }
+            spec::Condition::LenGe(len) => {
+                schema.insert(min.to_string(), (*len).into());
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Adds constraints applicable to the given simple type to the schema.
+fn add_simple_constraints(
+    schema: &mut Map<String, Value>,
+    simple_type: &spec::SimpleType,
+    validation: &spec::Validation,
+) {
+    for check in validation.checks.iter().filter(|check| check.applies_to(simple_type)) {
+        match check {
+            spec::Check::Email => {
+                schema.insert("format".to_string(), "email".into());
+            }
+            spec::Check::Url => {
+                schema.insert("format".to_string(), "uri".into());
+            }
+            spec::Check::Uuid => {
+                schema.insert("format".to_string(), "uuid".into());
+            }
+            spec::Check::NonEmpty => {}
+        }
+    }
+
+    // Length of byte arrays cannot be expressed in JSON Schema as they are encoded in base64.
+    if *simple_type == spec::SimpleType::Str {
+        add_length_constraints(schema, validation, "minLength", "maxLength");
+    }
+
+    for condition in validation.conditions.iter().filter(|c| c.applies_to(simple_type)) {
+        match condition {
+            spec::Condition::Le(value) => {
+                schema.insert("maximum".to_string(), number(*value));
+            }
+            spec::Condition::Ge(value) => {
+                schema.insert("minimum".to_string(), number(*value));
+            }
+            spec::Condition::Regex(pattern) => {
+                schema.insert("pattern".to_string(), format!("^(?:{})$", pattern).into());
+            }
+            spec::Condition::OneOf(values) => {
+                schema.insert("enum".to_string(), values.clone().into());
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Returns a JSON Schema of an object with the given members.
+fn object_schema(members: &[spec::Member]) -> Map<String, Value> {
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+    for member in members.iter() {
+        let mut schema = member.openapi_schema();
+        add_annotations(&mut schema, &member.description, &member.example);
+        properties.insert(member.name.snake_case(), schema);
+        if member.is_required() {
+            required.push(Value::String(member.name.snake_case()));
+        }
+    }
+
+    let mut schema = Map::new();
+    schema.insert("type".to_string(), "object".into());
+    schema.insert("properties".to_string(), properties.into());
+    if !required.is_empty() {
+        schema.insert("required".to_string(), required.into());
+    }
+    schema
+}
+
+/// Returns a JSON Schema of an object with a tag and the given members.
+fn tagged_object_schema(tag: &str, value: &str, members: &[spec::Member]) -> Value {
+    let mut schema = object_schema(members);
+    let mut properties = Map::new();
+    properties.insert(tag.to_string(), json!({ "type": "string", "enum": [value] }));
+    if let Some(Value::Object(members)) = schema.remove("properties") {
+        properties.extend(members);
+    }
+    let mut required = vec![Value::String(tag.to_string())];
+    if let Some(Value::Array(members)) = schema.remove("required") {
+        required.extend(members);
+    }
+    schema.insert("properties".to_string(), properties.into());
+    schema.insert("required".to_string(), required.into());
+    schema.into()
+}
+
+/// Returns a JSON Schema of a response with the given result variant and content.
+fn result_schema(result: &str, content: Value) -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "result": { "type": "string", "enum": [result] },
+            "content": content,
+        },
+        "required": ["result", "content"],
+    })
+}
+
+/// Returns a JSON Schema of the given simple type with constraints applied.
+fn simple_type_schema(
+    simple_type: &spec::SimpleType,
+    validation: &Option<spec::Validation>,
+) -> Value {
+    let mut schema = simple_type.openapi_schema();
+    if let Some(validation) = validation {
+        add_simple_constraints(&mut schema, simple_type, validation);
+    }
+    schema.into()
+}
+
+/// Returns a JSON Schema of the given type definition.
+fn type_schema(tipe: &spec::TypeDef) -> Value {
+    match &tipe.container {
+        spec::TypeRepr::Simple { simple_type, validation } => {
+            simple_type_schema(simple_type, validation)
+        }
+        spec::TypeRepr::External => json!({ "description": "External type" }),
+        spec::TypeRepr::Struct { members } => object_schema(members).into(),
+        spec::TypeRepr::Union { members } => {
+            let variants: Vec<Value> = members
+                .iter()
+                .map(|member| {
+                    json!({
+                        "type": "object",
+                        "properties": {
+                            "variant": { "type": "string", "enum": [member.name.snake_case()] },
+                            "content": member.openapi_schema(),
+                        },
+                        "required": ["variant", "content"],
+                    })
+                })
+                .collect();
+            json!({ "oneOf": variants })
+        }
+        spec::TypeRepr::Enum { values } => {
+            let values: Vec<String> = values.iter().map(|value| value.snake_case()).collect();
+            json!({ "type": "string", "enum": values })
+        }
+    }
+}
+
+/// Formats the path in the OpenAPI format (with parameters in braces).
+pub(crate) fn format_path(path: &spec::Path) -> String {
+    if path.segments.is_empty() {
+        return "/".to_string();
+    }
+
+    let mut result = String::new();
+    for segment in path.segments.iter() {
+        result += "/";
+        match segment {
+            spec::Segment::Exact(name) => result += &name.snake_case(),
+            spec::Segment::Str(name) => result += &format!("{{{}}}", name.snake_case()),
+        }
+    }
+    result
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Generator of OpenAPI 3 documents.
+pub struct OpenApiGenerator {
+    title: String,
+    version: String,
+}
+
+impl OpenApiGenerator {
+    /// Constructs a new `OpenApiGenerator`.
+    pub fn new() -> Self {
+        Self { title: DEFAULT_TITLE.to_string(), version: DEFAULT_VERSION.to_string() }
+    }
+
+    /// Sets the title of the API.
+    pub fn with_title(mut self, title: &str) -> Self {
+        self.title = title.to_string();
+        self
+    }
+
+    /// Sets the version of the API.
+    pub fn with_version(mut self, version: &str) -> Self {
+        self.version = version.to_string();
+        self
+    }
+
+    /// Generates the OpenAPI document as a JSON value. Fails if a method refers to a path, yield
+    /// or reason which does not exist.
+    pub fn generate_value(&self, api: &spec::Api) -> Result<Value, Error> {
+        Ok(json!({
+            "openapi": OPENAPI_VERSION,
+            "info": { "title": self.title, "version": self.version },
+            "paths": self.generate_paths(api)?,
+            "components": { "schemas": self.generate_schemas(api) },
+        }))
+    }
+
+    /// Generates the OpenAPI document as a JSON string.
+    pub fn generate_api(&self, api: &spec::Api) -> Result<String, Error> {
+        let value = self.generate_value(api)?;
+        Ok(serde_json::to_string_pretty(&value).expect("Serialize OpenAPI document to JSON"))
+    }
+}
+
+impl OpenApiGenerator {
+    fn generate_schemas(&self, api: &spec::Api) -> Map<String, Value> {
+        let mut schemas = Map::new();
+        for tipe in api.types.iter() {
+            let mut schema = type_schema(tipe);
+            add_annotations(&mut schema, &tipe.description, &tipe.example);
+            schemas.insert(tipe.name.camel_case(), schema);
+        }
+        for yeeld in api.yields.iter().filter(|yeeld| !yeeld.is_binary()) {
+            schemas.insert(yeeld.name.camel_case() + "Yield", object_schema(&yeeld.args).into());
+        }
+        for reason in api.reasons.iter() {
+            let cases: Vec<Value> = reason
+                .cases
+                .iter()
+                .map(|case| tagged_object_schema("reason", &case.name.snake_case(), &case.args))
+                .collect();
+            schemas.insert(reason.name.camel_case() + "Reason", json!({ "oneOf": cases }));
+        }
+        for method in api.methods.iter() {
+            if method.request.has_body() {
+                schemas.insert(
+                    method.name.camel_case() + "Request",
+                    object_schema(&method.request.body_args()).into(),
+                );
+            }
+        }
+        schemas
+    }
+
+    fn generate_paths(&self, api: &spec::Api) -> Result<Map<String, Value>, Error> {
+        let paths = spec::routes_to_paths(None, &api.routes);
+        let mut result = Map::new();
+        for method in api.methods.iter() {
+            let path = paths
+                .iter()
+                .find(|path| path.name == method.request.path)
+                .ok_or_else(|| Error::unknown_reference("path", &method.request.path))?;
+
+            let item = result
+                .entry(format_path(path))
+                .or_insert_with(|| Value::Object(Map::new()))
+                .as_object_mut()
+                .expect("Path item is an object");
+            item.insert(
+                method.request.method.to_str().to_lowercase(),
+                self.generate_operation(api, method, path)?,
+            );
+        }
+        Ok(result)
+    }
+
+    fn generate_operation(
+        &self,
+        api: &spec::Api,
+        method: &spec::Method,
+        path: &spec::Path,
+    ) -> Result<Value, Error> {
+        let mut parameters = Vec::new();
+        for segment in path.segments.iter() {
+            if let spec::Segment::Str(name) = segment {
+                parameters.push(json!({
+                    "name": name.snake_case(),
+                    "in": "path",
+                    "required": true,
+                    "schema": { "type": "string" },
+                }));
+            }
+        }
+
+        let mut operation = Map::new();
+        operation.insert("operationId".to_string(), method.name.snake_case().into());
+        if let Some(description) = &method.description {
+            operation.insert("description".to_string(), description.clone().into());
+        }
+        for arg in method.request.args.iter() {
+            let location = method.request.location_of(arg);
+            let name = match location {
+                spec::ArgLocation::Body => continue,
+                spec::ArgLocation::Header => arg.name.kebab_case(),
+                spec::ArgLocation::Query | spec::ArgLocation::Cookie => arg.name.snake_case(),
+            };
+            let mut parameter = json!({
+                "name": name,
+                "in": location.as_str(),
+                "required": arg.is_required(),
+                "schema": arg.openapi_schema(),
+            });
+            add_annotations(&mut parameter, &arg.description, &arg.example);
+            parameters.push(parameter);
+        }
+        if method.request.has_body() {
+            let request_name = method.name.camel_case() + "Request";
+            let mut content = Map::new();
+            content.insert(
+                method.request.format.mime_type().to_string(),
+                json!({ "schema": schema_ref(&request_name) }),
+            );
+            operation
+                .insert("requestBody".to_string(), json!({ "required": true, "content": content }));
+        }
+        if !parameters.is_empty() {
+            operation.insert("parameters".to_string(), parameters.into());
+        }
+        operation.insert("responses".to_string(), self.generate_responses(api, method)?.into());
+        Ok(operation.into())
+    }
+
+    fn generate_responses(
+        &self,
+        api: &spec::Api,
+        method: &spec::Method,
+    ) -> Result<Map<String, Value>, Error> {
+        // Several cases may share the same response code so the schemas are gathered first.
+        let mut responses: Vec<(&'static str, Vec<String>, Vec<Value>)> = Vec::new();
+        let mut add = |code: &'static str, description: String, schema: Value| match responses
+            .iter_mut()
+            .find(|(c, _, _)| *c == code)
+        {
+            Some((_, descriptions, schemas)) => {
+                descriptions.push(description);
+                schemas.push(schema);
+            }
+            None => responses.push((code, vec![description], vec![schema])),
+        };
+
+        let yeeld = spec::find_yield(&method.response.success, &api.yields)?;
+        if !yeeld.is_binary() {
+            add(
+                yeeld.code.openapi_code(),
+                format!("Success: {}", yeeld.name.kebab_case()),
+                result_schema("success", schema_ref(&(yeeld.name.camel_case() + "Yield"))),
+            );
+        }
+
+        let mut reasons = Vec::new();
+        if let Some(failure) = &method.response.failure {
+            reasons.push(spec::find_reason(failure, &api.reasons)?);
+        }
+        reasons.push(spec::find_reason(&method.response.error, &api.reasons)?);
+        for reason in reasons.iter() {
+            let variant = reason.variant.as_str();
+            for case in reason.cases.iter() {
+                let name = case.name.snake_case();
+                add(
+                    case.code.openapi_code(),
+                    format!("{}: {}", utils::capitalize(variant), case.name.kebab_case()),
+                    result_schema(variant, tagged_object_schema("reason", &name, &case.args)),
+                );
+            }
+        }
+
+        let mut result = Map::new();
+        for (code, descriptions, mut schemas) in responses {
+            let schema =
+                if schemas.len() == 1 { schemas.remove(0) } else { json!({ "oneOf": schemas }) };
+            result.insert(
+                code.to_string(),
+                json!({
+                    "description": descriptions.join(", "),
+                    "content": { "application/json": { "schema": schema } },
+                }),
+            );
+        }
+
+        // Binary data is sent as is, with a different content type than other responses.
+        if let Some(content_type) = &yeeld.content_type {
+            let description = format!("Success: {}", yeeld.name.kebab_case());
+            let response = result
+                .entry(yeeld.code.openapi_code().to_string())
+                .or_insert_with(|| json!({ "description": description, "content": {} }));
+            response["content"][content_type.as_str()] =
+                json!({ "schema": spec::SimpleType::File.openapi_schema() });
+        }
+        Ok(result)
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{format_path, simple_type_schema, OpenApiGenerator};
+    use crate::error::Error;
+    use crate::spec::{
+        Api, Check, Condition, ContainerType, Member, MemberType, Path, Segment, SimpleType,
+        Validation,
+    };
+    use crate::utils::Name;
+
+    #[test]
+    fn test_validation_constraints() {
+        let validation = Validation {
+            checks: vec![Check::Email],
+            conditions: vec![Condition::LenGe(3), Condition::LenLe(64)],
+            each: None,
+        };
+        assert_eq!(
+            simple_type_schema(&SimpleType::Str, &Some(validation)),
+            json!({ "type": "string", "format": "email", "minLength": 3, "maxLength": 64 })
+        );
+
+        let validation = Validation {
+            checks: Vec::new(),
+            conditions: vec![Condition::Ge(0.5), Condition::Le(10.0)],
+            each: None,
+        };
+        assert_eq!(
+            simple_type_schema(&SimpleType::F32, &Some(validation)),
+            json!({ "type": "number", "format": "float", "minimum": 0.5, "maximum": 10.0 })
+        );
+
+        let validation = Validation {
+            checks: Vec::new(),
+            conditions: vec![Condition::Regex("[a-z]+".to_string())],
+            each: None,
+        };
+        assert_eq!(
+            simple_type_schema(&SimpleType::Str, &Some(validation)),
+            json!({ "type": "string", "pattern": "^(?:[a-z]+)$" })
+        );
+    }
+
+    #[test]
+    fn test_member_constraints() {
+        let member = Member {
+            name: Name::new("tags"),
+            tipe: MemberType::Simple(SimpleType::Str),
+            container: Some(ContainerType::Vector),
+            location: None,
+            description: None,
+            example: None,
+            validation: Some(Validation {
+                checks: vec![Check::NonEmpty],
+                conditions: vec![Condition::LenLe(5)],
+                each: Some(Box::new(Validation {
+                    checks: Vec::new(),
+                    conditions: vec![Condition::OneOf(vec!["a".to_string(), "b".to_string()])],
+                    each: None,
+                })),
+            }),
+        };
+        assert_eq!(
+            member.openapi_schema(),
+            json!({
+                "type": "array",
+                "items": { "type": "string", "enum": ["a", "b"] },
+                "minItems": 1,
+                "maxItems": 5,
+            })
+        );
+    }
+
+    #[test]
+    fn test_format_path() {
+        let path = Path {
+            name: Name::new("user"),
+            segments: vec![Segment::Exact(Name::new("users")), Segment::Str(Name::new("user-id"))],
+        };
+        assert_eq!(format_path(&path), "/users/{user_id}");
+        assert_eq!(format_path(&Path { name: Name::new("index"), segments: Vec::new() }), "/");
+    }
+
+    #[test]
+    fn test_unknown_reference() {
+        let spec = "---
+routes:
+  - name: items
+    exact: items
+reasons:
+  - name: item-error
+    variant: error
+    cases:
+      - name: internal
+        code: 500-internal-server-error
+methods:
+  - name: get-items
+    request:
+      method: get
+      path: items
+    response:
+      success: items
+      error: item-error
+";
+        let api = Api::from_str(spec).unwrap();
+        let expected = Error::UnknownReference { kind: "yield", name: "items".to_string() };
+        assert_eq!(OpenApiGenerator::new().generate_value(&api), Err(expected));
+    }
+}
@@ -0,0 +1,285 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy of
+// the MPL was not distributed with this file, You can obtain one at http://mozilla.org/MPL/2.0/
+
+//! Expansion of paginated methods.
+//!
+//! A method returning a list may declare its pagination instead of hand-writing the arguments and
+//! the yield:
+//!
+//! ```yaml
+//! methods:
+//!   - name: list-items
+//!     request:
+//!       method: get
+//!       path: items
+//!     pagination:
+//!       style: cursor
+//!       item: item
+//!       max_limit: 50
+//!       filter: [status]
+//!       sort: [name, created]
+//!     response:
+//!       success: item-page
+//!       error: items-error
+//! ```
+//!
+//! When the specification is read the method gets the following arguments (unless it already has
+//! arguments with these names):
+//!
+//! - `cursor: optional<string>` or `offset: optional<u32>` depending on the style,
+//! - `limit: optional<u32>` validated to be between 1 and `max_limit`,
+//! - `sort: optional<list-items-sort>` if sort fields are declared; the generated enum contains
+//!   every field in ascending (e.g. `name`) and descending (e.g. `name_desc`) order,
+//! - `filter-field: optional<list-items-filter-field>` and `filter-value: optional<string>` if
+//!   filter fields are declared; the generated enum contains all the filter fields.
+//!
+//! If the success yield is not defined, it is generated with the `items: vector<item>` member and
+//! `next-cursor: optional<string>` or `next-offset: optional<u32>` depending on the style.
+//! Expansion is idempotent so expanded specifications can be serialized and read again.
+
+use crate::{spec, utils};
+
+/// Name of the argument with the cursor of the requested page.
+pub const CURSOR_ARG: &str = "cursor";
+
+/// Name of the argument with the number of skipped items.
+pub const OFFSET_ARG: &str = "offset";
+
+/// Name of the argument with the maximal number of items in the page.
+pub const LIMIT_ARG: &str = "limit";
+
+/// Name of the argument with the sort order.
+pub const SORT_ARG: &str = "sort";
+
+/// Name of the argument with the field to filter by.
+pub const FILTER_FIELD_ARG: &str = "filter-field";
+
+/// Name of the argument with the value to filter by.
+pub const FILTER_VALUE_ARG: &str = "filter-value";
+
+/// Name of the yield member with the items of the page.
+pub const ITEMS_MEMBER: &str = "items";
+
+/// Name of the yield member with the cursor of the next page.
+pub const NEXT_CURSOR_MEMBER: &str = "next-cursor";
+
+/// Name of the yield member with the offset of the next page.
+pub const NEXT_OFFSET_MEMBER: &str = "next-offset";
+
+// -------------------------------------------------------------------------------------------------
+
+/// Constructs a member without location, description, example and validation.
+fn member(
+    name: &str,
+    tipe: spec::MemberType,
+    container: Option<spec::ContainerType>,
+) -> spec::Member {
+    spec::Member {
+        name: utils::Name::new(name),
+        tipe,
+        container,
+        location: None,
+        description: None,
+        example: None,
+        validation: None,
+    }
+}
+
+/// Constructs an optional member of a simple type.
+fn optional(name: &str, simple_type: spec::SimpleType) -> spec::Member {
+    member(name, spec::MemberType::Simple(simple_type), Some(spec::ContainerType::Optional))
+}
+
+/// Returns the name of the enum with the sort orders of the method.
+pub fn sort_type_name(method: &spec::Method) -> utils::Name {
+    method.name.with_suffix(&utils::Name::new("sort"))
+}
+
+/// Returns the name of the enum with the filter fields of the method.
+pub fn filter_type_name(method: &spec::Method) -> utils::Name {
+    method.name.with_suffix(&utils::Name::new("filter-field"))
+}
+
+/// Returns the arguments generated for the paginated method.
+pub fn args(method: &spec::Method, pagination: &spec::Pagination) -> Vec<spec::Member> {
+    let mut args = Vec::new();
+    if pagination.style.is_cursor() {
+        args.push(optional(CURSOR_ARG, spec::SimpleType::Str));
+    } else {
+        args.push(optional(OFFSET_ARG, spec::SimpleType::U32));
+    }
+
+    let mut limit = optional(LIMIT_ARG, spec::SimpleType::U32);
+    limit.validation = Some(spec::Validation {
+        checks: Vec::new(),
+        conditions: vec![
+            spec::Condition::Ge(1.0),
+            spec::Condition::Le(pagination.max_limit as f32),
+        ],
+        each: None,
+    });
+    args.push(limit);
+
+    let optional_enum = Some(spec::ContainerType::Optional);
+    if !pagination.sort.is_empty() {
+        let tipe = spec::MemberType::Defined(sort_type_name(method));
+        args.push(member(SORT_ARG, tipe, optional_enum.clone()));
+    }
+    if !pagination.filter.is_empty() {
+        let tipe = spec::MemberType::Defined(filter_type_name(method));
+        args.push(member(FILTER_FIELD_ARG, tipe, optional_enum));
+        args.push(optional(FILTER_VALUE_ARG, spec::SimpleType::Str));
+    }
+    args
+}
+
+/// Returns the types generated for the paginated method.
+pub fn types(method: &spec::Method, pagination: &spec::Pagination) -> Vec<spec::TypeDef> {
+    let mut types = Vec::new();
+    if !pagination.sort.is_empty() {
+        let values = pagination
+            .sort
+            .iter()
+            .flat_map(|field| vec![field.clone(), field.with_suffix(&utils::Name::new("desc"))])
+            .collect();
+        let container = spec::TypeRepr::Enum { values };
+        let name = sort_type_name(method);
+        types.push(spec::TypeDef { name, container, description: None, example: None });
+    }
+    if !pagination.filter.is_empty() {
+        let container = spec::TypeRepr::Enum { values: pagination.filter.clone() };
+        let name = filter_type_name(method);
+        types.push(spec::TypeDef { name, container, description: None, example: None });
+    }
+    types
+}
+
+/// Returns the success yield generated for the paginated method.
+pub fn page_yield(method: &spec::Method, pagination: &spec::Pagination) -> spec::Yield {
+    let items = member(ITEMS_MEMBER, pagination.item.clone(), Some(spec::ContainerType::Vector));
+    let next = if pagination.style.is_cursor() {
+        optional(NEXT_CURSOR_MEMBER, spec::SimpleType::Str)
+    } else {
+        optional(NEXT_OFFSET_MEMBER, spec::SimpleType::U32)
+    };
+    spec::Yield {
+        name: method.response.success.clone(),
+        code: spec::HttpResponse::Ok,
+        content_type: None,
+        args: vec![items, next],
+        description: None,
+        example: None,
+    }
+}
+
+/// Adds the arguments, types and yields of all the paginated methods to the specification.
+/// Definitions already present in the specification are not replaced.
+pub fn expand(api: &mut spec::Api) {
+    for index in 0..api.methods.len() {
+        let method = api.methods[index].clone();
+        let pagination = match &method.pagination {
+            Some(pagination) => pagination,
+            None => continue,
+        };
+
+        for arg in args(&method, pagination) {
+            let args = &mut api.methods[index].request.args;
+            if !args.iter().any(|present| present.name == arg.name) {
+                args.push(arg);
+            }
+        }
+        for tipe in types(&method, pagination) {
+            if !api.types.iter().any(|present| present.name == tipe.name) {
+                api.types.push(tipe);
+            }
+        }
+        if !api.yields.iter().any(|present| present.name == method.response.success) {
+            api.yields.push(page_yield(&method, pagination));
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::Fixture;
+    use crate::spec::{Api, ContainerType, MemberType, SimpleType, TypeRepr};
+    use crate::utils::Name;
+
+    /// Returns the shared specification with a paginated `list-items` method having the given
+    /// `limit` argument type.
+    fn spec(limit_type: &str) -> String {
+        Fixture::new()
+            .with_members("- name: name\n  type: string\n")
+            .with_methods(&format!(
+                "- name: list-items
+  request:
+    method: get
+    path: items
+    args:
+      - name: limit
+        type: {}
+  pagination:
+    style: cursor
+    item: item
+    filter: [name]
+    sort: [name]
+  response:
+    success: item-page
+    error: items-error
+",
+                limit_type
+            ))
+            .to_yaml()
+    }
+
+    #[test]
+    fn test_expand() {
+        let api = Api::from_str(&spec("optional<u32>")).unwrap();
+        assert!(!api.check().iter().any(|diagnostic| diagnostic.is_error()));
+
+        let args: Vec<String> =
+            api.methods[1].request.args.iter().map(|arg| arg.name.kebab_case()).collect();
+        assert_eq!(args, vec!["limit", "cursor", "sort", "filter-field", "filter-value"]);
+        assert_eq!(api.methods[1].request.args[0].tipe, MemberType::Simple(SimpleType::U32));
+
+        let types: Vec<String> = api.types.iter().map(|tipe| tipe.name.kebab_case()).collect();
+        assert_eq!(types, vec!["item", "list-items-sort", "list-items-filter-field"]);
+        match &api.types[1].container {
+            TypeRepr::Enum { values } => {
+                assert_eq!(values, &vec![Name::new("name"), Name::new("name-desc")])
+            }
+            _ => panic!("Sort type is not an enum"),
+        }
+
+        let page = &api.yields[1];
+        assert_eq!(page.name, Name::new("item-page"));
+        assert_eq!(page.args[0].container, Some(ContainerType::Vector));
+        assert_eq!(page.args[1].name, Name::new("next-cursor"));
+
+        let serialized = serde_yaml::to_string(&api).unwrap();
+        let reread = Api::from_str(&serialized).unwrap();
+        assert_eq!(reread.methods[1].request.args.len(), 5);
+        assert_eq!(reread.types.len(), 3);
+        assert_eq!(reread.yields.len(), 2);
+    }
+
+    #[test]
+    fn test_limit_type_mismatch() {
+        let api = Api::from_str(&spec("optional<u8>")).unwrap();
+        let errors: Vec<String> = api
+            .check()
+            .into_iter()
+            .filter(|diagnostic| diagnostic.is_error())
+            .map(|diagnostic| diagnostic.message)
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                "Argument 'limit' of paginated method 'list-items' must be of type 'optional<u32>'"
+            ]
+        );
+    }
+}
@@ -7,7 +7,7 @@ use askama::Template;
 
 use bluefire_build::cargo;
 
-use crate::{spec, utils};
+use crate::{error::Error, imports, spec, utils};
 
 // -------------------------------------------------------------------------------------------------
 
@@ -44,13 +44,21 @@ impl spec::SimpleType {
     /// Formats given type as a `Rust` type.
     fn rust_format(&self) -> &'static str {
         match self {
+            spec::SimpleType::Bool => "bool",
             spec::SimpleType::U8 => "u8",
             spec::SimpleType::U32 => "u32",
+            spec::SimpleType::U64 => "u64",
             spec::SimpleType::I32 => "i32",
+            spec::SimpleType::I64 => "i64",
             spec::SimpleType::F32 => "f32",
             spec::SimpleType::F64 => "f64",
             spec::SimpleType::Str => "String",
             spec::SimpleType::Id => "bluefire_twine::Id",
+            spec::SimpleType::DateTime => "chrono::DateTime<chrono::Utc>",
+            spec::SimpleType::Date => "chrono::NaiveDate",
+            spec::SimpleType::Uuid => "uuid::Uuid",
+            spec::SimpleType::Bytes => "bluefire_twine::Bytes",
+            spec::SimpleType::File => "bluefire_twine::multipart::File",
         }
     }
 }
@@ -67,16 +75,228 @@ impl spec::Member {
         };
 
         if let Some(container) = &self.container {
-            match container {
-                spec::ContainerType::Vector => format!("Vec<{}>", raw_type),
-                spec::ContainerType::Optional => format!("Option<{}>", raw_type),
-            }
+            container.wrap(raw_type, |layer, inner| match layer {
+                spec::Layer::Vector => format!("Vec<{}>", inner),
+                spec::Layer::Optional => format!("Option<{}>", inner),
+                spec::Layer::Map => {
+                    format!("std::collections::HashMap<String, {}>", inner)
+                }
+            })
         } else {
             raw_type
         }
     }
 }
 
+// -------------------------------------------------------------------------------------------------
+// Validation
+//
+// The generated validation code expects variables `value` (a reference to the validated value),
+// `path` (path to the value) and `validation_result` to be in scope.
+
+/// Formats a number from the specification as a literal of the given type.
+fn number_literal(value: f32, simple_type: &spec::SimpleType) -> String {
+    if simple_type.is_float() {
+        format!("{:.4}", value)
+    } else {
+        format!("{}", value)
+    }
+}
+
+/// Generates code adding an error to the validation result. The name of the error is given as an
+/// expression.
+fn add_error_expr(error: &str) -> String {
+    format!(
+        "validation_result.add(bluefire_twine::validation::ValidationError::new(&path, {}));",
+        error
+    )
+}
+
+/// Generates code adding an error with the given name to the validation result.
+fn add_error(name: &utils::Name) -> String {
+    add_error_expr(&format!("\"{}\"", name.snake_case()))
+}
+
+/// Generates code verifying a single check on a simple value.
+fn check_code(check: &spec::Check) -> String {
+    let predicate = match check {
+        spec::Check::Email => "!bluefire_twine::validation::validate_email(value)".to_string(),
+        spec::Check::Url => "!bluefire_twine::validation::validate_url(value)".to_string(),
+        spec::Check::Uuid => "!bluefire_twine::validation::validate_uuid(value)".to_string(),
+        spec::Check::NonEmpty => "value.is_empty()".to_string(),
+    };
+    format!("if {} {{ {} }}\n", predicate, add_error(&check.get_error_name()))
+}
+
+/// Generates code verifying a single condition on a simple value.
+fn condition_code(condition: &spec::Condition, simple_type: &spec::SimpleType) -> String {
+    let predicate = match condition {
+        spec::Condition::Le(value) => format!("*value > {}", number_literal(*value, simple_type)),
+        spec::Condition::Ge(value) => format!("*value < {}", number_literal(*value, simple_type)),
+        spec::Condition::LenEq(len) => format!("value.len() != {}", len),
+        spec::Condition::LenLe(len) => format!("value.len() > {}", len),
+        spec::Condition::LenGe(len) => format!("value.len() < {}", len),
+        spec::Condition::Regex(pattern) => {
+            // The expression is compiled once and kept in a static.
+            return format!(
+                "{{\nstatic PATTERN: bluefire_twine::validation::Pattern = \
+                 bluefire_twine::validation::Pattern::new({:?});\n\
+                 if !PATTERN.is_match(value) {{ {} }}\n}}\n",
+                pattern,
+                add_error(&condition.get_error_name())
+            );
+        }
+        spec::Condition::OneOf(values) => {
+            let literals: Vec<String> = values.iter().map(|value| format!("{:?}", value)).collect();
+            format!("![{}].contains(&value.as_str())", literals.join(", "))
+        }
+        spec::Condition::EqField(name) => format!("*value != self.{}", name.snake_case()),
+        spec::Condition::LeField(name) => format!("*value > self.{}", name.snake_case()),
+        spec::Condition::GeField(name) => format!("*value < self.{}", name.snake_case()),
+    };
+    format!("if {} {{ {} }}\n", predicate, add_error(&condition.get_error_name()))
+}
+
+/// Generates code verifying rules applicable to vectors and maps.
+fn collection_rules_code(validation: &spec::Validation) -> String {
+    let mut code = String::new();
+    for check in validation.checks.iter().filter(|check| check.applies_to_collection()) {
+        code += &check_code(check);
+    }
+    for condition in validation.conditions.iter().filter(|c| c.applies_to_collection()) {
+        code += &condition_code(condition, &spec::SimpleType::U32);
+    }
+    code
+}
+
+/// Generates code verifying rules applicable to values of a simple type.
+fn simple_rules_code(validation: &spec::Validation, simple_type: &spec::SimpleType) -> String {
+    let mut code = String::new();
+    for check in validation.checks.iter().filter(|check| check.applies_to(simple_type)) {
+        code += &check_code(check);
+    }
+    for condition in validation.conditions.iter().filter(|c| c.applies_to(simple_type)) {
+        code += &condition_code(condition, simple_type);
+    }
+    code
+}
+
+/// Generates code validating a value which is not wrapped in any container.
+fn value_code(
+    tipe: &spec::MemberType,
+    validation: Option<&spec::Validation>,
+    types: &Vec<spec::TypeDef>,
+) -> Result<String, Error> {
+    let mut code = String::new();
+    match tipe {
+        spec::MemberType::Simple(simple_type) => {
+            if let Some(validation) = validation {
+                code += &simple_rules_code(validation, simple_type);
+            }
+        }
+        spec::MemberType::Defined(name) => match spec::find_type(name, types)?.container {
+            spec::TypeRepr::Simple { simple_type, validation: type_validation } => {
+                if let Some(validation) = validation {
+                    code += &simple_rules_code(validation, &simple_type);
+                }
+                if type_validation.is_some() {
+                    code += &format!(
+                        "for error in __validate_{}(value).get_errors() {{ {} }}\n",
+                        name.snake_case(),
+                        add_error_expr("error.as_str()")
+                    );
+                }
+            }
+            spec::TypeRepr::Struct { .. } | spec::TypeRepr::Union { .. } => {
+                code += "validation_result.join(\
+                         bluefire_twine::validation::Validate::validate(value).nested(&path));\n";
+            }
+            spec::TypeRepr::External | spec::TypeRepr::Enum { .. } => {}
+        },
+    }
+    Ok(code)
+}
+
+/// Generates code validating a value wrapped in the given containers (starting from the outermost
+/// one).
+fn layers_code(
+    layers: &[spec::Layer],
+    tipe: &spec::MemberType,
+    validation: Option<&spec::Validation>,
+    types: &Vec<spec::TypeDef>,
+) -> Result<String, Error> {
+    let (layer, rest) = match layers.split_first() {
+        Some(split) => split,
+        None => return value_code(tipe, validation, types),
+    };
+
+    match layer {
+        spec::Layer::Optional => {
+            let inner = layers_code(rest, tipe, validation, types)?;
+            if inner.is_empty() {
+                Ok(inner)
+            } else {
+                Ok(format!("if let Some(value) = value {{\n{}}}\n", inner))
+            }
+        }
+        spec::Layer::Vector | spec::Layer::Map => {
+            let mut code = validation.map(collection_rules_code).unwrap_or_default();
+            let each = validation.and_then(|validation| validation.each.as_ref());
+            let inner = layers_code(rest, tipe, each.map(|each| &**each), types)?;
+            if !inner.is_empty() {
+                let iteration = if *layer == spec::Layer::Vector {
+                    "for (key, value) in value.iter().enumerate()"
+                } else {
+                    "for (key, value) in value.iter()"
+                };
+                code += &format!(
+                    "{} {{\nlet path = format!(\"{{}}.{{}}\", path, key);\n{}}}\n",
+                    iteration, inner
+                );
+            }
+            Ok(code)
+        }
+    }
+}
+
+/// Generates code validating the given member. Conditions comparing members are verified only if
+/// both members are not wrapped in containers.
+fn member_code(
+    member: &spec::Member,
+    siblings: &[spec::Member],
+    types: &Vec<spec::TypeDef>,
+) -> Result<String, Error> {
+    let layers = member.container.as_ref().map(|container| container.layers()).unwrap_or_default();
+    let mut code = layers_code(&layers, &member.tipe, member.validation.as_ref(), types)?;
+
+    if let (None, Some(validation)) = (&member.container, &member.validation) {
+        for condition in validation.conditions.iter() {
+            if let Some(field) = condition.field() {
+                let comparable = siblings
+                    .iter()
+                    .any(|sibling| sibling.name == *field && sibling.container.is_none());
+                if comparable {
+                    code += &condition_code(condition, &spec::SimpleType::Str);
+                }
+            }
+        }
+    }
+    Ok(code)
+}
+
+/// Wraps the code validating values into the body of `Validate::validate` method.
+fn validate_body(blocks: Vec<String>) -> String {
+    if blocks.is_empty() {
+        "bluefire_twine::ValidationResult::new()".to_string()
+    } else {
+        format!(
+            "let mut validation_result = bluefire_twine::ValidationResult::new();\n{}\
+             validation_result",
+            blocks.concat()
+        )
+    }
+}
+
 // -------------------------------------------------------------------------------------------------
 
 /// Template for type imports.
@@ -95,11 +315,12 @@ impl RustImportsTemplate {
 #[template(path = "types.rs", escape = "none")]
 struct RustTypesTemplate<'a> {
     pub api: &'a spec::Api,
+    pub generator: GeneratorCallback,
 }
 
 impl<'a> RustTypesTemplate<'a> {
-    pub fn new(api: &'a spec::Api) -> Self {
-        Self { api }
+    pub fn new(api: &'a spec::Api, generator: GeneratorCallback) -> Self {
+        Self { api, generator }
     }
 }
 
@@ -121,11 +342,12 @@ impl<'a> RustPathsTemplate<'a> {
 #[template(path = "yields.rs", escape = "none")]
 struct RustYieldsTemplate<'a> {
     pub api: &'a spec::Api,
+    pub generator: GeneratorCallback,
 }
 
 impl<'a> RustYieldsTemplate<'a> {
-    pub fn new(api: &'a spec::Api) -> Self {
-        Self { api }
+    pub fn new(api: &'a spec::Api, generator: GeneratorCallback) -> Self {
+        Self { api, generator }
     }
 }
 
@@ -156,6 +378,39 @@ impl<'a> RustMethodsTemplate<'a> {
     }
 }
 
+/// Template for generating handler traits and their registration.
+#[derive(Template)]
+#[template(path = "handlers.rs", escape = "none")]
+struct RustHandlersTemplate<'a> {
+    pub api: &'a spec::Api,
+    pub paths: &'a Vec<spec::Path>,
+    pub generator: GeneratorCallback,
+}
+
+impl<'a> RustHandlersTemplate<'a> {
+    pub fn new(
+        api: &'a spec::Api,
+        paths: &'a Vec<spec::Path>,
+        generator: GeneratorCallback,
+    ) -> Self {
+        Self { api, paths, generator }
+    }
+}
+
+/// Template for generating the API client.
+#[derive(Template)]
+#[template(path = "client.rs", escape = "none")]
+struct RustClientTemplate<'a> {
+    pub api: &'a spec::Api,
+    pub generator: GeneratorCallback,
+}
+
+impl<'a> RustClientTemplate<'a> {
+    pub fn new(api: &'a spec::Api, generator: GeneratorCallback) -> Self {
+        Self { api, generator }
+    }
+}
+
 /// Template for generating route (called recursively).
 #[derive(Template)]
 #[template(path = "route.rs", escape = "none")]
@@ -210,17 +465,128 @@ impl GeneratorCallback {
 
     /// Searches for a `TypeDef` with given name.
     pub fn find_type(&self, name: &utils::Name, types: &Vec<spec::TypeDef>) -> spec::TypeDef {
-        spec::find_type(name, types)
+        spec::find_type(name, types).expect(spec::CHECKED_REFERENCE)
     }
 
     /// Searches for a `Yield` with given name.
     pub fn find_yield(&self, name: utils::Name, yields: &Vec<spec::Yield>) -> spec::Yield {
-        spec::find_yield(&name, yields)
+        spec::find_yield(&name, yields).expect(spec::CHECKED_REFERENCE)
     }
 
     /// Searches for a `Reason` with given name.
     pub fn find_reason(&self, name: utils::Name, reasons: &Vec<spec::Reason>) -> spec::Reason {
-        spec::find_reason(&name, reasons)
+        spec::find_reason(&name, reasons).expect(spec::CHECKED_REFERENCE)
+    }
+
+    /// Searches for the pagination of a method returning the yield with given name.
+    pub fn find_pagination(
+        &self,
+        name: utils::Name,
+        methods: &Vec<spec::Method>,
+    ) -> Option<spec::Pagination> {
+        methods
+            .iter()
+            .filter(|method| method.response.success == name)
+            .find_map(|method| method.pagination.clone())
+    }
+
+    /// Formats the type of the items of a paginated method as a `Rust` type.
+    pub fn item_type(&self, item: &spec::MemberType) -> String {
+        match item {
+            spec::MemberType::Simple(simple_type) => simple_type.rust_format().to_string(),
+            spec::MemberType::Defined(name) => name.camel_case(),
+        }
+    }
+
+    /// Returns the methods using the given path.
+    pub fn methods_of(&self, path: &spec::Path, methods: &Vec<spec::Method>) -> Vec<spec::Method> {
+        methods.iter().filter(|method| method.request.path == path.name).cloned().collect()
+    }
+
+    /// Formats the list of HTTP methods as a value of the `Allow` header.
+    pub fn allowed_methods(&self, methods: &Vec<spec::Method>) -> String {
+        let mut names: Vec<&str> = methods.iter().map(|m| m.request.method.to_str()).collect();
+        names.push("OPTIONS");
+        names.join(", ")
+    }
+
+    /// Generates the expression building the route with handlers of the paths used by methods
+    /// taken from variables named after the paths (e.g. `items_handler`). Named routes get labels
+    /// equal to their names.
+    pub fn handled_route(&self, route: &spec::Route, methods: &Vec<spec::Method>) -> String {
+        let mut result = match &route.segment {
+            spec::Segment::Exact(name) => {
+                format!("bluefire_backend::router::Route::exact(\"{}\")", name.snake_case())
+            }
+            spec::Segment::Str(name) => {
+                format!("bluefire_backend::router::Route::param(\"{}\")", name.snake_case())
+            }
+        };
+        if let Some(name) = &route.name {
+            if methods.iter().any(|method| method.request.path == *name) {
+                result += &format!(
+                    ".with_view(Box::new({}RouteHandler::new({}_handler)))",
+                    name.camel_case(),
+                    name.snake_case()
+                );
+            }
+            result += &format!(".with_label(\"{}\")", name.snake_case());
+        }
+        if !route.routes.is_empty() {
+            let routes: Vec<String> =
+                route.routes.iter().map(|route| self.handled_route(route, methods)).collect();
+            result += &format!(".with_routes(vec![{}])", routes.join(", "));
+        }
+        result
+    }
+
+    /// Generates the body of `Validate::validate` method for a structure or request.
+    pub fn validate_members(
+        &self,
+        members: &Vec<spec::Member>,
+        types: &Vec<spec::TypeDef>,
+    ) -> String {
+        let mut blocks = Vec::new();
+        for member in members.iter() {
+            let code = member_code(member, members, types).expect(spec::CHECKED_REFERENCE);
+            if !code.is_empty() {
+                let name = member.name.snake_case();
+                blocks.push(format!(
+                    "{{\nlet value = &self.{};\nlet path = \"{}\";\n{}}}\n",
+                    name, name, code
+                ));
+            }
+        }
+        validate_body(blocks)
+    }
+
+    /// Generates the body of `Validate::validate` method for a union.
+    pub fn validate_variants(
+        &self,
+        name: &utils::Name,
+        members: &Vec<spec::Member>,
+        types: &Vec<spec::TypeDef>,
+    ) -> String {
+        let mut arms = String::new();
+        let mut empty = true;
+        for member in members.iter() {
+            let variant = format!("{}::{}", name.camel_case(), member.name.camel_case());
+            let code = member_code(member, &[], types).expect(spec::CHECKED_REFERENCE);
+            if code.is_empty() {
+                arms += &format!("{}(..) => {{}}\n", variant);
+            } else {
+                empty = false;
+                let path = member.name.snake_case();
+                arms +=
+                    &format!("{}(value) => {{\nlet path = \"{}\";\n{}}}\n", variant, path, code);
+            }
+        }
+
+        if empty {
+            validate_body(Vec::new())
+        } else {
+            validate_body(vec![format!("match self {{\n{}}}\n", arms)])
+        }
     }
 }
 
@@ -235,15 +601,18 @@ impl RustGenerator {
         Self
     }
 
-    /// Generate API.
-    pub fn generate_api(self, api: &spec::Api) -> String {
+    /// Generate API. Fails if checking the specification finds any errors.
+    pub fn generate_api(self, api: &spec::Api) -> Result<String, Error> {
+        api.ensure_checked()?;
         let paths = spec::routes_to_paths(None, &api.routes);
         let imports_template = RustImportsTemplate::new();
-        let types_template = RustTypesTemplate::new(&api);
+        let types_template = RustTypesTemplate::new(&api, GeneratorCallback::new());
         let paths_template = RustPathsTemplate::new(&paths);
-        let yields_template = RustYieldsTemplate::new(&api);
+        let yields_template = RustYieldsTemplate::new(&api, GeneratorCallback::new());
         let reasons_template = RustReasonsTemplate::new(&api);
         let methods_template = RustMethodsTemplate::new(&api, GeneratorCallback::new());
+        let handlers_template = RustHandlersTemplate::new(&api, &paths, GeneratorCallback::new());
+        let client_template = RustClientTemplate::new(&api, GeneratorCallback::new());
 
         let buffer = [
             imports_template.render().expect("Render imports template"),
@@ -252,9 +621,11 @@ impl RustGenerator {
             yields_template.render().expect("Render yields template"),
             reasons_template.render().expect("Render reasons template"),
             methods_template.render().expect("Render methods template"),
+            handlers_template.render().expect("Render handlers template"),
+            client_template.render().expect("Render client template"),
         ];
 
-        buffer.concat()
+        Ok(buffer.concat())
     }
 
     /// Generate path definitions.
@@ -279,58 +650,259 @@ impl RustGenerator {
     }
 
     /// Generate API from given input file and save to the given output file.
-    pub fn generate_api_file(self, input: &str, output: &str) {
-        let content = cargo::read_manifest_path(input);
-        let api = match spec::Api::from_str(&content) {
-            Ok(api) => api,
-            Err(err) => panic!("Parse file ({}): {}", input, err),
-        };
-        let result = self.generate_api(&api);
-        Self::write_output_file(output, &result);
+    pub fn generate_api_file(self, input: &str, output: &str) -> Result<(), Error> {
+        let input_path = cargo::as_absolute_path(input);
+        let api = spec::Api::read_checked(&input_path)?;
+        let result = self.generate_api(&api)?;
+        Self::write_output_file(output, &result)?;
         println!("cargo:rerun-if-changed={}", input);
+        for path in imports::imported_files(&input_path)? {
+            println!("cargo:rerun-if-changed={}", path.display());
+        }
+        Ok(())
     }
 
     /// Generate paths from given input file and save to the given output file.
-    pub fn generate_paths_file(self, input: &str, output: &str) {
-        let content = cargo::read_manifest_path(input);
-        let paths = match spec::Routes::from_str(&content) {
-            Ok(api) => api,
-            Err(err) => panic!("Parse file ({}): {}", input, err),
-        };
+    pub fn generate_paths_file(self, input: &str, output: &str) -> Result<(), Error> {
+        let paths = spec::Routes::read(cargo::as_absolute_path(input))?;
         let result = self.generate_paths(&paths);
-        Self::write_output_file(output, &result);
+        Self::write_output_file(output, &result)?;
         println!("cargo:rerun-if-changed={}", input);
+        Ok(())
     }
 
     /// Generate routes from given input file and save to the given output file.
-    pub fn generate_routes_file(self, input: &str, output: &str) {
-        let content = cargo::read_manifest_path(input);
-        let routes = match spec::Routes::from_str(&content) {
-            Ok(api) => api,
-            Err(err) => panic!("Parse file ({}): {}", input, err),
-        };
+    pub fn generate_routes_file(self, input: &str, output: &str) -> Result<(), Error> {
+        let routes = spec::Routes::read(cargo::as_absolute_path(input))?;
         let result = self.generate_routes(&routes);
-        Self::write_output_file(output, &result);
+        Self::write_output_file(output, &result)?;
         println!("cargo:rerun-if-changed={}", input);
+        Ok(())
     }
 }
 
 impl RustGenerator {
     /// Writes to a file in output directory.
-    pub fn write_output_file(output: &str, content: &str) {
+    pub fn write_output_file(output: &str, content: &str) -> Result<(), Error> {
         #[cfg(not(feature = "fmt"))]
         {
-            cargo::write_out_file(output, content);
+            cargo::write_out_file(output, content)?;
         }
 
         #[cfg(feature = "fmt")]
         {
-            let output_path = cargo::write_out_file(output, content);
+            let output_path = cargo::write_out_file(output, content)?;
 
             let out = &mut &mut std::io::stdout();
             let config = rustfmt_nightly::Config::default();
             let mut session = rustfmt_nightly::Session::new(config, Some(out));
             session.format(rustfmt_nightly::Input::File(output_path)).expect("Format with rustfmt");
         }
+
+        Ok(())
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use askama::Template;
+
+    use super::{member_code, GeneratorCallback, RustGenerator, RustTypesTemplate};
+    use crate::error::Error;
+    use crate::fixtures::Fixture;
+    use crate::spec::{
+        Api, Check, Condition, ContainerType, Member, MemberType, SimpleType, Validation,
+    };
+    use crate::utils::Name;
+
+    fn validation(checks: Vec<Check>, conditions: Vec<Condition>) -> Validation {
+        Validation { checks, conditions, each: None }
+    }
+
+    fn short_code(member: &Member, siblings: &[Member]) -> String {
+        member_code(member, siblings, &Vec::new()).unwrap().replace(
+            "validation_result.add(bluefire_twine::validation::ValidationError::new",
+            "add",
+        )
+    }
+
+    #[test]
+    fn test_nested_validation_code() {
+        let mut tags_validation = validation(Vec::new(), vec![Condition::LenLe(3)]);
+        tags_validation.each = Some(Box::new(validation(vec![Check::NonEmpty], Vec::new())));
+        let member = Member {
+            name: Name::new("tags"),
+            tipe: MemberType::Simple(SimpleType::Str),
+            container: Some(ContainerType::Nested(vec![
+                ContainerType::Optional,
+                ContainerType::Vector,
+            ])),
+            location: None,
+            description: None,
+            example: None,
+            validation: Some(tags_validation),
+        };
+        assert_eq!(
+            short_code(&member, &[]),
+            "if let Some(value) = value {\n\
+             if value.len() > 3 { add(&path, \"too_long\")); }\n\
+             for (key, value) in value.iter().enumerate() {\n\
+             let path = format!(\"{}.{}\", path, key);\n\
+             if value.is_empty() { add(&path, \"empty\")); }\n\
+             }\n\
+             }\n"
+        );
+    }
+
+    #[test]
+    fn test_cross_field_validation_code() {
+        let password = Member {
+            name: Name::new("password"),
+            tipe: MemberType::Simple(SimpleType::Str),
+            container: None,
+            location: None,
+            description: None,
+            example: None,
+            validation: None,
+        };
+        let confirmation = Member {
+            name: Name::new("password-confirmation"),
+            tipe: MemberType::Simple(SimpleType::Str),
+            container: None,
+            location: None,
+            description: None,
+            example: None,
+            validation: Some(validation(
+                Vec::new(),
+                vec![Condition::EqField(Name::new("password"))],
+            )),
+        };
+        let siblings = vec![password, confirmation.clone()];
+        assert_eq!(
+            short_code(&confirmation, &siblings),
+            "if *value != self.password { add(&path, \"not_equal\")); }\n"
+        );
+        assert_eq!(short_code(&confirmation, &[]), "");
+    }
+
+    #[test]
+    fn test_regex_validation_code() {
+        let member = Member {
+            name: Name::new("code"),
+            tipe: MemberType::Simple(SimpleType::Str),
+            container: None,
+            location: None,
+            description: None,
+            example: None,
+            validation: Some(validation(Vec::new(), vec![Condition::Regex("[A-Z]+".into())])),
+        };
+        assert_eq!(
+            short_code(&member, &[]),
+            "{\nstatic PATTERN: bluefire_twine::validation::Pattern = \
+             bluefire_twine::validation::Pattern::new(\"[A-Z]+\");\n\
+             if !PATTERN.is_match(value) { add(&path, \"wrong_format\")); }\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_repeated_error_names() {
+        let api = Fixture::new()
+            .with_types(
+                "- name: code
+  container:
+    repr: simple
+    type: string
+    validation:
+      conditions:
+        - regex: '^[A-Z]+$'
+        - regex: '^.{2,8}$'
+        - len_le: 10
+        - len_le: 12
+",
+            )
+            .to_api();
+        let code = RustTypesTemplate::new(&api, GeneratorCallback::new()).render().unwrap();
+        assert_eq!(code.matches("WrongFormat,").count(), 1);
+        assert_eq!(code.matches("TooLong,").count(), 1);
+        assert_eq!(code.matches("CodeValidationResult::WrongFormat =>").count(), 1);
+        assert_eq!(code.matches("CodeValidationResult::TooLong =>").count(), 1);
+    }
+
+    #[test]
+    fn test_handled_route() {
+        let api = Api::from_str(
+            "---
+routes:
+  - name: items
+    exact: items
+    routes:
+      - name: item
+        string: item-id
+yields:
+  - name: empty
+    code: 200-ok
+reasons:
+  - name: error
+    variant: error
+    cases:
+      - name: internal
+        code: 500-internal-server-error
+methods:
+  - name: get-item
+    request:
+      method: get
+      path: item
+    response:
+      success: empty
+      error: error
+  - name: delete-item
+    request:
+      method: delete
+      path: item
+    response:
+      success: empty
+      error: error
+",
+        )
+        .unwrap();
+        let generator = GeneratorCallback::new();
+        assert_eq!(
+            generator.handled_route(&api.routes[0], &api.methods),
+            "bluefire_backend::router::Route::exact(\"items\").with_label(\"items\")\
+             .with_routes(vec![bluefire_backend::router::Route::param(\"item_id\")\
+             .with_view(Box::new(ItemRouteHandler::new(item_handler))).with_label(\"item\")])"
+        );
+        assert_eq!(generator.allowed_methods(&api.methods), "GET, DELETE, OPTIONS");
+    }
+
+    #[test]
+    fn test_generate_unchecked_api() {
+        let api = Api::from_str(
+            "---
+routes:
+  - name: items
+    exact: items
+methods:
+  - name: get-items
+    request:
+      method: get
+      path: items
+    response:
+      success: missing-yield
+      error: missing-reason
+",
+        )
+        .unwrap();
+        match RustGenerator::new().generate_api(&api) {
+            Err(Error::Check { diagnostics, .. }) => {
+                let messages: Vec<_> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+                assert!(messages.contains(&"Yield 'missing-yield' is not defined"));
+                assert!(messages.contains(&"Reason 'missing-reason' is not defined"));
+            }
+            result => panic!("Expected check error, got {:?}", result),
+        }
     }
 }
@@ -3,10 +3,12 @@
 
 //! Specification of the API file format.
 
+use serde::de::{Error as _, IntoDeserializer};
 use serde_derive::{Deserialize, Serialize};
 use serde_yaml;
 
-use crate::utils;
+use crate::error::{Error, ReadError};
+use crate::{imports, pagination, utils};
 
 // -------------------------------------------------------------------------------------------------
 // Common definitions
@@ -75,15 +77,27 @@ pub enum HttpResponse {
 // Validation
 
 /// Represents as predefined check if a value is correct.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum Check {
     /// A string value should be a valid e-mail.
     #[serde(rename = "email")]
     Email,
+
+    /// A string value should be a valid absolute URL.
+    #[serde(rename = "url")]
+    Url,
+
+    /// A string value should be a UUID in hyphenated format.
+    #[serde(rename = "uuid")]
+    Uuid,
+
+    /// A string, vector or map should not be empty.
+    #[serde(rename = "non_empty")]
+    NonEmpty,
 }
 
 /// Represents a parametrized condition to validate a value.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum Condition {
     /// The value is a number and must be lesser or equal to this one.
     #[serde(rename = "le")]
@@ -104,10 +118,33 @@ pub enum Condition {
     /// The value is a string or vector and its length must be greater or equal to this one.
     #[serde(rename = "len_ge")]
     LenGe(u32),
+
+    /// The value is a string and must match this regular expression as a whole.
+    #[serde(rename = "regex")]
+    Regex(String),
+
+    /// The value is a string and must be equal to one of these values.
+    #[serde(rename = "one_of")]
+    OneOf(Vec<String>),
+
+    /// The value must be equal to the value of the given member of the same structure.
+    #[serde(rename = "eq_field")]
+    EqField(utils::Name),
+
+    /// The value must be lesser or equal to the value of the given member of the same structure.
+    #[serde(rename = "le_field")]
+    LeField(utils::Name),
+
+    /// The value must be greater or equal to the value of the given member of the same structure.
+    #[serde(rename = "ge_field")]
+    GeField(utils::Name),
 }
 
 /// Represents a list of checks and conditions to verify if given value is valid.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+///
+/// For members being vectors or maps the length rules apply to the collection while `each` applies
+/// to its elements. Optional members are validated only if present.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Validation {
     /// A list of `Check`s.
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
@@ -116,14 +153,25 @@ pub struct Validation {
     /// A list of `Condition`s.
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub conditions: Vec<Condition>,
+
+    /// Validation of elements of a vector or map.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub each: Option<Box<Validation>>,
 }
 
 // -------------------------------------------------------------------------------------------------
 // Types
 
 /// Represents a simple type.
+///
+/// Code generated for `datetime`, `date` and `uuid` types requires `chrono` and `uuid` crates with
+/// `serde` feature enabled.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum SimpleType {
+    /// A boolean value.
+    #[serde(rename = "bool")]
+    Bool,
+
     /// A one-byte unsigned integer.
     #[serde(rename = "u8")]
     U8,
@@ -132,10 +180,18 @@ pub enum SimpleType {
     #[serde(rename = "u32")]
     U32,
 
+    /// An eight-byte unsigned integer.
+    #[serde(rename = "u64")]
+    U64,
+
     /// A four-byte signed integer.
     #[serde(rename = "i32")]
     I32,
 
+    /// An eight-byte signed integer.
+    #[serde(rename = "i64")]
+    I64,
+
     /// A four-byte floating-point number.
     #[serde(rename = "f32")]
     F32,
@@ -151,18 +207,59 @@ pub enum SimpleType {
     /// An object ID.
     #[serde(rename = "id")]
     Id,
+
+    /// A point in time in UTC, serialized in RFC 3339 format.
+    #[serde(rename = "datetime")]
+    DateTime,
+
+    /// A calendar date, serialized in `YYYY-MM-DD` format.
+    #[serde(rename = "date")]
+    Date,
+
+    /// A UUID, serialized in hyphenated format.
+    #[serde(rename = "uuid")]
+    Uuid,
+
+    /// An array of bytes, serialized as a base64 string.
+    #[serde(rename = "bytes")]
+    Bytes,
+
+    /// A file uploaded in a `multipart/form-data` request.
+    #[serde(rename = "file")]
+    File,
 }
 
 /// Represents a alternative way of how to modify the members type.
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+///
+/// Containers are written as `vector`, `optional` or `map` (with string keys). Nested containers
+/// are written from the outermost one, e.g. `optional<vector>`.
+#[derive(Clone, Debug, PartialEq)]
 pub enum ContainerType {
     /// Store as a vector.
-    #[serde(rename = "vector")]
     Vector,
 
     /// Serialize/deserialize optionally.
-    #[serde(rename = "optional")]
     Optional,
+
+    /// Store as a map with string keys.
+    Map,
+
+    /// A list of nested containers starting from the outermost one.
+    Nested(Vec<ContainerType>),
+}
+
+/// A single container layer. Unlike `ContainerType` it cannot be nested, so code going through the
+/// layers of a container does not have to handle nesting.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Layer {
+    /// Store as a vector.
+    Vector,
+
+    /// Serialize/deserialize optionally.
+    Optional,
+
+    /// Store as a map with string keys.
+    Map,
 }
 
 /// Represents and argument of request or return value of response.
@@ -177,7 +274,10 @@ pub enum MemberType {
 }
 
 /// Represents and argument of request or return value of response.
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+///
+/// The type may contain containers, e.g. `optional<vector<string>>`. They are equivalent to
+/// containers provided in the `container` field.
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub struct Member {
     /// Member name.
     pub name: utils::Name,
@@ -189,6 +289,22 @@ pub struct Member {
     /// Members container.
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub container: Option<ContainerType>,
+
+    /// Location of the argument in the request. Used only for request arguments.
+    #[serde(rename = "in", skip_serializing_if = "Option::is_none", default)]
+    pub location: Option<ArgLocation>,
+
+    /// Human-readable description of the member.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub description: Option<String>,
+
+    /// Example value used instead of a generated one by the mock server.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub example: Option<serde_json::Value>,
+
+    /// Validation of the members value.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub validation: Option<Validation>,
 }
 
 /// Defines how a type should be represented in the API protocol (JSON).
@@ -241,6 +357,14 @@ pub struct TypeDef {
 
     /// The way the type should be represented.
     pub container: TypeRepr,
+
+    /// Human-readable description of the type.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub description: Option<String>,
+
+    /// Example value of the type used when a member of this type has no example.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub example: Option<serde_json::Value>,
 }
 
 // -------------------------------------------------------------------------------------------------
@@ -255,9 +379,22 @@ pub struct Yield {
     /// HTTP code used in this response.
     pub code: HttpResponse,
 
+    /// If present, the response body is raw binary data of this (default) content type instead of
+    /// JSON and the yield does not have any arguments.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub content_type: Option<String>,
+
     /// Values sent in response.
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub args: Vec<Member>,
+
+    /// Human-readable description of the yield.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub description: Option<String>,
+
+    /// Example content of the response used instead of a generated one.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub example: Option<serde_json::Value>,
 }
 
 // -------------------------------------------------------------------------------------------------
@@ -287,6 +424,10 @@ pub struct Reason {
 
     /// List of possible cases.
     pub cases: Vec<Case>,
+
+    /// Human-readable description of the reason.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub description: Option<String>,
 }
 
 /// Represents a possible case of failure reason.
@@ -301,6 +442,14 @@ pub struct Case {
     /// Values sent in response.
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub args: Vec<Member>,
+
+    /// Human-readable description of the case.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub description: Option<String>,
+
+    /// Example content of the response (without the `reason`) used instead of a generated one.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub example: Option<serde_json::Value>,
 }
 
 // -------------------------------------------------------------------------------------------------
@@ -347,16 +496,69 @@ pub struct Path {
 // -------------------------------------------------------------------------------------------------
 // Methods
 
+/// Represents a part of the request an argument is sent in.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ArgLocation {
+    /// Query part of the URL.
+    #[serde(rename = "query")]
+    Query,
+
+    /// JSON body.
+    #[serde(rename = "body")]
+    Body,
+
+    /// HTTP header named as the argument in kebab-case.
+    #[serde(rename = "header")]
+    Header,
+
+    /// Cookie named as the argument in snake-case.
+    #[serde(rename = "cookie")]
+    Cookie,
+}
+
+/// Represents the format of the request body.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum BodyFormat {
+    /// JSON object.
+    #[serde(rename = "json")]
+    Json,
+
+    /// `application/x-www-form-urlencoded` form.
+    #[serde(rename = "form")]
+    Form,
+
+    /// `multipart/form-data` form which may contain files.
+    #[serde(rename = "multipart")]
+    Multipart,
+}
+
+impl Default for BodyFormat {
+    fn default() -> Self {
+        BodyFormat::Json
+    }
+}
+
 /// Represents a request in an API call.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Request {
     /// HTTP method.
     pub method: HttpMethod,
 
+    /// Format of the body.
+    #[serde(skip_serializing_if = "BodyFormat::is_json", default)]
+    pub format: BodyFormat,
+
+    /// Maximal size of a `multipart/form-data` body in bytes. If not present, the default limit of
+    /// `bluefire_backend::multipart` is used.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub limit: Option<usize>,
+
     /// Path part of the URL.
     pub path: utils::Name,
 
-    /// Arguments of the call (serialized either to JSON or query part of the URL).
+    /// Arguments of the call. By default they are serialized to the query part of the URL for
+    /// `GET` requests and to JSON body otherwise.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub args: Vec<Member>,
 }
 
@@ -373,6 +575,55 @@ pub struct Response {
     pub error: utils::Name,
 }
 
+/// Represents the way of selecting a page of a list.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum PaginationStyle {
+    /// Pages are selected with an opaque cursor returned with the previous page.
+    #[serde(rename = "cursor")]
+    Cursor,
+
+    /// Pages are selected with the number of items to skip.
+    #[serde(rename = "offset")]
+    Offset,
+}
+
+/// Represents pagination of a method returning a list of items.
+///
+/// The arguments selecting the page, filtering and sorting the items as well as the success yield
+/// are generated from it when the specification is read. See the `pagination` module.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Pagination {
+    /// The way of selecting a page.
+    pub style: PaginationStyle,
+
+    /// Type of the items.
+    pub item: MemberType,
+
+    /// Number of items in a page if the limit is not given in the request.
+    #[serde(default = "default_page_limit")]
+    pub default_limit: u32,
+
+    /// Maximal number of items in a page.
+    #[serde(default = "max_page_limit")]
+    pub max_limit: u32,
+
+    /// Names of the fields of the items the list can be filtered by.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub filter: Vec<utils::Name>,
+
+    /// Names of the fields of the items the list can be sorted by.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub sort: Vec<utils::Name>,
+}
+
+fn default_page_limit() -> u32 {
+    20
+}
+
+fn max_page_limit() -> u32 {
+    100
+}
+
 /// Represents an API call method.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Method {
@@ -382,8 +633,20 @@ pub struct Method {
     /// The definition of a request.
     pub request: Request,
 
+    /// Pagination of the returned list.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub pagination: Option<Pagination>,
+
     /// The definition of a response.
     pub response: Response,
+
+    /// Human-readable description of the method.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub description: Option<String>,
+
+    /// Example values of the request arguments.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub example: Option<serde_json::Value>,
 }
 
 // -------------------------------------------------------------------------------------------------
@@ -403,28 +666,183 @@ pub struct Routes {
     pub routes: Vec<Route>,
 }
 
+/// Represents an import of definitions from another specification file.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Import {
+    /// Path to the imported file, relative to the importing file.
+    pub path: String,
+
+    /// Prefix added to the names of all imported types, yields and reasons. If not present, the
+    /// names are imported as they are.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub namespace: Option<utils::Name>,
+}
+
 /// Represents a definition of an API.
+///
+/// All the sections are optional so that files containing only shared definitions can be
+/// imported by other specifications.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Api {
+    /// List of files to import types, yields and reasons from.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub imports: Vec<Import>,
+
     /// List of definitions of data structures used in the API.
+    #[serde(default)]
     pub types: Vec<TypeDef>,
 
     /// A list of tree-like structures representing API routes.
+    #[serde(default)]
     pub routes: Vec<Route>,
 
     /// A list of possible success results.
+    #[serde(default)]
     pub yields: Vec<Yield>,
 
     /// A list of possible failure reasons.
+    #[serde(default)]
     pub reasons: Vec<Reason>,
 
     /// List of possible API calls (request and corresponding paths and responses).
+    #[serde(default)]
     pub methods: Vec<Method>,
 }
 
 // -------------------------------------------------------------------------------------------------
 // Helper implementations
 
+impl HttpResponse {
+    /// Returns the numeric status code.
+    pub fn code(&self) -> u16 {
+        match self {
+            HttpResponse::Ok => 200,
+            HttpResponse::Created => 201,
+            HttpResponse::BadRequest => 400,
+            HttpResponse::Unauthorized => 401,
+            HttpResponse::Forbidden => 403,
+            HttpResponse::NotFound => 404,
+            HttpResponse::Conflict => 409,
+            HttpResponse::InternalServerError => 500,
+        }
+    }
+}
+
+impl Request {
+    /// Checks if the request has a JSON body.
+    pub fn has_body(&self) -> bool {
+        self.method != HttpMethod::Get
+    }
+
+    /// Returns the location of the given argument, taking into account the default for the method.
+    pub fn location_of(&self, arg: &Member) -> ArgLocation {
+        match &arg.location {
+            Some(location) => location.clone(),
+            None if self.has_body() => ArgLocation::Body,
+            None => ArgLocation::Query,
+        }
+    }
+
+    /// Returns the arguments sent in the given location.
+    pub fn args_in(&self, location: &ArgLocation) -> Vec<Member> {
+        self.args.iter().filter(|arg| self.location_of(arg) == *location).cloned().collect()
+    }
+
+    /// Returns the arguments sent in the query part of the URL.
+    pub fn query_args(&self) -> Vec<Member> {
+        self.args_in(&ArgLocation::Query)
+    }
+
+    /// Returns the arguments sent in the body.
+    pub fn body_args(&self) -> Vec<Member> {
+        self.args_in(&ArgLocation::Body)
+    }
+
+    /// Returns the arguments sent in the body which are not files.
+    pub fn text_body_args(&self) -> Vec<Member> {
+        self.body_args().into_iter().filter(|arg| !arg.is_file()).collect()
+    }
+
+    /// Returns the file arguments.
+    pub fn file_args(&self) -> Vec<Member> {
+        self.body_args().into_iter().filter(|arg| arg.is_file()).collect()
+    }
+
+    /// Checks if the request has a JSON body.
+    pub fn is_json(&self) -> bool {
+        self.has_body() && self.format == BodyFormat::Json
+    }
+
+    /// Checks if the request has an `application/x-www-form-urlencoded` body.
+    pub fn is_form(&self) -> bool {
+        self.has_body() && self.format == BodyFormat::Form
+    }
+
+    /// Checks if the request has a `multipart/form-data` body.
+    pub fn is_multipart(&self) -> bool {
+        self.has_body() && self.format == BodyFormat::Multipart
+    }
+
+    /// Returns the arguments sent as headers.
+    pub fn header_args(&self) -> Vec<Member> {
+        self.args_in(&ArgLocation::Header)
+    }
+
+    /// Returns the arguments sent as cookies.
+    pub fn cookie_args(&self) -> Vec<Member> {
+        self.args_in(&ArgLocation::Cookie)
+    }
+}
+
+impl BodyFormat {
+    /// Checks if the format is JSON.
+    pub fn is_json(&self) -> bool {
+        *self == BodyFormat::Json
+    }
+
+    /// Returns a string representation of the enum.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BodyFormat::Json => "json",
+            BodyFormat::Form => "form",
+            BodyFormat::Multipart => "multipart",
+        }
+    }
+
+    /// Returns the MIME type of the body.
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            BodyFormat::Json => "application/json",
+            BodyFormat::Form => "application/x-www-form-urlencoded",
+            BodyFormat::Multipart => "multipart/form-data",
+        }
+    }
+}
+
+impl Yield {
+    /// Checks if the response body is raw binary data.
+    pub fn is_binary(&self) -> bool {
+        self.content_type.is_some()
+    }
+
+    /// Returns the default content type of a binary yield or an empty string.
+    pub fn default_content_type(&self) -> &str {
+        self.content_type.as_ref().map(String::as_str).unwrap_or_default()
+    }
+}
+
+impl ArgLocation {
+    /// Returns a string representation of the enum.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ArgLocation::Query => "query",
+            ArgLocation::Body => "body",
+            ArgLocation::Header => "header",
+            ArgLocation::Cookie => "cookie",
+        }
+    }
+}
+
 impl HttpMethod {
     /// Returns a name of the HTTP method.
     pub fn to_str(&self) -> &'static str {
@@ -443,6 +861,57 @@ impl Check {
     pub fn get_error_name(&self) -> utils::Name {
         match self {
             Check::Email => utils::Name::from_parts(vec!["email"]),
+            Check::Url => utils::Name::from_parts(vec!["url"]),
+            Check::Uuid => utils::Name::from_parts(vec!["uuid"]),
+            Check::NonEmpty => utils::Name::from_parts(vec!["empty"]),
+        }
+    }
+}
+
+impl Check {
+    /// Checks if the check can be applied to values of the given type.
+    pub fn applies_to(&self, simple_type: &SimpleType) -> bool {
+        match self {
+            Check::Email | Check::Url | Check::Uuid | Check::NonEmpty => {
+                *simple_type == SimpleType::Str
+            }
+        }
+    }
+
+    /// Checks if the check can be applied to vectors and maps.
+    pub fn applies_to_collection(&self) -> bool {
+        *self == Check::NonEmpty
+    }
+}
+
+impl Condition {
+    /// Checks if the condition can be applied to values of the given type.
+    pub fn applies_to(&self, simple_type: &SimpleType) -> bool {
+        match self {
+            Condition::Le(..) | Condition::Ge(..) => simple_type.is_number(),
+            Condition::LenEq(..) | Condition::LenLe(..) | Condition::LenGe(..) => {
+                *simple_type == SimpleType::Str || *simple_type == SimpleType::Bytes
+            }
+            Condition::Regex(..) | Condition::OneOf(..) => *simple_type == SimpleType::Str,
+            Condition::EqField(..) | Condition::LeField(..) | Condition::GeField(..) => false,
+        }
+    }
+
+    /// Checks if the condition can be applied to vectors and maps.
+    pub fn applies_to_collection(&self) -> bool {
+        match self {
+            Condition::LenEq(..) | Condition::LenLe(..) | Condition::LenGe(..) => true,
+            _ => false,
+        }
+    }
+
+    /// Returns the name of the other member if the condition compares two members of a structure.
+    pub fn field(&self) -> Option<&utils::Name> {
+        match self {
+            Condition::EqField(name) | Condition::LeField(name) | Condition::GeField(name) => {
+                Some(name)
+            }
+            _ => None,
         }
     }
 }
@@ -456,10 +925,211 @@ impl Condition {
             Condition::LenEq(..) => utils::Name::from_parts(vec!["wrong", "length"]),
             Condition::LenLe(..) => utils::Name::from_parts(vec!["too", "long"]),
             Condition::LenGe(..) => utils::Name::from_parts(vec!["too", "short"]),
+            Condition::Regex(..) => utils::Name::from_parts(vec!["wrong", "format"]),
+            Condition::OneOf(..) => utils::Name::from_parts(vec!["not", "allowed"]),
+            Condition::EqField(..) => utils::Name::from_parts(vec!["not", "equal"]),
+            Condition::LeField(..) => utils::Name::from_parts(vec!["too", "big"]),
+            Condition::GeField(..) => utils::Name::from_parts(vec!["too", "small"]),
+        }
+    }
+}
+
+impl Validation {
+    /// Returns names of errors of the checks and conditions applicable to values of the given type.
+    /// Each name is returned once even if many rules report it, e.g. two `regex` conditions.
+    pub fn error_names(&self, simple_type: &SimpleType) -> Vec<utils::Name> {
+        let checks = self.checks.iter().filter(|check| check.applies_to(simple_type));
+        let conditions = self.conditions.iter().filter(|c| c.applies_to(simple_type));
+        let names =
+            checks.map(Check::get_error_name).chain(conditions.map(Condition::get_error_name));
+        let mut result: Vec<utils::Name> = Vec::new();
+        for name in names {
+            if !result.contains(&name) {
+                result.push(name);
+            }
+        }
+        result
+    }
+}
+
+impl SimpleType {
+    /// Checks if the type is an integer or a floating-point number.
+    pub fn is_number(&self) -> bool {
+        match self {
+            SimpleType::U8
+            | SimpleType::U32
+            | SimpleType::U64
+            | SimpleType::I32
+            | SimpleType::I64
+            | SimpleType::F32
+            | SimpleType::F64 => true,
+            _ => false,
+        }
+    }
+
+    /// Checks if the type is a floating-point number.
+    pub fn is_float(&self) -> bool {
+        *self == SimpleType::F32 || *self == SimpleType::F64
+    }
+}
+
+impl ContainerType {
+    /// Returns the nested containers starting from the outermost one.
+    pub fn layers(&self) -> Vec<Layer> {
+        match self {
+            ContainerType::Vector => vec![Layer::Vector],
+            ContainerType::Optional => vec![Layer::Optional],
+            ContainerType::Map => vec![Layer::Map],
+            ContainerType::Nested(layers) => {
+                layers.iter().flat_map(|layer| layer.layers()).collect()
+            }
+        }
+    }
+
+    /// Checks if the outermost container is `Optional`.
+    pub fn is_optional(&self) -> bool {
+        self.layers().first() == Some(&Layer::Optional)
+    }
+
+    /// Wraps the representation of a type into the representations of containers starting from the
+    /// innermost one.
+    pub fn wrap<T, F>(&self, inner: T, wrap_layer: F) -> T
+    where
+        F: Fn(&Layer, T) -> T,
+    {
+        self.layers().iter().rev().fold(inner, |inner, layer| wrap_layer(layer, inner))
+    }
+
+    /// Constructs a container from a list of nested containers starting from the outermost one.
+    pub fn from_layers(mut layers: Vec<Layer>) -> Option<ContainerType> {
+        match layers.len() {
+            0 => None,
+            1 => layers.pop().map(ContainerType::from),
+            _ => Some(ContainerType::Nested(layers.into_iter().map(ContainerType::from).collect())),
+        }
+    }
+}
+
+impl Layer {
+    fn name(&self) -> &'static str {
+        match self {
+            Layer::Vector => "vector",
+            Layer::Optional => "optional",
+            Layer::Map => "map",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Layer> {
+        match name {
+            "vector" => Some(Layer::Vector),
+            "optional" => Some(Layer::Optional),
+            "map" => Some(Layer::Map),
+            _ => None,
+        }
+    }
+}
+
+impl From<Layer> for ContainerType {
+    fn from(layer: Layer) -> Self {
+        match layer {
+            Layer::Vector => ContainerType::Vector,
+            Layer::Optional => ContainerType::Optional,
+            Layer::Map => ContainerType::Map,
         }
     }
 }
 
+impl serde::Serialize for ContainerType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let layers: Vec<&'static str> = self.layers().iter().map(|layer| layer.name()).collect();
+        let expression = layers.join("<") + &">".repeat(layers.len().saturating_sub(1));
+        serializer.serialize_str(&expression)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for ContainerType {
+    fn deserialize<D>(deserializer: D) -> Result<ContainerType, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let expression: String = serde::Deserialize::deserialize(deserializer)?;
+        let (mut layers, last) = parse_type_expression(&expression).map_err(D::Error::custom)?;
+        layers.push(
+            Layer::from_name(&last)
+                .ok_or_else(|| D::Error::custom(format!("Unknown container '{}'", last)))?,
+        );
+        Ok(ContainerType::from_layers(layers).expect("At least one container"))
+    }
+}
+
+impl Member {
+    /// Checks if the member may be omitted.
+    pub fn is_optional(&self) -> bool {
+        self.container.as_ref().map(|container| container.is_optional()).unwrap_or(false)
+    }
+
+    /// Checks if the member is a file.
+    pub fn is_file(&self) -> bool {
+        self.tipe == MemberType::Simple(SimpleType::File)
+    }
+
+    /// Formats the type of the member as in the specification, e.g. `optional<vector<item>>`.
+    pub fn type_expression(&self) -> String {
+        let base = serde_yaml::to_value(&self.tipe)
+            .ok()
+            .and_then(|value| value.as_str().map(|name| name.to_string()))
+            .unwrap_or_default();
+        let layers =
+            self.container.as_ref().map(|container| container.layers()).unwrap_or_default();
+        layers.iter().rev().fold(base, |inner, layer| format!("{}<{}>", layer.name(), inner))
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Member {
+    fn deserialize<D>(deserializer: D) -> Result<Member, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        struct RawMember {
+            name: utils::Name,
+            #[serde(rename = "type")]
+            tipe: String,
+            #[serde(default)]
+            container: Option<ContainerType>,
+            #[serde(rename = "in", default)]
+            location: Option<ArgLocation>,
+            #[serde(default)]
+            description: Option<String>,
+            #[serde(default)]
+            example: Option<serde_json::Value>,
+            #[serde(default)]
+            validation: Option<Validation>,
+        }
+
+        let raw: RawMember = serde::Deserialize::deserialize(deserializer)?;
+        let (type_layers, base) = parse_type_expression(&raw.tipe).map_err(D::Error::custom)?;
+        let base: serde::de::value::StringDeserializer<D::Error> = base.into_deserializer();
+        let tipe: MemberType = serde::Deserialize::deserialize(base)?;
+        let mut layers = raw.container.map(|container| container.layers()).unwrap_or_default();
+        layers.extend(type_layers);
+        let container = ContainerType::from_layers(layers);
+        let (location, description) = (raw.location, raw.description);
+        let (example, validation) = (raw.example, raw.validation);
+        Ok(Member { name: raw.name, tipe, container, location, description, example, validation })
+    }
+}
+
+impl PaginationStyle {
+    /// Checks if the pages are selected with a cursor.
+    pub fn is_cursor(&self) -> bool {
+        *self == PaginationStyle::Cursor
+    }
+}
+
 impl ReasonVariant {
     /// Returns a string representation of the enum.
     pub fn as_str(&self) -> &'static str {
@@ -475,46 +1145,119 @@ impl Routes {
     pub fn from_str(spec_str: &str) -> Result<Self, serde_yaml::Error> {
         serde_yaml::from_str::<Self>(spec_str)
     }
+
+    /// Reads `Routes` structure from the given API file.
+    pub fn read<P: AsRef<std::path::Path>>(path: P) -> Result<Self, Error> {
+        read_yaml(path.as_ref())
+    }
 }
 
 impl Api {
     /// Constructs `Api` structure from API file content.
+    ///
+    /// Imports are not resolved since their paths are relative to the file.
     pub fn from_str(spec_str: &str) -> Result<Self, serde_yaml::Error> {
-        serde_yaml::from_str::<Self>(spec_str)
+        let mut api = serde_yaml::from_str::<Self>(spec_str)?;
+        pagination::expand(&mut api);
+        Ok(api)
+    }
+
+    /// Reads `Api` structure from the given API file and merges in the definitions from all the
+    /// imported files.
+    pub fn read<P: AsRef<std::path::Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let mut api = imports::resolve(path, read_yaml(path)?)?;
+        pagination::expand(&mut api);
+        Ok(api)
+    }
+
+    /// Returns the paths used by at least one method.
+    pub fn paths_in_use<'a>(&self, paths: &'a [Path]) -> Vec<&'a Path> {
+        let used = |path: &&Path| self.methods.iter().any(|m| m.request.path == path.name);
+        paths.iter().filter(used).collect()
     }
 }
 
 // -------------------------------------------------------------------------------------------------
 // Helper functions
 
-/// Searches for a `TypeDef` with given name.
-pub fn find_type(name: &utils::Name, types: &Vec<TypeDef>) -> TypeDef {
-    for tipe in types.iter() {
-        if *name == tipe.name {
-            return tipe.clone();
-        }
-    }
-    panic!("No type '{}' found", name.kebab_case());
+/// Reads and deserializes the given YAML file.
+pub(crate) fn read_yaml<T>(path: &std::path::Path) -> Result<T, Error>
+where
+    T: serde::de::DeserializeOwned,
+{
+    parse_yaml(path, &read_file(path)?)
 }
 
-/// Searches for a `Yield` with given name.
-pub fn find_yield(name: &utils::Name, yields: &Vec<Yield>) -> Yield {
-    for yeeld in yields.iter() {
-        if *name == yeeld.name {
-            return yeeld.clone();
-        }
-    }
-    panic!("No yield '{}' found", name.kebab_case());
+/// Reads the content of the given file.
+pub(crate) fn read_file(path: &std::path::Path) -> Result<String, Error> {
+    std::fs::read_to_string(path)
+        .map_err(|err| ReadError::Io { path: path.to_path_buf(), message: err.to_string() }.into())
+}
+
+/// Deserializes YAML content read from the given file.
+pub(crate) fn parse_yaml<T>(path: &std::path::Path, content: &str) -> Result<T, Error>
+where
+    T: serde::de::DeserializeOwned,
+{
+    serde_yaml::from_str::<T>(content)
+        .map_err(|err| ReadError::from_yaml(path.to_path_buf(), err).into())
 }
 
-/// Searches for a `Reason` with given name.
-pub fn find_reason(name: &utils::Name, reasons: &Vec<Reason>) -> Reason {
-    for reason in reasons.iter() {
-        if *name == reason.name {
-            return reason.clone();
+/// Splits a type expression like `optional<vector<string>>` into a list of containers (starting
+/// from the outermost one) and the name of the innermost type.
+fn parse_type_expression(expression: &str) -> Result<(Vec<Layer>, String), String> {
+    let expression = expression.trim();
+    let closing = expression.len() - expression.trim_end_matches('>').len();
+    let mut parts: Vec<&str> = expression[..expression.len() - closing].split('<').collect();
+    if parts.len() != closing + 1 {
+        return Err(format!("Unbalanced brackets in type '{}'", expression));
+    }
+
+    let base = parts.pop().unwrap_or_default().trim().to_string();
+    let mut layers = Vec::with_capacity(parts.len());
+    for part in parts {
+        match Layer::from_name(part.trim()) {
+            Some(layer) => layers.push(layer),
+            None => return Err(format!("Unknown container '{}' in type '{}'", part, expression)),
         }
     }
-    panic!("No reason '{}' found", name.kebab_case());
+    Ok((layers, base))
+}
+
+/// Message of the panic raised by template helpers if a reference is missing. Templates cannot
+/// propagate errors, so the generators check the specification with `Api::ensure_checked` before
+/// rendering and the references are expected to exist.
+pub(crate) const CHECKED_REFERENCE: &str = "Find a reference in a checked specification";
+
+/// Searches for a `TypeDef` with given name. Fails with `Error::UnknownReference` if there is no
+/// such type.
+pub fn find_type(name: &utils::Name, types: &Vec<TypeDef>) -> Result<TypeDef, Error> {
+    types
+        .iter()
+        .find(|tipe| *name == tipe.name)
+        .cloned()
+        .ok_or_else(|| Error::unknown_reference("type", name))
+}
+
+/// Searches for a `Yield` with given name. Fails with `Error::UnknownReference` if there is no
+/// such yield.
+pub fn find_yield(name: &utils::Name, yields: &Vec<Yield>) -> Result<Yield, Error> {
+    yields
+        .iter()
+        .find(|yeeld| *name == yeeld.name)
+        .cloned()
+        .ok_or_else(|| Error::unknown_reference("yield", name))
+}
+
+/// Searches for a `Reason` with given name. Fails with `Error::UnknownReference` if there is no
+/// such reason.
+pub fn find_reason(name: &utils::Name, reasons: &Vec<Reason>) -> Result<Reason, Error> {
+    reasons
+        .iter()
+        .find(|reason| *name == reason.name)
+        .cloned()
+        .ok_or_else(|| Error::unknown_reference("reason", name))
 }
 
 /// Transforms routes representation from tree-like structure to a vector of vectors of path
@@ -550,7 +1293,8 @@ mod tests {
     use serde_yaml;
 
     use crate::spec::{
-        ContainerType, Member, MemberType, Route, Segment, SimpleType, TypeDef, TypeRepr,
+        ArgLocation, BodyFormat, Check, Condition, ContainerType, Member, MemberType, Request,
+        Route, Segment, SimpleType, TypeDef, TypeRepr, Validation, Yield,
     };
     use crate::utils::Name;
 
@@ -560,17 +1304,27 @@ mod tests {
             name: Name::new("abcd"),
             tipe: MemberType::Defined(Name::new("custom")),
             container: None,
+            location: None,
+            description: None,
+            example: None,
+            validation: None,
         };
         let member2 = Member {
             name: Name::new("edfg"),
             tipe: MemberType::Defined(Name::new("custom")),
             container: None,
+            location: None,
+            description: None,
+            example: None,
+            validation: None,
         };
         let members = vec![member1, member2];
         assert_eq!(
             serde_yaml::to_string(&TypeDef {
                 name: Name::new("name"),
-                container: TypeRepr::Struct { members: members.clone() }
+                container: TypeRepr::Struct { members: members.clone() },
+                description: None,
+                example: None,
             })
             .unwrap(),
             "---\nname: name\ncontainer:\n  repr: struct\n  members:\n    - name: abcd\
@@ -607,6 +1361,10 @@ mod tests {
                 name: Name::new("abc"),
                 tipe: MemberType::Defined(Name::new("custom")),
                 container: Some(ContainerType::Vector),
+                location: None,
+                description: None,
+                example: None,
+                validation: None,
             })
             .unwrap(),
             "---\nname: abc\ntype: custom\ncontainer: vector"
@@ -631,31 +1389,55 @@ mod tests {
             name: Name::new("abc"),
             tipe: MemberType::Simple(SimpleType::U8),
             container: None,
+            location: None,
+            description: None,
+            example: None,
+            validation: None,
         };
         let m2 = Member {
             name: Name::new("abc"),
             tipe: MemberType::Simple(SimpleType::Str),
             container: None,
+            location: None,
+            description: None,
+            example: None,
+            validation: None,
         };
         let m3 = Member {
             name: Name::new("abc"),
             tipe: MemberType::Defined(Name::new("custom")),
             container: None,
+            location: None,
+            description: None,
+            example: None,
+            validation: None,
         };
         let m4 = Member {
             name: Name::new("abc"),
             tipe: MemberType::Defined(Name::new("string-2")),
             container: None,
+            location: None,
+            description: None,
+            example: None,
+            validation: None,
         };
         let m5 = Member {
             name: Name::new("abc"),
             tipe: MemberType::Simple(SimpleType::Str),
             container: Some(ContainerType::Optional),
+            location: None,
+            description: None,
+            example: None,
+            validation: None,
         };
         let m6 = Member {
             name: Name::new("abc"),
             tipe: MemberType::Defined(Name::new("custom")),
             container: Some(ContainerType::Vector),
+            location: None,
+            description: None,
+            example: None,
+            validation: None,
         };
         assert_eq!(s1, m1);
         assert_eq!(s2, m2);
@@ -664,4 +1446,91 @@ mod tests {
         assert_eq!(s5, m5);
         assert_eq!(s6, m6);
     }
+
+    #[test]
+    fn test_nested_member_deserialization() {
+        let expected = Member {
+            name: Name::new("abc"),
+            tipe: MemberType::Simple(SimpleType::Str),
+            container: Some(ContainerType::Nested(vec![
+                ContainerType::Optional,
+                ContainerType::Vector,
+            ])),
+            location: None,
+            description: None,
+            example: None,
+            validation: None,
+        };
+
+        let d1 = "---\nname: abc\ntype: optional<vector<string>>".to_owned();
+        let d2 = "---\nname: abc\ntype: string\ncontainer: optional<vector>".to_owned();
+        let d3 = "---\nname: abc\ntype: vector<string>\ncontainer: optional".to_owned();
+        assert_eq!(serde_yaml::from_str::<Member>(&d1).unwrap(), expected);
+        assert_eq!(serde_yaml::from_str::<Member>(&d2).unwrap(), expected);
+        assert_eq!(serde_yaml::from_str::<Member>(&d3).unwrap(), expected);
+
+        let d4 = "---\nname: abc\ntype: list<string>".to_owned();
+        let d5 = "---\nname: abc\ntype: vector<string".to_owned();
+        assert!(serde_yaml::from_str::<Member>(&d4).is_err());
+        assert!(serde_yaml::from_str::<Member>(&d5).is_err());
+    }
+
+    #[test]
+    fn test_member_validation_deserialization() {
+        let data = "---\nname: tags\ntype: vector<string>\nvalidation:\n  checks: [non_empty]\
+                    \n  each:\n    conditions:\n      - regex: \"[a-z]+\"\n      - one_of: [a, b]";
+        let expected = Validation {
+            checks: vec![Check::NonEmpty],
+            conditions: Vec::new(),
+            each: Some(Box::new(Validation {
+                checks: Vec::new(),
+                conditions: vec![
+                    Condition::Regex("[a-z]+".to_string()),
+                    Condition::OneOf(vec!["a".to_string(), "b".to_string()]),
+                ],
+                each: None,
+            })),
+        };
+        let member = serde_yaml::from_str::<Member>(data).unwrap();
+        assert_eq!(member.container, Some(ContainerType::Vector));
+        assert_eq!(member.validation, Some(expected));
+    }
+
+    #[test]
+    fn test_request_arg_locations() {
+        let data = "---\nmethod: post\npath: items\nargs:\n  - name: if-match\n    type: string\
+                    \n    in: header\n  - name: page\n    type: u32\n    in: query\
+                    \n  - name: title\n    type: string";
+        let request = serde_yaml::from_str::<Request>(data).unwrap();
+        assert_eq!(request.args[0].location, Some(ArgLocation::Header));
+        assert_eq!(request.location_of(&request.args[2]), ArgLocation::Body);
+        let names = |args: Vec<Member>| -> Vec<String> {
+            args.iter().map(|arg| arg.name.kebab_case()).collect()
+        };
+        assert_eq!(names(request.header_args()), vec!["if-match"]);
+        assert_eq!(names(request.query_args()), vec!["page"]);
+        assert_eq!(names(request.body_args()), vec!["title"]);
+        assert!(request.cookie_args().is_empty());
+    }
+
+    #[test]
+    fn test_multipart_request_and_binary_yield() {
+        let data = "---\nmethod: post\npath: avatar\nformat: multipart\nlimit: 1024\nargs:\
+                    \n  - name: image\n    type: file\n  - name: title\n    type: string";
+        let request = serde_yaml::from_str::<Request>(data).unwrap();
+        assert!(request.is_multipart());
+        assert_eq!(request.limit, Some(1024));
+        assert_eq!(request.args[0].tipe, MemberType::Simple(SimpleType::File));
+        assert_eq!(request.file_args().len(), 1);
+        assert_eq!(request.text_body_args()[0].name.kebab_case(), "title");
+
+        let request = serde_yaml::from_str::<Request>("---\nmethod: get\npath: avatar").unwrap();
+        assert_eq!(request.format, BodyFormat::Json);
+        assert!(!request.is_json());
+
+        let data = "---\nname: avatar\ncode: 200-ok\ncontent_type: image/png";
+        let yeeld = serde_yaml::from_str::<Yield>(data).unwrap();
+        assert!(yeeld.is_binary());
+        assert_eq!(yeeld.default_content_type(), "image/png");
+    }
 }
@@ -0,0 +1,306 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy of
+// the MPL was not distributed with this file, You can obtain one at http://mozilla.org/MPL/2.0/
+
+//! Generation of `TypeScript` API code.
+//!
+//! The generated code contains type definitions matching the JSON representation produced by the
+//! code generated by `RustGenerator`, path builders and a `fetch`-based client.
+
+use askama::Template;
+
+use bluefire_twine::constants::{BLUEFIRE_TOKEN_HEADER, SESSION_COOKIE_KEY};
+
+use crate::{error::Error, spec, utils};
+
+// -------------------------------------------------------------------------------------------------
+
+impl spec::SimpleType {
+    /// Formats given type as a `TypeScript` type.
+    fn typescript_format(&self) -> &'static str {
+        match self {
+            spec::SimpleType::Bool => "boolean",
+            spec::SimpleType::U8 => "number",
+            spec::SimpleType::U32 => "number",
+            spec::SimpleType::U64 => "number",
+            spec::SimpleType::I32 => "number",
+            spec::SimpleType::I64 => "number",
+            spec::SimpleType::F32 => "number",
+            spec::SimpleType::F64 => "number",
+            spec::SimpleType::Str => "string",
+            spec::SimpleType::Id => "string",
+            spec::SimpleType::DateTime => "string",
+            spec::SimpleType::Date => "string",
+            spec::SimpleType::Uuid => "string",
+            spec::SimpleType::Bytes => "string",
+            spec::SimpleType::File => "Blob",
+        }
+    }
+}
+
+impl spec::Member {
+    fn typescript_type(&self) -> String {
+        let raw_type = match &self.tipe {
+            spec::MemberType::Simple(tipe) => tipe.typescript_format().to_string(),
+            spec::MemberType::Defined(name) => name.camel_case(),
+        };
+
+        match &self.container {
+            Some(container) => container.wrap(raw_type, |layer, inner| match layer {
+                spec::Layer::Vector => format!("Array<{}>", inner),
+                spec::Layer::Optional => format!("{} | null", inner),
+                spec::Layer::Map => format!("Record<string, {}>", inner),
+            }),
+            None => raw_type,
+        }
+    }
+
+    /// Formats the member as a field of a `TypeScript` interface.
+    fn typescript_field(&self) -> String {
+        if self.is_optional() {
+            format!("{}?: {}", self.name.snake_case(), self.typescript_type())
+        } else {
+            format!("{}: {}", self.name.snake_case(), self.typescript_type())
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Template for generating the whole `TypeScript` module.
+#[derive(Template)]
+#[template(path = "typescript.ts", escape = "none")]
+struct TypeScriptTemplate<'a> {
+    pub api: &'a spec::Api,
+    pub paths: &'a Vec<spec::Path>,
+    pub token_header: &'static str,
+    pub session_cookie_key: &'static str,
+    pub generator: TypeScriptCallback<'a>,
+}
+
+impl<'a> TypeScriptTemplate<'a> {
+    pub fn new(api: &'a spec::Api, paths: &'a Vec<spec::Path>) -> Self {
+        Self {
+            api,
+            paths,
+            token_header: BLUEFIRE_TOKEN_HEADER,
+            session_cookie_key: SESSION_COOKIE_KEY,
+            generator: TypeScriptCallback::new(paths, &api.yields),
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Helper structure for calling rust code from within a template.
+struct TypeScriptCallback<'a> {
+    paths: &'a Vec<spec::Path>,
+    yields: &'a Vec<spec::Yield>,
+}
+
+impl<'a> TypeScriptCallback<'a> {
+    /// Constructs a new `TypeScriptCallback`.
+    pub fn new(paths: &'a Vec<spec::Path>, yields: &'a Vec<spec::Yield>) -> Self {
+        Self { paths, yields }
+    }
+
+    /// Returns the yield with the given name.
+    pub fn find_yield(&self, name: &utils::Name) -> spec::Yield {
+        spec::find_yield(name, self.yields).expect(spec::CHECKED_REFERENCE)
+    }
+
+    /// Formats the given values as a union of string literals.
+    pub fn literals(&self, values: &Vec<utils::Name>) -> String {
+        let literals: Vec<String> =
+            values.iter().map(|value| format!("\"{}\"", value.snake_case())).collect();
+        literals.join(" | ")
+    }
+
+    /// Returns names of the parameters of the given path.
+    pub fn path_params(&self, path: &spec::Path) -> Vec<String> {
+        path.segments
+            .iter()
+            .filter_map(|segment| match segment {
+                spec::Segment::Exact(..) => None,
+                spec::Segment::Str(name) => Some(name.snake_case()),
+            })
+            .collect()
+    }
+
+    /// Formats the list of arguments of the path builder.
+    pub fn path_args(&self, path: &spec::Path) -> String {
+        let args: Vec<String> =
+            self.path_params(path).iter().map(|name| format!("{}: string", name)).collect();
+        args.join(", ")
+    }
+
+    /// Formats the expression building the given path.
+    pub fn path_expr(&self, path: &spec::Path) -> String {
+        if path.segments.is_empty() {
+            return "\"/\"".to_string();
+        }
+
+        let parts: Vec<String> = path
+            .segments
+            .iter()
+            .map(|segment| match segment {
+                spec::Segment::Exact(name) => format!("\"/{}\"", name.snake_case()),
+                spec::Segment::Str(name) => {
+                    format!("\"/\" + encodeURIComponent({})", name.snake_case())
+                }
+            })
+            .collect();
+        parts.join(" + ")
+    }
+
+    /// Formats an object literal with the arguments of the request sent in the given location,
+    /// taking their values from the `request` object. Headers are named in kebab-case.
+    pub fn request_part(&self, request: &spec::Request, location: spec::ArgLocation) -> String {
+        let fields: Vec<String> = request
+            .args_in(&location)
+            .iter()
+            .map(|arg| {
+                let key = match location {
+                    spec::ArgLocation::Header => format!("\"{}\"", arg.name.kebab_case()),
+                    _ => arg.name.snake_case(),
+                };
+                format!("{}: request.{}", key, arg.name.snake_case())
+            })
+            .collect();
+        if fields.is_empty() {
+            "{}".to_string()
+        } else {
+            format!("{{ {} }}", fields.join(", "))
+        }
+    }
+
+    /// Formats the call to the builder of the path with the given name taking the arguments from
+    /// the `params` object.
+    pub fn path_call(&self, name: &utils::Name) -> String {
+        let path = self
+            .paths
+            .iter()
+            .find(|path| path.name == *name)
+            .expect(spec::CHECKED_REFERENCE);
+        let args: Vec<String> =
+            self.path_params(path).iter().map(|param| format!("params.{}", param)).collect();
+        format!("{}Path({})", name.lower_camel_case(), args.join(", "))
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Generator of `TypeScript` API code.
+pub struct TypeScriptGenerator;
+
+impl TypeScriptGenerator {
+    /// Constructs a new `TypeScriptGenerator`.
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Generate API types, path builders and client. Fails if checking the specification finds any
+    /// errors, e.g. a method uses a path which is not defined.
+    pub fn generate_api(self, api: &spec::Api) -> Result<String, Error> {
+        api.ensure_checked()?;
+        let paths = spec::routes_to_paths(None, &api.routes);
+        Ok(TypeScriptTemplate::new(api, &paths).render().expect("Render TypeScript template"))
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::{TypeScriptCallback, TypeScriptGenerator};
+    use crate::error::Error;
+    use crate::spec::{Api, ContainerType, Member, MemberType, Path, Segment, SimpleType};
+    use crate::utils::Name;
+
+    #[test]
+    fn test_member_field() {
+        let member = Member {
+            name: Name::new("nick-name"),
+            tipe: MemberType::Simple(SimpleType::Str),
+            container: Some(ContainerType::Optional),
+            location: None,
+            description: None,
+            example: None,
+            validation: None,
+        };
+        assert_eq!(member.typescript_field(), "nick_name?: string | null");
+
+        let member = Member {
+            name: Name::new("tags"),
+            tipe: MemberType::Defined(Name::new("tag")),
+            container: Some(ContainerType::Vector),
+            location: None,
+            description: None,
+            example: None,
+            validation: None,
+        };
+        assert_eq!(member.typescript_field(), "tags: Array<Tag>");
+
+        let member = Member {
+            name: Name::new("scores"),
+            tipe: MemberType::Simple(SimpleType::I64),
+            container: Some(ContainerType::Nested(vec![
+                ContainerType::Optional,
+                ContainerType::Map,
+            ])),
+            location: None,
+            description: None,
+            example: None,
+            validation: None,
+        };
+        assert_eq!(member.typescript_field(), "scores?: Record<string, number> | null");
+    }
+
+    #[test]
+    fn test_path_builder() {
+        let paths = vec![Path {
+            name: Name::new("user"),
+            segments: vec![Segment::Exact(Name::new("users")), Segment::Str(Name::new("user-id"))],
+        }];
+        let yields = Vec::new();
+        let callback = TypeScriptCallback::new(&paths, &yields);
+        assert_eq!(callback.path_args(&paths[0]), "user_id: string");
+        assert_eq!(
+            callback.path_expr(&paths[0]),
+            "\"/users\" + \"/\" + encodeURIComponent(user_id)"
+        );
+        assert_eq!(callback.path_call(&Name::new("user")), "userPath(params.user_id)");
+    }
+
+    #[test]
+    fn test_undefined_path() {
+        let api = Api::from_str(
+            "---
+yields:
+  - name: empty
+    code: 200-ok
+reasons:
+  - name: error
+    variant: error
+    cases:
+      - name: internal
+        code: 500-internal-server-error
+methods:
+  - name: get-items
+    request:
+      method: get
+      path: items
+    response:
+      success: empty
+      error: error
+",
+        )
+        .unwrap();
+        match TypeScriptGenerator::new().generate_api(&api) {
+            Err(Error::Check { diagnostics, .. }) => {
+                assert!(diagnostics.iter().any(|d| d.message == "Path 'items' is not defined"));
+            }
+            result => panic!("Expected check error, got {:?}", result),
+        }
+    }
+}
@@ -58,6 +58,29 @@ impl Name {
         buffer.join("")
     }
 
+    /// Returns a camel-case representation of the identifier name starting with a lowercase letter.
+    pub fn lower_camel_case(&self) -> String {
+        let mut buffer = Vec::with_capacity(self.parts.len());
+        for (i, part) in self.parts.iter().enumerate() {
+            if i == 0 {
+                buffer.push(part.to_lowercase());
+            } else {
+                buffer.push(capitalize(&part));
+            }
+        }
+        buffer.join("")
+    }
+
+    /// Returns a new name consisting of the given prefix followed by this name.
+    pub fn with_prefix(&self, prefix: &Name) -> Self {
+        Self { parts: prefix.parts.iter().chain(self.parts.iter()).cloned().collect() }
+    }
+
+    /// Returns a new name consisting of this name followed by the given suffix.
+    pub fn with_suffix(&self, suffix: &Name) -> Self {
+        Self { parts: self.parts.iter().chain(suffix.parts.iter()).cloned().collect() }
+    }
+
     /// Returns a kebab-case representation of the identifier name.
     pub fn kebab_case(&self) -> String {
         let mut buffer = Vec::with_capacity(self.parts.len());
@@ -87,13 +110,29 @@ impl<'de> Deserialize<'de> for Name {
     }
 }
 
+/// Writes the given files (paths relative to the directory and their content) into a directory
+/// inside the system temporary directory and returns its path.
+#[cfg(test)]
+pub(crate) fn write_test_files(name: &str, files: &[(&str, &str)]) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("bluefire_protogen_{}", name));
+    for (path, content) in files {
+        let path = dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+    dir
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
     fn name() {
         let name = super::Name::new("one-two-three");
         assert_eq!(name.camel_case(), "OneTwoThree");
+        assert_eq!(name.lower_camel_case(), "oneTwoThree");
         assert_eq!(name.snake_case(), "one_two_three");
         assert_eq!(name.kebab_case(), "one-two-three");
+        assert_eq!(name.with_prefix(&super::Name::new("zero")).camel_case(), "ZeroOneTwoThree");
+        assert_eq!(name.with_suffix(&super::Name::new("four")).kebab_case(), "one-two-three-four");
     }
 }
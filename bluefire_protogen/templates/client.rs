@@ -0,0 +1,100 @@
+{% for method in api.methods %}
+    {% let response_name = method.name.camel_case() + "Response" %}
+    {% let error_name = method.name.camel_case() + "Error" %}
+    {% let yield_name = method.response.success.camel_case() + "Yield" %}
+    {% let success_yield = generator.find_yield(method.response.success.clone(), api.yields) %}
+
+    #[derive(Clone, Debug)]
+    pub enum {{ error_name }} {
+        {% match method.response.failure %}
+            {% when Some with (failure) %}
+                Failure({{ failure.camel_case() }}Reason),
+            {% when None %}
+        {% endmatch %}
+        Error({{ method.response.error.camel_case() }}Reason),
+        Transport(bluefire_twine::transport::TransportError),
+        InvalidResponse(String),
+    }
+
+    impl {{ response_name }} {
+        pub fn into_result(self) -> Result<{{ yield_name }}, {{ error_name }}> {
+            match self {
+                {{ response_name }}::Success(yeeld) => Ok(yeeld),
+                {% match method.response.failure %}
+                    {% when Some with (failure) %}
+                        {{ response_name }}::Failure(failure) => Err({{ error_name }}::Failure(failure)),
+                    {% when None %}
+                {% endmatch %}
+                {{ response_name }}::Error(error) => Err({{ error_name }}::Error(error)),
+            }
+        }
+
+        pub fn parse_raw(
+            raw: Result<bluefire_twine::transport::RawResponse, bluefire_twine::transport::TransportError>,
+        ) -> Result<{{ yield_name }}, {{ error_name }}> {
+            let raw = raw.map_err({{ error_name }}::Transport)?;
+            {% if success_yield.is_binary() %}
+                if raw.status >= 200 && raw.status < 300 {
+                    let yeeld = {{ yield_name }}::new(raw.body);
+                    return Ok(match &raw.content_type {
+                        Some(content_type) => yeeld.with_content_type(content_type),
+                        None => yeeld,
+                    });
+                }
+            {% endif %}
+            Self::from_json_slice(&raw.body)
+                .map_err(|err| {{ error_name }}::InvalidResponse(err.to_string()))?
+                .into_result()
+        }
+    }
+{% endfor %}
+
+pub struct ApiClient<T> {
+    transport: T,
+}
+
+impl<T> ApiClient<T> {
+    pub fn new(transport: T) -> Self {
+        Self { transport }
+    }
+
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    {% for method in api.methods %}
+        {% let request_name = method.name.camel_case() + "Request" %}
+        {% let response_name = method.name.camel_case() + "Response" %}
+        {% let path_name = method.request.path.camel_case() + "PathParams" %}
+        {% let result_type = "Result<{}Yield, {}Error>"|format(method.response.success.camel_case(), method.name.camel_case()) %}
+
+        pub fn {{ method.name.snake_case() }}(
+            &self,
+            params: &{{ path_name }},
+            request: &{{ request_name }},
+        ) -> <T as bluefire_twine::transport::Transport<{{ result_type }}>>::Output
+        where
+            T: bluefire_twine::transport::Transport<{{ result_type }}>,
+        {
+            self.transport.send(request.to_message(params), {{ response_name }}::parse_raw)
+        }
+
+        {% match method.pagination %}
+            {% when Some with (pagination) %}
+                pub fn {{ method.name.snake_case() }}_pages<'a>(
+                    &'a self,
+                    params: &'a {{ path_name }},
+                    request: &{{ request_name }},
+                ) -> impl Iterator<Item = {{ result_type }}> + 'a
+                where
+                    T: bluefire_twine::transport::Transport<{{ result_type }}, Output = {{ result_type }}>,
+                {
+                    let request = request.clone();
+                    bluefire_twine::pagination::PageIterator::new(request.page_params(), move |page| {
+                        self.{{ method.name.snake_case() }}(params, &request.with_page_params(page))
+                    })
+                }
+            {% when None %}
+        {% endmatch %}
+    {% endfor %}
+}
@@ -0,0 +1,69 @@
+{% for path in api.paths_in_use(paths) %}
+    {% let trait_name = path.name.camel_case() + "Handler" %}
+    {% let handler_name = path.name.camel_case() + "RouteHandler" %}
+    {% let methods = generator.methods_of(path, api.methods) %}
+    {% let allowed = generator.allowed_methods(methods) %}
+
+    pub trait {{ trait_name }}: Clone + std::fmt::Debug + Send + Sync + 'static {
+        {% for method in methods %}
+            fn {{ method.name.snake_case() }}(
+                &self,
+                context: &bluefire_backend::BlueFire,
+                request: {{ method.name.camel_case() }}Request,
+                path: {{ path.name.camel_case() }}PathParams,
+            ) -> bluefire_backend::rest::Reply<{{ method.name.camel_case() }}Response>;
+        {% endfor %}
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct {{ handler_name }}<H: {{ trait_name }}> {
+        handler: H,
+    }
+
+    impl<H: {{ trait_name }}> {{ handler_name }}<H> {
+        pub fn new(handler: H) -> Self {
+            Self { handler }
+        }
+    }
+
+    impl<H: {{ trait_name }}> bluefire_backend::Handler for {{ handler_name }}<H> {
+        fn handle(
+            &self,
+            context: &bluefire_backend::BlueFire,
+            request: bluefire_backend::Request,
+        ) -> bluefire_backend::Response {
+            match *request.method() {
+                http::Method::OPTIONS => bluefire_backend::rest::make_options_response("{{ allowed }}"),
+                {% for method in methods %}
+                    {{ method.request.method.rust_format() }} => {
+                        bluefire_backend::rest::handle_method::<{{ method.name.camel_case() }}Method, _>(
+                            context,
+                            request,
+                            |context, request, path| self.handler.{{ method.name.snake_case() }}(context, request, path),
+                        )
+                    }
+                {% endfor %}
+                _ => bluefire_backend::rest::make_method_not_allowed_response("{{ allowed }}"),
+            }
+        }
+
+        fn duplicate(&self) -> Box<dyn bluefire_backend::Handler> {
+            Box::new(self.clone())
+        }
+    }
+{% endfor %}
+
+pub fn register(
+    builder: &mut bluefire_backend::router::RoutingBuilder,
+    host: bluefire_backend::router::Host,
+    {% for path in api.paths_in_use(paths) %}
+        {{ path.name.snake_case() }}_handler: impl {{ path.name.camel_case() }}Handler,
+    {% endfor %}
+) {
+    let route = bluefire_backend::router::Route::index().with_routes(vec![
+        {% for route in api.routes %}
+            {{ generator.handled_route(route, api.methods) }},
+        {% endfor %}
+    ]);
+    builder.insert(host, route);
+}
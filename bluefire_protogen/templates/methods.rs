@@ -3,6 +3,12 @@
     {% let request_name = method.name.camel_case() + "Request" %}
     {% let response_name = method.name.camel_case() + "Response" %}
     {% let path_name = method.request.path.camel_case() + "PathParams" %}
+    {% let query_args = method.request.query_args() %}
+    {% let body_args = method.request.text_body_args() %}
+    {% let file_args = method.request.file_args() %}
+    {% let success_yield = generator.find_yield(method.response.success.clone(), api.yields) %}
+    {% let header_args = method.request.header_args() %}
+    {% let cookie_args = method.request.cookie_args() %}
 
     {# REQUEST #}
 
@@ -13,6 +19,37 @@
         {% endfor %}
     }
 
+    #[derive(Serialize, Deserialize)]
+    struct __{{ request_name }}Query {
+        {% for arg in query_args %}
+            {{ arg.name().snake_case() }}: {{ arg.rust_type() }},
+        {% endfor %}
+    }
+
+    {% if method.request.has_body() %}
+        #[derive(Serialize, Deserialize)]
+        struct __{{ request_name }}Body {
+            {% for arg in body_args %}
+                {{ arg.name().snake_case() }}: {{ arg.rust_type() }},
+            {% endfor %}
+        }
+    {% endif %}
+
+    #[derive(Serialize, Deserialize)]
+    struct __{{ request_name }}Headers {
+        {% for arg in header_args %}
+            #[serde(rename = "{{ arg.name().kebab_case() }}")]
+            {{ arg.name().snake_case() }}: {{ arg.rust_type() }},
+        {% endfor %}
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct __{{ request_name }}Cookies {
+        {% for arg in cookie_args %}
+            {{ arg.name().snake_case() }}: {{ arg.rust_type() }},
+        {% endfor %}
+    }
+
     impl {{ request_name }} {
         pub fn new (
             {% for arg in method.request.args %}
@@ -27,27 +64,95 @@
         }
 
         {% if method.request.method == spec::HttpMethod::Get %}
+            #[deprecated(note = "Parses all the arguments from the query string regardless of their \
+                                 location; use `TryFrom<bluefire_backend::Request>` instead")]
             pub fn from_query_string(query_str: &str) -> Result<Self, serde_urlencoded::de::Error> {
                 serde_urlencoded::from_str(query_str)
             }
-
-            pub fn to_query_string(&self) -> Result<String, serde_urlencoded::ser::Error> {
-                serde_urlencoded::to_string(self)
-            }
         {% else %}
+            #[deprecated(note = "Parses all the arguments from the body regardless of their \
+                                 location; use `TryFrom<bluefire_backend::Request>` instead")]
             pub fn from_json_slice(json_slice: &[u8]) -> Result<Self, serde_json::Error> {
                 serde_json::from_slice(json_slice)
             }
 
+            #[deprecated(note = "Parses all the arguments from the body regardless of their \
+                                 location; use `TryFrom<bluefire_backend::Request>` instead")]
             pub fn from_json_string(json_str: &str) -> Result<Self, serde_json::Error> {
                 serde_json::from_str(json_str)
             }
+        {% endif %}
 
+        pub fn to_query_string(&self) -> Result<String, serde_urlencoded::ser::Error> {
+            serde_urlencoded::to_string(&__{{ request_name }}Query {
+                {% for arg in query_args %}
+                    {{ arg.name().snake_case() }}: self.{{ arg.name().snake_case() }}.clone(),
+                {% endfor %}
+            })
+        }
+
+        {% if method.request.is_json() %}
             pub fn to_json_string(&self) -> Result<String, serde_json::Error> {
-                serde_json::to_string(self)
+                serde_json::to_string(&__{{ request_name }}Body {
+                    {% for arg in body_args %}
+                        {{ arg.name().snake_case() }}: self.{{ arg.name().snake_case() }}.clone(),
+                    {% endfor %}
+                })
             }
         {% endif %}
 
+        {% if method.request.is_form() %}
+            pub fn to_form_string(&self) -> Result<String, serde_urlencoded::ser::Error> {
+                serde_urlencoded::to_string(&__{{ request_name }}Body {
+                    {% for arg in body_args %}
+                        {{ arg.name().snake_case() }}: self.{{ arg.name().snake_case() }}.clone(),
+                    {% endfor %}
+                })
+            }
+        {% endif %}
+
+        {% if method.request.is_multipart() %}
+            pub fn to_parts(&self) -> Result<Vec<bluefire_twine::multipart::Part>, String> {
+                let pairs = bluefire_twine::message::to_pairs(&__{{ request_name }}Body {
+                    {% for arg in body_args %}
+                        {{ arg.name().snake_case() }}: self.{{ arg.name().snake_case() }}.clone(),
+                    {% endfor %}
+                })?;
+                #[allow(unused_mut)]
+                let mut parts: Vec<bluefire_twine::multipart::Part> = pairs
+                    .iter()
+                    .map(|(name, value)| bluefire_twine::multipart::Part::text(name, value))
+                    .collect();
+                {% for arg in file_args %}
+                    parts.extend(bluefire_twine::multipart::FileArg::to_parts(
+                        &self.{{ arg.name().snake_case() }},
+                        "{{ arg.name().snake_case() }}",
+                    ));
+                {% endfor %}
+                Ok(parts)
+            }
+        {% endif %}
+
+        pub fn to_headers(&self) -> Result<Vec<(String, String)>, String> {
+            let mut headers = bluefire_twine::message::to_pairs(&__{{ request_name }}Headers {
+                {% for arg in header_args %}
+                    {{ arg.name().snake_case() }}: self.{{ arg.name().snake_case() }}.clone(),
+                {% endfor %}
+            })?;
+            let cookies = bluefire_twine::message::to_pairs(&__{{ request_name }}Cookies {
+                {% for arg in cookie_args %}
+                    {{ arg.name().snake_case() }}: self.{{ arg.name().snake_case() }}.clone(),
+                {% endfor %}
+            })?;
+            if !cookies.is_empty() {
+                headers.push(("cookie".to_string(), bluefire_twine::message::format_cookies(&cookies)));
+            }
+            {% if method.request.is_form() %}
+                headers.push(("content-type".to_string(), "{{ method.request.format.mime_type() }}".to_string()));
+            {% endif %}
+            Ok(headers)
+        }
+
         pub fn get_method(&self) -> http::method::Method {
             {{ method.request.method.rust_format() }}
         }
@@ -60,16 +165,49 @@
             bluefire_twine::Message::new(
                 self.get_method_name(),
                 params.to_path(),
-                {% if method.request.method == spec::HttpMethod::Get %}
-                    self.to_query_string().expect("Cast to query string"),
-                    String::new(),
+                self.to_query_string().expect("Cast to query string"),
+                {% if method.request.is_json() %}
+                    self.to_json_string().expect("Cast to json string"),
+                {% else if method.request.is_form() %}
+                    self.to_form_string().expect("Cast to form string"),
                 {% else %}
                     String::new(),
-                    self.to_json_string().expect("Cast to json string"),
                 {% endif %}
             )
+            .with_headers(self.to_headers().expect("Cast to headers"))
+            {% if method.request.is_multipart() %}
+                .with_parts(self.to_parts().expect("Cast to parts"))
+            {% endif %}
         }
 
+        {% match method.pagination %}
+            {% when Some with (pagination) %}
+                pub fn page_params(&self) -> bluefire_twine::pagination::PageParams {
+                    bluefire_twine::pagination::PageParams {
+                        {% if pagination.style.is_cursor() %}
+                            cursor: self.cursor.clone(),
+                            offset: 0,
+                        {% else %}
+                            cursor: None,
+                            offset: self.offset.unwrap_or(0),
+                        {% endif %}
+                        limit: self.limit.unwrap_or({{ pagination.default_limit }}),
+                    }
+                }
+
+                pub fn with_page_params(&self, params: &bluefire_twine::pagination::PageParams) -> Self {
+                    let mut request = self.clone();
+                    {% if pagination.style.is_cursor() %}
+                        request.cursor = params.cursor.clone();
+                    {% else %}
+                        request.offset = Some(params.offset);
+                    {% endif %}
+                    request.limit = Some(params.limit);
+                    request
+                }
+            {% when None %}
+        {% endmatch %}
+
         {% for member in method.request.args %}
             {% if member.container.is_none() %}
                 {% match member.tipe %}
@@ -99,19 +237,92 @@
         {% endfor %}
     }
 
+    impl bluefire_twine::validation::Validate for {{ request_name }} {
+        fn validate(&self)
+        -> bluefire_twine::ValidationResult<bluefire_twine::validation::ValidationError> {
+            {{ generator.validate_members(method.request.args, api.types) }}
+        }
+    }
+
     impl std::convert::TryFrom<bluefire_backend::Request> for {{ request_name }} {
-        {% if method.request.method == spec::HttpMethod::Get %}
-            type Error = serde::de::value::Error;
-        {% else %}
+        {% if method.request.has_body() %}
             type Error = serde_json::error::Error;
+        {% else %}
+            type Error = serde::de::value::Error;
         {% endif %}
 
+        #[allow(unused_variables)]
         fn try_from(request: bluefire_backend::Request) -> Result<{{ request_name }}, Self::Error> {
-            {% if method.request.method == spec::HttpMethod::Get %}
-                Self::from_query_string(&request.uri().query().unwrap_or(""))
+            {% if query_args.len() > 0 %}
+                let query: __{{ request_name }}Query =
+                    serde_urlencoded::from_str(request.uri().query().unwrap_or(""))
+                        .map_err(<Self::Error as serde::de::Error>::custom)?;
+            {% endif %}
+            {% if method.request.is_multipart() %}
+                let parts = bluefire_backend::multipart::MultipartParser::new()
+                    {% match method.request.limit %}
+                        {% when Some with (limit) %}
+                            .with_limit({{ limit }})
+                        {% when None %}
+                    {% endmatch %}
+                    .parse_request(&request)
+                    .map_err(<Self::Error as serde::de::Error>::custom)?;
+                {% if body_args.len() > 0 %}
+                    let body: __{{ request_name }}Body = bluefire_twine::message::from_pairs(
+                        &bluefire_twine::multipart::text_pairs(&parts),
+                    )
+                    .map_err(<Self::Error as serde::de::Error>::custom)?;
+                {% endif %}
+            {% else if method.request.is_form() %}
+                {% if body_args.len() > 0 %}
+                    let body: __{{ request_name }}Body =
+                        serde_urlencoded::from_bytes(request.body().as_slice())
+                            .map_err(<Self::Error as serde::de::Error>::custom)?;
+                {% endif %}
             {% else %}
-                Self::from_json_slice(request.body().as_slice())
+                {% if body_args.len() > 0 %}
+                    let body: __{{ request_name }}Body = if request.body().is_empty() {
+                        serde_json::from_str("{}")?
+                    } else {
+                        serde_json::from_slice(request.body().as_slice())?
+                    };
+                {% endif %}
             {% endif %}
+            {% if header_args.len() > 0 %}
+                let headers: __{{ request_name }}Headers = bluefire_twine::message::from_pairs(
+                    &bluefire_backend::rest::header_pairs(&request),
+                )
+                .map_err(<Self::Error as serde::de::Error>::custom)?;
+            {% endif %}
+            {% if cookie_args.len() > 0 %}
+                let cookies: __{{ request_name }}Cookies = bluefire_twine::message::from_pairs(
+                    &bluefire_backend::rest::cookie_pairs(&request),
+                )
+                .map_err(<Self::Error as serde::de::Error>::custom)?;
+            {% endif %}
+
+            Ok({{ request_name }} {
+                {% for arg in query_args %}
+                    {{ arg.name().snake_case() }}: query.{{ arg.name().snake_case() }},
+                {% endfor %}
+                {% for arg in body_args %}
+                    {{ arg.name().snake_case() }}: body.{{ arg.name().snake_case() }},
+                {% endfor %}
+                {% for arg in file_args %}
+                    {{ arg.name().snake_case() }}:
+                        <{{ arg.rust_type() }} as bluefire_twine::multipart::FileArg>::from_parts(
+                            "{{ arg.name().snake_case() }}",
+                            &parts,
+                        )
+                        .map_err(<Self::Error as serde::de::Error>::custom)?,
+                {% endfor %}
+                {% for arg in header_args %}
+                    {{ arg.name().snake_case() }}: headers.{{ arg.name().snake_case() }},
+                {% endfor %}
+                {% for arg in cookie_args %}
+                    {{ arg.name().snake_case() }}: cookies.{{ arg.name().snake_case() }},
+                {% endfor %}
+            })
         }
      }
 
@@ -133,19 +344,25 @@
     }
 
     impl {{ response_name }} {
-        {% let yeeld = generator.find_yield(method.response.success.clone(), api.yields) %}
-        pub fn success(
-            {% for arg in yeeld.args %}
-                {{ arg.name().snake_case() }}: {{ arg.rust_type() }},
-            {% endfor %}
-        ) -> (http::StatusCode, {{ response_name }}) {(
-            {{ yeeld.code.rust_format() }},
-            {{ response_name }}::Success({{ yeeld.name.camel_case() }}Yield {
-                {% for arg in yeeld.args %}
-                    {{ arg.name().snake_case() }},
+        {% if success_yield.is_binary() %}
+            pub fn success(data: Vec<u8>) -> (http::StatusCode, {{ response_name }}) {(
+                {{ success_yield.code.rust_format() }},
+                {{ response_name }}::Success({{ success_yield.name.camel_case() }}Yield::new(data))
+            )}
+        {% else %}
+            pub fn success(
+                {% for arg in success_yield.args %}
+                    {{ arg.name().snake_case() }}: {{ arg.rust_type() }},
                 {% endfor %}
-            })
-        )}
+            ) -> (http::StatusCode, {{ response_name }}) {(
+                {{ success_yield.code.rust_format() }},
+                {{ response_name }}::Success({{ success_yield.name.camel_case() }}Yield {
+                    {% for arg in success_yield.args %}
+                        {{ arg.name().snake_case() }},
+                    {% endfor %}
+                })
+            )}
+        {% endif %}
 
         {% match method.response.failure %}
             {% when Some with (failure) %}
@@ -210,6 +427,15 @@
 
     impl From<{{ response_name }}> for bluefire_backend::Response {
         fn from(response: {{ response_name }}) -> bluefire_backend::Response {
+            {% if success_yield.is_binary() %}
+                if let {{ response_name }}::Success(yeeld) = response {
+                    return http::response::Builder::new()
+                        .status(yeeld.get_code())
+                        .header(http::header::CONTENT_TYPE, yeeld.content_type.as_str())
+                        .body(yeeld.data.into_inner())
+                        .expect("Build response");
+                }
+            {% endif %}
             http::response::Builder::new()
                 .status(response.get_code())
                 .body(serde_json::to_string(&response).expect("Serialize response to JSON").into_bytes())
@@ -247,5 +473,10 @@
         type PathParams = {{ path_name }};
         type Request = {{ request_name }};
         type Response = {{ response_name }};
+
+        fn validate(request: &Self::Request)
+        -> bluefire_twine::ValidationResult<bluefire_twine::validation::ValidationError> {
+            bluefire_twine::validation::Validate::validate(request)
+        }
     }
 {% endfor %}
@@ -7,91 +7,80 @@
                 {% when Some with (validation) %}
                     pub enum {{ tipe.name.camel_case() }}ValidationResult {
                         Ok,
-                        {% for check in validation.checks %}
-                            {{ check.get_error_name().camel_case() }},
-                        {% endfor %}
-                        {% for condition in validation.conditions %}
-                            {{ condition.get_error_name().camel_case() }},
+                        {% for name in validation.error_names(simple_type) %}
+                            {{ name.camel_case() }},
                         {% endfor %}
                     }
 
+                    impl {{ tipe.name.camel_case() }}ValidationResult {
+                        pub fn as_str(&self) -> &'static str {
+                            match self {
+                                {{ tipe.name.camel_case() }}ValidationResult::Ok => "ok",
+                                {% for name in validation.error_names(simple_type) %}
+                                    {{ tipe.name.camel_case() }}ValidationResult::{{ name.camel_case() }} => "{{ name.snake_case() }}",
+                                {% endfor %}
+                            }
+                        }
+                    }
+
                     pub fn __validate_{{ tipe.name.snake_case() }}(item: &{{ tipe.name.camel_case() }})
                     -> bluefire_twine::ValidationResult<{{ tipe.name.camel_case() }}ValidationResult> {
                         let mut validation_result = bluefire_twine::ValidationResult::new();
 
                         {% for condition in validation.conditions %}
-                            {% match condition %}
-                                {% when spec::Condition::Le with (value) %}
-                                    {% match simple_type %}
-                                        {% when spec::SimpleType::U8 %}
-                                            if *item > {{ value }} {
-                                        {% when spec::SimpleType::U32 %}
-                                            if *item > {{ value }} {
-                                        {% when spec::SimpleType::I32 %}
-                                            if *item > {{ value }} {
-                                        {% when spec::SimpleType::F32 %}
+                            {% if condition.applies_to(simple_type) %}
+                                {% match condition %}
+                                    {% when spec::Condition::Le with (value) %}
+                                        {% if simple_type.is_float() %}
                                             if *item > {{ "{:.4}"|format(value) }} {
-                                        {% when spec::SimpleType::F64 %}
-                                            if *item > {{ "{:.4}"|format(value) }} {
-                                        {% when spec::SimpleType::Str %}
-                                            {# nothing to generate - this type cannot be compared #}
-                                        {% when spec::SimpleType::Id %}
-                                            {# nothing to generate - this type cannot be compared #}
-                                    {% endmatch %}
-                                {% when spec::Condition::Ge with (value) %}
-                                    {% match simple_type %}
-                                        {% when spec::SimpleType::U8 %}
-                                            if *item < {{ value }} {
-                                        {% when spec::SimpleType::U32 %}
-                                            if *item < {{ value }} {
-                                        {% when spec::SimpleType::I32 %}
-                                            if *item < {{ value }} {
-                                        {% when spec::SimpleType::F32 %}
-                                            if *item < {{ "{:.4}"|format(value) }} {
-                                        {% when spec::SimpleType::F64 %}
+                                        {% else %}
+                                            if *item > {{ value }} {
+                                        {% endif %}
+                                    {% when spec::Condition::Ge with (value) %}
+                                        {% if simple_type.is_float() %}
                                             if *item < {{ "{:.4}"|format(value) }} {
-                                        {% when spec::SimpleType::Str %}
-                                            {# nothing to generate - this type cannot be compared #}
-                                        {% when spec::SimpleType::Id %}
-                                            {# nothing to generate - this type cannot be compared #}
-                                    {% endmatch %}
-                                {% when spec::Condition::LenEq with (len) %}
-                                    {% match simple_type %}
-                                        {% when spec::SimpleType::Str %}
-                                            if item.len() != {{ len }} {
-                                        {% when _ %}
-                                            {# nothing to generate - this type does not have length #}
-                                    {% endmatch %}
-                                {% when spec::Condition::LenLe with (len) %}
-                                    {% match simple_type %}
-                                        {% when spec::SimpleType::Str %}
-                                            if item.len() > {{ len }} {
-                                        {% when _ %}
-                                            {# nothing to generate - this type does not have length #}
-                                    {% endmatch %}
-                                {% when spec::Condition::LenGe with (len) %}
-                                    {% match simple_type %}
-                                        {% when spec::SimpleType::Str %}
-                                            if item.len() < {{ len }} {
-                                        {% when _ %}
-                                            {# nothing to generate - this type does not have length #}
-                                    {% endmatch %}
-                            {% endmatch %}
-                                validation_result.add(
-                                    {{ tipe.name.camel_case() }}ValidationResult::{{ condition.get_error_name().camel_case() }}
-                                );
-                            }
+                                        {% else %}
+                                            if *item < {{ value }} {
+                                        {% endif %}
+                                    {% when spec::Condition::LenEq with (len) %}
+                                        if item.len() != {{ len }} {
+                                    {% when spec::Condition::LenLe with (len) %}
+                                        if item.len() > {{ len }} {
+                                    {% when spec::Condition::LenGe with (len) %}
+                                        if item.len() < {{ len }} {
+                                    {% when spec::Condition::Regex with (pattern) %}
+                                        static PATTERN_{{ loop.index }}: bluefire_twine::validation::Pattern =
+                                            bluefire_twine::validation::Pattern::new({{ "{:?}"|format(pattern) }});
+                                        if !PATTERN_{{ loop.index }}.is_match(item) {
+                                    {% when spec::Condition::OneOf with (values) %}
+                                        if ![{% for value in values %}{{ "{:?}"|format(value) }}, {% endfor %}].contains(&item.as_str()) {
+                                    {% when _ %}
+                                        {# not applicable to simple types #}
+                                {% endmatch %}
+                                    validation_result.add(
+                                        {{ tipe.name.camel_case() }}ValidationResult::{{ condition.get_error_name().camel_case() }}
+                                    );
+                                }
+                            {% endif %}
                         {% endfor %}
 
                         {% for check in validation.checks %}
-                            {% match check %}
-                                {% when spec::Check::Email %}
-                                    if !bluefire_twine::validation::validate_email(item) {
-                                        validation_result.add(
-                                            {{ tipe.name.camel_case() }}ValidationResult::{{ check.get_error_name().camel_case() }}
-                                        );
-                                    }
-                            {% endmatch %}
+                            {% if check.applies_to(simple_type) %}
+                                {% match check %}
+                                    {% when spec::Check::Email %}
+                                        if !bluefire_twine::validation::validate_email(item) {
+                                    {% when spec::Check::Url %}
+                                        if !bluefire_twine::validation::validate_url(item) {
+                                    {% when spec::Check::Uuid %}
+                                        if !bluefire_twine::validation::validate_uuid(item) {
+                                    {% when spec::Check::NonEmpty %}
+                                        if item.is_empty() {
+                                {% endmatch %}
+                                    validation_result.add(
+                                        {{ tipe.name.camel_case() }}ValidationResult::{{ check.get_error_name().camel_case() }}
+                                    );
+                                }
+                            {% endif %}
                         {% endfor %}
 
                         validation_result
@@ -121,6 +110,13 @@
                     }
                 }
             }
+
+            impl bluefire_twine::validation::Validate for {{ tipe.name.camel_case() }} {
+                fn validate(&self)
+                -> bluefire_twine::ValidationResult<bluefire_twine::validation::ValidationError> {
+                    {{ generator.validate_members(members, api.types) }}
+                }
+            }
         {% when spec::TypeRepr::Union with {members} %}
             #[derive(Clone, Debug, Serialize, Deserialize)]
             #[serde(tag = "variant", content = "content")]
@@ -130,6 +126,13 @@
                     {{ member.name().camel_case() }}({{ member.rust_type() }}),
                 {% endfor %}
             }
+
+            impl bluefire_twine::validation::Validate for {{ tipe.name.camel_case() }} {
+                fn validate(&self)
+                -> bluefire_twine::ValidationResult<bluefire_twine::validation::ValidationError> {
+                    {{ generator.validate_variants(tipe.name, members, api.types) }}
+                }
+            }
         {% when spec::TypeRepr::Enum with {values} %}
             #[derive(Clone, Debug, Serialize, Deserialize)]
             pub enum {{ tipe.name.camel_case() }} {
@@ -0,0 +1,261 @@
+// Generated by bluefire_protogen. Do not edit.
+
+/** Name of the HTTP header used for transmitting the session token. */
+export const BLUEFIRE_TOKEN_HEADER = "{{ token_header }}";
+
+/** Name of the cookie holding the session token. */
+export const SESSION_COOKIE_KEY = "{{ session_cookie_key }}";
+
+/** Content of a yield sent as raw binary data. */
+export interface BinaryContent {
+    content_type: string;
+    data: Blob;
+}
+
+// Types
+{% for tipe in api.types %}
+    {%- match tipe.container %}
+        {%- when spec::TypeRepr::Simple with {simple_type, validation} %}
+export type {{ tipe.name.camel_case() }} = {{ simple_type.typescript_format() }};
+        {%- when spec::TypeRepr::External %}
+export type {{ tipe.name.camel_case() }} = unknown;
+        {%- when spec::TypeRepr::Struct with {members} %}
+export interface {{ tipe.name.camel_case() }} {
+            {%- for member in members %}
+    {{ member.typescript_field() }};
+            {%- endfor %}
+}
+        {%- when spec::TypeRepr::Union with {members} %}
+export type {{ tipe.name.camel_case() }} =
+            {%- for member in members %}
+    | { variant: "{{ member.name.snake_case() }}"; content: {{ member.typescript_type() }} }
+            {%- endfor %};
+        {%- when spec::TypeRepr::Enum with {values} %}
+export type {{ tipe.name.camel_case() }} = {{ generator.literals(values) }};
+    {%- endmatch %}
+{% endfor %}
+// Yields
+{% for yeeld in api.yields %}
+    {%- if yeeld.is_binary() %}
+export type {{ yeeld.name.camel_case() }}Yield = BinaryContent;
+    {%- else %}
+export interface {{ yeeld.name.camel_case() }}Yield {
+        {%- for arg in yeeld.args %}
+    {{ arg.typescript_field() }};
+        {%- endfor %}
+}
+    {%- endif %}
+{% endfor %}
+// Reasons
+{% for reason in api.reasons %}
+export type {{ reason.name.camel_case() }}Reason =
+    {%- for case in reason.cases %}
+    | {
+          reason: "{{ case.name.snake_case() }}";
+        {%- for arg in case.args %}
+          {{ arg.typescript_field() }};
+        {%- endfor %}
+      }
+    {%- endfor %};
+{% endfor %}
+// Paths
+{% for path in paths %}
+export interface {{ path.name.camel_case() }}PathParams {
+    {%- for param in generator.path_params(path) %}
+    {{ param }}: string;
+    {%- endfor %}
+}
+
+export function {{ path.name.lower_camel_case() }}Path({{ generator.path_args(path) }}): string {
+    return {{ generator.path_expr(path) }};
+}
+{% endfor %}
+// Methods
+{% for method in api.methods %}
+export interface {{ method.name.camel_case() }}Request {
+    {%- for arg in method.request.args %}
+    {{ arg.typescript_field() }};
+    {%- endfor %}
+}
+
+export type {{ method.name.camel_case() }}Response =
+    | { result: "success"; content: {{ method.response.success.camel_case() }}Yield }
+    {%- match method.response.failure %}
+        {%- when Some with (failure) %}
+    | { result: "failure"; content: {{ failure.camel_case() }}Reason }
+        {%- when None %}
+    {%- endmatch %}
+    | { result: "error"; content: {{ method.response.error.camel_case() }}Reason };
+{% endfor %}
+// Client
+
+/** Returns the session token stored in the session cookie if present. */
+export function getSessionToken(): string | null {
+    if (typeof document === "undefined") {
+        return null;
+    }
+    for (const keyValue of document.cookie.split(";")) {
+        const position = keyValue.indexOf("=");
+        if (position !== -1 && keyValue.substring(0, position).trim() === SESSION_COOKIE_KEY) {
+            return keyValue.substring(position + 1);
+        }
+    }
+    return null;
+}
+
+function toSearchParams(request: object): URLSearchParams {
+    const params = new URLSearchParams();
+    for (const [key, value] of Object.entries(request)) {
+        if (value !== undefined && value !== null) {
+            params.append(key, String(value));
+        }
+    }
+    return params;
+}
+
+function toQueryString(request: object): string {
+    return toSearchParams(request).toString();
+}
+
+function toFormData(request: object): FormData {
+    const form = new FormData();
+    for (const [key, value] of Object.entries(request)) {
+        for (const item of Array.isArray(value) ? value : [value]) {
+            if (item instanceof Blob) {
+                form.append(key, item);
+            } else if (item !== undefined && item !== null) {
+                form.append(key, String(item));
+            }
+        }
+    }
+    return form;
+}
+
+async function toJsonResponse<T>(response: Response): Promise<T> {
+    return (await response.json()) as T;
+}
+
+async function toBinaryResponse<T>(
+    response: Response,
+    contentType: string,
+): Promise<{ result: "success"; content: BinaryContent } | T> {
+    if (!response.ok) {
+        return toJsonResponse<T>(response);
+    }
+    const content = {
+        content_type: response.headers.get("Content-Type") || contentType,
+        data: await response.blob(),
+    };
+    return { result: "success", content };
+}
+
+function toHeaders(headers: object, cookies: object): Record<string, string> {
+    const result: Record<string, string> = {};
+    for (const [key, value] of Object.entries(headers)) {
+        if (value !== undefined && value !== null) {
+            result[key] = String(value);
+        }
+    }
+    // Browsers do not allow setting cookies this way and send the stored ones instead.
+    const cookie = Object.entries(cookies)
+        .filter(([, value]) => value !== undefined && value !== null)
+        .map(([key, value]) => key + "=" + String(value))
+        .join("; ");
+    if (cookie.length > 0) {
+        result["cookie"] = cookie;
+    }
+    return result;
+}
+
+/** Client sending requests to the API. */
+export class Client {
+    private readonly host: string;
+    private readonly token: () => string | null;
+
+    /**
+     * Constructs a new client sending requests to the given host. By default the session token is
+     * read from the session cookie.
+     */
+    constructor(host: string = "", token: () => string | null = getSessionToken) {
+        this.host = host;
+        this.token = token;
+    }
+{% for method in api.methods %}
+    {%- let request_name = method.name.camel_case() + "Request" %}
+    {%- let response_name = method.name.camel_case() + "Response" %}
+    {%- let path_name = method.request.path.camel_case() + "PathParams" %}
+    {%- let success_yield = generator.find_yield(method.response.success) %}
+
+    async {{ method.name.lower_camel_case() }}(params: {{ path_name }}, request: {{ request_name }}): Promise<{{ response_name }}> {
+        const response = await this.send(
+            "{{ method.request.method.to_str() }}",
+            {{ generator.path_call(method.request.path) }},
+            toQueryString({{ generator.request_part(method.request, spec::ArgLocation::Query) }}),
+            {%- if method.request.is_json() %}
+            JSON.stringify({{ generator.request_part(method.request, spec::ArgLocation::Body) }}),
+            {%- else if method.request.is_form() %}
+            toSearchParams({{ generator.request_part(method.request, spec::ArgLocation::Body) }}),
+            {%- else if method.request.is_multipart() %}
+            toFormData({{ generator.request_part(method.request, spec::ArgLocation::Body) }}),
+            {%- else %}
+            undefined,
+            {%- endif %}
+            toHeaders(
+                {{ generator.request_part(method.request, spec::ArgLocation::Header) }},
+                {{ generator.request_part(method.request, spec::ArgLocation::Cookie) }},
+            ),
+        );
+        {%- if success_yield.is_binary() %}
+        return toBinaryResponse<{{ response_name }}>(response, "{{ success_yield.default_content_type() }}");
+        {%- else %}
+        return toJsonResponse<{{ response_name }}>(response);
+        {%- endif %}
+    }
+    {%- match method.pagination %}
+        {%- when Some with (pagination) %}
+
+    /** Yields the responses for consecutive pages until the last page or a failure. */
+    async *{{ method.name.lower_camel_case() }}Pages(
+        params: {{ path_name }},
+        request: {{ request_name }},
+    ): AsyncGenerator<{{ response_name }}, void, undefined> {
+        let next: {{ request_name }} | null = request;
+        while (next !== null) {
+            const response: {{ response_name }} = await this.{{ method.name.lower_camel_case() }}(params, next);
+            yield response;
+            if (response.result !== "success") {
+                return;
+            }
+            {%- if pagination.style.is_cursor() %}
+            const cursor = response.content.next_cursor;
+            next = cursor !== undefined && cursor !== null ? { ...next, cursor } : null;
+            {%- else %}
+            const offset = response.content.next_offset;
+            next = offset !== undefined && offset !== null ? { ...next, offset } : null;
+            {%- endif %}
+        }
+    }
+        {%- when None %}
+    {%- endmatch %}
+{%- endfor %}
+
+    private send(
+        method: string,
+        path: string,
+        query: string,
+        body: BodyInit | undefined,
+        headers: Record<string, string>,
+    ): Promise<Response> {
+        const token = this.token();
+        if (token !== null) {
+            headers[BLUEFIRE_TOKEN_HEADER] = token;
+        }
+        // Browsers set the content type of forms themselves (including the multipart boundary).
+        if (typeof body === "string") {
+            headers["Content-Type"] = "application/json";
+        }
+
+        const url = query.length > 0 ? this.host + path + "?" + query : this.host + path;
+        return fetch(url, { method, headers, body });
+    }
+}
@@ -1,16 +1,68 @@
 {% for yeeld in api.yields %}
     {% let yield_name = yeeld.name.camel_case() + "Yield" %}
 
-    #[derive(Clone, Debug, Serialize, Deserialize)]
-    pub struct {{ yield_name }} {
-        {% for arg in yeeld.args %}
-            pub {{ arg.name().snake_case() }}: {{ arg.rust_type() }},
-        {% endfor %}
-    }
+    {% if yeeld.is_binary() %}
+        #[derive(Clone, Debug, Serialize, Deserialize)]
+        pub struct {{ yield_name }} {
+            pub content_type: String,
+            pub data: bluefire_twine::Bytes,
+        }
+
+        impl {{ yield_name }} {
+            pub fn new(data: Vec<u8>) -> Self {
+                Self {
+                    content_type: "{{ yeeld.default_content_type() }}".to_string(),
+                    data: bluefire_twine::Bytes::new(data),
+                }
+            }
+
+            pub fn with_content_type(mut self, content_type: &str) -> Self {
+                self.content_type = content_type.to_string();
+                self
+            }
+        }
+    {% else %}
+        #[derive(Clone, Debug, Serialize, Deserialize)]
+        pub struct {{ yield_name }} {
+            {% for arg in yeeld.args %}
+                pub {{ arg.name().snake_case() }}: {{ arg.rust_type() }},
+            {% endfor %}
+        }
+    {% endif %}
 
     impl {{ yield_name }} {
         pub fn get_code(&self) -> http::StatusCode {
             {{ yeeld.code.rust_format() }}
         }
     }
+
+    {% let found_pagination = generator.find_pagination(yeeld.name.clone(), api.methods) %}
+    {% match found_pagination %}
+        {% when Some with (pagination) %}
+            impl bluefire_twine::pagination::Page for {{ yield_name }} {
+                type Item = {{ generator.item_type(pagination.item) }};
+
+                fn items(&self) -> &[Self::Item] {
+                    &self.items
+                }
+
+                fn next_page(
+                    &self,
+                    params: &bluefire_twine::pagination::PageParams,
+                ) -> Option<bluefire_twine::pagination::PageParams> {
+                    {% if pagination.style.is_cursor() %}
+                        self.next_cursor.as_ref().map(|cursor| bluefire_twine::pagination::PageParams {
+                            cursor: Some(cursor.clone()),
+                            ..params.clone()
+                        })
+                    {% else %}
+                        self.next_offset.map(|offset| bluefire_twine::pagination::PageParams {
+                            offset,
+                            ..params.clone()
+                        })
+                    {% endif %}
+                }
+            }
+        {% when None %}
+    {% endmatch %}
 {% endfor %}
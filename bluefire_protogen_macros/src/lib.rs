@@ -6,6 +6,10 @@
 //! The goal of `bluefire_protogen` is to define the structure of an HTTP API in one place (YAML
 //! file) and generate `Rust` code from it to mitigate later need to modify the code in many places
 //! (potentially introducing bugs).
+//!
+//! The generated code serializes headers, cookies and forms with `bluefire_twine` so the crate using
+//! the macros needs its `urlencoded` feature. Specifications with `url` or `pattern` checks also
+//! need the `validation` feature.
 
 // TODO: Uncomment after https://github.com/rust-lang/rust/issues/42008 is fixed
 // #![warn(missing_docs)]
@@ -20,6 +24,7 @@ use std::path::PathBuf;
 
 struct Config {
     file_path: String,
+    span: proc_macro2::Span,
 }
 
 // -------------------------------------------------------------------------------------------------
@@ -33,27 +38,55 @@ fn as_cargo_absolute_path(relative_path: &str) -> PathBuf {
 
 // -------------------------------------------------------------------------------------------------
 
-fn parse_params(stream: proc_macro::TokenStream) -> Config {
-    let lit: syn::Lit = syn::parse(stream).expect("Failed to parse input into a literal");
+fn parse_params(stream: proc_macro2::TokenStream) -> Result<Config, syn::Error> {
+    let lit: syn::Lit = syn::parse2(stream)?;
     match lit {
-        syn::Lit::Str(lit_str) => Config { file_path: lit_str.value() },
-        _ => panic!("The argument should be a string"),
+        syn::Lit::Str(lit_str) => Ok(Config { file_path: lit_str.value(), span: lit_str.span() }),
+        lit => Err(syn::Error::new_spanned(lit, "The argument should be a string")),
     }
 }
 
+fn read_routes(config: &Config) -> Result<bluefire_protogen::spec::Routes, syn::Error> {
+    let path = as_cargo_absolute_path(&config.file_path);
+    bluefire_protogen::spec::Routes::read(&path)
+        .map_err(|err| syn::Error::new(config.span, err.to_string()))
+}
+
+/// Generates code making the compiler track changes in the specification file and all the files it
+/// imports, so that the macro is expanded again when any of them changes.
+fn track_files(config: &Config) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let path = as_cargo_absolute_path(&config.file_path);
+    let mut paths = bluefire_protogen::imports::imported_files(&path)
+        .map_err(|err| syn::Error::new(config.span, err.to_string()))?;
+    paths.insert(0, path);
+
+    let literals = paths
+        .iter()
+        .map(|path| syn::LitStr::new(&path.display().to_string(), config.span))
+        .collect::<Vec<_>>();
+    Ok(quote::quote! { #(const _: &[u8] = include_bytes!(#literals);)* })
+}
+
+fn read_routes_tracked(
+    stream: proc_macro::TokenStream,
+) -> Result<(bluefire_protogen::spec::Routes, proc_macro2::TokenStream), syn::Error> {
+    let config = parse_params(stream.into())?;
+    Ok((read_routes(&config)?, track_files(&config)?))
+}
+
 // -------------------------------------------------------------------------------------------------
 
 /// Generates `bluefire_backend::Route` from API definition.
 #[proc_macro]
 pub fn routes(stream: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    let config = parse_params(stream);
-    let path = as_cargo_absolute_path(&config.file_path);
-    let input = std::fs::read_to_string(&path).expect(&format!("Read '{}' file", config.file_path));
-    let routes =
-        bluefire_protogen::spec::Routes::from_str(&input).expect(&format!("Parse {:?} file", path));
+    let (routes, tracking) = match read_routes_tracked(stream) {
+        Ok(result) => result,
+        Err(err) => return err.to_compile_error().into(),
+    };
     let generator = bluefire_protogen::rust_generator::RustGenerator::new();
-    let result = generator.generate_routes(&routes);
-    result.parse().expect("Parse into TokenStream")
+    let result: proc_macro2::TokenStream =
+        generator.generate_routes(&routes).parse().expect("Parse into TokenStream");
+    quote::quote!({ #tracking #result }).into()
 }
 
 // -------------------------------------------------------------------------------------------------
@@ -63,12 +96,48 @@ pub fn routes(stream: proc_macro::TokenStream) -> proc_macro::TokenStream {
 /// The generated structures provide constructors for parametrized path.
 #[proc_macro]
 pub fn paths(stream: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    let config = parse_params(stream);
-    let path = as_cargo_absolute_path(&config.file_path);
-    let input = std::fs::read_to_string(&path).expect(&format!("Read '{}' file", config.file_path));
-    let routes =
-        bluefire_protogen::spec::Routes::from_str(&input).expect(&format!("Parse {:?} file", path));
+    let (routes, tracking) = match read_routes_tracked(stream) {
+        Ok(result) => result,
+        Err(err) => return err.to_compile_error().into(),
+    };
     let generator = bluefire_protogen::rust_generator::RustGenerator::new();
-    let result = generator.generate_paths(&routes);
-    result.parse().expect("Parse into TokenStream")
+    let result: proc_macro2::TokenStream =
+        generator.generate_paths(&routes).parse().expect("Parse into TokenStream");
+    quote::quote!(#tracking #result).into()
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_params, read_routes, track_files};
+
+    fn tokens(source: &str) -> proc_macro2::TokenStream {
+        source.parse().expect("Parse tokens")
+    }
+
+    fn error_of<T>(result: Result<T, syn::Error>) -> String {
+        match result {
+            Ok(_) => panic!("Expected an error"),
+            Err(err) => err.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_params() {
+        let file_path = parse_params(tokens(r#""api.yaml""#)).map(|config| config.file_path);
+        assert_eq!(file_path.ok(), Some("api.yaml".to_string()));
+        assert_eq!(error_of(parse_params(tokens("42"))), "The argument should be a string");
+        assert!(parse_params(tokens("api.yaml")).is_err());
+    }
+
+    #[test]
+    fn test_missing_spec() {
+        let config = match parse_params(tokens(r#""missing/api.yaml""#)) {
+            Ok(config) => config,
+            Err(err) => panic!("{}", err),
+        };
+        assert!(error_of(read_routes(&config)).contains("missing/api.yaml"));
+        assert!(error_of(track_files(&config)).contains("missing/api.yaml"));
+    }
 }
@@ -6,6 +6,10 @@
 //! This includes:
 //!  - `css` files with default themes
 //!  - `js` files for interacting popular JS libraries from WASM
+//!
+//! With `dev` feature enabled the files are served from the disk instead of being embedded in the
+//! binary, SCSS files are recompiled on change and `make_live_reload` provides a `LiveReload`
+//! reloading the pages whenever the files change.
 
 #![warn(missing_docs)]
 
@@ -42,18 +42,231 @@ pub enum Type {
 
         /// Name of the generated field for the binding Javascript file.
         field_name_js: String,
+
+        /// Target triple the WASM file was compiled for.
+        #[serde(default = "default_wasm_triple")]
+        triple: String,
+
+        /// Kind of the generated binding Javascript file.
+        #[serde(default)]
+        target: WasmTarget,
+
+        /// Optimization level for `wasm-opt`. `wasm-opt` is not run if not given.
+        #[serde(default)]
+        wasm_opt: Option<WasmOptLevel>,
+
+        /// If `true` debug sections are removed from the WASM file. By default debug sections are
+        /// removed only in release builds.
+        #[serde(default)]
+        strip_debug: Option<bool>,
+
+        /// If `true` `wasm-bindgen` keeps the debug information of the WASM file. Ignored when debug
+        /// sections are removed.
+        #[serde(default)]
+        keep_debug: bool,
+    },
+
+    /// CSS file. The file is minified.
+    #[serde(rename = "css")]
+    Css {
+        /// Name of the generated field.
+        field_name: String,
+    },
+
+    /// HTML file.
+    #[serde(rename = "html")]
+    Html {
+        /// Name of the generated field.
+        field_name: String,
+    },
+
+    /// JSON file.
+    #[serde(rename = "json")]
+    Json {
+        /// Name of the generated field.
+        field_name: String,
+    },
+
+    /// SVG image.
+    #[serde(rename = "svg")]
+    Svg {
+        /// Name of the generated field.
+        field_name: String,
+    },
+
+    /// Raster image (e.g. `png`, `jpg`, `gif`, `webp`, `ico`).
+    #[serde(rename = "image")]
+    Image {
+        /// Extension of the image file.
+        extension: String,
+
+        /// Name of the generated field.
+        field_name: String,
+    },
+
+    /// Font file (e.g. `woff`, `woff2`, `ttf`, `otf`).
+    #[serde(rename = "font")]
+    Font {
+        /// Extension of the font file.
+        extension: String,
+
+        /// Name of the generated field.
+        field_name: String,
+    },
+
+    /// All files matching the glob pattern (relative to `source_dir`), e.g. `img/**/*.png`. The
+    /// files are served under their paths relative to `source_dir` with slashes replaced by
+    /// dashes; SCSS files are compiled and served with the `css` extension. Names of the generated
+    /// fields are made from the served names by replacing all non-alphanumeric characters with
+    /// underscores (and prefixing them with an underscore if they would start with a digit). Files
+    /// ending up with the same name or field name are reported as an error.
+    #[serde(rename = "glob")]
+    Glob {
+        /// Pattern matching the files.
+        pattern: String,
+
+        /// Prefix for names of the generated fields.
+        #[serde(default)]
+        field_prefix: String,
     },
 }
 
+impl Type {
+    /// Returns the extension of the output file for single-file sources.
+    pub fn output_extension(&self) -> Option<&str> {
+        match self {
+            Type::Js { .. } => Some("js"),
+            Type::Scss { .. } => Some("css"),
+            Type::Wasm { .. } => Some("wasm"),
+            Type::Css { .. } => Some("css"),
+            Type::Html { .. } => Some("html"),
+            Type::Json { .. } => Some("json"),
+            Type::Svg { .. } => Some("svg"),
+            Type::Image { extension, .. } => Some(extension),
+            Type::Font { extension, .. } => Some(extension),
+            Type::Glob { .. } => None,
+        }
+    }
+
+    /// Returns the extension of the input file for sources read from the source directory.
+    pub fn input_extension(&self) -> Option<&str> {
+        match self {
+            Type::Scss { .. } => Some("scss"),
+            Type::Wasm { .. } | Type::Glob { .. } => None,
+            _ => self.output_extension(),
+        }
+    }
+}
+
+/// Returns the content type for a file with the given extension.
+pub fn content_type(extension: &str) -> &'static str {
+    match extension.to_lowercase().as_str() {
+        "css" => "text/css",
+        "js" => "application/javascript",
+        "wasm" => "application/wasm",
+        "html" | "htm" => "text/html",
+        "json" => "application/json",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "ico" => "image/x-icon",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "ttf" => "font/ttf",
+        "otf" => "font/otf",
+        "txt" => "text/plain",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Kind of the binding Javascript file generated by `wasm-bindgen`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WasmTarget {
+    /// ES module loaded with `<script type="module">`.
+    #[serde(rename = "web")]
+    Web,
+
+    /// Script defining a global `wasm_bindgen` function.
+    #[serde(rename = "no-modules")]
+    NoModules,
+}
+
+impl Default for WasmTarget {
+    fn default() -> Self {
+        WasmTarget::NoModules
+    }
+}
+
+/// Optimization level passed to `wasm-opt` (`O0` to `O4` optimize for speed, `Os` and `Oz` for
+/// size).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WasmOptLevel {
+    #[allow(missing_docs)]
+    O0,
+    #[allow(missing_docs)]
+    O1,
+    #[allow(missing_docs)]
+    O2,
+    #[allow(missing_docs)]
+    O3,
+    #[allow(missing_docs)]
+    O4,
+    #[allow(missing_docs)]
+    Os,
+    #[allow(missing_docs)]
+    Oz,
+}
+
+impl WasmOptLevel {
+    /// Returns the command line flag for the level.
+    pub fn to_flag(&self) -> &'static str {
+        match self {
+            WasmOptLevel::O0 => "-O0",
+            WasmOptLevel::O1 => "-O1",
+            WasmOptLevel::O2 => "-O2",
+            WasmOptLevel::O3 => "-O3",
+            WasmOptLevel::O4 => "-O4",
+            WasmOptLevel::Os => "-Os",
+            WasmOptLevel::Oz => "-Oz",
+        }
+    }
+}
+
+fn default_wasm_triple() -> String {
+    "wasm32-unknown-unknown".to_string()
+}
+
+/// Compiler used for SCSS files.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ScssBackend {
+    /// In-process compiler (`rsass`).
+    #[serde(rename = "native")]
+    Native,
+
+    /// External `sass` command.
+    #[serde(rename = "external")]
+    External,
+}
+
+impl Default for ScssBackend {
+    fn default() -> Self {
+        ScssBackend::Native
+    }
+}
+
 /// Source of the data.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Source {
     /// Basename of the input file. The extension is deduced from the type and path the file is
-    /// provided by `Spec`.
+    /// provided by `Spec`. Ignored for glob sources.
+    #[serde(default)]
     pub input_base_name: String,
 
     /// Basename of the file as it will be accessible from the server. The extension is deduced
-    /// from the type and path the file is
+    /// from the type and path the file is provided by `Spec`. Ignored for glob sources.
+    #[serde(default)]
     pub output_base_name: String,
 
     /// Type of the file plus additional options.
@@ -69,30 +282,109 @@ pub struct Spec {
 
     /// Source files to be taken into consideration.
     pub sources: Vec<Source>,
+
+    /// If `true` (default) gzip and Brotli compressed variants of every output file are created
+    /// at build time and served to clients accepting them.
+    #[serde(default = "default_precompress")]
+    pub precompress: bool,
+
+    /// Number of seconds the files may be cached by clients without revalidation. If not given,
+    /// clients have to revalidate the files before each use.
+    #[serde(default)]
+    pub max_age: Option<u32>,
+
+    /// If `true` (default) the names of served files contain the hash of their content (e.g.
+    /// `theme-gray.3fa9c1.css`) and the files are served as immutable. Links to the files have to
+    /// be resolved through the generated name provider or the manifest. Set to `false` to serve
+    /// the files under their plain names.
+    #[serde(default = "default_hash_names")]
+    pub hash_names: bool,
+
+    /// Path (relative to the crate manifest directory) where a copy of the JSON manifest mapping
+    /// names of the files to hashed names should be written.
+    #[serde(default)]
+    pub manifest: Option<String>,
+
+    /// Compiler used for SCSS files. The in-process compiler is used by default.
+    #[serde(default)]
+    pub scss_backend: ScssBackend,
+}
+
+fn default_precompress() -> bool {
+    true
+}
+
+fn default_hash_names() -> bool {
+    true
 }
 
 impl Spec {
     /// Read the spec from the given file.
-    pub fn read(path: PathBuf) -> Self {
-        let input = {
-            match std::fs::read_to_string(&path) {
-                Ok(input) => input,
-                Err(err) => panic!("Failed to read file {:?}: {}", path, err),
-            }
-        };
+    pub fn read(path: PathBuf) -> Result<Self, Error> {
+        let input = std::fs::read_to_string(&path)
+            .map_err(|err| Error::Io { path: path.clone(), message: err.to_string() })?;
+        serde_yaml::from_str::<Self>(&input).map_err(|err| Error::from_yaml(path, err))
+    }
+}
 
-        let spec = {
-            match serde_yaml::from_str::<Self>(&input) {
-                Ok(spec) => spec,
-                Err(err) => panic!("Failed to parse file {:?}: {}", path, err),
-            }
-        };
+// -------------------------------------------------------------------------------------------------
+
+/// Error returned when the spec could not be read.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The file could not be read.
+    Io {
+        /// Path to the file.
+        path: PathBuf,
+
+        /// Description of the problem.
+        message: String,
+    },
 
-        spec
+    /// The file is not a valid spec.
+    Yaml {
+        /// Path to the file.
+        path: PathBuf,
+
+        /// Line (starting from 1) of the invalid node, if known.
+        line: Option<usize>,
+
+        /// Column (starting from 1) of the invalid node, if known.
+        column: Option<usize>,
+
+        /// Description of the problem.
+        message: String,
+    },
+}
+
+impl Error {
+    /// Constructs a new `Error` from a YAML parsing error.
+    pub fn from_yaml(path: PathBuf, err: serde_yaml::Error) -> Self {
+        let location = err.location();
+        Error::Yaml {
+            path,
+            line: location.as_ref().map(|location| location.line()),
+            column: location.as_ref().map(|location| location.column()),
+            message: err.to_string(),
+        }
     }
 }
 
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Io { path, message } => write!(f, "{}: {}", path.display(), message),
+            Error::Yaml { path, line: Some(line), column: Some(column), message } => {
+                write!(f, "{}:{}:{}: {}", path.display(), line, column, message)
+            }
+            Error::Yaml { path, message, .. } => write!(f, "{}: {}", path.display(), message),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
 /// Prelude for this crate.
 pub mod prelude {
-    pub use super::{Source, Spec, Type};
+    pub use super::{content_type, ScssBackend, Source, Spec, Type, WasmOptLevel, WasmTarget};
 }
@@ -18,7 +18,22 @@ mod static_files;
 ///
 /// # Parameters
 ///
+/// * `path` - directory (relative to the crate manifest) containing `static.yaml`.
 /// * `namespace` - directory in the server from which the files will be served.
+/// * `dev_feature` - name of the feature of the calling crate enabling the development mode
+///   (`dev` by default).
+///
+/// # Development mode
+///
+/// The generated code chooses between embedded files and files served from the disk with
+/// `#[cfg(feature = ...)]` attributes, which are evaluated in the calling crate. Therefore the
+/// calling crate has to define the feature named by `dev_feature`, enabling the `live_reload`
+/// feature of `bluefire_backend` and depending on `bluefire_build`, e.g.:
+///
+/// ```toml
+/// [features]
+/// dev = ["bluefire_backend/live_reload", "bluefire_build"]
+/// ```
 #[proc_macro_attribute]
 pub fn generate(
     attributes: proc_macro::TokenStream,
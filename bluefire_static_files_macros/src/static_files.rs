@@ -7,16 +7,21 @@ use std::path::PathBuf;
 
 use askama::Template;
 
-use bluefire_build::cargo;
+use bluefire_build::{
+    cargo,
+    compression::{self, BROTLI_EXTENSION, GZIP_EXTENSION},
+    error::BuildError,
+    hashing, static_files,
+};
 use bluefire_static_files_core::prelude::*;
 
 const DEFAULT_PATH: &str = ".";
 const DEFAULT_NAMESPACE: &str = "_";
+const DEFAULT_DEV_FEATURE: &str = "dev";
 const TEMPLATE_FILENAME: &str = "static.yaml";
 
-const CONTENT_TYPE_CSS: &str = "text/css";
-const CONTENT_TYPE_JS: &str = "application/javascript";
-const CONTENT_TYPE_WASM: &str = "application/wasm";
+const SCSS_PROCESSOR_NATIVE: &str = "bluefire_build::static_files::compile_scss";
+const SCSS_PROCESSOR_EXTERNAL: &str = "bluefire_build::static_files::compile_scss_external";
 
 // -------------------------------------------------------------------------------------------------
 
@@ -24,6 +29,12 @@ const CONTENT_TYPE_WASM: &str = "application/wasm";
 struct Config {
     template_path: PathBuf,
     namespace: String,
+
+    /// Name of the feature of the calling crate enabling the development mode.
+    dev_feature: String,
+
+    /// Span errors related to the spec file are reported at.
+    template_span: proc_macro2::Span,
 }
 
 impl Default for Config {
@@ -31,6 +42,8 @@ impl Default for Config {
         Self {
             template_path: cargo::as_absolute_path(DEFAULT_PATH),
             namespace: DEFAULT_NAMESPACE.to_string(),
+            dev_feature: DEFAULT_DEV_FEATURE.to_string(),
+            template_span: proc_macro2::Span::call_site(),
         }
     }
 }
@@ -39,108 +52,232 @@ struct Info {
     struct_name: proc_macro2::Ident,
 }
 
-fn parse_attributes(attributes: proc_macro::TokenStream) -> Config {
+fn parse_attributes(attributes: proc_macro2::TokenStream) -> Result<Config, syn::Error> {
+    fn parse_string(value: &syn::MetaNameValue, name: &str) -> Result<syn::LitStr, syn::Error> {
+        match value.lit {
+            syn::Lit::Str(ref lit_str) => Ok(lit_str.clone()),
+            ref lit => {
+                Err(syn::Error::new_spanned(lit, format!("Argument '{}' must be a string", name)))
+            }
+        }
+    }
+
     let mut config = Config::default();
-    if !attributes.is_empty() {
-        let meta: syn::Meta = syn::parse(attributes).expect("failed to parse attributes");
+    let parser = syn::punctuated::Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated;
+    for meta in syn::parse::Parser::parse2(parser, attributes)? {
         match meta {
-            syn::Meta::NameValue(value) => {
-                let a = value.path.get_ident().expect("Get ident").to_string();
-                match a.as_ref() {
-                    "path" => match value.lit {
-                        syn::Lit::Str(ref lit_str) => {
-                            let path = cargo::as_absolute_path(&lit_str.value());
-                            config.template_path = path;
-                        }
-                        _ => panic!("Argument '{}' must be a string", a),
-                    },
-                    "namespace" => match value.lit {
-                        syn::Lit::Str(ref lit_str) => {
-                            config.namespace = lit_str.value();
-                        }
-                        _ => panic!("Argument '{}' must be a string", a),
-                    },
-                    _ => panic!("Unknown argument '{}'", a),
+            syn::Meta::NameValue(ref value) => {
+                let name = match value.path.get_ident() {
+                    Some(ident) => ident.to_string(),
+                    None => {
+                        return Err(syn::Error::new_spanned(&value.path, "Expected an identifier"))
+                    }
+                };
+                match name.as_ref() {
+                    "path" => {
+                        let lit_str = parse_string(value, &name)?;
+                        config.template_path = cargo::as_absolute_path(&lit_str.value());
+                        config.template_span = lit_str.span();
+                    }
+                    "namespace" => {
+                        config.namespace = parse_string(value, &name)?.value();
+                    }
+                    "dev_feature" => {
+                        config.dev_feature = parse_string(value, &name)?.value();
+                    }
+                    _ => {
+                        return Err(syn::Error::new_spanned(
+                            &value.path,
+                            format!("Unknown argument '{}'", name),
+                        ))
+                    }
                 }
             }
-            _ => panic!("All arguments are expected to be name-value"),
+            meta => {
+                return Err(syn::Error::new_spanned(
+                    meta,
+                    "All arguments are expected to be name-value",
+                ))
+            }
         }
     }
-    config
+    Ok(config)
 }
 
-fn parse_input(input: proc_macro::TokenStream) -> Info {
-    let item: syn::Item = syn::parse(input).expect("Failed to parse token stream");
+fn parse_input(input: proc_macro2::TokenStream) -> Result<Info, syn::Error> {
+    let item: syn::Item = syn::parse2(input)?;
     match item {
         syn::Item::Struct(item_struct) => {
             match item_struct.fields {
                 syn::Fields::Unit => {}
-                _ => panic!("This macro can be applied only to unit structures"),
+                ref fields => {
+                    return Err(syn::Error::new_spanned(
+                        fields,
+                        "This macro can be applied only to unit structures",
+                    ))
+                }
             }
-            Info { struct_name: item_struct.ident }
+            Ok(Info { struct_name: item_struct.ident })
         }
-        _ => panic!("This macro can be applied only to structures"),
+        item => Err(syn::Error::new_spanned(item, "This macro can be applied only to structures")),
     }
 }
 
 // -------------------------------------------------------------------------------------------------
 
-fn read_template(config: &Config) -> Spec {
+fn read_template(config: &Config) -> Result<Spec, syn::Error> {
     let mut path = cargo::get_manifest_path();
     path.push(&config.template_path);
     path.push(TEMPLATE_FILENAME);
-    Spec::read(path)
+    Spec::read(path).map_err(|err| syn::Error::new(config.template_span, err.to_string()))
 }
 
 // -------------------------------------------------------------------------------------------------
 
-/// Helper structure for calling rust code from within a template.
+/// Paths to precompressed variants of a file.
 #[derive(Clone, Debug)]
-struct GeneratorCallback;
+struct Precompressed {
+    /// Path to the gzip-compressed variant.
+    gzip_path: String,
 
-impl GeneratorCallback {
-    /// Constructs a new `GeneratorCallback`.
-    pub fn new() -> Self {
-        Self
-    }
+    /// Path to the Brotli-compressed variant.
+    brotli_path: String,
+}
+
+/// Describes a single file served by the generated route.
+#[derive(Clone, Debug)]
+struct FileInfo {
+    /// Name of the generated field.
+    field_name: String,
+
+    /// Path the file will be read from.
+    input_path: String,
+
+    /// Name through which the file will be accessible on the server.
+    output_name: String,
+
+    /// Name through which the file will be accessible on the server in development mode.
+    dev_name: String,
+
+    /// Path the file will be read from in development mode.
+    dev_path: String,
 
-    /// Generates
-    ///  - input paths for the given source to be read from
-    ///  - output paths through with they will be accessible on the server
-    ///  - content type of the given resource
-    pub fn make_paths(&self, source: &Source) -> Vec<(String, String, String)> {
-        fn make_input_path(source: &Source, suffix: &str, extension: &str) -> String {
-            let mut path = cargo::get_out_dir();
-            path.push(source.input_base_name.clone() + suffix);
-            path.set_extension(extension);
-            path.to_str().expect("Cast path to a string").to_string()
+    /// Function processing the file before serving it in development mode.
+    dev_processor: Option<String>,
+
+    /// Content type of the file.
+    content_type: String,
+
+    /// Entity tag of the file (hash of its content).
+    etag: String,
+
+    /// Subresource Integrity hash of the file (only for JS and WASM files).
+    integrity: Option<String>,
+
+    /// Time of the last modification of the file in seconds since Unix epoch.
+    last_modified: i64,
+
+    /// Cache policy the file will be served with.
+    cache_policy: String,
+
+    /// Paths to compressed variants of the file if they were created.
+    precompressed: Option<Precompressed>,
+}
+
+/// Generates info about all the files defined in the spec:
+///  - input paths for the given source to be read from
+///  - output names through with they will be accessible on the server
+///  - paths and processors used in development mode
+///  - content type of the given resource
+///  - entity tag, integrity hash and modification time of the given resource
+///  - paths to the precompressed variants of the resource
+fn make_files(spec: &Spec) -> Result<Vec<FileInfo>, BuildError> {
+    let root = cargo::get_manifest_path();
+    let scss_processor = match spec.scss_backend {
+        ScssBackend::Native => SCSS_PROCESSOR_NATIVE,
+        ScssBackend::External => SCSS_PROCESSOR_EXTERNAL,
+    };
+
+    let mut result = Vec::new();
+    for source in spec.sources.iter() {
+        for output_file in static_files::output_files(&root, spec, source)? {
+            let input_path = &output_file.path;
+            let precompressed = if spec.precompress && compression::is_compressible(input_path) {
+                let gzip_path = compression::compressed_path(input_path, GZIP_EXTENSION);
+                let brotli_path = compression::compressed_path(input_path, BROTLI_EXTENSION);
+                Some(Precompressed {
+                    gzip_path: path_to_string(&gzip_path),
+                    brotli_path: path_to_string(&brotli_path),
+                })
+            } else {
+                None
+            };
+
+            let cache_policy = match (spec.hash_names, spec.max_age) {
+                (true, _) => "CachePolicy::Immutable".to_string(),
+                (false, Some(max_age)) => format!("CachePolicy::MaxAge({})", max_age),
+                (false, None) => "CachePolicy::Revalidate".to_string(),
+            };
+
+            let (dev_path, dev_processor) = match &output_file.source_path {
+                Some(path) if path.extension().and_then(|e| e.to_str()) == Some("scss") => {
+                    (path.clone(), Some(scss_processor.to_string()))
+                }
+                Some(path) => (path.clone(), None),
+                None => (input_path.clone(), None),
+            };
+
+            let integrity = match input_path.extension().and_then(|extension| extension.to_str()) {
+                Some("js") | Some("wasm") => Some(hashing::file_integrity(input_path)?),
+                _ => None,
+            };
+
+            result.push(FileInfo {
+                field_name: output_file.field_name.clone(),
+                input_path: path_to_string(input_path),
+                output_name: static_files::served_name(spec, &output_file)?,
+                dev_name: output_file.name.clone(),
+                dev_path: path_to_string(&dev_path),
+                dev_processor,
+                content_type: content_type_of(input_path).to_string(),
+                etag: hashing::file_hash(input_path)?,
+                integrity,
+                last_modified: hashing::file_modification_timestamp(input_path)?,
+                cache_policy,
+                precompressed,
+            });
         }
+    }
+    Ok(result)
+}
 
-        match &source.variant {
-            Type::Scss { .. } => {
-                let input_path = make_input_path(&source, "", "css");
-                let output_name = source.output_base_name.clone() + ".css";
-                vec![(input_path, output_name, CONTENT_TYPE_CSS.to_string())]
-            }
-            Type::Js { .. } => {
-                let input_path = make_input_path(&source, "", "js");
-                let output_name = source.output_base_name.clone() + ".js";
-                vec![(input_path, output_name, CONTENT_TYPE_JS.to_string())]
-            }
-            Type::Wasm { .. } => {
-                let input_path_wasm = make_input_path(&source, "_bg", "wasm");
-                let output_name_wasm = source.output_base_name.clone() + ".wasm";
+fn content_type_of(path: &PathBuf) -> &'static str {
+    let extension = path.extension().and_then(|extension| extension.to_str()).unwrap_or("");
+    content_type(extension)
+}
 
-                let input_path_js = make_input_path(&source, "", "js");
-                let output_name_js = source.output_base_name.clone() + ".js";
+fn path_to_string(path: &PathBuf) -> String {
+    path.to_str().expect("Cast path to a string").to_string()
+}
 
-                vec![
-                    (input_path_wasm, output_name_wasm, CONTENT_TYPE_WASM.to_string()),
-                    (input_path_js, output_name_js, CONTENT_TYPE_JS.to_string()),
-                ]
-            }
+/// Returns the path to the source directory of the spec. In development mode the whole directory is
+/// watched, so that changes of files included by other files (like SCSS partials) are noticed.
+fn make_source_dir(spec: &Spec) -> String {
+    let mut source_dir = cargo::get_manifest_path();
+    source_dir.push(&spec.source_dir);
+    path_to_string(&source_dir)
+}
+
+/// Returns paths watched in development mode: the source directory and the files served from
+/// outside of it (like the WASM files).
+fn make_watched_paths(source_dir: &str, files: &[FileInfo]) -> Vec<String> {
+    let mut result = vec![source_dir.to_string()];
+    for file in files.iter() {
+        if !file.dev_path.starts_with(source_dir) && !result.contains(&file.dev_path) {
+            result.push(file.dev_path.clone());
         }
     }
+    result
 }
 
 // -------------------------------------------------------------------------------------------------
@@ -151,18 +288,16 @@ impl GeneratorCallback {
 struct StaticFilesTemplate<'a> {
     pub config: &'a Config,
     pub info: &'a Info,
-    pub spec: &'a Spec,
-    pub generator: GeneratorCallback,
+    pub files: &'a [FileInfo],
+    pub source_dir: String,
+    pub watched_paths: Vec<String>,
 }
 
 impl<'a> StaticFilesTemplate<'a> {
-    pub fn new(
-        config: &'a Config,
-        info: &'a Info,
-        spec: &'a Spec,
-        generator: GeneratorCallback,
-    ) -> Self {
-        Self { config, info, spec, generator }
+    pub fn new(config: &'a Config, info: &'a Info, files: &'a [FileInfo], spec: &Spec) -> Self {
+        let source_dir = make_source_dir(spec);
+        let watched_paths = make_watched_paths(&source_dir, files);
+        Self { config, info, files, source_dir, watched_paths }
     }
 }
 
@@ -172,14 +307,78 @@ pub fn generate(
     attributes: proc_macro::TokenStream,
     input: proc_macro::TokenStream,
 ) -> proc_macro::TokenStream {
-    let config = parse_attributes(attributes);
-    let info = parse_input(input);
-    let spec = read_template(&config);
-    let gen = GeneratorCallback::new();
+    match try_generate(attributes, input) {
+        Ok(stream) => stream,
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn try_generate(
+    attributes: proc_macro::TokenStream,
+    input: proc_macro::TokenStream,
+) -> Result<proc_macro::TokenStream, syn::Error> {
+    let config = parse_attributes(attributes.into())?;
+    let info = parse_input(input.into())?;
+    let spec = read_template(&config)?;
+    let files = make_files(&spec).map_err(|err| syn::Error::new(config.template_span, err))?;
 
-    StaticFilesTemplate::new(&config, &info, &spec, gen)
+    Ok(StaticFilesTemplate::new(&config, &info, &files, &spec)
         .render()
         .expect("Render template")
         .parse()
-        .expect("Parse template into a token stream")
+        .expect("Parse template into a token stream"))
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_attributes, parse_input, read_template, Config};
+
+    fn tokens(source: &str) -> proc_macro2::TokenStream {
+        source.parse().expect("Parse tokens")
+    }
+
+    fn attributes_error(source: &str) -> String {
+        parse_attributes(tokens(source)).unwrap_err().to_string()
+    }
+
+    fn input_error(source: &str) -> Option<String> {
+        parse_input(tokens(source)).err().map(|err| err.to_string())
+    }
+
+    #[test]
+    fn test_parse_attributes() {
+        let config = parse_attributes(tokens(r#"namespace = "files", dev_feature = "debug""#));
+        let config = config.unwrap();
+        assert_eq!(config.namespace, "files");
+        assert_eq!(config.dev_feature, "debug");
+
+        assert_eq!(attributes_error(r#"name = "files""#), "Unknown argument 'name'");
+        assert_eq!(attributes_error("path = 1"), "Argument 'path' must be a string");
+        assert_eq!(attributes_error("path"), "All arguments are expected to be name-value");
+    }
+
+    #[test]
+    fn test_parse_input() {
+        assert_eq!(input_error("struct Files;"), None);
+        assert_eq!(
+            input_error("struct Files { count: u32 }").as_ref().map(String::as_str),
+            Some("This macro can be applied only to unit structures")
+        );
+        assert_eq!(
+            input_error("enum Files {}").as_ref().map(String::as_str),
+            Some("This macro can be applied only to structures")
+        );
+    }
+
+    #[test]
+    fn test_read_template_error() {
+        let template_path = std::env::temp_dir().join("bluefire_static_files_macros_missing");
+        let config = Config { template_path, ..Config::default() };
+        match read_template(&config) {
+            Err(err) => assert!(err.to_string().contains("static.yaml")),
+            Ok(_) => panic!("Expected an error"),
+        }
+    }
 }
@@ -1,53 +1,103 @@
 /// Provides info about static files.
 pub struct {{ info.struct_name }} {
-    {% for source in spec.sources %}
-        {% match source.variant %}
-            {% when Type::Js with { field_name } %}
-                /// `/{}/{{ config.namespace }}/{{ source.output_base_name }}.js`
-                pub {{ field_name }}: String,
-            {% when Type::Scss with { field_name } %}
-                /// `/{}/{{ config.namespace }}/{{ source.output_base_name }}.css`
-                pub {{ field_name }}: String,
-            {% when Type::Wasm with { field_name_wasm, field_name_js, target_path } %}
-                /// `/{}/{{ config.namespace }}/{{ source.output_base_name }}.wasm`
-                pub {{ field_name_wasm }}: String,
-                /// `/{}/{{ config.namespace }}/{{ source.output_base_name }}.js`
-                pub {{ field_name_js }}: String,
-        {% endmatch %}
+    {% for file in files %}
+        /// `/{}/{{ config.namespace }}/{{ file.output_name }}`
+        pub {{ file.field_name }}: String,
+        {% if file.integrity.is_some() %}
+            /// Subresource Integrity hash of `{{ file.field_name }}` for use in `integrity`
+            /// attribute (empty in development mode).
+            pub {{ file.field_name }}_integrity: String,
+        {% endif %}
     {% endfor %}
 }
 
 impl {{ info.struct_name }} {
     /// Constructs a new `{{ info.struct_name }}`.
+    #[cfg(not(feature = "{{ config.dev_feature }}"))]
     pub fn new(static_root: &str) -> Self {
         Self {
-            {% for source in spec.sources %}
-                {% match source.variant %}
-                    {% when Type::Js with { field_name } %}
-                        {{ field_name }}: format!("/{}/{{ config.namespace }}/{{ source.output_base_name }}.js", static_root),
-                    {% when Type::Scss with { field_name } %}
-                        {{ field_name }}: format!("/{}/{{ config.namespace }}/{{ source.output_base_name }}.css", static_root),
-                    {% when Type::Wasm with { field_name_wasm, field_name_js, target_path } %}
-                        {{ field_name_wasm }}: format!("/{}/{{ config.namespace }}/{{ source.output_base_name }}.wasm", static_root),
-                        {{ field_name_js }}: format!("/{}/{{ config.namespace }}/{{ source.output_base_name }}.js", static_root),
+            {% for file in files %}
+                {{ file.field_name }}: format!("/{}/{{ config.namespace }}/{{ file.output_name }}", static_root),
+                {% match file.integrity %}
+                    {% when Some with (integrity) %}
+                        {{ file.field_name }}_integrity: "{{ integrity }}".to_string(),
+                    {% when None %}
                 {% endmatch %}
             {% endfor %}
         }
     }
 
+    /// Constructs a new `{{ info.struct_name }}`.
+    #[cfg(feature = "{{ config.dev_feature }}")]
+    pub fn new(static_root: &str) -> Self {
+        Self {
+            {% for file in files %}
+                {{ file.field_name }}: format!("/{}/{{ config.namespace }}/{{ file.dev_name }}", static_root),
+                {% if file.integrity.is_some() %}
+                    {{ file.field_name }}_integrity: String::new(),
+                {% endif %}
+            {% endfor %}
+        }
+    }
+
     /// Builds a route for the static files.
+    #[cfg(not(feature = "{{ config.dev_feature }}"))]
     pub fn make_route() -> bluefire_backend::router::Route {
-        use bluefire_backend::{router::Route, static_files::StaticHandler};
+        use bluefire_backend::compression::Encoding;
+        use bluefire_backend::router::Route;
+        use bluefire_backend::static_files::{CachePolicy, StaticHandler};
         let mut route = Route::exact("{{ config.namespace }}");
 
-        {% for source in spec.sources %}
-            {% for (input_path, output_name, content_type) in generator.make_paths(source) %}
-                let data = include_bytes!("{{ input_path }}").to_vec();
-                let handler = Box::new(StaticHandler::new(data, "{{ content_type }}".to_string()));
-                route.add_route(Route::exact("{{ output_name }}").with_view(handler));
-            {% endfor %}
+        {% for file in files %}
+            let data = include_bytes!("{{ file.input_path }}").to_vec();
+            let handler = StaticHandler::new(data, "{{ file.content_type }}".to_string())
+                .with_etag("{{ file.etag }}")
+                .with_last_modified_timestamp({{ file.last_modified }})
+                .with_cache_policy({{ file.cache_policy }});
+            {% match file.precompressed %}
+                {% when Some with (precompressed) %}
+                    let handler = handler
+                        .with_encoded(Encoding::Brotli, include_bytes!("{{ precompressed.brotli_path }}").to_vec())
+                        .with_encoded(Encoding::Gzip, include_bytes!("{{ precompressed.gzip_path }}").to_vec());
+                {% when None %}
+            {% endmatch %}
+            route.add_route(Route::exact("{{ file.output_name }}").with_view(Box::new(handler)));
         {% endfor %}
 
         route
     }
+
+    /// Builds a route for the static files serving them from the disk (development mode).
+    #[cfg(feature = "{{ config.dev_feature }}")]
+    pub fn make_route() -> bluefire_backend::router::Route {
+        use bluefire_backend::router::Route;
+        use bluefire_backend::static_files::DiskHandler;
+        let mut route = Route::exact("{{ config.namespace }}");
+
+        {% for file in files %}
+            let path = std::path::PathBuf::from("{{ file.dev_path }}");
+            let handler = DiskHandler::new(path, "{{ file.content_type }}".to_string());
+            {% match file.dev_processor %}
+                {% when Some with (processor) %}
+                    let handler = handler
+                        .with_processor({{ processor }})
+                        .with_dependency(std::path::PathBuf::from("{{ source_dir }}"));
+                {% when None %}
+            {% endmatch %}
+            route.add_route(Route::exact("{{ file.dev_name }}").with_view(Box::new(handler)));
+        {% endfor %}
+
+        route
+    }
+
+    /// Builds a live reload watching the source directory and the other served files (development
+    /// mode).
+    #[cfg(feature = "{{ config.dev_feature }}")]
+    pub fn make_live_reload() -> bluefire_backend::live_reload::LiveReload {
+        bluefire_backend::live_reload::LiveReload::new().watch(vec![
+            {% for path in watched_paths %}
+                std::path::PathBuf::from("{{ path }}"),
+            {% endfor %}
+        ])
+    }
 }
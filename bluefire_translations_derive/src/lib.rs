@@ -24,6 +24,9 @@ const DEFAULT_LANG: &str = "en";
 struct Config {
     translations_path: PathBuf,
     default_lang: String,
+
+    /// Span errors related to the translation files are reported at.
+    span: proc_macro2::Span,
 }
 
 impl Default for Config {
@@ -31,6 +34,7 @@ impl Default for Config {
         Self {
             translations_path: as_cargo_absolute_path(DEFAULT_PATH),
             default_lang: DEFAULT_LANG.to_string(),
+            span: proc_macro2::Span::call_site(),
         }
     }
 }
@@ -78,58 +82,64 @@ impl TranslationFile {
     }
 }
 
-fn read_translations(config: &Config) -> HashMap<String, HashMap<String, String>> {
-    let mut result = HashMap::new();
-    match std::fs::read_dir(&config.translations_path) {
-        Ok(directory) => {
-            let mut processed_files = 0;
-            for entry in directory {
-                let path = match entry {
-                    Ok(entry) => entry.path(),
-                    Err(err) => panic!("Failed to read translation directory: {}", err),
-                };
-
-                if path.is_file() && path.extension().filter(|e| *e == "yaml").is_some() {
-                    let string = match std::fs::read_to_string(&path) {
-                        Ok(string) => string,
-                        Err(err) => panic!("Failed to read file ({:?}): {}", path, err),
-                    };
-                    let trans: TranslationFile = match serde_yaml::from_str(&string) {
-                        Ok(trans) => trans,
-                        Err(err) => panic!("Parse translation file ({:?}): {}", path, err),
-                    };
-                    result.insert(trans.lang_code.clone(), trans.into_map());
-                    processed_files += 1;
-                }
-            }
+fn read_translations(
+    config: &Config,
+) -> Result<HashMap<String, HashMap<String, String>>, syn::Error> {
+    let to_error = |message: String| syn::Error::new(config.span, message);
+    let directory_error =
+        |err: std::io::Error| to_error(format!("{}: {}", config.translations_path.display(), err));
 
-            if processed_files == 0 {
-                panic!("No translations were provided in {:?}", config.translations_path);
-            }
-        }
-        Err(err) => {
-            panic!("Failed to read directory ({:?}): {}", config.translations_path, err);
+    let mut result = HashMap::new();
+    for entry in std::fs::read_dir(&config.translations_path).map_err(directory_error)? {
+        let path = entry.map_err(directory_error)?.path();
+        if path.is_file() && path.extension().filter(|e| *e == "yaml").is_some() {
+            let string = std::fs::read_to_string(&path)
+                .map_err(|err| to_error(format!("{}: {}", path.display(), err)))?;
+            let trans: TranslationFile =
+                serde_yaml::from_str(&string).map_err(|err| match err.location() {
+                    Some(location) => to_error(format!(
+                        "{}:{}:{}: {}",
+                        path.display(),
+                        location.line(),
+                        location.column(),
+                        err
+                    )),
+                    None => to_error(format!("{}: {}", path.display(), err)),
+                })?;
+            result.insert(trans.lang_code.clone(), trans.into_map());
         }
     }
-    result
+
+    if result.is_empty() {
+        return Err(to_error(format!(
+            "No translations were provided in {}",
+            config.translations_path.display()
+        )));
+    }
+    Ok(result)
 }
 
 // -------------------------------------------------------------------------------------------------
 
-fn prepare_match_arms(info: &Info) -> proc_macro2::TokenStream {
+fn prepare_match_arms(info: &Info) -> Result<proc_macro2::TokenStream, syn::Error> {
     let struct_name = &info.code.struct_name;
-    let translations = read_translations(&info.config);
+    let translations = read_translations(&info.config)?;
 
-    let langs: Vec<&String> = translations.keys().collect();
+    let mut langs: Vec<&String> = translations.keys().collect();
+    langs.sort();
     if !langs.contains(&&info.config.default_lang) {
-        panic!(
-            "Default language '{}' not provided among translations '{:?}'",
-            info.config.default_lang, langs
-        );
+        return Err(syn::Error::new(
+            info.config.span,
+            format!(
+                "Default language '{}' not provided among translations {:?}",
+                info.config.default_lang, langs
+            ),
+        ));
     }
 
     let mut arms = Vec::new();
-    for (lang_code, lang_translations) in translations.iter() {
+    for lang_code in langs {
+        let lang_translations = &translations[lang_code];
         let lang_code_lit = syn::LitStr::new(lang_code, proc_macro2::Span::call_site());
         let keys = &info.code.keys;
         let mut values = Vec::new();
@@ -137,7 +147,10 @@ fn prepare_match_arms(info: &Info) -> proc_macro2::TokenStream {
             if let Some(value) = lang_translations.get(&key.to_string()) {
                 values.push(syn::LitStr::new(value, proc_macro2::Span::call_site()));
             } else {
-                panic!("Translation not found for key '{}' for lang '{}'", key, lang_code);
+                return Err(syn::Error::new(
+                    key.span(),
+                    format!("Translation not found for key '{}' for lang '{}'", key, lang_code),
+                ));
             }
         }
         let arm = quote::quote! {
@@ -150,66 +163,73 @@ fn prepare_match_arms(info: &Info) -> proc_macro2::TokenStream {
         arms.push(arm);
     }
 
-    quote::quote! { #(#arms)* }
+    Ok(quote::quote! { #(#arms)* })
 }
 
 // -------------------------------------------------------------------------------------------------
 
-fn parse_attibute_args(args: &syn::MetaList) -> Config {
+fn parse_attibute_args(args: &syn::MetaList) -> Result<Config, syn::Error> {
     let mut config = Config::default();
+    config.span = syn::spanned::Spanned::span(args);
     for arg in args.nested.iter() {
         match arg {
-            syn::NestedMeta::Meta(meta) => match meta {
-                syn::Meta::NameValue(value) => {
-                    let a = value.path.get_ident().expect("Get ident").to_string();
-                    match a.as_ref() {
-                        "path" => match value.lit {
-                            syn::Lit::Str(ref lit_str) => {
-                                let path = as_cargo_absolute_path(&lit_str.value());
-                                config.translations_path = path;
-                            }
-                            _ => panic!("Argument '{}' must be a string", a),
-                        },
-                        "default_language" => match value.lit {
-                            syn::Lit::Str(ref lit_str) => {
-                                config.default_lang = lit_str.value();
-                            }
-                            _ => panic!("Argument '{}' must be a string", a),
-                        },
-                        _ => panic!("Unknown argument '{}'", a),
+            syn::NestedMeta::Meta(syn::Meta::NameValue(value)) => {
+                let name = match value.path.get_ident() {
+                    Some(ident) => ident.to_string(),
+                    None => {
+                        return Err(syn::Error::new_spanned(&value.path, "Expected an identifier"))
+                    }
+                };
+                let lit_str = match value.lit {
+                    syn::Lit::Str(ref lit_str) => lit_str,
+                    ref lit => {
+                        return Err(syn::Error::new_spanned(
+                            lit,
+                            format!("Argument '{}' must be a string", name),
+                        ))
+                    }
+                };
+                match name.as_ref() {
+                    "path" => {
+                        config.translations_path = as_cargo_absolute_path(&lit_str.value());
+                        config.span = lit_str.span();
+                    }
+                    "default_language" => config.default_lang = lit_str.value(),
+                    _ => {
+                        return Err(syn::Error::new_spanned(
+                            &value.path,
+                            format!("Unknown argument '{}'", name),
+                        ))
                     }
                 }
-                _ => panic!("All arguments are expected to be name-value"),
-            },
-            _ => panic!("the attribute is expected to have nested arguments"),
+            }
+            arg => {
+                return Err(syn::Error::new_spanned(
+                    arg,
+                    "All arguments are expected to be name-value",
+                ))
+            }
         }
     }
-    config
+    Ok(config)
 }
 
-fn parse_attributes(attrs: &Vec<syn::Attribute>) -> Config {
+fn parse_attributes(attrs: &Vec<syn::Attribute>) -> Result<Config, syn::Error> {
     for attr in attrs.iter() {
-        match attr.parse_meta() {
-            Ok(meta) => {
-                let name = meta.path().get_ident().expect("Get ident").to_string();
-                if name == ATTRIBUTE_NAME {
-                    match meta {
-                        syn::Meta::List(meta_list) => {
-                            return parse_attibute_args(&meta_list);
-                        }
-                        _ => panic!("'translations' attribute is expected to be a list"),
-                    }
-                }
-            }
-            Err(err) => {
-                panic!("Failed to parse attribute metadata: {}", err);
-            }
+        if attr.path.is_ident(ATTRIBUTE_NAME) {
+            return match attr.parse_meta()? {
+                syn::Meta::List(meta_list) => parse_attibute_args(&meta_list),
+                meta => Err(syn::Error::new_spanned(
+                    meta,
+                    "'translations' attribute is expected to be a list",
+                )),
+            };
         }
     }
-    Config::default()
+    Ok(Config::default())
 }
 
-fn parse_fields(fields: &syn::Fields) -> Vec<syn::Ident> {
+fn parse_fields(fields: &syn::Fields) -> Result<Vec<syn::Ident>, syn::Error> {
     match fields {
         syn::Fields::Named(named_fields) => {
             let mut keys = Vec::new();
@@ -217,27 +237,55 @@ fn parse_fields(fields: &syn::Fields) -> Vec<syn::Ident> {
                 if let Some(ref ident) = field.ident {
                     keys.push(ident.clone());
                 } else {
-                    panic!("All fields should be named");
+                    return Err(syn::Error::new_spanned(field, "All fields should be named"));
                 }
             }
-            keys
+            Ok(keys)
         }
-        _ => panic!("This macro can be applied only to structures with named members"),
+        fields => Err(syn::Error::new_spanned(
+            fields,
+            "This macro can be applied only to structures with named members",
+        )),
     }
 }
 
-fn parse_item(stream: proc_macro::TokenStream) -> Info {
-    let ast: syn::DeriveInput = syn::parse(stream).expect("Failed to parse token stream");
+fn parse_item(stream: proc_macro::TokenStream) -> Result<Info, syn::Error> {
+    let ast: syn::DeriveInput = syn::parse(stream)?;
     match ast.data {
         syn::Data::Struct(data_struct) => {
-            let config = parse_attributes(&ast.attrs);
-            let keys = parse_fields(&data_struct.fields);
-            Info { config: config, code: Code { struct_name: ast.ident, keys: keys } }
+            let config = parse_attributes(&ast.attrs)?;
+            let keys = parse_fields(&data_struct.fields)?;
+            Ok(Info { config: config, code: Code { struct_name: ast.ident, keys: keys } })
+        }
+        _ => {
+            Err(syn::Error::new_spanned(&ast.ident, "This macro can be applied only to structures"))
         }
-        _ => panic!("This macro can be applied only to structures"),
     }
 }
 
+fn generate(stream: proc_macro::TokenStream) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let info = parse_item(stream)?;
+    let struct_name = &info.code.struct_name;
+    let default_lang = syn::LitStr::new(&info.config.default_lang, proc_macro2::Span::call_site());
+    let match_arms = prepare_match_arms(&info)?;
+
+    Ok(quote::quote! {
+        impl bluefire_translations::TranslationProvider for #struct_name {
+            fn provide(lang_code: &str) -> Option<Self> {
+                match lang_code {
+                    #match_arms
+                    _ => None,
+                }
+            }
+
+            fn provide_default() -> Self {
+                #struct_name::provide(#default_lang)
+                    .expect("BlueFire: default language is not present")
+            }
+        }
+    })
+}
+
 // -------------------------------------------------------------------------------------------------
 
 /// Implements `bluefire_translations::TranslationProvider`.
@@ -273,25 +321,8 @@ fn parse_item(stream: proc_macro::TokenStream) -> Info {
 /// the same.
 #[proc_macro_derive(Translations, attributes(translations))]
 pub fn derive_translations(stream: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    let info = parse_item(stream);
-    let struct_name = &info.code.struct_name;
-    let default_lang = syn::LitStr::new(&info.config.default_lang, proc_macro2::Span::call_site());
-    let match_arms = prepare_match_arms(&info);
-
-    let gen = quote::quote! {
-        impl bluefire_translations::TranslationProvider for #struct_name {
-            fn provide(lang_code: &str) -> Option<Self> {
-                match lang_code {
-                    #match_arms
-                    _ => None,
-                }
-            }
-
-            fn provide_default() -> Self {
-                #struct_name::provide(#default_lang)
-                    .expect("BlueFire: default language is not present")
-            }
-        }
-    };
-    gen.into()
+    match generate(stream) {
+        Ok(gen) => gen.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
 }
@@ -6,10 +6,16 @@
 
 #![warn(missing_docs)]
 
+pub mod bytes;
 pub mod class_names;
 pub mod constants;
 pub mod id;
 pub mod message;
+pub mod multipart;
+pub mod pagination;
+pub mod transport;
 pub mod validation;
 
-pub use crate::{class_names::ClassNames, id::Id, message::Message, validation::ValidationResult};
+pub use crate::{
+    bytes::Bytes, class_names::ClassNames, id::Id, message::Message, validation::ValidationResult,
+};
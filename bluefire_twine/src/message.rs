@@ -3,10 +3,14 @@
 
 //! Provides definition for `Message`.
 
+#[cfg(feature = "urlencoded")]
+use serde::de::DeserializeOwned;
 use serde_derive::{Deserialize, Serialize};
 
+use crate::multipart::Part;
+
 /// A serialized message ready to be sent over HTTP.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Message {
     /// HTTP method of the request.
     pub method: &'static str,
@@ -19,12 +23,38 @@ pub struct Message {
 
     /// Content of the message.
     pub body: String,
+
+    /// Parts of a `multipart/form-data` message. If not empty, they are sent instead of `body`.
+    #[serde(default)]
+    pub parts: Vec<Part>,
+
+    /// HTTP headers as name-value pairs. Cookies are sent in the `cookie` header.
+    #[serde(default)]
+    pub headers: Vec<(String, String)>,
 }
 
 impl Message {
     /// Constructs a new `Message`.
     pub fn new(method: &'static str, path: String, query: String, body: String) -> Self {
-        Self { method, path, query, body }
+        Self { method, path, query, body, parts: Vec::new(), headers: Vec::new() }
+    }
+
+    /// Adds a header to the message.
+    pub fn with_header(mut self, name: &str, value: String) -> Self {
+        self.headers.push((name.to_string(), value));
+        self
+    }
+
+    /// Adds parts of a `multipart/form-data` message.
+    pub fn with_parts(mut self, parts: Vec<Part>) -> Self {
+        self.parts.extend(parts);
+        self
+    }
+
+    /// Adds headers to the message.
+    pub fn with_headers(mut self, headers: Vec<(String, String)>) -> Self {
+        self.headers.extend(headers);
+        self
     }
 
     /// Returns the HTTP method of the message.
@@ -46,4 +76,161 @@ impl Message {
     pub fn body(&self) -> &str {
         &self.body
     }
+
+    /// Returns the parts of a `multipart/form-data` message.
+    pub fn parts(&self) -> &[Part] {
+        &self.parts
+    }
+
+    /// Returns the headers of the message.
+    pub fn headers(&self) -> &[(String, String)] {
+        &self.headers
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Serializes a flat structure into a list of name-value pairs (e.g. headers or cookies).
+#[cfg(feature = "urlencoded")]
+pub fn to_pairs<T: serde::Serialize>(value: &T) -> Result<Vec<(String, String)>, String> {
+    let encoded = serde_urlencoded::to_string(value).map_err(|err| err.to_string())?;
+    serde_urlencoded::from_str(&encoded).map_err(|err| err.to_string())
+}
+
+/// Deserializes a flat structure from a list of name-value pairs. Pairs with names not matching any
+/// field are ignored.
+#[cfg(feature = "urlencoded")]
+pub fn from_pairs<T: DeserializeOwned>(pairs: &[(String, String)]) -> Result<T, String> {
+    let encoded = serde_urlencoded::to_string(pairs).map_err(|err| err.to_string())?;
+    serde_urlencoded::from_str(&encoded).map_err(|err| err.to_string())
+}
+
+/// Checks if the byte may appear in a cookie value as it is. These are the `cookie-octet`s of
+/// RFC 6265 except `%` which starts an escaped byte.
+fn is_cookie_octet(byte: u8) -> bool {
+    match byte {
+        0x21 | 0x23..=0x24 | 0x26..=0x2B | 0x2D..=0x3A | 0x3C..=0x5B | 0x5D..=0x7E => true,
+        _ => false,
+    }
+}
+
+/// Percent-encodes bytes of the value which are not allowed in cookie values.
+fn encode_cookie_value(value: &str) -> String {
+    let mut result = String::new();
+    for byte in value.bytes() {
+        if is_cookie_octet(byte) {
+            result.push(byte as char);
+        } else {
+            result += &format!("%{:02X}", byte);
+        }
+    }
+    result
+}
+
+/// Decodes a percent-encoded cookie value. Returns the value as it is if it is not valid.
+fn decode_cookie_value(value: &str) -> String {
+    let hex = |byte: u8| (byte as char).to_digit(16).map(|digit| digit as u8);
+    let bytes = value.as_bytes();
+    let mut result = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = match bytes.get(i + 1..i + 3) {
+            Some(&[high, low]) if bytes[i] == b'%' => {
+                hex(high).and_then(|high| hex(low).map(|low| (high, low)))
+            }
+            _ => None,
+        };
+        match escaped {
+            Some((high, low)) => {
+                result.push(high * 16 + low);
+                i += 3;
+            }
+            None => {
+                result.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(result).unwrap_or_else(|_| value.to_string())
+}
+
+/// Formats cookies as the value of the `cookie` header. Values are percent-encoded.
+pub fn format_cookies(cookies: &[(String, String)]) -> String {
+    let cookies: Vec<String> = cookies
+        .iter()
+        .map(|(name, value)| format!("{}={}", name, encode_cookie_value(value)))
+        .collect();
+    cookies.join("; ")
+}
+
+/// Parses the value of the `cookie` header. Values are percent-decoded.
+pub fn parse_cookies(header: &str) -> Vec<(String, String)> {
+    header
+        .split(';')
+        .filter_map(|cookie| {
+            let mut parts = cookie.trim().splitn(2, '=');
+            match (parts.next(), parts.next()) {
+                (Some(name), Some(value)) if !name.is_empty() => {
+                    Some((name.to_string(), decode_cookie_value(value)))
+                }
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "urlencoded")]
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Headers {
+        #[serde(rename = "if-match")]
+        if_match: String,
+        count: Option<u32>,
+    }
+
+    #[cfg(feature = "urlencoded")]
+    #[test]
+    fn test_pairs() {
+        let headers = Headers { if_match: "abc".to_string(), count: Some(3) };
+        let pairs = to_pairs(&headers).unwrap();
+        let expected = vec![
+            ("if-match".to_string(), "abc".to_string()),
+            ("count".to_string(), "3".to_string()),
+        ];
+        assert_eq!(pairs, expected);
+
+        let mut pairs = pairs;
+        pairs.push(("other".to_string(), "value".to_string()));
+        assert_eq!(from_pairs::<Headers>(&pairs).unwrap(), headers);
+        assert!(from_pairs::<Headers>(&[]).is_err());
+    }
+
+    #[test]
+    fn test_cookies() {
+        let cookies = vec![
+            ("session".to_string(), "abc".to_string()),
+            ("theme".to_string(), "dark".to_string()),
+        ];
+        let header = format_cookies(&cookies);
+        assert_eq!(header, "session=abc; theme=dark");
+        assert_eq!(parse_cookies(&header), cookies);
+        assert_eq!(parse_cookies(" ; broken"), Vec::new());
+
+        let cookies = vec![("query".to_string(), "a b; c=\"d\" 50% żółw".to_string())];
+        let header = format_cookies(&cookies);
+        assert_eq!(header, "query=a%20b%3B%20c=%22d%22%2050%25%20%C5%BC%C3%B3%C5%82w");
+        assert_eq!(parse_cookies(&header), cookies);
+        assert_eq!(
+            parse_cookies("raw=100%; bad=%zz%C5"),
+            vec![
+                ("raw".to_string(), "100%".to_string()),
+                ("bad".to_string(), "%zz%C5".to_string()),
+            ]
+        );
+    }
 }
@@ -0,0 +1,181 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy of
+// the MPL was not distributed with this file, You can obtain one at http://mozilla.org/MPL/2.0/
+
+//! Provides definitions of files and parts of `multipart/form-data` messages.
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::bytes::Bytes;
+
+/// A file sent in a multipart request.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct File {
+    /// Name of the file given by the sender.
+    pub name: Option<String>,
+
+    /// MIME type of the content given by the sender.
+    pub content_type: Option<String>,
+
+    /// Content of the file.
+    pub data: Bytes,
+}
+
+impl File {
+    /// Constructs a new `File` without name and content type.
+    pub fn new(data: Vec<u8>) -> Self {
+        Self { name: None, content_type: None, data: Bytes::new(data) }
+    }
+
+    /// Sets the name of the file.
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    /// Sets the MIME type of the content.
+    pub fn with_content_type(mut self, content_type: &str) -> Self {
+        self.content_type = Some(content_type.to_string());
+        self
+    }
+}
+
+/// A part of a multipart message: either a text field or a file.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Part {
+    /// Name of the field.
+    pub name: String,
+
+    /// Name of the file if the part is a file.
+    pub file_name: Option<String>,
+
+    /// MIME type of the content.
+    pub content_type: Option<String>,
+
+    /// Content of the part.
+    pub data: Vec<u8>,
+}
+
+impl Part {
+    /// Constructs a new text field.
+    pub fn text(name: &str, value: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            file_name: None,
+            content_type: None,
+            data: value.as_bytes().to_vec(),
+        }
+    }
+
+    /// Constructs a new file part.
+    pub fn file(name: &str, file: &File) -> Self {
+        Self {
+            name: name.to_string(),
+            file_name: Some(file.name.clone().unwrap_or_else(|| name.to_string())),
+            content_type: file.content_type.clone(),
+            data: file.data.to_vec(),
+        }
+    }
+
+    /// Checks if the part is a file.
+    pub fn is_file(&self) -> bool {
+        self.file_name.is_some()
+    }
+
+    /// Converts the part into a `File`.
+    pub fn to_file(&self) -> File {
+        File {
+            name: self.file_name.clone(),
+            content_type: self.content_type.clone(),
+            data: Bytes::new(self.data.clone()),
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Conversion of file arguments of multipart requests to and from parts. Implemented for a single
+/// file, an optional file and a vector of files sent in parts with the same name.
+pub trait FileArg: Sized {
+    /// Converts the argument to parts with the given name.
+    fn to_parts(&self, name: &str) -> Vec<Part>;
+
+    /// Extracts the argument from file parts with the given name.
+    fn from_parts(name: &str, parts: &[Part]) -> Result<Self, String>;
+}
+
+/// Returns the files sent in parts with the given name.
+fn files_named(name: &str, parts: &[Part]) -> Vec<File> {
+    parts.iter().filter(|part| part.name == name && part.is_file()).map(Part::to_file).collect()
+}
+
+impl FileArg for File {
+    fn to_parts(&self, name: &str) -> Vec<Part> {
+        vec![Part::file(name, self)]
+    }
+
+    fn from_parts(name: &str, parts: &[Part]) -> Result<Self, String> {
+        let mut files = files_named(name, parts);
+        match files.len() {
+            1 => Ok(files.remove(0)),
+            0 => Err(format!("missing file '{}'", name)),
+            _ => Err(format!("more than one file '{}'", name)),
+        }
+    }
+}
+
+impl FileArg for Option<File> {
+    fn to_parts(&self, name: &str) -> Vec<Part> {
+        self.iter().map(|file| Part::file(name, file)).collect()
+    }
+
+    fn from_parts(name: &str, parts: &[Part]) -> Result<Self, String> {
+        let mut files = files_named(name, parts);
+        match files.len() {
+            0 => Ok(None),
+            1 => Ok(Some(files.remove(0))),
+            _ => Err(format!("more than one file '{}'", name)),
+        }
+    }
+}
+
+impl FileArg for Vec<File> {
+    fn to_parts(&self, name: &str) -> Vec<Part> {
+        self.iter().map(|file| Part::file(name, file)).collect()
+    }
+
+    fn from_parts(name: &str, parts: &[Part]) -> Result<Self, String> {
+        Ok(files_named(name, parts))
+    }
+}
+
+/// Returns name-value pairs of the text fields.
+pub fn text_pairs(parts: &[Part]) -> Vec<(String, String)> {
+    parts
+        .iter()
+        .filter(|part| !part.is_file())
+        .map(|part| (part.name.clone(), String::from_utf8_lossy(&part.data).into_owned()))
+        .collect()
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_args() {
+        let avatar =
+            File::new(vec![1, 2, 3]).with_name("avatar.png").with_content_type("image/png");
+        let mut parts = avatar.to_parts("avatar");
+        parts.push(Part::text("title", "Profile"));
+        parts.extend(vec![File::new(vec![4]), File::new(vec![5])].to_parts("attachments"));
+
+        assert_eq!(File::from_parts("avatar", &parts), Ok(avatar));
+        assert_eq!(Option::<File>::from_parts("missing", &parts), Ok(None));
+        assert!(File::from_parts("missing", &parts).is_err());
+        assert!(File::from_parts("attachments", &parts).is_err());
+        assert_eq!(Vec::<File>::from_parts("attachments", &parts).unwrap().len(), 2);
+        assert_eq!(text_pairs(&parts), vec![("title".to_string(), "Profile".to_string())]);
+    }
+}
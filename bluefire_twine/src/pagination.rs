@@ -0,0 +1,117 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy of
+// the MPL was not distributed with this file, You can obtain one at http://mozilla.org/MPL/2.0/
+
+//! Definitions used by generated code of paginated methods.
+
+use std::marker::PhantomData;
+
+/// Pagination parameters of a request with the defaults applied.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PageParams {
+    /// Position of the page for cursor-based pagination.
+    pub cursor: Option<String>,
+
+    /// Number of skipped items for offset-based pagination.
+    pub offset: u32,
+
+    /// Maximal number of items in the page.
+    pub limit: u32,
+}
+
+/// A yield containing one page of a list.
+pub trait Page {
+    /// Type of the items.
+    type Item;
+
+    /// Returns the items of the page.
+    fn items(&self) -> &[Self::Item];
+
+    /// Returns the parameters selecting the next page or `None` if this is the last one.
+    fn next_page(&self, params: &PageParams) -> Option<PageParams>;
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Iterator fetching consecutive pages until the last one or the first error.
+pub struct PageIterator<P, E, F> {
+    next: Option<PageParams>,
+    fetch: F,
+    phantom: PhantomData<fn() -> (P, E)>,
+}
+
+impl<P, E, F> PageIterator<P, E, F>
+where
+    P: Page,
+    F: FnMut(&PageParams) -> Result<P, E>,
+{
+    /// Constructs a new `PageIterator` starting from the page selected by `params`.
+    pub fn new(params: PageParams, fetch: F) -> Self {
+        Self { next: Some(params), fetch, phantom: PhantomData }
+    }
+}
+
+impl<P, E, F> Iterator for PageIterator<P, E, F>
+where
+    P: Page,
+    F: FnMut(&PageParams) -> Result<P, E>,
+{
+    type Item = Result<P, E>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let params = self.next.take()?;
+        let result = (self.fetch)(&params);
+        if let Ok(page) = &result {
+            self.next = page.next_page(&params);
+        }
+        Some(result)
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct NumbersPage {
+        numbers: Vec<u32>,
+        next_offset: Option<u32>,
+    }
+
+    impl Page for NumbersPage {
+        type Item = u32;
+
+        fn items(&self) -> &[u32] {
+            &self.numbers
+        }
+
+        fn next_page(&self, params: &PageParams) -> Option<PageParams> {
+            self.next_offset.map(|offset| PageParams { offset, ..params.clone() })
+        }
+    }
+
+    #[test]
+    fn test_page_iterator() {
+        let fetch = |params: &PageParams| -> Result<NumbersPage, String> {
+            let numbers: Vec<u32> =
+                (params.offset..(params.offset + params.limit).min(5)).collect();
+            let next_offset = if params.offset + params.limit < 5 {
+                Some(params.offset + params.limit)
+            } else {
+                None
+            };
+            Ok(NumbersPage { numbers, next_offset })
+        };
+        let params = PageParams { cursor: None, offset: 0, limit: 2 };
+        let pages: Vec<Vec<u32>> =
+            PageIterator::new(params, fetch).map(|page| page.unwrap().items().to_vec()).collect();
+        assert_eq!(pages, vec![vec![0, 1], vec![2, 3], vec![4]]);
+
+        let params = PageParams { cursor: None, offset: 0, limit: 2 };
+        let mut failing = PageIterator::new(params, |_: &PageParams| -> Result<NumbersPage, _> {
+            Err("offline".to_string())
+        });
+        assert_eq!(failing.next().map(|page| page.err()), Some(Some("offline".to_string())));
+        assert!(failing.next().is_none());
+    }
+}
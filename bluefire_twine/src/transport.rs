@@ -0,0 +1,167 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy of
+// the MPL was not distributed with this file, You can obtain one at http://mozilla.org/MPL/2.0/
+
+//! Transports used by generated API clients to send `Message`s.
+//!
+//! Blocking transports return the parsed result directly while asynchronous transports return a
+//! future resolving to it. `bluefire_backend::fetch` and `bluefire_frontend::fetch` provide
+//! transports for the backend and the browser respectively.
+
+use std::{collections::VecDeque, sync::Mutex};
+
+use crate::message::Message;
+
+/// A response received from the server before parsing.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RawResponse {
+    /// HTTP status code.
+    pub status: u16,
+
+    /// Value of the `content-type` header.
+    pub content_type: Option<String>,
+
+    /// Content of the response.
+    pub body: Vec<u8>,
+}
+
+impl RawResponse {
+    /// Constructs a new `RawResponse`.
+    pub fn new(status: u16, body: Vec<u8>) -> Self {
+        Self { status, content_type: None, body }
+    }
+
+    /// Sets the content type of the response.
+    pub fn with_content_type(mut self, content_type: &str) -> Self {
+        self.content_type = Some(content_type.to_string());
+        self
+    }
+}
+
+/// Error returned when the message could not be sent or the response could not be received.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransportError {
+    message: String,
+}
+
+impl TransportError {
+    /// Constructs a new `TransportError`.
+    pub fn new(message: String) -> Self {
+        Self { message }
+    }
+
+    /// Returns the description of the error.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl std::fmt::Display for TransportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for TransportError {}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Sends messages and passes the received responses to a parser producing the result `T`.
+pub trait Transport<T> {
+    /// The result of sending a message: `T` for blocking transports or a future resolving to `T`
+    /// for asynchronous ones.
+    type Output;
+
+    /// Sends the message and converts the response with `parse`.
+    fn send<F>(&self, message: Message, parse: F) -> Self::Output
+    where
+        F: FnOnce(Result<RawResponse, TransportError>) -> T + 'static;
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// A blocking transport returning predefined responses and recording sent messages. Useful for
+/// testing code using generated API clients.
+#[derive(Debug, Default)]
+pub struct MockTransport {
+    responses: Mutex<VecDeque<Result<RawResponse, TransportError>>>,
+    messages: Mutex<Vec<Message>>,
+}
+
+impl MockTransport {
+    /// Constructs a new `MockTransport` without any responses.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a response returned after all the previously added responses.
+    pub fn with_response(self, status: u16, body: &str) -> Self {
+        self.push(Ok(RawResponse::new(status, body.as_bytes().to_vec())));
+        self
+    }
+
+    /// Adds a binary response returned after all the previously added responses.
+    pub fn with_binary_response(self, status: u16, content_type: &str, body: Vec<u8>) -> Self {
+        self.push(Ok(RawResponse::new(status, body).with_content_type(content_type)));
+        self
+    }
+
+    /// Adds an error returned after all the previously added responses.
+    pub fn with_error(self, message: &str) -> Self {
+        self.push(Err(TransportError::new(message.to_string())));
+        self
+    }
+
+    /// Adds a response or an error returned after all the previously added responses.
+    pub fn push(&self, response: Result<RawResponse, TransportError>) {
+        self.responses.lock().expect("Lock mock responses").push_back(response);
+    }
+
+    /// Returns the messages sent so far.
+    pub fn messages(&self) -> Vec<Message> {
+        self.messages.lock().expect("Lock mock messages").clone()
+    }
+}
+
+impl<T> Transport<T> for MockTransport {
+    type Output = T;
+
+    fn send<F>(&self, message: Message, parse: F) -> T
+    where
+        F: FnOnce(Result<RawResponse, TransportError>) -> T + 'static,
+    {
+        self.messages.lock().expect("Lock mock messages").push(message);
+        let response = self.responses.lock().expect("Lock mock responses").pop_front();
+        parse(response.unwrap_or_else(|| Err(TransportError::new("No mock response".to_string()))))
+    }
+}
+
+/// Prelude for `transport` module.
+pub mod prelude {
+    pub use super::{MockTransport, RawResponse, Transport, TransportError};
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mock_transport() {
+        let transport = MockTransport::new().with_response(200, "first").with_error("offline");
+        let parse = |response: Result<RawResponse, TransportError>| response;
+
+        let message = Message::new("GET", "/a".to_string(), String::new(), String::new());
+        assert_eq!(transport.send(message, parse), Ok(RawResponse::new(200, b"first".to_vec())));
+
+        let message = Message::new("POST", "/b".to_string(), String::new(), "{}".to_string());
+        assert_eq!(transport.send(message, parse), Err(TransportError::new("offline".to_string())));
+
+        let message = Message::new("GET", "/c".to_string(), String::new(), String::new());
+        assert!(transport.send(message, parse).is_err());
+
+        let paths: Vec<String> =
+            transport.messages().iter().map(|message| message.path().to_string()).collect();
+        assert_eq!(paths, vec!["/a", "/b", "/c"]);
+    }
+}
@@ -3,9 +3,12 @@
 
 //! Validation for user input.
 
+use serde_derive::{Deserialize, Serialize};
+
 /// A generic result of validation.
 ///
 /// Provides a way to report more than one error if many problems were found.
+#[derive(Clone, Debug, PartialEq)]
 pub struct ValidationResult<T> {
     errors: Vec<T>,
 }
@@ -26,6 +29,11 @@ impl<T> ValidationResult<T> {
         &self.errors
     }
 
+    /// Consumes the result returning the list of errors.
+    pub fn into_errors(self) -> Vec<T> {
+        self.errors
+    }
+
     /// Consumes another validation result by appending its errors to oneself.
     pub fn join(&mut self, other: ValidationResult<T>) {
         self.errors.extend(other.errors);
@@ -37,12 +45,154 @@ impl<T> ValidationResult<T> {
     }
 }
 
+impl ValidationResult<ValidationError> {
+    /// Prefixes paths of all the errors with the given path. Used when validating nested values.
+    pub fn nested(self, prefix: &str) -> Self {
+        Self { errors: self.errors.into_iter().map(|error| error.nested(prefix)).collect() }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// An error found in a value nested in a structure.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidationError {
+    /// Dot-separated path to the invalid value, e.g. `address.zip` or `tags.2`.
+    pub path: String,
+
+    /// Name of the error, e.g. `too_short`.
+    pub error: String,
+}
+
+impl ValidationError {
+    /// Constructs a new `ValidationError`.
+    pub fn new(path: &str, error: &str) -> Self {
+        Self { path: path.to_string(), error: error.to_string() }
+    }
+
+    /// Prefixes the path with the given one.
+    pub fn nested(self, prefix: &str) -> Self {
+        let path = if self.path.is_empty() {
+            prefix.to_string()
+        } else {
+            format!("{}.{}", prefix, self.path)
+        };
+        Self { path, error: self.error }
+    }
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}: {}", self.path, self.error)
+    }
+}
+
+/// Trait for values which can check their own correctness.
+///
+/// `bluefire_protogen` generates implementations of this trait for structures, unions and
+/// requests defined in the API specification.
+pub trait Validate {
+    /// Validates the value returning all the found errors.
+    fn validate(&self) -> ValidationResult<ValidationError>;
+}
+
+// -------------------------------------------------------------------------------------------------
+
 /// Checks if the passed string is a valid e-mail.
 pub fn validate_email(email: &String) -> bool {
     checkmail::validate_email(&email)
 }
 
+/// Checks if the passed string is a valid absolute URL.
+#[cfg(feature = "validation")]
+pub fn validate_url(value: &str) -> bool {
+    url::Url::parse(value).is_ok()
+}
+
+/// Checks if the passed string is a UUID in hyphenated format.
+pub fn validate_uuid(value: &str) -> bool {
+    let groups: Vec<&str> = value.split('-').collect();
+    let lengths = [8, 4, 4, 4, 12];
+    groups.len() == lengths.len()
+        && groups.iter().zip(lengths.iter()).all(|(group, length)| {
+            group.len() == *length && group.chars().all(|c| c.is_ascii_hexdigit())
+        })
+}
+
+/// Compiles the regular expression so that it matches only whole strings.
+#[cfg(feature = "validation")]
+pub fn compile_regex(pattern: &str) -> Result<regex::Regex, regex::Error> {
+    regex::Regex::new(&format!("^(?:{})$", pattern))
+}
+
+/// Regular expression compiled on first use. Meant to be stored in a `static` so that the
+/// expression is compiled only once.
+///
+/// `bluefire_protogen` rejects invalid expressions when checking the specification.
+#[cfg(feature = "validation")]
+pub struct Pattern {
+    pattern: &'static str,
+    regex: once_cell::sync::OnceCell<regex::Regex>,
+}
+
+#[cfg(feature = "validation")]
+impl Pattern {
+    /// Constructs a new `Pattern`.
+    pub const fn new(pattern: &'static str) -> Self {
+        Self { pattern, regex: once_cell::sync::OnceCell::new() }
+    }
+
+    /// Checks if the whole passed string matches the expression.
+    ///
+    /// Panics if the expression is not valid.
+    pub fn is_match(&self, value: &str) -> bool {
+        self.regex
+            .get_or_init(|| compile_regex(self.pattern).expect("Compile regular expression"))
+            .is_match(value)
+    }
+}
+
 /// Prelude for `validation` module.
 pub mod prelude {
-    pub use super::{validate_email, ValidationResult};
+    pub use super::{validate_email, validate_uuid, Validate, ValidationError, ValidationResult};
+
+    #[cfg(feature = "validation")]
+    pub use super::{compile_regex, validate_url, Pattern};
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nested_errors() {
+        let mut result = ValidationResult::new();
+        result.add(ValidationError::new("zip", "too_short"));
+        result.add(ValidationError::new("", "missing"));
+
+        let errors: Vec<String> =
+            result.nested("address").get_errors().iter().map(|e| e.to_string()).collect();
+        assert_eq!(errors, vec!["address.zip: too_short", "address: missing"]);
+    }
+
+    #[test]
+    fn test_checks() {
+        assert!(validate_uuid("936da01f-9abd-4d9d-80c7-02af85c822a8"));
+        assert!(!validate_uuid("936da01f-9abd-4d9d-80c7-02af85c822a"));
+        assert!(!validate_uuid("936da01f9abd4d9d80c702af85c822a8"));
+    }
+
+    #[cfg(feature = "validation")]
+    #[test]
+    fn test_urls_and_patterns() {
+        assert!(validate_url("https://example.com/path?query=1"));
+        assert!(!validate_url("example"));
+
+        static PATTERN: Pattern = Pattern::new("[a-z]+-[0-9]+");
+        assert!(PATTERN.is_match("ab-12"));
+        assert!(!PATTERN.is_match("ab-12x"));
+        assert!(compile_regex("(").is_err());
+    }
 }